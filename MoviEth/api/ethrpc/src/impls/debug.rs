@@ -0,0 +1,246 @@
+use crate::{
+    impls::eth::{transact_virtual, EthHandler},
+    traits::debug::DebugServer,
+    types::{
+        block_number::BlockNumber,
+        call_request::{sign_call, CallRequest},
+        trace::{call_frame, Prestate, PrestateAccount, Tracer, TracerOptions},
+    },
+};
+use aptos_block_executor::{
+    aptos_vm::AptosVM, data_cache::AsMoveResolver, logging::AdapterLogSchema,
+};
+use aptos_evm::{
+    extract_evm_executed, make_executor, EvmContext, EvmState, EvmTransaction, ExecutionOutcome,
+    StorageKey, TransactOptions, TransactionInfo, ViewWrapper,
+};
+use aptos_state_view::{StateView, StateViewId, TStateView};
+use aptos_types::state_store::{state_key::StateKey, state_storage_usage::StateStorageUsage};
+use cfx_evm::execution::EstimateRequest;
+use cfx_types::AddressSpaceUtil;
+use ethereum_types::{BigEndianHash, H256, U256};
+use jsonrpsee::core::{async_trait, Error, RpcResult};
+use serde_json::Value;
+use std::{cell::RefCell, collections::HashSet, sync::Arc};
+
+#[async_trait]
+impl DebugServer for Arc<EthHandler> {
+    async fn trace_transaction(
+        &self,
+        tx_hash: H256,
+        options: Option<TracerOptions>,
+    ) -> RpcResult<Value> {
+        let ledger_info = self.context.db.reader.get_latest_ledger_info()?;
+        let tx = self
+            .context
+            .db
+            .reader
+            .get_transaction_by_eth_hash(
+                tx_hash.into(),
+                ledger_info.ledger_info().version(),
+                false, /* fetch_events */
+            )?
+            .ok_or_else(|| Error::Custom(format!("transaction {:?} not found", tx_hash)))?;
+        let user_tx = tx.transaction.as_signed_user_txn()?;
+        let eth_tx = EvmTransaction::try_from(user_tx)
+            .map_err(|_| Error::Custom("not an EVM transaction".to_string()))?;
+
+        // A block starts with its metadata transaction, so the state before an EVM transaction
+        // is at the previous version of the same block.
+        let state_view = self
+            .context
+            .db
+            .reader
+            .state_view_at_version(Some(tx.version - 1))?;
+        let context = self.get_evm_context(&state_view);
+        let vm = AptosVM::new(&state_view);
+        let recording_view = RecordingStateView::new(&state_view);
+        let outcome = self.transact_traced(&recording_view, &vm, &context, &eth_tx)?;
+        render_trace(
+            &outcome,
+            *eth_tx.gas(),
+            options.unwrap_or_default(),
+            &state_view,
+            recording_view.into_keys(),
+        )
+    }
+
+    async fn trace_call(
+        &self,
+        request: CallRequest,
+        maybe_block_number: Option<BlockNumber>,
+        options: Option<TracerOptions>,
+    ) -> RpcResult<Value> {
+        let state_version = self.get_version_at_block_number(maybe_block_number)?;
+        let state_view = self
+            .context
+            .db
+            .reader
+            .state_view_at_version(Some(state_version))?;
+        let context = self.get_evm_context(&state_view);
+        let vm = AptosVM::new(&state_view);
+        let estimate_request = EstimateRequest {
+            has_sender: request.from.is_some(),
+            has_gas_limit: request.gas.is_some(),
+            has_gas_price: request.gas_price.is_some(),
+            has_nonce: request.nonce.is_some(),
+            has_storage_limit: false,
+        };
+        let eth_tx = sign_call(self.context.evm_chain_id() as u32, request)?;
+        let gas = *eth_tx.gas();
+        let recording_view = RecordingStateView::new(&state_view);
+        let outcome = transact_virtual(
+            &self.evm_machine,
+            &recording_view,
+            &vm,
+            &context,
+            eth_tx,
+            estimate_request,
+            None,
+        )?;
+        render_trace(
+            &outcome,
+            gas,
+            options.unwrap_or_default(),
+            &state_view,
+            recording_view.into_keys(),
+        )
+    }
+}
+
+impl EthHandler {
+    /// Executes a transaction on top of `state_view` with tracing, as the block executor does,
+    /// without committing it.
+    fn transact_traced<S: StateView>(
+        &self,
+        state_view: &S,
+        vm: &AptosVM,
+        context: &EvmContext,
+        tx: &EvmTransaction,
+    ) -> RpcResult<ExecutionOutcome> {
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let data_cache = state_view.as_move_resolver();
+        let call_move_handler =
+            vm.make_cross_space_handler(&data_cache, &self.evm_machine, context, &log_context);
+        let mut view_wrapper = ViewWrapper {
+            inner: &data_cache,
+            cache: Default::default(),
+        };
+        let mut state = EvmState::new_with_move_vm(&mut view_wrapper, &call_move_handler);
+        let mut executor = make_executor(&self.evm_machine, context, &mut state);
+        executor
+            .transact(tx, TransactOptions::exec_with_tracing())
+            .map_err(|e| Error::Custom(e.to_string()))
+    }
+}
+
+/// Renders the trace of an execution with `gas` in the format of the tracer. The prestate tracer
+/// reads the EVM state keys in `keys` from `pre_state_view`, the state the execution started on.
+fn render_trace<S: StateView>(
+    outcome: &ExecutionOutcome,
+    gas: U256,
+    options: TracerOptions,
+    pre_state_view: &S,
+    keys: HashSet<StorageKey>,
+) -> RpcResult<Value> {
+    let executed = extract_evm_executed(outcome)
+        .ok_or_else(|| Error::Custom(format!("transaction not executed: {:?}", outcome)))?;
+    match options.tracer.unwrap_or(Tracer::CallTracer) {
+        Tracer::CallTracer => {
+            let mut frame = call_frame(executed.trace.clone())?;
+            // The top frame covers the whole transaction, as in geth.
+            frame.gas = gas;
+            frame.gas_used = executed.gas_used;
+            if options.tracer_config.only_top_call {
+                frame.calls.clear();
+            }
+            Ok(serde_json::to_value(frame)?)
+        },
+        Tracer::PrestateTracer => Ok(serde_json::to_value(prestate(pre_state_view, keys)?)?),
+    }
+}
+
+/// Reads the accounts and storage slots of `keys` from `state_view`.
+fn prestate<S: StateView>(state_view: &S, keys: HashSet<StorageKey>) -> RpcResult<Prestate> {
+    fn db_error(e: impl ToString) -> Error {
+        Error::Custom(e.to_string())
+    }
+
+    let mut view_wrapper = ViewWrapper {
+        inner: &state_view.as_move_resolver(),
+        cache: Default::default(),
+    };
+    let EvmState { state } = EvmState::new(&mut view_wrapper);
+    let mut prestate = Prestate::new();
+    for key in keys {
+        let address = key.address().with_evm_space();
+        if !prestate.contains_key(&address.address) {
+            let code = state
+                .code(&address)
+                .map_err(db_error)?
+                .map(|code| (*code).clone().into());
+            prestate.insert(
+                address.address,
+                PrestateAccount {
+                    balance: state.balance(&address).map_err(db_error)?,
+                    nonce: state.nonce(&address).map_err(db_error)?.as_u64(),
+                    code,
+                    storage: Default::default(),
+                },
+            );
+        }
+        if let Some(slot) = key.storage_slot() {
+            let value = state
+                .storage_at(&address, slot.as_bytes())
+                .map_err(db_error)?;
+            prestate
+                .get_mut(&address.address)
+                .expect("inserted above")
+                .storage
+                .insert(slot, H256::from_uint(&value));
+        }
+    }
+    Ok(prestate)
+}
+
+/// A state view which records the keys of the EVM state read through it.
+struct RecordingStateView<'a, S> {
+    inner: &'a S,
+    keys: RefCell<HashSet<StorageKey>>,
+}
+
+impl<'a, S: StateView> RecordingStateView<'a, S> {
+    fn new(inner: &'a S) -> Self {
+        Self {
+            inner,
+            keys: Default::default(),
+        }
+    }
+
+    fn into_keys(self) -> HashSet<StorageKey> {
+        self.keys.into_inner()
+    }
+}
+
+impl<'a, S: StateView> TStateView for RecordingStateView<'a, S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.inner.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(key) = StorageKey::from_state_key(state_key) {
+            self.keys.borrow_mut().insert(key);
+        }
+        self.inner.get_state_value(state_key)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.inner.is_genesis()
+    }
+
+    fn get_usage(&self) -> anyhow::Result<StateStorageUsage> {
+        self.inner.get_usage()
+    }
+}
@@ -1,50 +1,72 @@
 use crate::{
     traits::eth::EthServer,
     types::{
+        account_proof::{AccountProof, StorageProof},
         block::{Block, BlockTransactions},
         block_number::BlockNumber,
-        call_request::{sign_call, CallRequest},
+        call_request::{sign_call, CallRequest, MAX_GAS_CALL_REQUEST},
+        fee_history::{priority_fee_per_gas, reward_percentiles, FeeHistory},
+        filter::Filter,
         log::Log,
         receipts::Receipt,
+        state_override::StateOverride,
         transaction::{deployed_contract_address, Transaction as RpcTransaction},
     },
 };
 
-use aptos_block_executor::state_view::DbStateViewAtVersion;
+use aptos_block_executor::state_view::{DbStateViewAtVersion, Order};
 use aptos_api::Context;
 use aptos_api_types::HexEncodedBytes;
 use aptos_evm::{
-    aptos_events_to_evm_events, make_executor, EvmContext, EvmMachine, EvmState, EvmTransaction,
-    ViewWrapper,
+    aptos_events_to_evm_events, created_contract, is_evm_event, make_executor, EvmContext,
+    EvmMachine, EvmState, StorageKey, ViewWrapper,
 };
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_logger::prelude::*;
-use aptos_state_view::TStateView;
+use aptos_state_view::{StateView, TStateView};
+use aptos_storage_interface::MAX_REQUEST_LIMIT;
 use aptos_block_executor::state_view::DbStateView;
 use aptos_types::{
+    account_config::{EvmLinkResource, MoveLinkResource, NewBlockEvent},
     contract_event::ContractEvent,
+    event::EventKey,
+    mempool_status::{MempoolStatus, MempoolStatusCode},
     transaction::{
-        aptos_address_to_eth_address, EthAddress, SignedTransaction, Transaction, TransactionInfo
+        aptos_address_to_eth_address, eth_address_to_aptos_address, SignedTransaction, Transaction,
+        TransactionInfo,
     },
+    vm_status::StatusCode,
 };
 use aptos_block_executor::{
     data_cache::AsMoveResolver, evm_context_loader::ContextView, logging::AdapterLogSchema, aptos_vm::AptosVM,
 };
-use cfx_evm::{ExecutionOutcome, TransactOptions};
+use cfx_evm::{
+    execution::{EstimateRequest, ExecutionError},
+    vm, ExecutionOutcome, State, TransactionInfo as _,
+};
 use cfx_primitives::{
-    transaction::eip155_signature, Action, SignedTransaction as EthSignedTransaction,
-    TransactionWithSignature as EthTransaction,
+    SignedTransaction as EthSignedTransaction, TransactionWithSignature as EthTransaction,
 };
 use cfx_state::{state_trait::StateOpsTrait, CleanupMode};
 use cfx_types::AddressSpaceUtil;
-use ethereum_types::{Bloom, H160, H256, U256, U64};
-use jsonrpsee::core::{async_trait, Error, RpcResult};
-use rlp::Encodable;
+use ethereum_types::{BigEndianHash, Bloom, H160, H256, U256, U64};
+use keccak_hash::KECCAK_EMPTY;
+use move_core_types::{
+    account_address::AccountAddress, move_resource::MoveResource, resolver::ResourceResolver,
+};
+use jsonrpsee::{
+    core::{async_trait, Error, RpcResult},
+    types::error::{CallError, ErrorObject},
+};
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
 use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use pprof::protos::Message;
-use aptos_crypto::HashValue;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_infallible::Mutex;
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_types::transaction::Transaction::UserTransaction;
 use aptos_types::{
@@ -54,6 +76,21 @@ use aptos_types::{
     transaction::Version,
 };
 
+/// The maximum number of blocks covered by one `eth_feeHistory` call.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
+/// JSON-RPC error code of a reverted call, as returned by geth.
+const EXECUTION_REVERTED_CODE: i32 = 3;
+
+/// JSON-RPC error code of a transaction rejected on submission, as returned by geth.
+const INVALID_TRANSACTION_CODE: i32 = -32000;
+
+/// How long a log filter lives without being polled, as in geth.
+const FILTER_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// The maximum number of log filters installed at the same time.
+const MAX_FILTERS: usize = 10_000;
+
 // fn type_of<T>(_: T) -> &'static str {
 //     type_name::<T>()
 // }
@@ -82,9 +119,25 @@ pub(crate) fn gen_li_with_sigs(
 
 
 
+/// A log filter installed by `eth_newFilter`.
+struct PollFilter {
+    filter: Filter,
+    /// The first block whose logs have not been returned by `eth_getFilterChanges` yet.
+    next_block: u64,
+    last_polled: Instant,
+}
+
+impl PollFilter {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.last_polled) >= FILTER_TIMEOUT
+    }
+}
+
 pub struct EthHandler {
-    context: Context,
-    evm_machine: EvmMachine,
+    pub(super) context: Context,
+    pub(super) evm_machine: EvmMachine,
+    filters: Mutex<HashMap<U256, PollFilter>>,
+    next_filter_id: AtomicU64,
 }
 
 impl EthHandler {
@@ -92,12 +145,14 @@ impl EthHandler {
         Self {
             context,
             evm_machine: EvmMachine::new(),
+            filters: Mutex::new(HashMap::new()),
+            next_filter_id: AtomicU64::new(1),
         }
     }
 }
 
 #[async_trait]
-impl EthServer for EthHandler {
+impl EthServer for Arc<EthHandler> {
     async fn net_version(&self) -> RpcResult<String> {
         let id = self.context.evm_chain_id();
         Ok(format!("{}", id))
@@ -174,14 +229,23 @@ impl EthServer for EthHandler {
     }
 
     async fn send_raw_transaction(&self, bytes: HexEncodedBytes) -> RpcResult<H256> {
-        let eth_tx: EthTransaction = rlp::decode(bytes.inner()).map_err(Error::to_call_error)?;
+        let eth_tx = EthTransaction::from_raw(bytes.inner()).map_err(Error::to_call_error)?;
         let tx_hash = eth_tx.hash();
-        // println!("{}", type_of(&eth_tx));
-        if let Err(e) = self.context.submit_transaction(eth_tx.try_into()?).await {
-            info!("eth submit tx error: e={:?}", e);
-            return Err(e.into());
+        let txn = SignedTransaction::try_from(eth_tx)
+            .map_err(|e| invalid_transaction_error(e.to_string(), None))?;
+        match self.context.submit_transaction(txn).await {
+            Ok((mempool_status, _)) if mempool_status.code == MempoolStatusCode::Accepted => {
+                Ok(tx_hash)
+            },
+            Ok((mempool_status, vm_status)) => {
+                info!("eth submit tx rejected: status={}", mempool_status);
+                Err(submission_error(mempool_status, vm_status))
+            },
+            Err(e) => {
+                info!("eth submit tx error: e={:?}", e);
+                Err(e.into())
+            },
         }
-        Ok(tx_hash)
     }
 
     async fn transaction_receipt(&self, tx_hash: H256) -> RpcResult<Option<Receipt>> {
@@ -194,7 +258,9 @@ impl EthServer for EthHandler {
                 } else {
                     Some(serde_json::to_string(info.status())?)
                 };
-                let events = aptos_events_to_evm_events(events)?;
+                let contracts_created = events.iter().filter_map(created_contract).collect();
+                let events =
+                    aptos_events_to_evm_events(events.into_iter().filter(is_evm_event).collect())?;
                 let mut logs_bloom = Bloom::default();
                 for log in &events {
                     logs_bloom.accrue_bloom(&log.bloom());
@@ -215,10 +281,12 @@ impl EthServer for EthHandler {
                     cumulative_gas_used: Default::default(),
                     gas_used: info.gas_used().into(),
                     contract_address: eth_tx.creates,
+                    contracts_created,
                     logs_bloom,
                     // TODO(lpl): Check if all cases are covered.
                     status_code: eth_tx.status.expect("executed"),
                     effective_gas_price: eth_tx.gas_price,
+                    transaction_type: eth_tx.transaction_type.unwrap_or_default(),
                     // TODO(lpl): Make error msg compatible.
                     tx_exec_error_msg,
                 }))
@@ -246,63 +314,35 @@ impl EthServer for EthHandler {
 
     async fn call(
         &self,
-        mut request: CallRequest,
+        request: CallRequest,
         maybe_block_number: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
     ) -> RpcResult<HexEncodedBytes> {
         let state_version = self.get_version_at_block_number(maybe_block_number)?;
         let state_view = self.context.db.reader.state_view_at_version(Some(state_version))?;
-        let context = self.get_evm_context(&state_view);
-        let mut view_wrapper = ViewWrapper {
-            inner: &state_view.as_move_resolver(),
-            cache: Default::default(),
-        };
-        let mut state = EvmState::new(&mut view_wrapper);
-
-        if request.from.is_none() {
-            let random_address = EthAddress::random();
-            state
-                .state
-                .add_balance(
-                    &random_address.with_evm_space(),
-                    &(U256::from(5_000_000_000u64) * U256::from(1_000_000_000_000_000_000u64)),
-                    CleanupMode::ForceCreate,
-                    0.into(),
-                )
-                .map_err(|e| Error::Custom(e.to_string()))?;
-            request.from = Some(random_address);
-        }
-        if request.nonce.is_none() {
-            request.nonce = Some(
-                state
-                    .state
-                    .nonce(&request.from.unwrap().with_evm_space())
-                    .map_err(|e| Error::Custom(e.to_string()))?,
-            );
-        }
-        let eth_tx = sign_call(self.context.evm_chain_id() as u32, request)?;
-
-        let mut executor = make_executor(&self.evm_machine, &context, &mut state);
-        let mut options = TransactOptions::exec_with_no_tracing();
-        options.check_settings.real_execution = false;
-        let output = executor
-            .transact(
-                &EvmTransaction::try_from(&SignedTransaction::try_from(eth_tx)?).expect("eth tx"),
-                options,
-            )
-            .map_err(|e| Error::Custom(e.to_string()))?;
-        match output {
-            ExecutionOutcome::Finished(executed) => Ok(executed.output.into()),
-            e => Err(Error::Custom(format!("{:?}", e))),
-        }
+        let output = virtual_call(
+            &self.evm_machine,
+            &state_view,
+            self.context.evm_chain_id() as u32,
+            request,
+            state_override.as_ref(),
+        )?;
+        Ok(output.into())
     }
 
     async fn estimate_gas(
         &self,
-        _request: CallRequest,
-        _maybe_block_number: Option<BlockNumber>,
+        request: CallRequest,
+        maybe_block_number: Option<BlockNumber>,
     ) -> RpcResult<U256> {
-        // FIXME(lpl)
-        Ok(3_000_000.into()) // magic constant?
+        let state_version = self.get_version_at_block_number(maybe_block_number)?;
+        let state_view = self.context.db.reader.state_view_at_version(Some(state_version))?;
+        estimate_gas_limit(
+            &self.evm_machine,
+            &state_view,
+            self.context.evm_chain_id() as u32,
+            request,
+        )
     }
 
     async fn chain_id(&self) -> RpcResult<Option<U64>> {
@@ -310,14 +350,7 @@ impl EthServer for EthHandler {
     }
 
     async fn block_number(&self) -> RpcResult<U256> {
-        let version = self
-            .context
-            .db
-            .reader.get_latest_ledger_info()?
-            .ledger_info()
-            .version();
-        let (_, _, new_block_event) = self.context.db.reader.get_block_info_by_version(version)?;
-        Ok(new_block_event.height().into())
+        Ok(self.get_latest_block_height()?.into())
     }
 
     async fn block_by_number(
@@ -368,6 +401,93 @@ impl EthServer for EthHandler {
         Ok(code.into())
     }
 
+    async fn storage_at(
+        &self,
+        address: H160,
+        position: U256,
+        maybe_block_number: Option<BlockNumber>,
+    ) -> RpcResult<H256> {
+        let state_version = self.get_version_at_block_number(maybe_block_number)?;
+        let state_view = self.context.db.reader.state_view_at_version(Some(state_version))?;
+        let mut view_wrapper = ViewWrapper {
+            inner: &state_view.as_move_resolver(),
+            cache: Default::default(),
+        };
+        let state = EvmState::new(&mut view_wrapper);
+        let value = state
+            .state
+            .storage_at(
+                &address.with_evm_space(),
+                H256::from_uint(&position).as_bytes(),
+            )
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(H256::from_uint(&value))
+    }
+
+    async fn proof(
+        &self,
+        address: H160,
+        storage_keys: Vec<H256>,
+        maybe_block_number: Option<BlockNumber>,
+    ) -> RpcResult<AccountProof> {
+        let state_version = self.get_version_at_block_number(maybe_block_number)?;
+        let ledger_version = self.context.db.reader.get_latest_version()?;
+        // The proofs are only available at state checkpoints, which end every block.
+        let storage_hash: H256 = self
+            .context
+            .db
+            .reader
+            .get_transaction_by_version(state_version, ledger_version, false)?
+            .proof
+            .transaction_info
+            .ensure_state_checkpoint_hash()?
+            .into();
+        let state_view = self.context.db.reader.state_view_at_version(Some(state_version))?;
+        let mut view_wrapper = ViewWrapper {
+            inner: &state_view.as_move_resolver(),
+            cache: Default::default(),
+        };
+        let state = EvmState::new(&mut view_wrapper);
+        let evm_address = address.with_evm_space();
+        let storage_proof = storage_keys
+            .into_iter()
+            .map(|key| {
+                let value = state
+                    .state
+                    .storage_at(&evm_address, key.as_bytes())
+                    .map_err(|e| Error::Custom(e.to_string()))?;
+                let proof =
+                    self.get_state_proof(StorageKey::storage_key(address, &key), state_version)?;
+                Ok(StorageProof {
+                    key,
+                    value,
+                    proof: vec![proof],
+                })
+            })
+            .collect::<RpcResult<_>>()?;
+        Ok(AccountProof {
+            address,
+            account_proof: vec![
+                self.get_state_proof(StorageKey::account_key(address), state_version)?
+            ],
+            balance: state
+                .state
+                .balance(&evm_address)
+                .map_err(|e| Error::Custom(e.to_string()))?,
+            code_hash: state
+                .state
+                .code_hash(&evm_address)
+                .map_err(|e| Error::Custom(e.to_string()))?
+                .unwrap_or(KECCAK_EMPTY),
+            nonce: state
+                .state
+                .nonce(&evm_address)
+                .map_err(|e| Error::Custom(e.to_string()))?,
+            storage_hash,
+            storage_proof,
+        })
+    }
+
     async fn transaction_count(
         &self,
         address: H160,
@@ -387,17 +507,235 @@ impl EthServer for EthHandler {
             .map_err(|e| Error::Custom(e.to_string()))
     }
 
+    async fn linked_move_account(
+        &self,
+        address: H160,
+        maybe_block_number: Option<BlockNumber>,
+    ) -> RpcResult<Option<H256>> {
+        let state_version = self.get_version_at_block_number(maybe_block_number)?;
+        let state_view = self.context.db.reader.state_view_at_version(Some(state_version))?;
+        let link: Option<MoveLinkResource> =
+            get_resource(&state_view, eth_address_to_aptos_address(&address))?;
+        Ok(link.map(|link| H256(link.account().into_bytes())))
+    }
+
+    async fn linked_evm_address(
+        &self,
+        account: H256,
+        maybe_block_number: Option<BlockNumber>,
+    ) -> RpcResult<Option<H160>> {
+        let state_version = self.get_version_at_block_number(maybe_block_number)?;
+        let state_view = self.context.db.reader.state_view_at_version(Some(state_version))?;
+        let link: Option<EvmLinkResource> =
+            get_resource(&state_view, AccountAddress::new(account.0))?;
+        Ok(link.and_then(|link| link.evm_address()))
+    }
+
     async fn gas_price(&self) -> RpcResult<U256> {
         Ok(1.into())
     }
 
+    async fn fee_history(
+        &self,
+        block_count: U256,
+        newest_block: BlockNumber,
+        percentiles: Option<Vec<f64>>,
+    ) -> RpcResult<FeeHistory> {
+        if let Some(percentiles) = &percentiles {
+            let in_range = percentiles.iter().all(|p| (0.0..=100.0).contains(p));
+            if !in_range || percentiles.windows(2).any(|w| w[0] > w[1]) {
+                return Err(Error::Custom(
+                    "reward percentiles must be increasing values in [0, 100]".to_string(),
+                ));
+            }
+        }
+        let block_count = block_count.min(MAX_FEE_HISTORY_BLOCKS.into()).as_u64();
+        if block_count == 0 {
+            return Ok(FeeHistory::default());
+        }
+        let newest_block = self
+            .get_block_height(Some(newest_block))?
+            .min(self.get_latest_block_height()?);
+        let oldest_block = (newest_block + 1).saturating_sub(block_count);
+
+        let mut base_fee_per_gas = vec![];
+        let mut gas_used_ratio = vec![];
+        let mut reward = vec![];
+        for height in oldest_block..=newest_block {
+            let (start_version, end_version, _) =
+                self.context.db.reader.get_block_info_by_height(height)?;
+            let state_view = self
+                .context
+                .db
+                .reader
+                .state_view_at_version(Some(start_version))?;
+            let evm_context = self.get_evm_context(&state_view);
+            let base_fee = evm_context.base_fee_per_gas();
+            let limit = end_version - start_version + 1;
+            let transactions = self
+                .context
+                .db
+                .reader
+                .get_transaction_iterator(start_version, limit)?;
+            let infos = self
+                .context
+                .db
+                .reader
+                .get_transaction_info_iterator(start_version, limit)?;
+            // Rewards are priority fees in wei, weighted by the EVM gas the transactions used.
+            let mut eth_txs = vec![];
+            for (txn, info) in transactions.zip(infos) {
+                if let Transaction::UserTransaction(user_tx) = txn? {
+                    if let Some(eth_tx) = user_tx.eth_transaction() {
+                        let priority_fee = priority_fee_per_gas(
+                            *eth_tx.gas_price(),
+                            eth_tx.max_priority_fee_per_gas().copied(),
+                            base_fee,
+                        );
+                        eth_txs.push((priority_fee, info?.gas_used()));
+                    }
+                }
+            }
+            let gas_used: u64 = eth_txs.iter().map(|(_, gas_used)| gas_used).sum();
+            let block_gas_limit = evm_context.block_gas_limit();
+            base_fee_per_gas.push(base_fee);
+            gas_used_ratio.push(gas_used as f64 / block_gas_limit.as_u64().max(1) as f64);
+            if let Some(percentiles) = &percentiles {
+                reward.push(reward_percentiles(eth_txs, percentiles));
+            }
+        }
+        // The base fee does not depend on the gas used, so the next block pays the current one.
+        let (_, latest_version, _) = self
+            .context
+            .db
+            .reader
+            .get_block_info_by_height(newest_block)?;
+        let state_view = self
+            .context
+            .db
+            .reader
+            .state_view_at_version(Some(latest_version))?;
+        base_fee_per_gas.push(self.get_evm_context(&state_view).base_fee_per_gas());
+        Ok(FeeHistory {
+            oldest_block: oldest_block.into(),
+            base_fee_per_gas,
+            gas_used_ratio,
+            reward: percentiles.map(|_| reward),
+        })
+    }
+
     async fn transaction_by_hash(&self, h: H256) -> RpcResult<Option<RpcTransaction>> {
         Ok(self.transaction_with_info_by_hash(h)?.map(|(tx, _, _)| tx))
     }
+
+    async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        let (from_block, to_block) = match filter.block_hash {
+            Some(block_hash) => {
+                if filter.from_block.is_some() || filter.to_block.is_some() {
+                    return Err(Error::Custom(
+                        "blockHash cannot be used with fromBlock or toBlock".to_string(),
+                    ));
+                }
+                let height = self.get_block_height(Some(BlockNumber::Hash {
+                    hash: block_hash,
+                    require_canonical: false,
+                }))?;
+                (height, height)
+            },
+            None => (
+                self.get_block_height(filter.from_block.clone())?,
+                self.get_block_height(filter.to_block.clone())?,
+            ),
+        };
+        let to_block = to_block.min(self.get_latest_block_height()?);
+        if from_block > to_block {
+            return Ok(vec![]);
+        }
+        let max_block_range = self.context.node_config.eth_api.max_log_block_range;
+        if to_block - from_block >= max_block_range {
+            return Err(Error::Custom(format!(
+                "query exceeds the limit of {} blocks",
+                max_block_range
+            )));
+        }
+        self.logs_in_block_range(&filter, from_block, to_block)
+    }
+
+    async fn new_filter(&self, filter: Filter) -> RpcResult<U256> {
+        if filter.block_hash.is_some() {
+            return Err(Error::Custom(
+                "blockHash is not supported by log filters".to_string(),
+            ));
+        }
+        let next_block = match filter.from_block {
+            Some(BlockNumber::Num(_) | BlockNumber::Earliest | BlockNumber::Hash { .. }) => {
+                self.get_block_height(filter.from_block.clone())?
+            },
+            _ => self.get_latest_block_height()? + 1,
+        };
+        let now = Instant::now();
+        let mut filters = self.filters.lock();
+        filters.retain(|_, poll_filter| !poll_filter.is_expired(now));
+        if filters.len() >= MAX_FILTERS {
+            return Err(Error::Custom(format!(
+                "too many filters, the limit is {}",
+                MAX_FILTERS
+            )));
+        }
+        let id: U256 = self.next_filter_id.fetch_add(1, Ordering::Relaxed).into();
+        filters.insert(id, PollFilter {
+            filter,
+            next_block,
+            last_polled: now,
+        });
+        Ok(id)
+    }
+
+    async fn filter_changes(&self, id: U256) -> RpcResult<Vec<Log>> {
+        // The filter is copied out so that the lock is not held across the database reads.
+        let (filter, from_block) = {
+            let now = Instant::now();
+            let mut filters = self.filters.lock();
+            match filters.get_mut(&id) {
+                Some(poll_filter) if !poll_filter.is_expired(now) => {
+                    poll_filter.last_polled = now;
+                    (poll_filter.filter.clone(), poll_filter.next_block)
+                },
+                Some(_) => {
+                    filters.remove(&id);
+                    return Err(Error::Custom("filter not found".to_string()));
+                },
+                None => return Err(Error::Custom("filter not found".to_string())),
+            }
+        };
+        let mut to_block = self.get_latest_block_height()?;
+        if let Some(BlockNumber::Num(height)) = filter.to_block {
+            to_block = to_block.min(height);
+        }
+        if from_block > to_block {
+            return Ok(vec![]);
+        }
+        // Long-unpolled filters catch up over several polls.
+        let max_block_range = self.context.node_config.eth_api.max_log_block_range;
+        let to_block = to_block.min(from_block.saturating_add(max_block_range.max(1) - 1));
+        let logs = self.logs_in_block_range(&filter, from_block, to_block)?;
+        // A concurrent poll of the same filter which got there first returns these logs.
+        match self.filters.lock().get_mut(&id) {
+            Some(poll_filter) if poll_filter.next_block == from_block => {
+                poll_filter.next_block = to_block + 1;
+                Ok(logs)
+            },
+            _ => Ok(vec![]),
+        }
+    }
+
+    async fn uninstall_filter(&self, id: U256) -> RpcResult<bool> {
+        Ok(self.filters.lock().remove(&id).is_some())
+    }
 }
 
 impl EthHandler {
-    fn get_version_at_block_number(
+    pub(super) fn get_version_at_block_number(
         &self,
         maybe_block_number: Option<BlockNumber>,
     ) -> anyhow::Result<Version> {
@@ -419,19 +757,244 @@ impl EthHandler {
                 let (_, end_version, _) = self.context.db.reader.get_block_info_by_height(block_number)?;
                 end_version
             },
-            Some(BlockNumber::Hash { .. }) => {
-                todo!()
+            Some(BlockNumber::Hash { hash, .. }) => {
+                let version = self
+                    .context
+                    .db
+                    .reader
+                    .get_block_version_by_hash(hash.into())?
+                    .ok_or_else(|| anyhow::anyhow!("unknown block hash {:?}", hash))?;
+                let (_, end_version, _) =
+                    self.context.db.reader.get_block_info_by_version(version)?;
+                end_version
             },
         };
         Ok(state_version)
     }
 
-    fn get_evm_context(&self, state_view: &DbStateView) -> EvmContext {
-        let log_context = AdapterLogSchema::new(state_view.id(), 0);
-        let vm = AptosVM::new(&state_view);
-        let data_cache = state_view.as_move_resolver();
-        let context_reader = ContextView::new(&vm, &data_cache, &log_context);
-        self.evm_machine.make_context(&context_reader)
+    fn get_latest_block_height(&self) -> anyhow::Result<u64> {
+        let version = self
+            .context
+            .db
+            .reader.get_latest_ledger_info()?
+            .ledger_info()
+            .version();
+        let (_, _, new_block_event) = self.context.db.reader.get_block_info_by_version(version)?;
+        Ok(new_block_event.height())
+    }
+
+    fn get_block_height(&self, maybe_block_number: Option<BlockNumber>) -> anyhow::Result<u64> {
+        match maybe_block_number {
+            Some(BlockNumber::Num(block_number)) => Ok(block_number),
+            Some(BlockNumber::Earliest) => Ok(0),
+            Some(BlockNumber::Hash { hash, .. }) => {
+                let version = self
+                    .context
+                    .db
+                    .reader
+                    .get_block_version_by_hash(hash.into())?
+                    .ok_or_else(|| anyhow::anyhow!("unknown block hash {:?}", hash))?;
+                let (_, _, new_block_event) =
+                    self.context.db.reader.get_block_info_by_version(version)?;
+                Ok(new_block_event.height())
+            },
+            None
+            | Some(
+                BlockNumber::Latest
+                | BlockNumber::Finalized
+                | BlockNumber::Pending
+                | BlockNumber::Safe,
+            ) => self.get_latest_block_height(),
+        }
+    }
+
+    /// Returns the EVM logs matching `filter` in the blocks `from_block..=to_block`, failing once
+    /// there are more than `max_logs_per_request` of them.
+    ///
+    /// Filters which pin down the event streams of their logs are served from the event index;
+    /// the others scan every event in the range.
+    fn logs_in_block_range(
+        &self,
+        filter: &Filter,
+        from_block: u64,
+        to_block: u64,
+    ) -> RpcResult<Vec<Log>> {
+        let mut logs = vec![];
+        let event_keys = match filter.event_keys() {
+            Some(event_keys) => event_keys,
+            None => {
+                for height in from_block..=to_block {
+                    let (start_version, end_version, new_block_event) =
+                        self.context.db.reader.get_block_info_by_height(height)?;
+                    self.append_block_logs(
+                        filter,
+                        start_version,
+                        end_version,
+                        &new_block_event,
+                        None,
+                        &mut logs,
+                    )?;
+                }
+                return Ok(logs);
+            },
+        };
+
+        let reader = &self.context.db.reader;
+        let (from_version, _, _) = reader.get_block_info_by_height(from_block)?;
+        let (_, to_version, _) = reader.get_block_info_by_height(to_block)?;
+        let versions = self.versions_with_events(&event_keys, from_version, to_version)?;
+        let mut next_version = from_version;
+        while let Some(&version) = versions.range(next_version..).next() {
+            let (start_version, end_version, new_block_event) =
+                reader.get_block_info_by_version(version)?;
+            self.append_block_logs(
+                filter,
+                start_version,
+                end_version,
+                &new_block_event,
+                Some(&versions),
+                &mut logs,
+            )?;
+            next_version = end_version + 1;
+        }
+        Ok(logs)
+    }
+
+    /// Returns the versions of the transactions in `from_version..=to_version` which emitted an
+    /// event to one of `event_keys`.
+    fn versions_with_events(
+        &self,
+        event_keys: &[EventKey],
+        from_version: Version,
+        to_version: Version,
+    ) -> anyhow::Result<BTreeSet<Version>> {
+        let reader = &self.context.db.reader;
+        let mut versions = BTreeSet::new();
+        for event_key in event_keys {
+            let mut seq_num =
+                match reader.get_event_seq_num_at_or_after_version(event_key, from_version)? {
+                    Some(seq_num) => seq_num,
+                    None => continue,
+                };
+            loop {
+                let events = reader.get_events(
+                    event_key,
+                    seq_num,
+                    Order::Ascending,
+                    MAX_REQUEST_LIMIT,
+                    to_version,
+                )?;
+                versions.extend(events.iter().map(|event| event.transaction_version));
+                if (events.len() as u64) < MAX_REQUEST_LIMIT {
+                    break;
+                }
+                seq_num += MAX_REQUEST_LIMIT;
+            }
+        }
+        Ok(versions)
+    }
+
+    /// Appends the EVM logs matching `filter` of the block `start_version..=end_version` to
+    /// `logs`. If `versions` is given, only the transactions at those versions are looked at.
+    fn append_block_logs(
+        &self,
+        filter: &Filter,
+        start_version: Version,
+        end_version: Version,
+        new_block_event: &NewBlockEvent,
+        versions: Option<&BTreeSet<Version>>,
+        logs: &mut Vec<Log>,
+    ) -> RpcResult<()> {
+        let max_logs = self.context.node_config.eth_api.max_logs_per_request;
+        let block_hash: H256 = new_block_event.hash()?.into();
+        // The log index counts the logs of the preceding transactions of the block.
+        let last_version = match versions {
+            Some(versions) => match versions.range(start_version..=end_version).next_back() {
+                Some(version) => *version,
+                None => return Ok(()),
+            },
+            None => end_version,
+        };
+        let events = self
+            .context
+            .db
+            .reader
+            .get_events_iterator(start_version, last_version - start_version + 1)?;
+        let mut log_index = 0u64;
+        for (offset, txn_events) in events.enumerate() {
+            let version = start_version + offset as u64;
+            let txn_events: Vec<_> = txn_events?.into_iter().filter(is_evm_event).collect();
+            if txn_events.is_empty() {
+                continue;
+            }
+            let entries = aptos_events_to_evm_events(txn_events)?;
+            if versions.map_or(false, |versions| !versions.contains(&version)) {
+                log_index += entries.len() as u64;
+                continue;
+            }
+            let mut txn_hash = None;
+            for (transaction_log_index, entry) in entries.into_iter().enumerate() {
+                if filter.matches(&entry) {
+                    if logs.len() == max_logs {
+                        return Err(Error::Custom(format!(
+                            "query returns more than {} results",
+                            max_logs
+                        )));
+                    }
+                    let transaction_hash = match txn_hash {
+                        Some(txn_hash) => txn_hash,
+                        None => *txn_hash.insert(self.log_transaction_hash(version)?),
+                    };
+                    let mut log = Log::try_from(entry)?;
+                    log.block_hash = block_hash;
+                    log.block_number = new_block_event.height().into();
+                    log.transaction_hash = transaction_hash;
+                    log.transaction_index = offset.into();
+                    log.log_index = Some(log_index.into());
+                    log.transaction_log_index = Some(transaction_log_index.into());
+                    logs.push(log);
+                }
+                log_index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// The transaction hash of the logs of the transaction at `version`. Logs of Move
+    /// transactions calling into the EVM carry the Aptos hash.
+    fn log_transaction_hash(&self, version: Version) -> anyhow::Result<H256> {
+        let txn = self
+            .context
+            .db
+            .reader
+            .get_transaction_iterator(version, 1)?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("missing transaction at version {}", version))??;
+        Ok(match &txn {
+            Transaction::UserTransaction(user_tx) => {
+                user_tx.eth_transaction().map(|eth_tx| eth_tx.hash())
+            },
+            _ => None,
+        }
+        .unwrap_or_else(|| txn.hash().into()))
+    }
+
+    /// Returns the BCS encoded sparse Merkle proof of an EVM state key at a state checkpoint.
+    fn get_state_proof(
+        &self,
+        key: StorageKey,
+        version: Version,
+    ) -> anyhow::Result<HexEncodedBytes> {
+        let (_, proof) = self
+            .context
+            .db
+            .reader
+            .get_state_value_with_proof_by_version(&key.into(), version)?;
+        Ok(bcs::to_bytes(&proof)?.into())
+    }
+
+    pub(super) fn get_evm_context<S: StateView>(&self, state_view: &S) -> EvmContext {
+        evm_context(&self.evm_machine, state_view)
     }
 
     fn block_by_version(&self, version: Version, include_txs: bool) -> RpcResult<Option<Block>> {
@@ -462,6 +1025,13 @@ impl EthHandler {
                 _ => None,
             })
             .collect();
+        // The base fee of the block, which the context at its first version holds.
+        let state_view = self
+            .context
+            .db
+            .reader
+            .state_view_at_version(Some(start_version))?;
+        let base_fee = self.get_evm_context(&state_view).base_fee_per_gas();
         let block_transactions = if include_txs {
             BlockTransactions::Full(
                 txs_with_status
@@ -480,9 +1050,13 @@ impl EthHandler {
                         } else {
                             (Some(0.into()), None)
                         };
-                        Ok(RpcTransaction::from_signed(
-                            &signed_tx, block_info, exec_info,
-                        ))
+                        let mut rpc_tx =
+                            RpcTransaction::from_signed(&signed_tx, block_info, exec_info);
+                        // A mined dynamic fee transaction reports the price it paid.
+                        if signed_tx.max_priority_fee_per_gas().is_some() {
+                            rpc_tx.gas_price = signed_tx.effective_gas_price(&base_fee);
+                        }
+                        Ok(rpc_tx)
                     })
                     .collect::<RpcResult<_>>()?,
             )
@@ -511,7 +1085,7 @@ impl EthHandler {
             timestamp: new_block_event.proposed_time().into(),
             difficulty: Default::default(),
             total_difficulty: Default::default(),
-            base_fee_per_gas: None,
+            base_fee_per_gas: Some(base_fee),
             uncles: vec![],
             transactions: block_transactions,
             size: Default::default(),
@@ -543,48 +1117,28 @@ impl EthHandler {
                 let eth_tx = EthSignedTransaction::new(public, eth_tx);
                 trace!("eth_tx is {:?}", eth_tx);
                 // TODO(lpl): Check if we need to double-check the sender in `user_tx`.
-                let from = eth_tx.sender;
-                let to = match eth_tx.action() {
-                    Action::Create => None,
-                    Action::Call(address) => Some(*address),
-                };
-                let contract_address;
-                let status_code;
-                if tx.proof.transaction_info.status().is_success() {
-                    contract_address = deployed_contract_address(&eth_tx);
-                    status_code = 1.into();
+                let exec_info = if tx.proof.transaction_info.status().is_success() {
+                    (Some(1.into()), deployed_contract_address(&eth_tx))
                 } else {
-                    contract_address = None;
-                    status_code = 0.into();
-                }
-                let rpc_tx = RpcTransaction {
-                    hash: tx_hash,
-                    // TODO(lpl): Should Move transactions be counted?
-                    transaction_index: Some((tx.version - block_start_version).into()),
-                    block_hash: Some(block_info.hash()?.into()),
-                    from,
-                    to,
-                    value: *eth_tx.value(),
-                    gas_price: *eth_tx.gas_price(),
-                    max_fee_per_gas: None,
-                    gas: *eth_tx.gas(),
-                    input: eth_tx.data().clone().into(),
-                    creates: contract_address,
-                    raw: eth_tx.transaction.transaction.rlp_bytes().to_vec().into(),
-                    public_key: eth_tx.public().clone(),
-                    chain_id: eth_tx.chain_id().map(Into::into),
-                    standard_v: Some(eth_tx.signature().v().into()),
-                    v: eip155_signature::add_chain_replay_protection(
-                        eth_tx.signature().v(),
-                        eth_tx.chain_id().map(|x| x as u64),
-                    )
-                    .into(),
-                    r: eth_tx.signature().r().into(),
-                    s: eth_tx.signature().s().into(),
-                    block_number: Some(block_info.height().into()),
-                    nonce: *eth_tx.nonce(),
-                    status: Some(status_code),
+                    (Some(0.into()), None)
                 };
+                let block_info = (
+                    Some(block_info.hash()?.into()),
+                    Some(block_info.height().into()),
+                    // TODO(lpl): Should Move transactions be counted?
+                    Some((tx.version - block_start_version).into()),
+                );
+                let mut rpc_tx = RpcTransaction::from_signed(&eth_tx, block_info, exec_info);
+                // A mined dynamic fee transaction reports the price it paid.
+                if eth_tx.max_priority_fee_per_gas().is_some() {
+                    let state_view = self
+                        .context
+                        .db
+                        .reader
+                        .state_view_at_version(Some(tx.version))?;
+                    let base_fee = self.get_evm_context(&state_view).base_fee_per_gas();
+                    rpc_tx.gas_price = eth_tx.effective_gas_price(&base_fee);
+                }
                 Ok(Some((
                     rpc_tx,
                     tx.proof.transaction_info,
@@ -597,3 +1151,281 @@ impl EthHandler {
         }
     }
 }
+
+/// Reads the Move resource `T` held by `address`.
+fn get_resource<T: MoveResource>(
+    state_view: &DbStateView,
+    address: AccountAddress,
+) -> RpcResult<Option<T>> {
+    let bytes = state_view
+        .as_move_resolver()
+        .get_resource(&address, &T::struct_tag())
+        .map_err(|e| Error::Custom(e.to_string()))?;
+    bytes
+        .map(|bytes| bcs::from_bytes(&bytes))
+        .transpose()
+        .map_err(|e| Error::Custom(e.to_string()))
+}
+
+/// Returns the EVM context of the block after `state_view`.
+pub(super) fn evm_context<S: StateView>(machine: &EvmMachine, state_view: &S) -> EvmContext {
+    let log_context = AdapterLogSchema::new(state_view.id(), 0);
+    let vm = AptosVM::new(state_view);
+    let data_cache = state_view.as_move_resolver();
+    let context_reader = ContextView::new(&vm, &data_cache, &log_context);
+    machine.make_context(&context_reader)
+}
+
+/// Runs a transaction on top of `state_view` and the state overrides without committing it,
+/// with `callMove`s running in Move sessions which are thrown away afterwards.
+pub(super) fn transact_virtual<S: StateView>(
+    machine: &EvmMachine,
+    state_view: &S,
+    vm: &AptosVM,
+    context: &EvmContext,
+    tx: EthSignedTransaction,
+    request: EstimateRequest,
+    state_override: Option<&StateOverride>,
+) -> RpcResult<ExecutionOutcome> {
+    let log_context = AdapterLogSchema::new(state_view.id(), 0);
+    let data_cache = state_view.as_move_resolver();
+    let call_move_handler =
+        vm.make_cross_space_handler(&data_cache, machine, context, &log_context);
+    let mut view_wrapper = ViewWrapper {
+        inner: &data_cache,
+        cache: Default::default(),
+    };
+    let mut state = EvmState::new_with_move_vm(&mut view_wrapper, &call_move_handler);
+    if let Some(state_override) = state_override {
+        apply_state_override(&mut state.state, state_override)?;
+    }
+    let mut executor = make_executor(machine, context, &mut state);
+    executor
+        .transact_virtual(tx, request)
+        .map_err(|e| Error::Custom(e.to_string()))
+}
+
+/// Runs `request` on top of `state_view` and the state overrides as `eth_call` does, returning
+/// the output of the call.
+pub(super) fn virtual_call<S: StateView>(
+    machine: &EvmMachine,
+    state_view: &S,
+    chain_id: u32,
+    request: CallRequest,
+    state_override: Option<&StateOverride>,
+) -> RpcResult<Vec<u8>> {
+    let context = evm_context(machine, state_view);
+    let vm = AptosVM::new(state_view);
+    let estimate_request = EstimateRequest {
+        has_sender: request.from.is_some(),
+        has_gas_limit: request.gas.is_some(),
+        has_gas_price: request.gas_price.is_some(),
+        has_nonce: request.nonce.is_some(),
+        has_storage_limit: false,
+    };
+    let eth_tx = sign_call(chain_id, request)?;
+    match transact_virtual(
+        machine,
+        state_view,
+        &vm,
+        &context,
+        eth_tx,
+        estimate_request,
+        state_override,
+    )? {
+        ExecutionOutcome::Finished(executed) => Ok(executed.output),
+        outcome => Err(execution_error(outcome)),
+    }
+}
+
+/// Returns the lowest gas limit `request` succeeds with on top of `state_view`, found by a
+/// binary search over dry runs.
+pub(super) fn estimate_gas_limit<S: StateView>(
+    machine: &EvmMachine,
+    state_view: &S,
+    chain_id: u32,
+    request: CallRequest,
+) -> RpcResult<U256> {
+    let context = evm_context(machine, state_view);
+    let vm = AptosVM::new(state_view);
+    let estimate_request = EstimateRequest {
+        has_sender: request.from.is_some(),
+        has_gas_limit: true,
+        has_gas_price: request.gas_price.is_some(),
+        has_nonce: request.nonce.is_some(),
+        has_storage_limit: false,
+    };
+    let cap = request
+        .gas
+        .unwrap_or_else(|| context.block_gas_limit())
+        .min(MAX_GAS_CALL_REQUEST.into());
+    let run = |gas: U256| -> RpcResult<ExecutionOutcome> {
+        let mut request = request.clone();
+        request.gas = Some(gas);
+        let tx = sign_call(chain_id, request)?;
+        transact_virtual(
+            machine,
+            state_view,
+            &vm,
+            &context,
+            tx,
+            estimate_request,
+            None,
+        )
+    };
+
+    let executed = match run(cap)? {
+        ExecutionOutcome::Finished(executed) => executed,
+        outcome => return Err(execution_error(outcome)),
+    };
+    // Any limit below the gas used fails, and the limit estimated by the gas observer of the
+    // EVM, which accounts for the 63/64 rule, usually passes.
+    let mut lo = executed.gas_used.saturating_sub(1.into());
+    let mut hi = cap;
+    if let Some(estimated) = executed
+        .estimated_gas_limit
+        .filter(|gas| *gas > lo && *gas < hi)
+    {
+        match run(estimated)? {
+            ExecutionOutcome::Finished(_) => hi = estimated,
+            _ => lo = estimated,
+        }
+    }
+    while lo + 1 < hi {
+        let mid = (lo + hi) / 2;
+        match run(mid)? {
+            ExecutionOutcome::Finished(_) => hi = mid,
+            _ => lo = mid,
+        }
+    }
+    Ok(hi)
+}
+
+/// Overrides the balance, nonce, code and storage of accounts for a call. Replacing the whole
+/// storage of an account recreates it, so that its other storage slots read as zero.
+fn apply_state_override(state: &mut State, state_override: &StateOverride) -> RpcResult<()> {
+    fn db_error(e: impl ToString) -> Error {
+        Error::Custom(e.to_string())
+    }
+
+    for (address, account) in state_override {
+        let address = address.with_evm_space();
+        if account.state.is_some() && account.state_diff.is_some() {
+            return Err(Error::Custom(format!(
+                "both state and stateDiff are set for {:?}",
+                address.address
+            )));
+        }
+        let balance = match account.balance {
+            Some(balance) => balance,
+            None => state.balance(&address).map_err(db_error)?,
+        };
+        let nonce = match account.nonce {
+            Some(nonce) => nonce.as_u64().into(),
+            None => state.nonce(&address).map_err(db_error)?,
+        };
+        let code = match &account.code {
+            Some(code) => Some(code.0.clone()),
+            None => state
+                .code(&address)
+                .map_err(db_error)?
+                .map(|code| (*code).clone()),
+        };
+        if account.state.is_some() {
+            state
+                .new_contract(&address, balance, nonce, None)
+                .map_err(db_error)?;
+        } else {
+            state
+                .add_balance(&address, &U256::zero(), CleanupMode::ForceCreate, nonce)
+                .map_err(db_error)?;
+            let current_balance = state.balance(&address).map_err(db_error)?;
+            if balance > current_balance {
+                state
+                    .add_balance(
+                        &address,
+                        &(balance - current_balance),
+                        CleanupMode::NoEmpty,
+                        nonce,
+                    )
+                    .map_err(db_error)?;
+            } else if balance < current_balance {
+                state
+                    .sub_balance(
+                        &address,
+                        &(current_balance - balance),
+                        &mut CleanupMode::NoEmpty,
+                    )
+                    .map_err(db_error)?;
+            }
+            state.set_nonce(&address, &nonce).map_err(db_error)?;
+        }
+        if let Some(code) = code {
+            state.init_code(&address, code).map_err(db_error)?;
+        }
+        for (key, value) in account.state.iter().chain(&account.state_diff).flatten() {
+            state
+                .set_storage(&address, key.as_bytes().to_vec(), value.into_uint())
+                .map_err(db_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the error of a transaction mempool did not accept. The checks of the EVM prologue get
+/// the messages of geth, and the data carries the status code of the VM.
+pub(super) fn submission_error(
+    mempool_status: MempoolStatus,
+    vm_status: Option<StatusCode>,
+) -> Error {
+    let message = match (mempool_status.code, vm_status) {
+        (_, Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD))
+        | (MempoolStatusCode::InvalidSeqNumber, _) => "nonce too low".to_string(),
+        (_, Some(StatusCode::MAX_GAS_UNITS_BELOW_MIN_TRANSACTION_GAS_UNITS)) => {
+            "intrinsic gas too low".to_string()
+        },
+        (_, Some(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE)) => {
+            "insufficient funds for gas * price + value".to_string()
+        },
+        (_, Some(StatusCode::GAS_UNIT_PRICE_BELOW_MIN_BOUND)) => {
+            "max fee per gas less than block base fee".to_string()
+        },
+        (_, Some(StatusCode::BAD_CHAIN_ID)) => "invalid chain id".to_string(),
+        (_, Some(StatusCode::INVALID_SIGNATURE)) => "invalid sender".to_string(),
+        (_, Some(status)) => format!("invalid transaction: {:?}", status),
+        (MempoolStatusCode::MempoolIsFull | MempoolStatusCode::TooManyTransactions, None) => {
+            "txpool is full".to_string()
+        },
+        (MempoolStatusCode::InvalidUpdate, None) => "replacement transaction underpriced".to_string(),
+        (_, None) => mempool_status.message,
+    };
+    invalid_transaction_error(message, vm_status)
+}
+
+fn invalid_transaction_error(message: String, vm_status: Option<StatusCode>) -> Error {
+    Error::Call(CallError::Custom(ErrorObject::owned(
+        INVALID_TRANSACTION_CODE,
+        message,
+        vm_status.map(|status| status as u64),
+    )))
+}
+
+/// Returns the error of a failed call. Reverts carry the revert data, as in geth.
+fn execution_error(outcome: ExecutionOutcome) -> Error {
+    let revert_data = match outcome {
+        ExecutionOutcome::ExecutionErrorBumpNonce(
+            ExecutionError::VmError(vm::Error::Reverted),
+            executed,
+        ) => executed.output,
+        ExecutionOutcome::ExecutionErrorBumpNonce(
+            ExecutionError::VmError(vm::Error::InternalContractReverted(data)),
+            _,
+        ) => data,
+        outcome => return Error::Custom(format!("{:?}", outcome)),
+    };
+    Error::Call(CallError::Custom(ErrorObject::owned(
+        EXECUTION_REVERTED_CODE,
+        "execution reverted",
+        Some(HexEncodedBytes::from(revert_data)),
+    )))
+}
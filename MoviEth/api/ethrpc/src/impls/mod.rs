@@ -1 +1,5 @@
+pub mod debug;
 pub mod eth;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,398 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    impls::eth::{estimate_gas_limit, submission_error, virtual_call},
+    types::{
+        call_request::CallRequest,
+        state_override::{AccountOverride, StateOverride},
+    },
+};
+use aptos_evm::{Action, EvmMachine};
+use aptos_types::{
+    account_address::AccountAddress,
+    mempool_status::{MempoolStatus, MempoolStatusCode},
+    transaction::SignedTransaction,
+    vm_status::StatusCode,
+};
+use cfx_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+use cfx_primitives::{Eip155Transaction, Transaction as EthRawTransaction};
+use cfxkey::{Generator, KeyPair, Random};
+use e2e_move_tests::{assert_success, encode_call_move, MoveHarness};
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::{core::Error, types::error::CallError};
+use solidity_abi::ABIDecodable;
+use std::path::Path;
+
+const CHAIN_ID: u32 = 129;
+
+/// Returns the address of the caller as a 32 byte word.
+const CALLER_CODE: [u8; 9] = [0x33, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+/// Writes 1 to the storage slot 0.
+const SSTORE_CODE: [u8; 6] = [0x60, 0x01, 0x60, 0x00, 0x55, 0x00];
+
+/// Reverts without data.
+const REVERT_CODE: [u8; 5] = [0x60, 0x00, 0x60, 0x00, 0xfd];
+
+fn call(h: &MoveHarness, request: CallRequest) -> Result<Vec<u8>, Error> {
+    virtual_call(
+        &EvmMachine::new(),
+        h.executor.get_state_view(),
+        CHAIN_ID,
+        request,
+        None,
+    )
+}
+
+fn estimate(h: &MoveHarness, request: CallRequest) -> U256 {
+    estimate_gas_limit(
+        &EvmMachine::new(),
+        h.executor.get_state_view(),
+        CHAIN_ID,
+        request,
+    )
+    .unwrap()
+}
+
+/// Checks that `request` succeeds with `gas` and fails with one gas less.
+fn assert_lowest_gas_limit(h: &MoveHarness, request: &CallRequest, gas: U256) {
+    let with_gas = |gas: U256| CallRequest {
+        gas: Some(gas),
+        ..request.clone()
+    };
+    assert!(call(h, with_gas(gas)).is_ok());
+    assert!(call(h, with_gas(gas - 1)).is_err());
+}
+
+fn publish_cross_vm_package(h: &mut MoveHarness) -> AccountAddress {
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../aptos-move/e2e-move-tests/src/tests/cross_vm.data/pack");
+    assert_success!(h.publish_package(&account, &path));
+    *account.address()
+}
+
+#[test]
+fn test_call_runs_from_the_zero_address_by_default() {
+    let mut h = MoveHarness::new();
+    let contract = H160::repeat_byte(0x11);
+    h.set_eth_code(&contract, CALLER_CODE.to_vec());
+    let sender = h.new_eth_account(U256::exp10(18)).address();
+
+    let request = CallRequest {
+        to: Some(contract),
+        ..Default::default()
+    };
+    assert_eq!(call(&h, request.clone()).unwrap(), H256::zero().as_bytes());
+    let request = CallRequest {
+        from: Some(sender),
+        ..request
+    };
+    assert_eq!(call(&h, request).unwrap(), H256::from(sender).as_bytes());
+}
+
+#[test]
+fn test_call_without_sender_is_not_credited() {
+    let h = MoveHarness::new();
+    let request = CallRequest {
+        to: Some(H160::repeat_byte(0x11)),
+        value: Some(1.into()),
+        ..Default::default()
+    };
+    assert!(call(&h, request).is_err());
+}
+
+#[test]
+fn test_call_reverted() {
+    let mut h = MoveHarness::new();
+    let contract = H160::repeat_byte(0x11);
+    h.set_eth_code(&contract, REVERT_CODE.to_vec());
+    let request = CallRequest {
+        to: Some(contract),
+        ..Default::default()
+    };
+    match call(&h, request) {
+        Err(Error::Call(CallError::Custom(error))) => assert_eq!(error.code(), 3),
+        result => panic!("expect a revert, got {:?}", result),
+    }
+}
+
+#[test]
+fn test_call_with_state_override() {
+    let h = MoveHarness::new();
+    let contract = H160::repeat_byte(0x11);
+    let request = CallRequest {
+        to: Some(contract),
+        ..Default::default()
+    };
+    assert_eq!(call(&h, request.clone()).unwrap(), Vec::<u8>::new());
+
+    let mut state_override = StateOverride::new();
+    state_override.insert(contract, AccountOverride {
+        code: Some(CALLER_CODE.to_vec().into()),
+        ..Default::default()
+    });
+    let output = virtual_call(
+        &EvmMachine::new(),
+        h.executor.get_state_view(),
+        CHAIN_ID,
+        request,
+        Some(&state_override),
+    )
+    .unwrap();
+    assert_eq!(output, H256::zero().as_bytes());
+}
+
+#[test]
+fn test_call_move() {
+    let mut h = MoveHarness::new();
+    let module_address = publish_cross_vm_package(&mut h);
+    let sender = h.new_eth_account(U256::exp10(18)).address();
+    let data = encode_call_move(module_address, "evm_caller", "echo_caller", vec![]);
+    let request = CallRequest {
+        from: Some(sender),
+        to: Some(CROSS_SPACE_CONTRACT_ADDRESS),
+        data: Some(data.into()),
+        ..Default::default()
+    };
+    let output = call(&h, request).unwrap();
+    assert_eq!(<Vec<u8>>::abi_decode(&output).unwrap(), sender.as_bytes());
+}
+
+#[test]
+fn test_estimate_transfer() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_eth_account(U256::exp10(18)).address();
+    let request = CallRequest {
+        from: Some(sender),
+        to: Some(H160::repeat_byte(0x11)),
+        value: Some(1.into()),
+        ..Default::default()
+    };
+    assert_eq!(estimate(&h, request), 21_000.into());
+}
+
+#[test]
+fn test_estimate_is_the_lowest_gas_limit() {
+    let mut h = MoveHarness::new();
+    let contract = H160::repeat_byte(0x11);
+    h.set_eth_code(&contract, SSTORE_CODE.to_vec());
+    let request = CallRequest {
+        to: Some(contract),
+        ..Default::default()
+    };
+    let gas = estimate(&h, request.clone());
+    assert!(gas > U256::from(21_000));
+    assert_lowest_gas_limit(&h, &request, gas);
+}
+
+#[test]
+fn test_estimate_call_move() {
+    let mut h = MoveHarness::new();
+    let module_address = publish_cross_vm_package(&mut h);
+    let sender = h.new_eth_account(U256::exp10(18)).address();
+    let data = encode_call_move(module_address, "evm_caller", "echo_caller", vec![]);
+    let request = CallRequest {
+        from: Some(sender),
+        to: Some(CROSS_SPACE_CONTRACT_ADDRESS),
+        data: Some(data.into()),
+        ..Default::default()
+    };
+    let gas = estimate(&h, request.clone());
+    assert_lowest_gas_limit(&h, &request, gas);
+}
+
+#[test]
+fn test_estimate_fails_without_funds() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_eth_account(U256::zero()).address();
+    let request = CallRequest {
+        from: Some(sender),
+        to: Some(H160::repeat_byte(0x11)),
+        value: Some(1.into()),
+        ..Default::default()
+    };
+    assert!(estimate_gas_limit(
+        &EvmMachine::new(),
+        h.executor.get_state_view(),
+        CHAIN_ID,
+        request,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_estimate_without_sender_is_not_credited() {
+    let h = MoveHarness::new();
+    let request = CallRequest {
+        to: Some(H160::repeat_byte(0x11)),
+        value: Some(1.into()),
+        ..Default::default()
+    };
+    assert!(estimate_gas_limit(
+        &EvmMachine::new(),
+        h.executor.get_state_view(),
+        CHAIN_ID,
+        request,
+    )
+    .is_err());
+}
+
+/// Signs a transfer of `value` wei from `sender`, with a gas price of 1.
+fn eth_transfer(
+    sender: &KeyPair,
+    nonce: u64,
+    gas: u64,
+    value: U256,
+    chain_id: Option<u32>,
+) -> anyhow::Result<SignedTransaction> {
+    let txn = EthRawTransaction::Ethereum(Eip155Transaction {
+        nonce: nonce.into(),
+        gas_price: 1.into(),
+        gas: gas.into(),
+        action: Action::Call(H160::repeat_byte(0x11)),
+        value,
+        chain_id,
+        data: vec![],
+    })
+    .sign(sender.secret());
+    SignedTransaction::try_from(txn)
+}
+
+/// The status the validator discards a transaction with, if any.
+fn validate(h: &MoveHarness, txn: SignedTransaction) -> Option<StatusCode> {
+    h.executor.verify_transaction(txn).status()
+}
+
+#[test]
+fn test_validate_nonce() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_eth_account(U256::exp10(18));
+    let txn = eth_transfer(&sender, 0, 21_000, 1.into(), Some(CHAIN_ID)).unwrap();
+    assert_eq!(validate(&h, txn.clone()), None);
+    assert_success!(h.run(txn));
+
+    let txn = eth_transfer(&sender, 0, 21_000, 1.into(), Some(CHAIN_ID)).unwrap();
+    assert_eq!(validate(&h, txn), Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD));
+    // A nonce ahead of the account waits in mempool, as for Move transactions.
+    let txn = eth_transfer(&sender, 5, 21_000, 1.into(), Some(CHAIN_ID)).unwrap();
+    assert_eq!(validate(&h, txn), None);
+}
+
+#[test]
+fn test_validate_intrinsic_gas() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_eth_account(U256::exp10(18));
+    let txn = eth_transfer(&sender, 0, 20_999, 1.into(), Some(CHAIN_ID)).unwrap();
+    assert_eq!(
+        validate(&h, txn),
+        Some(StatusCode::MAX_GAS_UNITS_BELOW_MIN_TRANSACTION_GAS_UNITS)
+    );
+}
+
+#[test]
+fn test_validate_balance() {
+    let mut h = MoveHarness::new();
+    // Exactly enough for the gas and the value.
+    let sender = h.new_eth_account(U256::from(21_000 + 100));
+    let txn = eth_transfer(&sender, 0, 21_000, 100.into(), Some(CHAIN_ID)).unwrap();
+    assert_eq!(validate(&h, txn), None);
+    let txn = eth_transfer(&sender, 0, 21_000, 101.into(), Some(CHAIN_ID)).unwrap();
+    assert_eq!(
+        validate(&h, txn),
+        Some(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE)
+    );
+    let txn = eth_transfer(&sender, 0, 21_000, U256::MAX, Some(CHAIN_ID)).unwrap();
+    assert_eq!(
+        validate(&h, txn),
+        Some(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE)
+    );
+}
+
+#[test]
+fn test_validate_chain_id() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_eth_account(U256::exp10(18));
+    let txn = eth_transfer(&sender, 0, 21_000, 1.into(), Some(CHAIN_ID + 1)).unwrap();
+    assert_eq!(validate(&h, txn), Some(StatusCode::BAD_CHAIN_ID));
+}
+
+#[test]
+fn test_eth_transaction_needs_eip155_chain_id() {
+    let sender = Random.generate().unwrap();
+    assert!(eth_transfer(&sender, 0, 21_000, 1.into(), Some(CHAIN_ID)).is_ok());
+    // Without replay protection.
+    assert!(eth_transfer(&sender, 0, 21_000, 1.into(), None).is_err());
+    // Out of the range of a `ChainId`.
+    assert!(eth_transfer(&sender, 0, 21_000, 1.into(), Some(0)).is_err());
+    assert!(eth_transfer(&sender, 0, 21_000, 1.into(), Some(256)).is_err());
+}
+
+/// The message and the data of a submission error.
+fn submission_error_of(
+    mempool_status: MempoolStatus,
+    vm_status: Option<StatusCode>,
+) -> (String, Option<String>) {
+    match submission_error(mempool_status, vm_status) {
+        Error::Call(CallError::Custom(error)) => {
+            assert_eq!(error.code(), -32000);
+            (
+                error.message().to_string(),
+                error.data().map(|data| data.get().to_string()),
+            )
+        },
+        error => panic!("expect an invalid transaction error, got {:?}", error),
+    }
+}
+
+#[test]
+fn test_submission_error_messages() {
+    let rejected = || MempoolStatus::new(MempoolStatusCode::VmError);
+    let messages = [
+        (StatusCode::SEQUENCE_NUMBER_TOO_OLD, "nonce too low"),
+        (
+            StatusCode::MAX_GAS_UNITS_BELOW_MIN_TRANSACTION_GAS_UNITS,
+            "intrinsic gas too low",
+        ),
+        (
+            StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE,
+            "insufficient funds for gas * price + value",
+        ),
+        (
+            StatusCode::GAS_UNIT_PRICE_BELOW_MIN_BOUND,
+            "max fee per gas less than block base fee",
+        ),
+        (StatusCode::BAD_CHAIN_ID, "invalid chain id"),
+        (StatusCode::INVALID_SIGNATURE, "invalid sender"),
+    ];
+    for (status, message) in messages {
+        assert_eq!(
+            submission_error_of(rejected(), Some(status)),
+            (message.to_string(), Some((status as u64).to_string()))
+        );
+    }
+
+    assert_eq!(
+        submission_error_of(
+            MempoolStatus::new(MempoolStatusCode::InvalidSeqNumber),
+            None
+        ),
+        ("nonce too low".to_string(), None)
+    );
+    assert_eq!(
+        submission_error_of(MempoolStatus::new(MempoolStatusCode::MempoolIsFull), None),
+        ("txpool is full".to_string(), None)
+    );
+    assert_eq!(
+        submission_error_of(MempoolStatus::new(MempoolStatusCode::InvalidUpdate), None),
+        ("replacement transaction underpriced".to_string(), None)
+    );
+    assert_eq!(
+        submission_error_of(
+            MempoolStatus::new(MempoolStatusCode::UnknownStatus).with_message("busy".to_string()),
+            None
+        ),
+        ("busy".to_string(), None)
+    );
+}
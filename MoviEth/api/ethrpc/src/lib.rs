@@ -1,6 +1,10 @@
-use crate::{impls::eth::EthHandler, traits::eth::EthServer};
+use crate::{
+    impls::eth::EthHandler,
+    traits::{debug::DebugServer, eth::EthServer},
+};
 use aptos_api::Context;
 use jsonrpsee::{core::Error, server::ServerBuilder};
+use std::sync::Arc;
 use tokio::{self, task::JoinHandle};
 
 mod impls;
@@ -15,7 +19,10 @@ pub fn bootstrap(
     let _guard = runtime_handle.enter();
     let server = runtime_handle
         .block_on(ServerBuilder::default().build(context.node_config.eth_api.address))?;
-    let server_handle = server.start(EthHandler::new(context).into_rpc())?;
+    let handler = Arc::new(EthHandler::new(context));
+    let mut module = EthServer::into_rpc(handler.clone());
+    module.merge(DebugServer::into_rpc(handler))?;
+    let server_handle = server.start(module)?;
     let join_handle = runtime_handle.spawn(server_handle.stopped());
     Ok(join_handle)
 }
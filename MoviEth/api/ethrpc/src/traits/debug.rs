@@ -0,0 +1,27 @@
+use crate::types::{block_number::BlockNumber, call_request::CallRequest, trace::TracerOptions};
+use ethereum_types::H256;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde_json::Value;
+
+/// Debug rpc interface.
+#[rpc(server, client, namespace = "debug")]
+pub trait Debug {
+    /// Re-executes a committed transaction on the state before it, returning its trace in the
+    /// format of the given tracer.
+    #[method(name = "traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        tx_hash: H256,
+        options: Option<TracerOptions>,
+    ) -> RpcResult<Value>;
+
+    /// Runs a call on the state of the given block without committing it, returning its trace in
+    /// the format of the given tracer.
+    #[method(name = "traceCall")]
+    async fn trace_call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockNumber>,
+        options: Option<TracerOptions>,
+    ) -> RpcResult<Value>;
+}
@@ -1,6 +1,7 @@
 use crate::types::{
-    block::Block, block_number::BlockNumber, call_request::CallRequest, receipts::Receipt,
-    transaction::Transaction,
+    account_proof::AccountProof, block::Block, block_number::BlockNumber,
+    call_request::CallRequest, fee_history::FeeHistory, filter::Filter, log::Log,
+    receipts::Receipt, state_override::StateOverride, transaction::Transaction,
 };
 use aptos_api_types::HexEncodedBytes;
 use ethereum_types::{H160, H256, U256, U64};
@@ -24,12 +25,14 @@ pub trait Eth {
     #[method(name = "getBalance")]
     async fn balance(&self, address: H160, block_number: Option<BlockNumber>) -> RpcResult<U256>;
 
-    /// Call contract, returning the output data.
+    /// Call contract, returning the output data. The state the call runs on can be overridden
+    /// per account.
     #[method(name = "call")]
     async fn call(
         &self,
         request: CallRequest,
         maybe_block_number: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
     ) -> RpcResult<HexEncodedBytes>;
 
     /// Estimate gas needed for execution of given contract.
@@ -74,6 +77,24 @@ pub trait Eth {
         block_number: Option<BlockNumber>,
     ) -> RpcResult<HexEncodedBytes>;
 
+    /// Returns content of the storage at given address.
+    #[method(name = "getStorageAt")]
+    async fn storage_at(
+        &self,
+        address: H160,
+        position: U256,
+        block_number: Option<BlockNumber>,
+    ) -> RpcResult<H256>;
+
+    /// Returns the account and storage values of the given account, with their proofs.
+    #[method(name = "getProof")]
+    async fn proof(
+        &self,
+        address: H160,
+        storage_keys: Vec<H256>,
+        block_number: Option<BlockNumber>,
+    ) -> RpcResult<AccountProof>;
+
     /// Returns the number of transactions sent from given address at given time
     /// (block number).
     #[method(name = "getTransactionCount")]
@@ -83,14 +104,55 @@ pub trait Eth {
         maybe_block_number: Option<BlockNumber>,
     ) -> RpcResult<U256>;
 
+    /// Returns the Move account linked to the given address with `0x1::account_link`, if any.
+    #[method(name = "getLinkedMoveAccount")]
+    async fn linked_move_account(
+        &self,
+        address: H160,
+        maybe_block_number: Option<BlockNumber>,
+    ) -> RpcResult<Option<H256>>;
+
+    /// Returns the EVM address linked to the given Move account with `0x1::account_link`, if any.
+    #[method(name = "getLinkedEvmAddress")]
+    async fn linked_evm_address(
+        &self,
+        account: H256,
+        maybe_block_number: Option<BlockNumber>,
+    ) -> RpcResult<Option<H160>>;
+
     /// Returns current gas_price.
     #[method(name = "gasPrice")]
     async fn gas_price(&self) -> RpcResult<U256>;
 
+    /// Returns the base fees, gas used ratios and gas price percentiles of a range of blocks.
+    #[method(name = "feeHistory")]
+    async fn fee_history(
+        &self,
+        block_count: U256,
+        newest_block: BlockNumber,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> RpcResult<FeeHistory>;
+
     /// Get transaction by its hash.
     #[method(name = "getTransactionByHash")]
     async fn transaction_by_hash(&self, h: H256) -> RpcResult<Option<Transaction>>;
 
+    /// Returns logs matching given filter object.
+    #[method(name = "getLogs")]
+    async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>>;
+
+    /// Installs a log filter, returning its id.
+    #[method(name = "newFilter")]
+    async fn new_filter(&self, filter: Filter) -> RpcResult<U256>;
+
+    /// Returns the logs matching the filter in the blocks committed since the last poll.
+    #[method(name = "getFilterChanges")]
+    async fn filter_changes(&self, id: U256) -> RpcResult<Vec<Log>>;
+
+    /// Uninstalls a filter, returning whether it existed.
+    #[method(name = "uninstallFilter")]
+    async fn uninstall_filter(&self, id: U256) -> RpcResult<bool>;
+
     #[method(name = "maxPriorityFeePerGas")]
     async fn max_priority_fee(&self) -> RpcResult<U256>;
 
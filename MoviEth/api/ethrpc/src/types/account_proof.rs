@@ -0,0 +1,36 @@
+use aptos_api_types::HexEncodedBytes;
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// Account proof in the shape of EIP-1186. The proofs are BCS encoded Aptos sparse Merkle proofs
+/// against the state root in `storage_hash`, rather than Merkle Patricia proofs.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    /// Address
+    pub address: H160,
+    /// Proof of the account state
+    pub account_proof: Vec<HexEncodedBytes>,
+    /// Balance
+    pub balance: U256,
+    /// Code hash
+    pub code_hash: H256,
+    /// Nonce
+    pub nonce: U256,
+    /// Root of the Aptos state tree, which the account and storage proofs are checked against
+    pub storage_hash: H256,
+    /// Storage proofs
+    pub storage_proof: Vec<StorageProof>,
+}
+
+/// Proof of one storage slot.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageProof {
+    /// Storage key
+    pub key: H256,
+    /// Storage value
+    pub value: U256,
+    /// Proof of the storage slot
+    pub proof: Vec<HexEncodedBytes>,
+}
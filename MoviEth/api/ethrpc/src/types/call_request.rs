@@ -28,7 +28,7 @@ use serde::{Deserialize, Serialize};
 pub const MAX_GAS_CALL_REQUEST: u64 = 15_000_000;
 
 /// Call request
-#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CallRequest {
     /// From
@@ -0,0 +1,88 @@
+use ethereum_types::U256;
+use serde::{Deserialize, Serialize};
+
+/// Fee history of a range of blocks.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    /// Number of the oldest block of the range
+    pub oldest_block: U256,
+    /// Base fee per gas of each block of the range, and of the block after it
+    pub base_fee_per_gas: Vec<U256>,
+    /// Ratio of the gas used to the gas limit of each block of the range
+    pub gas_used_ratio: Vec<f64>,
+    /// Priority fees at the requested percentiles of each block of the range, weighted by gas used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reward: Option<Vec<Vec<U256>>>,
+}
+
+/// Returns the part of the gas price of a transaction above the base fee, in wei. `gas_price` is
+/// the max fee per gas of a dynamic fee transaction, whose priority fee is capped by
+/// `max_priority_fee_per_gas`.
+pub fn priority_fee_per_gas(
+    gas_price: U256,
+    max_priority_fee_per_gas: Option<U256>,
+    base_fee: U256,
+) -> U256 {
+    let priority_fee = gas_price.saturating_sub(base_fee);
+    match max_priority_fee_per_gas {
+        Some(max_priority_fee_per_gas) => priority_fee.min(max_priority_fee_per_gas),
+        None => priority_fee,
+    }
+}
+
+/// Returns the priority fees at `percentiles` of the transactions of a block given as
+/// `(priority_fee, gas_used)` pairs, with each transaction weighted by its gas used. An empty block
+/// yields zeroes.
+pub fn reward_percentiles(mut txs: Vec<(U256, u64)>, percentiles: &[f64]) -> Vec<U256> {
+    if txs.is_empty() {
+        return vec![U256::zero(); percentiles.len()];
+    }
+    txs.sort_by(|a, b| a.0.cmp(&b.0));
+    let total_gas_used: u64 = txs.iter().map(|(_, gas_used)| gas_used).sum();
+    let mut index = 0;
+    let mut cumulative_gas_used = txs[0].1;
+    percentiles
+        .iter()
+        .map(|percentile| {
+            let threshold = (total_gas_used as f64 * percentile / 100.0) as u64;
+            while cumulative_gas_used < threshold && index < txs.len() - 1 {
+                index += 1;
+                cumulative_gas_used += txs[index].1;
+            }
+            txs[index].0
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_fee_per_gas() {
+        let fee = |gas_price: u64, max_priority_fee: Option<u64>, base_fee: u64| {
+            priority_fee_per_gas(
+                gas_price.into(),
+                max_priority_fee.map(Into::into),
+                base_fee.into(),
+            )
+            .as_u64()
+        };
+        assert_eq!(fee(10, None, 7), 3);
+        assert_eq!(fee(10, Some(2), 7), 2);
+        assert_eq!(fee(10, Some(5), 7), 3);
+        assert_eq!(fee(5, None, 7), 0);
+    }
+
+    #[test]
+    fn test_reward_percentiles() {
+        let rewards = reward_percentiles(vec![], &[10.0, 90.0]);
+        assert_eq!(rewards, vec![U256::zero(); 2]);
+
+        let txs = vec![(3.into(), 100), (1.into(), 100), (2.into(), 200)];
+        let rewards = reward_percentiles(txs, &[0.0, 25.0, 50.0, 75.0, 100.0]);
+        let expected: Vec<U256> = vec![1.into(), 1.into(), 2.into(), 2.into(), 3.into()];
+        assert_eq!(rewards, expected);
+    }
+}
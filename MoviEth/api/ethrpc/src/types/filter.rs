@@ -0,0 +1,247 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::types::block_number::BlockNumber;
+use aptos_evm::{evm_event_key, EventKeyScheme};
+use aptos_types::event::EventKey;
+use cfx_primitives::log_entry::LogEntry;
+use ethereum_types::{H160, H256};
+use serde::{
+    de::{DeserializeOwned, Error},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{from_value, Value};
+
+/// Variadic value
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum VariadicValue<T> {
+    /// None
+    Null,
+    /// Single
+    Single(T),
+    /// List
+    Multiple(Vec<T>),
+}
+
+impl<T: PartialEq> VariadicValue<T> {
+    /// Whether `value` is allowed. `Null` allows any value.
+    pub fn matches(&self, value: &T) -> bool {
+        match self {
+            VariadicValue::Null => true,
+            VariadicValue::Single(expected) => expected == value,
+            VariadicValue::Multiple(expected) => expected.is_empty() || expected.contains(value),
+        }
+    }
+}
+
+impl<T> VariadicValue<T> {
+    /// The allowed values, or `None` if any value is allowed.
+    pub fn values(&self) -> Option<&[T]> {
+        match self {
+            VariadicValue::Null => None,
+            VariadicValue::Single(expected) => Some(std::slice::from_ref(expected)),
+            VariadicValue::Multiple(expected) if expected.is_empty() => None,
+            VariadicValue::Multiple(expected) => Some(expected),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for VariadicValue<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            VariadicValue::Null => serializer.serialize_none(),
+            VariadicValue::Single(ref x) => x.serialize(serializer),
+            VariadicValue::Multiple(ref xs) => xs.serialize(serializer),
+        }
+    }
+}
+
+impl<'a, T> Deserialize<'a> for VariadicValue<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<VariadicValue<T>, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let v: Value = Deserialize::deserialize(deserializer)?;
+
+        if v.is_null() {
+            return Ok(VariadicValue::Null);
+        }
+
+        from_value(v.clone())
+            .map(VariadicValue::Single)
+            .or_else(|_| from_value(v).map(VariadicValue::Multiple))
+            .map_err(|err| D::Error::custom(format!("Invalid variadic value type: {}", err)))
+    }
+}
+
+/// Filter Address
+pub type FilterAddress = VariadicValue<H160>;
+/// Topic
+pub type Topic = VariadicValue<H256>;
+
+/// Filter
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+    /// From Block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<BlockNumber>,
+    /// To Block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<BlockNumber>,
+    /// Block hash, exclusive with `from_block` and `to_block`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<H256>,
+    /// Address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<FilterAddress>,
+    /// Topics
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topics: Option<Vec<Topic>>,
+}
+
+impl Filter {
+    /// Whether the log is emitted by one of the addresses and carries the topics of the filter.
+    /// A topic position past the end of the filter topics matches anything.
+    pub fn matches(&self, log: &LogEntry) -> bool {
+        if let Some(address) = &self.address {
+            if !address.matches(&log.address) {
+                return false;
+            }
+        }
+        match &self.topics {
+            None => true,
+            Some(topics) => topics.iter().enumerate().all(|(i, topic)| match topic {
+                VariadicValue::Null => true,
+                _ => log.topics.get(i).map_or(false, |t| topic.matches(t)),
+            }),
+        }
+    }
+
+    /// The event streams which hold every log matching the filter, whichever event key scheme was
+    /// active when the logs were emitted, or `None` if the filter does not pin down the addresses
+    /// and the first topic.
+    pub fn event_keys(&self) -> Option<Vec<EventKey>> {
+        let addresses = self.address.as_ref()?.values()?;
+        let topic0s = self.topics.as_ref()?.first()?.values()?;
+        let mut keys = vec![];
+        for address in addresses {
+            keys.push(evm_event_key(address, None, EventKeyScheme::PerContract));
+            for topic0 in topic0s {
+                keys.push(evm_event_key(
+                    address,
+                    Some(topic0),
+                    EventKeyScheme::PerTopic0,
+                ));
+            }
+        }
+        Some(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cfx_types::Space;
+
+    fn log(address: H160, topics: Vec<H256>) -> LogEntry {
+        LogEntry {
+            address,
+            topics,
+            data: vec![],
+            space: Space::Ethereum,
+        }
+    }
+
+    #[test]
+    fn test_deserialize_filter() {
+        let s = r#"{
+            "fromBlock": "0x1",
+            "toBlock": "latest",
+            "address": "0x1111111111111111111111111111111111111111",
+            "topics": [null, ["0x2222222222222222222222222222222222222222222222222222222222222222"]]
+        }"#;
+        let filter: Filter = serde_json::from_str(s).unwrap();
+        assert_eq!(filter.from_block, Some(BlockNumber::Num(1)));
+        assert_eq!(filter.to_block, Some(BlockNumber::Latest));
+        assert_eq!(
+            filter.address,
+            Some(VariadicValue::Single(H160::repeat_byte(0x11)))
+        );
+        assert_eq!(
+            filter.topics,
+            Some(vec![
+                VariadicValue::Null,
+                VariadicValue::Multiple(vec![H256::repeat_byte(0x22)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let address = H160::repeat_byte(0x11);
+        let topic0 = H256::repeat_byte(0x22);
+        let topic1 = H256::repeat_byte(0x33);
+        let filter = Filter {
+            from_block: None,
+            to_block: None,
+            block_hash: None,
+            address: Some(VariadicValue::Multiple(vec![address])),
+            topics: Some(vec![VariadicValue::Null, VariadicValue::Single(topic1)]),
+        };
+        assert!(filter.matches(&log(address, vec![topic0, topic1])));
+        assert!(!filter.matches(&log(address, vec![topic0])));
+        assert!(!filter.matches(&log(address, vec![topic1, topic0])));
+        assert!(!filter.matches(&log(H160::zero(), vec![topic0, topic1])));
+    }
+
+    #[test]
+    fn test_filter_event_keys() {
+        let address = H160::repeat_byte(0x11);
+        let topic0 = H256::repeat_byte(0x22);
+        let mut filter = Filter {
+            from_block: None,
+            to_block: None,
+            block_hash: None,
+            address: Some(VariadicValue::Single(address)),
+            topics: Some(vec![VariadicValue::Multiple(vec![topic0])]),
+        };
+        assert_eq!(
+            filter.event_keys(),
+            Some(vec![
+                evm_event_key(&address, None, EventKeyScheme::PerContract),
+                evm_event_key(&address, Some(&topic0), EventKeyScheme::PerTopic0),
+            ])
+        );
+
+        // Logs of any first topic may be in any per topic stream of the address.
+        filter.topics = Some(vec![VariadicValue::Null]);
+        assert_eq!(filter.event_keys(), None);
+        filter.topics = None;
+        assert_eq!(filter.event_keys(), None);
+
+        filter.topics = Some(vec![VariadicValue::Single(topic0)]);
+        filter.address = Some(VariadicValue::Multiple(vec![]));
+        assert_eq!(filter.event_keys(), None);
+    }
+}
@@ -1,6 +1,11 @@
+pub mod account_proof;
 pub mod block;
 pub mod block_number;
 pub mod call_request;
+pub mod fee_history;
+pub mod filter;
 pub mod log;
 pub mod receipts;
+pub mod state_override;
+pub mod trace;
 pub mod transaction;
@@ -44,6 +44,9 @@ pub struct Receipt {
     pub gas_used: U256,
     /// Contract address
     pub contract_address: Option<H160>,
+    /// Every contract the transaction created, including the ones created by contracts and by
+    /// the Move calls of the transaction.
+    pub contracts_created: Vec<H160>,
     /// Logs
     pub logs: Vec<Log>,
     /// Logs bloom
@@ -53,6 +56,9 @@ pub struct Receipt {
     pub status_code: U64,
     /// Effective gas price
     pub effective_gas_price: U256,
+    /// Transaction type
+    #[serde(rename = "type")]
+    pub transaction_type: U64,
     /// Detailed error message if tx execution is unsuccessful. Error message
     /// is None if tx execution is successful or it can not be offered.
     /// Error message can not be offered by light client.
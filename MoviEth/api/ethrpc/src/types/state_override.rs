@@ -0,0 +1,61 @@
+use aptos_api_types::HexEncodedBytes;
+use ethereum_types::{H160, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// State overrides of a call, by account address.
+pub type StateOverride = HashMap<H160, AccountOverride>;
+
+/// Account state override of a call, in the format of geth.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountOverride {
+    /// Balance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// Nonce
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    /// Code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<HexEncodedBytes>,
+    /// Storage slots replacing the whole storage of the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<HashMap<H256, H256>>,
+    /// Storage slots overriding the given slots only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<HashMap<H256, H256>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_state_override() {
+        let s = r#"{
+            "0x1111111111111111111111111111111111111111": {
+                "balance": "0x10",
+                "nonce": "0x2",
+                "code": "0x6000",
+                "stateDiff": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001":
+                    "0x0000000000000000000000000000000000000000000000000000000000000002"
+                }
+            }
+        }"#;
+        let state_override: StateOverride = serde_json::from_str(s).unwrap();
+        let account = &state_override[&H160::repeat_byte(0x11)];
+        assert_eq!(account.balance, Some(16.into()));
+        assert_eq!(account.nonce, Some(2.into()));
+        assert_eq!(account.code, Some(vec![0x60, 0x00].into()));
+        assert_eq!(account.state, None);
+        assert_eq!(
+            account.state_diff.as_ref().unwrap()[&H256::from_low_u64_be(1)],
+            H256::from_low_u64_be(2)
+        );
+
+        let s = r#"{"0x1111111111111111111111111111111111111111": {"storage": {}}}"#;
+        assert!(serde_json::from_str::<StateOverride>(s).is_err());
+    }
+}
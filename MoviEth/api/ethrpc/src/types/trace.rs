@@ -0,0 +1,334 @@
+use aptos_api_types::HexEncodedBytes;
+use cfx_evm::{
+    observer::trace::{Action, ExecTrace, Outcome},
+    vm::{CallType, CreateType},
+};
+use cfx_parameters::cross_vm::internal_gas_to_evm_gas_ceil;
+use ethereum_types::{H160, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Tracer of `debug_traceTransaction` and `debug_traceCall`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Tracer {
+    /// Nested call frames, including the `callMove` legs into the Move VM
+    CallTracer,
+    /// The accounts read by the transaction, in the state before it
+    PrestateTracer,
+}
+
+/// Options of `debug_traceTransaction` and `debug_traceCall`, in the format of geth. The opcode
+/// logger of geth is not supported, so the call tracer is the default.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracerOptions {
+    /// Tracer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracer: Option<Tracer>,
+    /// Tracer config
+    #[serde(default)]
+    pub tracer_config: TracerConfig,
+}
+
+/// Config of the tracer.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracerConfig {
+    /// Whether the call tracer leaves out the frames below the top call
+    #[serde(default)]
+    pub only_top_call: bool,
+}
+
+/// Call frame of the call tracer.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// `CALL`, `STATICCALL`, `DELEGATECALL`, `CALLCODE`, `CREATE`, `CREATE2`, or `CALLMOVE` for a
+    /// call into the Move VM
+    #[serde(rename = "type")]
+    pub call_type: String,
+    /// Caller
+    pub from: H160,
+    /// Callee, or the created contract. Move calls leave it out, see `move_call`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<H160>,
+    /// Value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// Gas available to the frame
+    pub gas: U256,
+    /// Gas used by the frame
+    pub gas_used: U256,
+    /// Input data
+    pub input: HexEncodedBytes,
+    /// Output data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<HexEncodedBytes>,
+    /// Why the frame failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The Move function called by a `CALLMOVE` frame
+    #[serde(rename = "move", skip_serializing_if = "Option::is_none")]
+    pub move_call: Option<MoveCallFrame>,
+    /// Sub frames
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+}
+
+/// The Move side of a `CALLMOVE` frame.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveCallFrame {
+    /// Account of the module
+    pub address: H256,
+    /// Module, empty for a plain transfer
+    pub module: String,
+    /// Function
+    pub function: String,
+    /// Type arguments
+    pub type_args: Vec<String>,
+    /// Move internal gas used
+    pub gas_used: U64,
+    /// Move VM status of a failed call, with the abort code and location of an abort
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// Account of the prestate tracer.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct PrestateAccount {
+    /// Balance
+    pub balance: U256,
+    /// Nonce
+    pub nonce: u64,
+    /// Code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<HexEncodedBytes>,
+    /// Storage slots read by the transaction
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<H256, H256>,
+}
+
+/// Result of the prestate tracer.
+pub type Prestate = HashMap<H160, PrestateAccount>;
+
+/// Builds the call frame tree of the EVM traces of a transaction, including the frames of
+/// reverted calls.
+pub fn call_frame(traces: Vec<ExecTrace>) -> anyhow::Result<CallFrame> {
+    let mut stack: Vec<CallFrame> = vec![];
+    let mut root = None;
+    for trace in traces {
+        match trace.action {
+            Action::Call(call) => stack.push(CallFrame {
+                call_type: call_type_name(call.call_type).into(),
+                from: call.from,
+                to: Some(call.to),
+                value: match call.call_type {
+                    CallType::DelegateCall | CallType::StaticCall => None,
+                    _ => Some(call.value),
+                },
+                gas: call.gas,
+                input: call.input.into(),
+                ..Default::default()
+            }),
+            Action::Create(create) => stack.push(CallFrame {
+                call_type: match create.create_type {
+                    CreateType::CREATE2 => "CREATE2",
+                    _ => "CREATE",
+                }
+                .into(),
+                from: create.from,
+                value: Some(create.value),
+                gas: create.gas,
+                input: create.init.into(),
+                ..Default::default()
+            }),
+            Action::MoveCall(move_call) => stack.push(CallFrame {
+                call_type: "CALLMOVE".into(),
+                from: move_call.from,
+                value: Some(move_call.value),
+                gas: move_call.gas,
+                input: move_call.input.into(),
+                move_call: Some(MoveCallFrame {
+                    address: move_call.address,
+                    module: move_call.module,
+                    function: move_call.function,
+                    type_args: move_call.type_args,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            Action::CallResult(result) => {
+                let mut frame = pop_frame(&mut stack)?;
+                frame.gas_used = frame.gas.saturating_sub(result.gas_left);
+                set_outcome(&mut frame, result.outcome, result.return_data);
+                push_frame(&mut stack, &mut root, frame);
+            },
+            Action::CreateResult(result) => {
+                let mut frame = pop_frame(&mut stack)?;
+                frame.gas_used = frame.gas.saturating_sub(result.gas_left);
+                if result.outcome == Outcome::Success {
+                    frame.to = Some(result.addr);
+                }
+                set_outcome(&mut frame, result.outcome, result.return_data);
+                push_frame(&mut stack, &mut root, frame);
+            },
+            Action::MoveCallResult(result) => {
+                let mut frame = pop_frame(&mut stack)?;
+                let move_gas_used = result.gas_used.low_u64();
+                frame.gas_used =
+                    U256::from(internal_gas_to_evm_gas_ceil(move_gas_used)).min(frame.gas);
+                if let Some(move_call) = frame.move_call.as_mut() {
+                    move_call.gas_used = move_gas_used.into();
+                    if result.outcome != Outcome::Success {
+                        move_call.status = Some(result.error.clone());
+                    }
+                }
+                match result.outcome {
+                    Outcome::Success => frame.output = Some(result.return_data.into()),
+                    _ => frame.error = Some(result.error),
+                }
+                push_frame(&mut stack, &mut root, frame);
+            },
+            Action::InternalTransferAction(_) => {},
+        }
+    }
+    if !stack.is_empty() {
+        anyhow::bail!("trace actions left unmatched");
+    }
+    root.ok_or_else(|| anyhow::anyhow!("no call in the traces"))
+}
+
+fn call_type_name(call_type: CallType) -> &'static str {
+    match call_type {
+        CallType::CallCode => "CALLCODE",
+        CallType::DelegateCall => "DELEGATECALL",
+        CallType::StaticCall => "STATICCALL",
+        CallType::None | CallType::Call => "CALL",
+    }
+}
+
+fn pop_frame(stack: &mut Vec<CallFrame>) -> anyhow::Result<CallFrame> {
+    stack
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("trace result left unmatched"))
+}
+
+/// Adds a finished frame to its parent, or makes it the root if it has none.
+fn push_frame(stack: &mut [CallFrame], root: &mut Option<CallFrame>, frame: CallFrame) {
+    match stack.last_mut() {
+        Some(parent) => parent.calls.push(frame),
+        None => *root = Some(frame),
+    }
+}
+
+/// Sets the output of an EVM frame. A failure other than a revert carries its error message as
+/// the return data.
+fn set_outcome(frame: &mut CallFrame, outcome: Outcome, return_data: Vec<u8>) {
+    match outcome {
+        Outcome::Success => frame.output = Some(return_data.into()),
+        Outcome::Reverted => {
+            frame.output = Some(return_data.into());
+            frame.error = Some("execution reverted".into());
+        },
+        Outcome::Fail => {
+            frame.gas_used = frame.gas;
+            frame.error = Some(String::from_utf8_lossy(&return_data).into_owned());
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cfx_evm::observer::trace::{Call, CallResult, MoveCall, MoveCallResult};
+    use cfx_types::Space;
+
+    fn trace(action: Action) -> ExecTrace {
+        ExecTrace {
+            action,
+            valid: true,
+        }
+    }
+
+    #[test]
+    fn test_call_frame() {
+        let sender = H160::repeat_byte(0x11);
+        let contract = H160::repeat_byte(0x22);
+        let call = |from, to, gas: u64| {
+            trace(Action::Call(Call {
+                space: Space::Ethereum,
+                from,
+                to,
+                value: 0.into(),
+                gas: gas.into(),
+                input: vec![],
+                call_type: CallType::Call,
+            }))
+        };
+        let call_result = |outcome, gas_left: u64| {
+            trace(Action::CallResult(CallResult {
+                outcome,
+                gas_left: gas_left.into(),
+                return_data: vec![],
+            }))
+        };
+        let traces = vec![
+            call(sender, contract, 1000),
+            call(contract, H160::repeat_byte(0x33), 500),
+            trace(Action::MoveCall(MoveCall {
+                from: contract,
+                address: H256::from_low_u64_be(1),
+                module: "coin".into(),
+                function: "ihe_transfer".into(),
+                type_args: vec!["0x1::aptos_coin::AptosCoin".into()],
+                value: 0.into(),
+                gas: 400.into(),
+                input: vec![],
+            })),
+            trace(Action::MoveCallResult(MoveCallResult {
+                outcome: Outcome::Reverted,
+                gas_used: 2_950.into(),
+                return_data: vec![],
+                error: "Move VM error 4016 (sub status Some(7)) at 0x1::coin: ".into(),
+            })),
+            call_result(Outcome::Reverted, 450),
+            call_result(Outcome::Success, 400),
+        ];
+
+        let root = call_frame(traces).unwrap();
+        assert_eq!(root.call_type, "CALL");
+        assert_eq!(root.to, Some(contract));
+        assert_eq!(root.gas_used, 600.into());
+        assert_eq!(root.error, None);
+        assert_eq!(root.calls.len(), 1);
+
+        let inner = &root.calls[0];
+        assert_eq!(inner.gas_used, 50.into());
+        assert_eq!(inner.error.as_deref(), Some("execution reverted"));
+        assert_eq!(inner.calls.len(), 1);
+
+        let move_frame = &inner.calls[0];
+        assert_eq!(move_frame.call_type, "CALLMOVE");
+        assert_eq!(move_frame.to, None);
+        assert_eq!(move_frame.gas_used, 30.into());
+        let move_call = move_frame.move_call.as_ref().unwrap();
+        assert_eq!(move_call.function, "ihe_transfer");
+        assert_eq!(move_call.gas_used, 2_950.into());
+        assert!(move_call.status.as_ref().unwrap().contains("4016"));
+
+        assert!(call_frame(vec![call(sender, contract, 1000)]).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_tracer_options() {
+        let options: TracerOptions = serde_json::from_str(
+            r#"{"tracer": "prestateTracer", "tracerConfig": {"onlyTopCall": true}}"#,
+        )
+        .unwrap();
+        assert_eq!(options.tracer, Some(Tracer::PrestateTracer));
+        assert!(options.tracer_config.only_top_call);
+        assert!(serde_json::from_str::<TracerOptions>(r#"{"tracer": "4byteTracer"}"#).is_err());
+    }
+}
@@ -1,17 +1,18 @@
 use aptos_api_types::HexEncodedBytes;
 use cfx_evm::{contract_address, vm::CreateContractAddress};
-use cfx_primitives::{transaction::eip155_signature, Action, SignedTransaction as EthTransaction};
+use cfx_primitives::{
+    transaction::eip155_signature, AccessList, Action, SignedTransaction as EthTransaction,
+};
 use ethereum_types::{H160, H256, H512, U256, U64};
-use rlp::Encodable;
 use serde::{Deserialize, Serialize};
 
 /// Transaction
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
-    // /// transaction type
-    // #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    // pub transaction_type: Option<U64>,
+    /// transaction type
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<U64>,
     /// Hash
     pub hash: H256,
     /// Nonce
@@ -57,14 +58,12 @@ pub struct Transaction {
     pub s: U256,
     // Whether tx is success
     pub status: Option<U64>,
-    /* /// Transaction activates at specified block.
-     * pub condition: Option<TransactionCondition>,
-     * /// optional access list
-     * #[serde(skip_serializing_if = "Option::is_none")]
-     * pub access_list: Option<AccessList>,
-     * /// miner bribe
-     * #[serde(skip_serializing_if = "Option::is_none")]
-     * pub max_priority_fee_per_gas: Option<U256>, */
+    /// optional access list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    /// miner bribe
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
 }
 
 impl Transaction {
@@ -76,23 +75,17 @@ impl Transaction {
         exec_info: (Option<U64>, Option<H160>),
     ) -> Transaction {
         let signature = t.signature();
-        // We only support EIP-155
-        // let access_list = match t.as_unsigned() {
-        //     TypedTransaction::AccessList(tx) => {
-        //         Some(tx.access_list.clone().into_iter().map(Into::into).
-        // collect())     }
-        //     TypedTransaction::EIP1559Transaction(tx) => Some(
-        //         tx.transaction
-        //             .access_list
-        //             .clone()
-        //             .into_iter()
-        //             .map(Into::into)
-        //             .collect(),
-        //     ),
-        //     TypedTransaction::Legacy(_) => None,
-        // };
+        let v = match t.type_id() {
+            // Typed transactions sign the chain id, so `v` is the y parity.
+            0 => eip155_signature::add_chain_replay_protection(
+                signature.v(),
+                t.chain_id().map(|x| x as u64),
+            ),
+            _ => signature.v() as u64,
+        };
 
         Transaction {
+            transaction_type: Some(U64::from(t.type_id())),
             hash: t.hash(),
             nonce: *t.nonce(),
             block_hash: block_info.0,
@@ -105,22 +98,20 @@ impl Transaction {
             },
             value: *t.value(),
             gas_price: *t.gas_price(),
-            max_fee_per_gas: None,
+            max_fee_per_gas: t.max_priority_fee_per_gas().map(|_| *t.gas_price()),
             gas: *t.gas(),
             input: t.data().clone().into(),
             creates: exec_info.1,
-            raw: t.transaction.transaction.rlp_bytes().to_vec().into(),
+            raw: t.transaction.transaction.eip2718_bytes().into(),
             public_key: t.public().map(Into::into),
             chain_id: t.chain_id().map(|x| U64::from(x as u64)),
             standard_v: Some(signature.v().into()),
-            v: eip155_signature::add_chain_replay_protection(
-                signature.v(),
-                t.chain_id().map(|x| x as u64),
-            )
-            .into(), /* The protected EIP155 v */
+            v: v.into(),
             r: signature.r().into(),
             s: signature.s().into(),
             status: exec_info.0,
+            access_list: t.access_list().cloned(),
+            max_priority_fee_per_gas: t.max_priority_fee_per_gas().cloned(),
         }
     }
 }
@@ -31,7 +31,7 @@ use aptos_types::{
     contract_event::EventWithVersion,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    on_chain_config::{GasSchedule, GasScheduleV2, OnChainConfig},
+    on_chain_config::{EvmConfig, GasSchedule, GasScheduleV2, OnChainConfig},
     state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue},
     transaction::{SignedTransaction, Transaction, TransactionWithProof, Version},
 };
@@ -129,8 +129,12 @@ impl Context {
         self.chain_id
     }
 
+    /// The EVM chain id in the on-chain `EvmConfig`, or the default one if it is not set.
     pub fn evm_chain_id(&self) -> u64 {
-        cfx_evm::EVM_CHAINID
+        self.move_resolver()
+            .ok()
+            .and_then(|resolver| EvmConfig::fetch_config(&resolver))
+            .map_or(cfx_evm::EVM_CHAINID, |config| config.chain_id)
     }
 
     pub fn node_role(&self) -> RoleType {
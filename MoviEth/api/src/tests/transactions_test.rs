@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::new_test_context;
-use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
+use aptos_api_test_context::{
+    assert_json, current_function_name, new_test_context_with_config, pretty, TestContext,
+};
+use aptos_config::config::NodeConfig;
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey,
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
@@ -13,10 +16,14 @@ use aptos_types::{
     account_address::AccountAddress,
     transaction::{
         authenticator::{AuthenticationKey, TransactionAuthenticator},
-        EntryFunction, Script, SignedTransaction,
+        EntryFunction, Script, SignedTransaction, Transaction,
     },
     utility_coin::APTOS_COIN_TYPE,
 };
+use cfx_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+use cfx_primitives::{Action, Eip155Transaction, Transaction as EthRawTransaction};
+use cfxkey::{Generator, Random};
+use ethereum_types::U256;
 use move_core_types::{
     identifier::Identifier,
     language_storage::{ModuleId, TypeTag},
@@ -24,6 +31,7 @@ use move_core_types::{
 use poem_openapi::types::ParseFromJSON;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde_json::json;
+use solidity_abi::{function_selector, parse_signature, ABIEncodable};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_deserialize_genesis_transaction() {
@@ -403,6 +411,79 @@ async fn test_get_transaction_by_invalid_hash() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_cross_vm_trace_disabled() {
+    let mut context = new_test_context(current_function_name!());
+
+    // Cross-VM tracing is off in the default execution config.
+    context
+        .expect_status_code(403)
+        .get("/transactions/by_hash/0xdadfeddcca7cb6396c735e9094c76c6e4e9cb3e3ef814730693aed59bd87b31d/cross_vm_trace")
+        .await;
+}
+
+fn cross_vm_trace_path(txn: SignedTransaction) -> String {
+    let hash = Transaction::UserTransaction(txn).hash();
+    format!(
+        "/transactions/by_hash/{}/cross_vm_trace",
+        hash.to_hex_literal()
+    )
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_cross_vm_trace() {
+    let sender = Random.generate().unwrap();
+    let mut node_config = NodeConfig::default();
+    node_config.execution.cross_vm_tracing = true;
+    let mut context = new_test_context_with_config(
+        current_function_name!(),
+        false,
+        node_config,
+        Some(sender.address()),
+    );
+
+    // A plain transfer of one native token from the EVM to the validator owner.
+    let (name, types) = parse_signature("callMove(bytes32,string,string,bytes[],bytes[])").unwrap();
+    let params = (
+        context.validator_owner.into_bytes(),
+        String::new(),
+        String::new(),
+        Vec::<Vec<u8>>::new(),
+        Vec::<Vec<u8>>::new(),
+    );
+    let txn = EthRawTransaction::Ethereum(Eip155Transaction {
+        nonce: 0.into(),
+        gas_price: 1_000.into(),
+        gas: 2_000_000.into(),
+        action: Action::Call(CROSS_SPACE_CONTRACT_ADDRESS),
+        value: U256::exp10(18),
+        chain_id: Some(context.context.evm_chain_id() as u32),
+        data: [&function_selector(&name, &types)[..], &params.abi_encode()].concat(),
+    })
+    .sign(sender.secret());
+    let txn = SignedTransaction::try_from(txn).unwrap();
+    context.commit_block(&[txn.clone()]).await;
+
+    let resp = context.get(&cross_vm_trace_path(txn)).await;
+    let traces = resp.as_array().unwrap();
+    assert_eq!(traces.len(), 1, "{}", pretty(&resp));
+    let trace = &traces[0];
+    assert_eq!(trace["module"], "");
+    assert_eq!(trace["reverted"], false);
+    assert!(trace["error"].is_null());
+    assert!(!trace["calls"].as_array().unwrap().is_empty());
+    assert!(!trace["changes"].as_array().unwrap().is_empty());
+
+    // Only EVM transactions have a cross-VM trace.
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&[txn.clone()]).await;
+    context
+        .expect_status_code(400)
+        .get(&cross_vm_trace_path(txn))
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_transaction_by_version_not_found() {
     let mut context = new_test_context(current_function_name!());
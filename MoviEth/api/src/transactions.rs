@@ -10,17 +10,15 @@ use cfx_evm::vm::Exec;
 use hyper::header::{OccupiedEntry, VacantEntry};
 use std::collections::HashMap;
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::thread;
 use std::time::Instant;
 use std::{fs::File, sync::Mutex};
 use std::io::Write;
 use aptos_aggregator::delta_change_set::DeltaChangeSet;
 use aptos_aggregator::transaction::TransactionOutputExt;
 use aptos_block_executor::block_executor::vm_wrapper::AptosExecutorTask;
-use aptos_block_executor::executor::{WorkerType, ExecutionContext, ExecutionSpace, ExecutionStatusMultiWorker, ManagerSender, TwoWayChannelEndpoints, TwoWayChannels, WorkerSender};
+use aptos_block_executor::executor::{execute_prototype_transaction, spawn_prototype_worker, WorkerType, ExecutionSpace, ExecutionStatusMultiWorker, TwoWayChannelEndpoints, TwoWayChannels};
 use aptos_block_executor::task::ExecutorTask;
-use aptos_block_executor::view::LatestViewArc;
-use aptos_block_executor::{task::ExecutionStatus as ExecStatus, executor::PreprocessedTransaction, view::LatestView};
+use aptos_block_executor::{executor::PreprocessedTransaction, view::LatestView};
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_block_executor::cached_state_view::CachedStateView;
 use aptos_types::write_set::WriteSet;
@@ -47,8 +45,8 @@ use crate::{
 use anyhow::{anyhow, Context as AnyhowContext};
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
+    AsConverter, CrossVmCallTrace, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs,
+    HashValue, HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
     Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
@@ -267,6 +265,36 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get cross-VM trace by hash
+    ///
+    /// Returns the Move side traces of the `callMove`s of a committed EVM transaction:
+    /// the Move functions called, the events, the resource changes and the gas used by
+    /// every call. The endpoint is only available if cross-VM tracing is enabled in the
+    /// execution config of the node, which then saves the traces of the transactions it
+    /// executes.
+    ///
+    /// Only JSON output is supported.
+    #[oai(
+        path = "/transactions/by_hash/:txn_hash/cross_vm_trace",
+        method = "get",
+        operation_id = "get_cross_vm_trace_by_hash",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_cross_vm_trace_by_hash(
+        &self,
+        accept_type: AcceptType,
+        /// Hash of the EVM transaction to trace
+        txn_hash: Path<HashValue>,
+    ) -> BasicResultWith404<Vec<CrossVmCallTrace>> {
+        fail_point_poem("endpoint_cross_vm_trace_by_hash")?;
+        self.context
+            .check_api_output_enabled("Get cross-VM trace by hash", &accept_type)?;
+        if !self.context.node_config.execution.cross_vm_tracing {
+            return Err(api_disabled("Get cross-VM trace by hash"));
+        }
+        self.get_cross_vm_trace_by_hash_inner(&accept_type, txn_hash.0)
+    }
+
     /// Get transaction by version
     ///
     /// Retrieves a transaction by a given version. If the version has been
@@ -546,9 +574,9 @@ impl TransactionsApi {
     ) -> SubmitTransactionResult<PendingTransaction> {
         let _result = self.context.benchmark_mutex.lock().await;
         let (worker_sender, manager_reciever) = mpsc::channel::<Vec<ExecutionStatusMultiWorker>>();
-        let move_manager_sender = self.gen_move_handlers_and_spawn_worker(worker_sender.clone());
-        let eth_manager_sender = self.gen_eth_handlers_and_spawn_worker(worker_sender.clone());
-        let cross_manager_sender = self.gen_cross_handlers_and_spawn_worker(worker_sender);
+        let move_manager_sender = spawn_prototype_worker(WorkerType::Aptos, worker_sender.clone(), execute_prototype_transaction);
+        let eth_manager_sender = spawn_prototype_worker(WorkerType::Ethereum, worker_sender.clone(), execute_prototype_transaction);
+        let cross_manager_sender = spawn_prototype_worker(WorkerType::Cross, worker_sender, execute_prototype_transaction);
         let channels = TwoWayChannels {
             move_channel: (move_manager_sender, &manager_reciever),
             eth_channel: (eth_manager_sender, &manager_reciever),
@@ -1017,201 +1045,6 @@ impl TransactionsApi {
 
 
 impl TransactionsApi {
-    fn gen_move_handlers_and_spawn_worker(&self, worker_sender: WorkerSender) -> ManagerSender {
-        let (tx_req, rx_req) = mpsc::channel::<ExecutionContext>();
-        thread::Builder::new().name("move_execution_thread".to_string()).spawn (move || {
-            println!("inside MOVE thread");
-            loop {
-                {
-                    let received = rx_req.recv();
-                    match received {
-                        Ok(execution_context) => {
-                            let ExecutionContext { base_view, executor, execution_state_cache, idx, txn_batch } = execution_context;
-                            let mut execution_results = Vec::with_capacity(txn_batch.len());
-                            
-                            // println!("Received a txn_batch of size {} in aptos handler", txn_batch.len());
-                            for txn in txn_batch {
-                                let res: ExecStatus<AptosTransactionOutput, VMStatus> = 
-                                    executor.execute_transaction(
-                                        &LatestViewArc::<PreprocessedTransaction, CachedStateView>::new_btree_view(
-                                            base_view.clone(), 
-                                            execution_state_cache.clone(), 
-                                            idx
-                                        ),
-                                        &txn,
-                                        idx,
-                                        true,
-                                    );
-                                
-                                match res {
-                                    ExecStatus::Success(ref output) | ExecStatus::SkipRest(ref output) => {
-                                        let write_set = output.get_writes().into_iter();
-                                        {
-                                            // Apply the writes.
-                                            let mut executed_state_cache_writer = execution_state_cache.write().unwrap(); 
-                                            for (ap, write_op) in write_set {
-                                                (*executed_state_cache_writer).insert(ap, write_op);
-                                            }
-                                        }
-                                        execution_results.push(ExecutionStatusMultiWorker {status: res, worker_type: WorkerType::Aptos});
-                                    }
-                                    _ => {
-                                        println!("ERROR DURING EXECUTION");
-                                        panic!("MOVE WORKER THREAD PANICKED");
-                                    }
-                                }
-                            }
-                            
-                            std::mem::drop(executor);
-                            std::mem::drop(base_view);
-
-                            let reply_result = worker_sender.send(execution_results);
-                            // match reply_result {
-                            //     Ok(_) => println!("Sent back an executed native Aptos txn!"),
-                            //     Err(e) => println!("Could not send reply in Move thread... Error: {e:?}")
-                            // }
-                        },
-                        Err(e) => {
-                            // println!("error: {}", e);
-                        }
-                    }
-                }
-            }
-        });
-        tx_req
-    }
-
-    fn gen_eth_handlers_and_spawn_worker(&self, worker_sender: WorkerSender) -> ManagerSender {
-        let (tx_req, rx_req) = mpsc::channel::<ExecutionContext>();
-        thread::Builder::new().name("eth_execution_thread".to_string()).spawn (move || {
-            println!("inside ETH thread");
-            loop {
-                {
-                    let received = rx_req.recv();
-                    match received {
-                        Ok(execution_context) => {
-                            let ExecutionContext { base_view, executor, execution_state_cache, idx, txn_batch } = execution_context;
-                            let mut execution_results = Vec::with_capacity(txn_batch.len());
-                            
-                            // println!("Received a txn_batch of size {} in eth handler", txn_batch.len());
-                            for txn in txn_batch {
-                                let res: ExecStatus<AptosTransactionOutput, VMStatus> = 
-                                    executor.execute_transaction(
-                                        &LatestViewArc::<PreprocessedTransaction, CachedStateView>::new_btree_view(
-                                            base_view.clone(), 
-                                            execution_state_cache.clone(), 
-                                            idx
-                                        ),
-                                        &txn,
-                                        idx,
-                                        true,
-                                    );
-                                
-                                match res {
-                                    ExecStatus::Success(ref output) | ExecStatus::SkipRest(ref output) => {
-                                        let write_set = output.get_writes().into_iter();
-                                        {
-                                            // Apply the writes.
-                                            let mut executed_state_cache_writer = execution_state_cache.write().unwrap(); 
-                                            for (ap, write_op) in write_set {
-                                                (*executed_state_cache_writer).insert(ap, write_op);
-                                            }
-                                        }
-                                        execution_results.push(ExecutionStatusMultiWorker {status: res, worker_type: WorkerType::Ethereum});
-                                    }
-                                    _ => {
-                                        println!("ERROR DURING EXECUTION in ETH thread");
-                                        panic!("ETH WORKER THREAD PANICKED");
-                                    }
-                                }
-                            }
-
-                            // dbg!("Number of arc references left (called from ETH thread): {}", Arc::strong_count(&base_view_clone));
-                            std::mem::drop(executor);
-                            std::mem::drop(base_view);
-
-                            let reply_result = worker_sender.send(execution_results);
-                            // match reply_result {
-                            //     Ok(_) => println!("Sent back an executed native Ethereum txn!"),
-                            //     Err(e) => println!("Could not send reply in Eth thread... Error: {:?}", e)
-                            // };
-
-                        },
-                        Err(e) => {
-                            // println!("error: {}", e);
-                        }
-                    }
-                }
-            }
-        });
-        tx_req
-    }
-
-    fn gen_cross_handlers_and_spawn_worker(&self, worker_sender: WorkerSender) -> ManagerSender {
-        let (tx_req, rx_req) = mpsc::channel::<ExecutionContext>();
-        thread::Builder::new().name("cross_execution_thread".to_string()).spawn(move || {
-            println!("inside CROSS thread");
-            loop {            
-                {
-                    let received = rx_req.recv();
-                    match received {
-                        Ok(execution_context) => {
-                            let ExecutionContext { base_view, executor, execution_state_cache, idx, txn_batch } = execution_context;
-                            let mut execution_results = Vec::with_capacity(txn_batch.len());
-                            
-                            // println!("Received a txn_batch of size {} in cross handler", txn_batch.len());
-                            for txn in txn_batch {
-                                let res: ExecStatus<AptosTransactionOutput, VMStatus> = 
-                                    executor.execute_transaction(
-                                        &LatestViewArc::<PreprocessedTransaction, CachedStateView>::new_btree_view(
-                                            base_view.clone(), 
-                                            execution_state_cache.clone(), 
-                                            idx
-                                        ),
-                                        &txn,
-                                        idx,
-                                        true,
-                                    );
-                                
-                                match res {
-                                    ExecStatus::Success(ref output) | ExecStatus::SkipRest(ref output) => {
-                                        let write_set = output.get_writes().into_iter();
-                                        {
-                                            // Apply the writes.
-                                            let mut executed_state_cache_writer = execution_state_cache.write().unwrap(); 
-                                            for (ap, write_op) in write_set {
-                                                (*executed_state_cache_writer).insert(ap, write_op);
-                                            }
-                                        }
-                                        execution_results.push(ExecutionStatusMultiWorker {status: res, worker_type: WorkerType::Cross});
-                                    }
-                                    _ => {
-                                        println!("ERROR DURING EXECUTION in CROSS thread");
-                                        panic!("CROSS WORKER THREAD PANICKED");
-                                    }
-                                }
-                            }
-
-                            std::mem::drop(executor);
-                            std::mem::drop(base_view);
-
-                            let reply_result = worker_sender.send(execution_results);
-                            // match reply_result {
-                            //     Ok(_) => println!("Sent back an executed cross txn!"),
-                            //     Err(e) => println!("Could not send reply in Cross thread... Error: {:?}", e)
-                            // };
-
-                        },
-                        Err(e) => {
-                            // println!("error: {}", e);
-                        }
-                    }
-                }
-            }
-        });
-        tx_req
-    }
-
     fn read_tx_with_tx_type_padding(
         &self, 
         bytes: &Vec<u8>, 
@@ -1247,17 +1080,12 @@ impl TransactionsApi {
         
         let transaction = match encoded_tx_type {
             1 | 3 => {
-                let mut aptos_tx: SignedTransaction = self.get_signed_transaction(
+                // The type byte only tells the encoding, the executor classifies the transaction.
+                let aptos_tx: SignedTransaction = self.get_signed_transaction(
                     &ledger_info,
                     SubmitTransactionPost::Bcs(Bcs(Vec::from(tx_bytes)))
                 ).unwrap();
 
-                match encoded_tx_type {
-                    1 => aptos_tx.transaction_type = TxType::AptosTx,
-                    3 => aptos_tx.transaction_type = TxType::CrossTxAptosOrigin, 
-                    _ => panic!("Impossible...")
-                }
-
                 if aptos_tx.sequence_number() == 0 {
                     *no_setup_tx_aptos = true;
                 } else if there_were_setup_aptos_txs(no_setup_tx_aptos) {
@@ -1267,13 +1095,7 @@ impl TransactionsApi {
             }
             5 | 7 => {
                 let eth_tx: EthTransaction = rlp::decode(tx_bytes).map_err(Error::to_call_error).expect("Decoding of eth tx was not successful (native)");
-                let mut signed_tx = SignedTransaction::try_from(eth_tx).expect("Conversion to eth tx was not successful (native)"); 
-                
-                match encoded_tx_type {
-                    5 => signed_tx.transaction_type = TxType::EthereumTx,
-                    7 => signed_tx.transaction_type = TxType::CrossTxEthereumOrigin, 
-                    _ => panic!("Impossible...")
-                }
+                let signed_tx = SignedTransaction::try_from(eth_tx).expect("Conversion to eth tx was not successful (native)"); 
 
                 if signed_tx.sequence_number() == 0 {
                     *no_setup_tx_ethereum = true;
@@ -1420,6 +1242,82 @@ impl TransactionsApi {
             .await
     }
 
+    fn get_cross_vm_trace_by_hash_inner(
+        &self,
+        accept_type: &AcceptType,
+        hash: HashValue,
+    ) -> BasicResultWith404<Vec<CrossVmCallTrace>> {
+        // Events are rendered as JSON, so the traces have no BCS representation.
+        if accept_type == &AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                "BCS is not supported for cross-VM traces",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let txn = self
+            .context
+            .get_transaction_by_hash(hash.into(), ledger_info.version())
+            .context(format!("Failed to get transaction by hash {}", hash))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ok_or_else(|| transaction_not_found_by_hash(hash, &ledger_info))?;
+        if !matches!(
+            &txn.transaction,
+            transaction::Transaction::UserTransaction(signed_txn)
+                if matches!(signed_txn.payload(), TransactionPayload::EthTransactionPayload(_))
+        ) {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!("Transaction {} is not an EVM transaction", hash),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        // The node saves the trace when it executes the transaction, so a transaction executed
+        // before cross-VM tracing was enabled has none.
+        let trace = self
+            .context
+            .db
+            .reader
+            .get_cross_vm_trace(txn.version)
+            .context("Failed to read the cross-VM trace")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ok_or_else(|| {
+                BasicErrorWith404::not_found_with_code(
+                    format!("Cross-VM trace not found by Transaction hash({})", hash),
+                    AptosErrorCode::TransactionNotFound,
+                    &ledger_info,
+                )
+            })?;
+
+        let resolver = self.context.move_resolver_poem(&ledger_info)?;
+        let traces = resolver
+            .as_converter(self.context.db.clone())
+            .try_into_cross_vm_trace(trace)
+            .context("Failed to convert the cross-VM trace")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        BasicResponse::try_from_json((traces, &ledger_info, BasicResponseStatus::Ok))
+    }
+
     async fn get_transaction_by_version_inner(
         &self,
         accept_type: &AcceptType,
@@ -36,7 +36,7 @@ use aptos_types::{
     block_metadata::BlockMetadata,
     chain_id::ChainId,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    transaction::{Transaction, TransactionStatus},
+    transaction::{EthAddress, Transaction, TransactionStatus},
 };
 use aptos_vm::AptosVM;
 use aptos_vm_validator::vm_validator::VMValidator;
@@ -84,6 +84,23 @@ impl ApiSpecificConfig {
 }
 
 pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestContext {
+    new_test_context_with_config(test_name, use_db_with_indexer, NodeConfig::default(), None)
+}
+
+/// Like `new_test_context`, with `node_config` instead of the default config, and
+/// `evm_genesis_account` funded with native tokens in the EVM at genesis.
+pub fn new_test_context_with_config(
+    test_name: String,
+    use_db_with_indexer: bool,
+    node_config: NodeConfig,
+    evm_genesis_account: Option<EthAddress>,
+) -> TestContext {
+    // The flag is global to the process, so tests which enable tracing only add traces to the
+    // outputs of the other tests.
+    if node_config.execution.cross_vm_tracing {
+        AptosVM::set_cross_vm_tracing(true);
+    }
+
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -93,8 +110,9 @@ pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestCon
         aptos_cached_packages::head_release_bundle().clone(),
     )
     .unwrap()
-    .with_init_genesis_config(Some(Arc::new(|genesis_config| {
+    .with_init_genesis_config(Some(Arc::new(move |genesis_config| {
         genesis_config.recurring_lockup_duration_secs = 86400;
+        genesis_config.maybe_evm_genesis_account = evm_genesis_account;
     })))
     .with_randomize_first_validator_ports(false);
 
@@ -124,8 +142,6 @@ pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestCon
 
     let mempool = MockSharedMempool::new_in_runtime(&db_rw, VMValidator::new(db.clone()));
 
-    let node_config = NodeConfig::default();
-
     let context = Context::new(
         ChainId::test(),
         db.clone(),
@@ -9,11 +9,12 @@ use crate::{
         WriteResource, WriteTableItem,
     },
     view::ViewRequest,
-    Bytecode, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event, HexEncodedBytes,
-    MoveFunction, MoveModuleBytecode, MoveResource, MoveScriptBytecode, MoveType, MoveValue,
-    PendingTransaction, ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction,
-    TransactionInfo, TransactionOnChainData, TransactionPayload, UserTransactionRequest,
-    VersionedEvent, WriteSet, WriteSetChange, WriteSetPayload,
+    Bytecode, CrossVmCallTrace, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event,
+    HexEncodedBytes, MoveCallFrame, MoveFunction, MoveModuleBytecode, MoveResource,
+    MoveScriptBytecode, MoveType, MoveValue, PendingTransaction, ScriptPayload, ScriptWriteSet,
+    SubmitTransactionRequest, Transaction, TransactionInfo, TransactionOnChainData,
+    TransactionPayload, UserTransactionRequest, VersionedEvent, WriteSet, WriteSetChange,
+    WriteSetPayload,
 };
 use anyhow::{anyhow, bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -366,6 +367,57 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         }))
     }
 
+    pub fn try_into_cross_vm_trace(
+        &self,
+        trace: aptos_types::transaction::CrossVmTrace,
+    ) -> Result<Vec<CrossVmCallTrace>> {
+        trace
+            .into_iter()
+            .map(|call| {
+                Ok(CrossVmCallTrace {
+                    caller: call.caller.as_bytes().to_vec().into(),
+                    address: call.address.into(),
+                    module: call.module,
+                    function: call.function,
+                    type_arguments: call.ty_args.into_iter().map(|arg| arg.into()).collect(),
+                    calls: self.try_into_move_call_frames(call.calls)?,
+                    events: self.try_into_events(&call.events)?,
+                    changes: call
+                        .write_set
+                        .into_iter()
+                        .map(|(sk, wo)| self.try_into_write_set_change(sk, wo))
+                        .collect::<Result<_>>()?,
+                    execution_gas_used: call.execution_gas_used.into(),
+                    storage_gas_used: call.storage_gas_used.into(),
+                    error: call.error,
+                    reverted: call.reverted,
+                })
+            })
+            .collect()
+    }
+
+    fn try_into_move_call_frames(
+        &self,
+        frames: Vec<aptos_types::transaction::MoveCallFrame>,
+    ) -> Result<Vec<MoveCallFrame>> {
+        frames
+            .into_iter()
+            .map(|frame| {
+                Ok(MoveCallFrame {
+                    function: EntryFunctionId {
+                        module: frame.module.into(),
+                        name: Identifier::new(frame.function)?.into(),
+                    },
+                    type_arguments: frame.ty_args.into_iter().map(|arg| arg.into()).collect(),
+                    native: frame.native,
+                    gas_used: frame.gas_used.into(),
+                    aborted: frame.aborted,
+                    calls: self.try_into_move_call_frames(frame.calls)?,
+                })
+            })
+            .collect()
+    }
+
     pub fn try_into_events(&self, events: &[ContractEvent]) -> Result<Vec<Event>> {
         let mut ret = vec![];
         for event in events {
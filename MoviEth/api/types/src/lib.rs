@@ -40,16 +40,16 @@ use serde::{Deserialize, Deserializer};
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
 pub use transaction::{
-    AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
-    DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
-    GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction, ModuleBundlePayload,
-    MultiAgentSignature, MultiEd25519Signature, PendingTransaction, ScriptPayload, ScriptWriteSet,
-    Secp256k1Signature, SubmitTransactionRequest, Transaction, TransactionData, TransactionId,
-    TransactionInfo, TransactionOnChainData, TransactionPayload, TransactionSignature,
-    TransactionSigningMessage, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserCreateSigningMessageRequest, UserTransaction,
-    UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
-    WriteSetPayload, WriteTableItem,
+    AccountSignature, BlockMetadataTransaction, CrossVmCallTrace, DeleteModule, DeleteResource,
+    DeleteTableItem, DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest,
+    EntryFunctionPayload, Event, GasEstimation, GasEstimationBcs, GenesisPayload,
+    GenesisTransaction, ModuleBundlePayload, MoveCallFrame, MultiAgentSignature,
+    MultiEd25519Signature, PendingTransaction, ScriptPayload, ScriptWriteSet, Secp256k1Signature,
+    SubmitTransactionRequest, Transaction, TransactionData, TransactionId, TransactionInfo,
+    TransactionOnChainData, TransactionPayload, TransactionSignature, TransactionSigningMessage,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult,
+    UserCreateSigningMessageRequest, UserTransaction, UserTransactionRequest, VersionedEvent,
+    WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload, WriteTableItem,
 };
 pub use view::ViewRequest;
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
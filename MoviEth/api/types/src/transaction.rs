@@ -1348,3 +1348,44 @@ pub struct GasEstimation {
     /// The prioritized estimate for the gas unit price
     pub prioritized_gas_estimate: Option<u64>,
 }
+
+/// Move side trace of a `callMove` of an EVM transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct CrossVmCallTrace {
+    // The EVM contract which made the call
+    pub caller: HexEncodedBytes,
+    // Account of the called module
+    pub address: Address,
+    /// Called module, empty for a plain transfer
+    pub module: String,
+    /// Called function
+    pub function: String,
+    pub type_arguments: Vec<MoveType>,
+    /// Move functions run by the call, starting with the transfer of the value sent along
+    pub calls: Vec<MoveCallFrame>,
+    /// Events emitted by the call
+    pub events: Vec<Event>,
+    /// Resources written by the call
+    pub changes: Vec<WriteSetChange>,
+    // Gas used to execute the Move functions
+    pub execution_gas_used: U64,
+    // Gas used to store the changes
+    pub storage_gas_used: U64,
+    /// Why the call failed, with the abort code and location of an abort
+    pub error: Option<String>,
+    /// Whether the EVM frame of the call was reverted, dropping its changes
+    pub reverted: bool,
+}
+
+/// A Move function call of a cross-VM call, with the calls it made
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MoveCallFrame {
+    pub function: EntryFunctionId,
+    pub type_arguments: Vec<MoveType>,
+    pub native: bool,
+    // Execution gas used by the function and its callees
+    pub gas_used: U64,
+    /// Whether the function did not return, e.g. because it or a callee aborted
+    pub aborted: bool,
+    pub calls: Vec<MoveCallFrame>,
+}
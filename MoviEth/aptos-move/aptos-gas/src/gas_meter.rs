@@ -11,6 +11,7 @@ use crate::{
     transaction::{ChangeSetConfigs, TransactionGasParameters},
     StorageGasParameters,
 };
+use aptos_framework::natives::call_evm::MoveGasLeft;
 use aptos_types::{
     account_config::CORE_CODE_ADDRESS, state_store::state_key::StateKey, write_set::WriteOp,
 };
@@ -214,6 +215,8 @@ pub struct AptosGasMeter {
     memory_quota: AbstractValueSize,
 
     should_leak_memory_for_native: bool,
+    /// Where the balance is published before a native runs, for natives calling into the EVM.
+    shared_balance: Option<MoveGasLeft>,
 }
 
 impl AptosGasMeter {
@@ -233,16 +236,35 @@ impl AptosGasMeter {
             balance,
             memory_quota,
             should_leak_memory_for_native: false,
+            shared_balance: None,
         }
     }
 
+    /// Publishes the balance to `gas_left` whenever a native function is about to run.
+    pub fn share_balance(&mut self, gas_left: MoveGasLeft) {
+        gas_left.set(self.balance);
+        self.shared_balance = Some(gas_left);
+    }
+
     pub fn balance(&self) -> Gas {
         self.balance
             .to_unit_round_down_with_params(&self.gas_params.txn)
     }
 
-    pub fn cross_space_topup(&mut self, amount: impl Into<Gas>) {
-        self.balance += amount.into().to_unit_with_params(&self.gas_params.txn);
+    pub fn internal_balance(&self) -> InternalGas {
+        self.balance
+    }
+
+    pub fn cross_space_topup(&mut self, amount: InternalGas) {
+        self.balance += amount;
+    }
+
+    /// Takes the balance left from a cross-space top-up, so that unused gas of a call does not
+    /// carry over to the next one.
+    pub fn cross_space_refund(&mut self) -> Gas {
+        let balance = self.balance();
+        self.balance = 0.into();
+        balance
     }
 
     #[inline]
@@ -306,6 +328,10 @@ impl GasMeter for AptosGasMeter {
         _ty_args: impl ExactSizeIterator<Item = impl TypeView>,
         args: impl ExactSizeIterator<Item = impl ValueView>,
     ) -> PartialVMResult<()> {
+        if let Some(gas_left) = &self.shared_balance {
+            gas_left.set(self.balance);
+        }
+
         // TODO(Gas): https://github.com/aptos-labs/aptos-core/issues/5485
         if self.should_leak_memory_for_native {
             return Ok(());
@@ -32,6 +32,7 @@ mod instr;
 mod misc;
 mod move_stdlib;
 mod table;
+mod tracing_gas_meter;
 mod transaction;
 
 pub use algebra::*;
@@ -45,4 +46,5 @@ pub use move_core_types::gas_algebra::{
     Arg, Byte, GasQuantity, InternalGas, InternalGasPerArg, InternalGasPerByte, InternalGasUnit,
     NumArgs, NumBytes, UnitDiv,
 };
+pub use tracing_gas_meter::TracingGasMeter;
 pub use transaction::{ChangeSetConfigs, StorageGasParameters, TransactionGasParameters};
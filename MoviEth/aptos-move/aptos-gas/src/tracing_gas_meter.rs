@@ -0,0 +1,404 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module contains a gas meter which records the Move call tree of an execution on top of
+//! the official gas meter, for the tracing of cross-VM calls.
+
+use crate::gas_meter::AptosGasMeter;
+use aptos_types::transaction::{MoveCallFrame, MoveCallTreeBuilder};
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    language_storage::{ModuleId, TypeTag},
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+
+/// Charges gas exactly like the wrapped `AptosGasMeter`, and records which functions are called
+/// and how much gas each of them uses. It records nothing unless enabled, so that the same code
+/// runs with and without tracing.
+pub struct TracingGasMeter<'a> {
+    inner: &'a mut AptosGasMeter,
+    builder: Option<MoveCallTreeBuilder>,
+}
+
+impl<'a> TracingGasMeter<'a> {
+    pub fn new(inner: &'a mut AptosGasMeter, enabled: bool) -> Self {
+        Self {
+            inner,
+            builder: enabled.then(MoveCallTreeBuilder::default),
+        }
+    }
+
+    /// Records the call of a function which is not called from Move, e.g. the entry function of
+    /// a session.
+    pub fn enter(&mut self, module: &ModuleId, function: &str, ty_args: &[TypeTag]) {
+        if let Some(builder) = self.builder.as_mut() {
+            let balance = self.inner.balance().into();
+            builder.enter(module.clone(), function, ty_args.to_vec(), balance);
+        }
+    }
+
+    /// Returns the call tree, with the functions which have not returned marked as aborted.
+    pub fn finish(self) -> Vec<MoveCallFrame> {
+        let balance = self.inner.balance().into();
+        self.builder
+            .map_or_else(Vec::new, |builder| builder.finish(balance))
+    }
+
+    fn exit(&mut self) {
+        if let Some(builder) = self.builder.as_mut() {
+            builder.exit(self.inner.balance().into());
+        }
+    }
+}
+
+impl<'a> GasMeter for TracingGasMeter<'a> {
+    #[inline]
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        let res = self.inner.charge_simple_instr(instr);
+        if res.is_ok() && matches!(instr, SimpleInstruction::Ret) {
+            self.exit();
+        }
+        res
+    }
+
+    #[inline]
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        if let Some(builder) = self.builder.as_mut() {
+            builder.mark_native();
+        }
+        self.inner
+            .charge_native_function_before_execution(ty_args, args)
+    }
+
+    #[inline]
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        let res = self.inner.charge_native_function(amount, ret_vals);
+        if res.is_ok() {
+            self.exit();
+        }
+        res
+    }
+
+    #[inline]
+    fn charge_load_resource(
+        &mut self,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_load_resource(loaded)
+    }
+
+    #[inline]
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_pop(popped_val)
+    }
+
+    #[inline]
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.enter(module_id, func_name, &[]);
+        self.inner
+            .charge_call(module_id, func_name, args, num_locals)
+    }
+
+    #[inline]
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let ty_args = ty_args.collect::<Vec<_>>();
+        if self.builder.is_some() {
+            let ty_tags = ty_args
+                .iter()
+                .map(|ty| ty.to_type_tag())
+                .collect::<Vec<_>>();
+            self.enter(module_id, func_name, &ty_tags);
+        }
+        self.inner
+            .charge_call_generic(module_id, func_name, ty_args.into_iter(), args, num_locals)
+    }
+
+    #[inline]
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.inner.charge_ld_const(size)
+    }
+
+    #[inline]
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_ld_const_after_deserialization(val)
+    }
+
+    #[inline]
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_copy_loc(val)
+    }
+
+    #[inline]
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_move_loc(val)
+    }
+
+    #[inline]
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_store_loc(val)
+    }
+
+    #[inline]
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_pack(is_generic, args)
+    }
+
+    #[inline]
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_unpack(is_generic, args)
+    }
+
+    #[inline]
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_read_ref(val)
+    }
+
+    #[inline]
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_write_ref(new_val, old_val)
+    }
+
+    #[inline]
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_eq(lhs, rhs)
+    }
+
+    #[inline]
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.inner.charge_neq(lhs, rhs)
+    }
+
+    #[inline]
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.inner
+            .charge_borrow_global(is_mut, is_generic, ty, is_success)
+    }
+
+    #[inline]
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_exists(is_generic, ty, exists)
+    }
+
+    #[inline]
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_move_from(is_generic, ty, val)
+    }
+
+    #[inline]
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_move_to(is_generic, ty, val, is_success)
+    }
+
+    #[inline]
+    fn charge_vec_pack<'b>(
+        &mut self,
+        ty: impl TypeView + 'b,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_pack(ty, args)
+    }
+
+    #[inline]
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_unpack(ty, expect_num_elements, elems)
+    }
+
+    #[inline]
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.inner.charge_vec_len(ty)
+    }
+
+    #[inline]
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_borrow(is_mut, ty, is_success)
+    }
+
+    #[inline]
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_push_back(ty, val)
+    }
+
+    #[inline]
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_vec_pop_back(ty, val)
+    }
+
+    #[inline]
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.inner.charge_vec_swap(ty)
+    }
+
+    #[inline]
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.inner.charge_drop_frame(locals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AptosGasParameters, InitialGasSchedule, StorageGasParameters, LATEST_GAS_FEATURE_VERSION,
+    };
+    use move_core_types::{account_address::AccountAddress, ident_str};
+    use move_vm_types::values::Value;
+    use std::iter;
+
+    struct NoType;
+
+    impl TypeView for NoType {
+        fn to_type_tag(&self) -> TypeTag {
+            unreachable!()
+        }
+    }
+
+    fn gas_meter() -> AptosGasMeter {
+        AptosGasMeter::new(
+            LATEST_GAS_FEATURE_VERSION,
+            AptosGasParameters::initial(),
+            StorageGasParameters::free_and_unlimited(),
+            1_000_000,
+        )
+    }
+
+    /// Calls `coin::transfer`, which calls the native `coin::balance` and then aborts.
+    fn run(meter: &mut TracingGasMeter) {
+        let module = ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned());
+        meter.enter(&module, "transfer", &[]);
+        meter
+            .charge_call(&module, "balance", iter::empty::<Value>(), NumArgs::new(0))
+            .unwrap();
+        meter
+            .charge_native_function_before_execution(
+                iter::empty::<NoType>(),
+                iter::empty::<Value>(),
+            )
+            .unwrap();
+        meter
+            .charge_native_function(InternalGas::new(50_000), None::<iter::Empty<Value>>)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_tracing_gas_meter() {
+        let mut inner = gas_meter();
+        let mut meter = TracingGasMeter::new(&mut inner, true);
+        run(&mut meter);
+        let calls = meter.finish();
+
+        assert_eq!(calls.len(), 1);
+        let transfer = &calls[0];
+        assert_eq!(transfer.function, "transfer");
+        assert!(transfer.aborted);
+        assert!(!transfer.native);
+        assert_eq!(transfer.calls.len(), 1);
+
+        let balance = &transfer.calls[0];
+        assert_eq!(balance.function, "balance");
+        assert!(balance.native);
+        assert!(!balance.aborted);
+        assert!(balance.gas_used >= 5);
+        assert_eq!(transfer.gas_used, balance.gas_used);
+        assert_eq!(
+            transfer.gas_used,
+            u64::from(gas_meter().balance()) - u64::from(inner.balance())
+        );
+    }
+
+    #[test]
+    fn test_tracing_gas_meter_disabled() {
+        let mut traced = gas_meter();
+        let mut meter = TracingGasMeter::new(&mut traced, true);
+        run(&mut meter);
+        meter.finish();
+
+        let mut untraced = gas_meter();
+        let mut meter = TracingGasMeter::new(&mut untraced, false);
+        run(&mut meter);
+        assert!(meter.finish().is_empty());
+        assert_eq!(traced.balance(), untraced.balance());
+    }
+}
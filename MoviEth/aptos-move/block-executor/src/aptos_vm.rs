@@ -5,7 +5,7 @@ use crate::{
     adapter_common::{
         discard_error_output, discard_error_vm_status, validate_signature_checked_transaction,
         validate_signed_transaction, VMAdapter,
-    }, aptos_vm_impl::{get_transaction_output, AptosVMImpl, AptosVMInternals}, block_executor::{AptosTransactionOutput, BlockAptosVM}, cached_state_view::CachedStateView, counters::*, data_cache::{AsMoveResolver, IntoMoveResolver}, delta_state_view::DeltaStateView, errors::expect_only_successful_execution, evm_context_loader::ContextView, executor::{ExecutionContext, ExecutionSpace, PreprocessedTransaction, TwoWayChannelEndpoints, TwoWayChannels}, logging::AdapterLogSchema, move_vm_ext::{MoveResolverExt, SessionExt, SessionId}, system_module_names::*, transaction_metadata::TransactionMetadata, verifier, VMExecutor, VMValidator
+    }, aptos_vm_impl::{get_transaction_output, AptosVMImpl, AptosVMInternals}, block_executor::{AptosTransactionOutput, BlockAptosVM}, cached_state_view::CachedStateView, counters::*, data_cache::{AsMoveResolver, IntoMoveResolver, StorageAdapterOwned}, delta_state_view::DeltaStateView, errors::expect_only_successful_execution, evm_context_loader::ContextView, executor::{ExecutionContext, ExecutionSpace, PreprocessedTransaction, TwoWayChannelEndpoints, TwoWayChannels}, logging::AdapterLogSchema, move_vm_ext::{MoveResolverExt, SessionExt, SessionId}, system_module_names::*, transaction_metadata::TransactionMetadata, verifier, VMExecutor, VMValidator
 };
 use anyhow::{anyhow, Result};
 use aptos_aggregator::{
@@ -14,43 +14,66 @@ use aptos_aggregator::{
 };
 use crate::task::ExecutionStatus as OtherExecutionStatus;
 use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
 use aptos_evm::{
-    convert_exeuction_outcome, evm_events_to_aptos_events, extract_evm_executed, make_executor,
-    EvmContext, EvmContextReader, EvmMachine, EvmState, EvmTransaction, ExecutionOutcome,
-    TransactOptions, ViewWrapper,
+    abi_to_move_value, contracts_created_to_aptos_events, convert_exeuction_outcome,
+    evm_events_to_aptos_events, extract_evm_executed,
+    layout_to_abi_type, make_executor, make_executor_with_state, move_value_to_abi, Action,
+    EvmContext, EvmContextReader,
+    EvmMachine, EvmState, EvmTransaction, ExecutionOutcome, Substate, TransactOptions,
+    TransactionInfo, ViewWrapper,
+};
+use aptos_framework::natives::{
+    call_evm::{abort_codes, CrossVMContext, MoveGasLeft, INTERNAL_GAS_PER_EVM_GAS},
+    code::PublishRequest,
+};
+use aptos_gas::{
+    AptosGasMeter, AptosGasParameters, ChangeSetConfigs, Gas, StorageGasParameters,
+    TracingGasMeter,
 };
-use aptos_framework::natives::{call_evm::CrossVMContext, code::PublishRequest};
-use aptos_gas::{AptosGasMeter, ChangeSetConfigs};
 use aptos_logger::prelude::*;
-use aptos_state_view::{StateView, TStateView};
+use aptos_state_view::{StateView, StateViewId, TStateView};
 use aptos_types::{
-    account_config::{self, new_block_event_key, CORE_CODE_ADDRESS}, block_metadata::BlockMetadata, on_chain_config::{new_epoch_event_key, FeatureFlag}, transaction::{
-        ChangeSet, ExecutionStatus, ModuleBundle, SignatureCheckedTransaction, SignedTransaction,
-        Transaction, TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
-        WriteSetPayload,
-    }, vm_status::{AbortLocation, DiscardedVMStatus, StatusCode, VMStatus}, write_set::{WriteSet, WriteSetMut}
+    account_config::{
+        self, new_block_event_key, NativeBridgeResource, ReentrancyGuardResource, CORE_CODE_ADDRESS,
+    }, block_metadata::BlockMetadata, chain_id::ChainId, on_chain_config::{new_epoch_event_key, FeatureFlag}, state_store::{state_key::StateKey, state_storage_usage::StateStorageUsage}, transaction::{
+        AbortInfo, ChangeSet, CrossVmCallTrace, CrossVmTrace, ExecutionStatus, ModuleBundle,
+        SignatureCheckedTransaction, SignedTransaction, Transaction, TransactionOutput,
+        TransactionPayload, TransactionStatus, VMValidatorResult, WriteSetPayload,
+    }, vm_status::{AbortLocation, DiscardedVMStatus, StatusCode, VMStatus}, write_set::{WriteOp, WriteSet, WriteSetMut}
+};
+use cfx_state::{
+    native_token::{wei_to_octas, NativeTokenSupply},
+    state_trait::StateOpsTrait,
+    CallMoveVMError, CallMoveVMTrait, StateTrait,
 };
-use cfx_state::{CallMoveVMTrait, StateTrait};
 use ethereum_types::{Address, U256};
 use fail::fail_point;
 use move_binary_format::{
     access::ModuleAccess,
     compatibility::Compatibility,
     errors::{verification_error, Location, PartialVMError, VMError, VMResult},
+    normalized::Function,
     CompiledModule, IndexKind,
 };
 use move_core_types::{
     account_address::AccountAddress,
+    gas_algebra::InternalGas,
     ident_str,
     identifier::Identifier,
     language_storage::{ModuleId, TypeTag},
+    move_resource::MoveStructType,
+    resolver::{ModuleResolver, ResourceResolver},
     transaction_argument::convert_txn_args,
     value::{serialize_values, MoveValue},
 };
-use move_vm_types::gas::UnmeteredGasMeter;
+use move_vm_runtime::session::LoadedFunctionInstantiation;
+use move_vm_types::{gas::UnmeteredGasMeter, loaded_data::runtime_types::Type};
 use num_cpus;
 use once_cell::sync::OnceCell;
+use solidity_abi::{decode_list, encode_list};
 use std::{
+    cell::{Cell, RefCell},
     cmp::min,
     collections::{BTreeMap, BTreeSet, HashMap},
     convert::{AsMut, AsRef},
@@ -64,6 +87,8 @@ static EXECUTION_CONCURRENCY_LEVEL: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
+static CROSS_VM_TRACING: OnceCell<bool> = OnceCell::new();
+static PROTOTYPE_WORKERS: OnceCell<bool> = OnceCell::new();
 
 /// Remove this once the bundle is removed from the code.
 static MODULE_BUNDLE_DISALLOWED: AtomicBool = AtomicBool::new(true);
@@ -153,6 +178,36 @@ impl AptosVM {
         }
     }
 
+    /// Sets whether the outputs of EVM transactions carry the Move side traces of their cross-VM
+    /// calls, when invoked the first time.
+    pub fn set_cross_vm_tracing(enable: bool) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        CROSS_VM_TRACING.set(enable).ok();
+    }
+
+    /// Get the cross-VM tracing flag if already set, otherwise return default false
+    pub fn get_cross_vm_tracing() -> bool {
+        match CROSS_VM_TRACING.get() {
+            Some(enable) => *enable,
+            None => false,
+        }
+    }
+
+    /// Sets whether `execute_block_prototype` runs the three prototype workers instead of
+    /// Block-STM, when invoked the first time.
+    pub fn set_prototype_workers(enable: bool) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        PROTOTYPE_WORKERS.set(enable).ok();
+    }
+
+    /// Get the prototype workers flag if already set, otherwise return default false
+    pub fn get_prototype_workers() -> bool {
+        match PROTOTYPE_WORKERS.get() {
+            Some(enable) => *enable,
+            None => false,
+        }
+    }
+
     pub fn internals(&self) -> AptosVMInternals {
         AptosVMInternals::new(&self.0)
     }
@@ -315,6 +370,7 @@ impl AptosVM {
         &self,
         storage: &S,
         mut session: SessionExt<S>,
+        cross_vm_calls: &dyn CrossVmCalls,
         gas_meter: &mut AptosGasMeter,
         txn_data: &TransactionMetadata,
         payload: &TransactionPayload,
@@ -393,6 +449,8 @@ impl AptosVM {
             // Charge gas for write set
             gas_meter.charge_write_set_gas(change_set_ext.write_set().iter())?;
             // TODO(Gas): Charge for aggregator writes
+            let change_set_ext = cross_vm_calls
+                .merge_into_transaction(change_set_ext, gas_meter.change_set_configs())?;
 
             self.success_transaction_cleanup(
                 storage,
@@ -507,6 +565,7 @@ impl AptosVM {
         &self,
         storage: &S,
         mut session: SessionExt<S>,
+        cross_vm_calls: &dyn CrossVmCalls,
         gas_meter: &mut AptosGasMeter,
         txn_data: &TransactionMetadata,
         modules: &ModuleBundle,
@@ -558,6 +617,8 @@ impl AptosVM {
         // Charge gas for write set
         gas_meter.charge_write_set_gas(change_set_ext.write_set().iter())?;
         // TODO(Gas): Charge for aggregator writes
+        let change_set_ext = cross_vm_calls
+            .merge_into_transaction(change_set_ext, gas_meter.change_set_configs())?;
 
         self.success_transaction_cleanup(storage, change_set_ext, gas_meter, txn_data, log_context)
     }
@@ -676,20 +737,31 @@ impl AptosVM {
     pub fn make_cross_space_handler<'a, S: MoveResolverExt + StateView>(
         &'a self,
         storage: &'a S,
+        machine: &'a EvmMachine,
+        evm_context: &'a EvmContext,
         log_context: &AdapterLogSchema,
-    ) -> CrossSpaceHandler<S> {
-        let session = self.0.new_session(storage, SessionId::Void);
+    ) -> CrossSpaceHandler<'a, S> {
         // FIXME(0xg): better way to handler error
         let gas_params = self.0.get_gas_parameters(log_context).unwrap();
         let storage_gas_params = self.0.get_storage_gas_parameters(log_context).unwrap();
-        let gas_meter = AptosGasMeter::new(
-            self.0.get_gas_feature_version(),
-            gas_params.clone(),
-            storage_gas_params.clone(),
-            0,
-        );
 
-        CrossSpaceHandler { session, gas_meter }
+        CrossSpaceHandler {
+            vm: &self.0,
+            storage,
+            machine,
+            evm_context,
+            gas_params,
+            storage_gas_params,
+            changes: Default::default(),
+            accesses: Default::default(),
+            substates: Default::default(),
+            checkpoints: Default::default(),
+            call_stack: Default::default(),
+            next_call: Cell::new(TRANSACTION_CALL + 1),
+            last_call_gas_used: Cell::new(0),
+            traces: RefCell::new(AptosVM::get_cross_vm_tracing().then(Vec::new)),
+            called_move: Cell::new(false),
+        }
     }
 
     pub(crate) fn execute_user_transaction<'a, S: MoveResolverExt + StateView>(
@@ -709,22 +781,44 @@ impl AptosVM {
             };
         }
 
+        let gas_params = unwrap_or_discard!(self.0.get_gas_parameters(log_context));
+        let storage_gas_params = unwrap_or_discard!(self.0.get_storage_gas_parameters(log_context));
+        let txn_data = TransactionMetadata::new(txn);
+
+        // The `call_evm`s of the transaction can call back into Move, and the transaction reads
+        // what these calls write.
+        let call_move_handler =
+            self.make_cross_space_handler(storage, machine, evm_context, log_context);
+        call_move_handler.enter_transaction(txn.payload());
+        let txn_view = call_move_handler.transaction_view();
+        let mut evm_substate = Substate::new();
         let mut view_wrapper = ViewWrapper {
             inner: storage,
             cache: Default::default(),
         };
-        let mut state = EvmState::new(&mut view_wrapper);
+        let mut state = EvmState::new_with_move_vm(&mut view_wrapper, &call_move_handler);
+        let evm_issued = state.state.total_issued_tokens();
         let executor = make_executor(&machine, &evm_context, &mut state);
-        let cross_space_handler = CrossVMContext { executor };
+        let mut called_evm = false;
+        let mut evm_abort_info = None;
+        let move_gas_left = MoveGasLeft::default();
+        let cross_space_handler = CrossVMContext::new(
+            executor,
+            move_gas_left.clone(),
+            &mut evm_substate,
+            0,
+            &mut called_evm,
+            &mut evm_abort_info,
+        );
 
         // Revalidate the transaction.
         let mut session =
             self.0
-                .new_session_with_evm_ref(storage, SessionId::txn(txn), cross_space_handler);
-        if let Err(err) = validate_signature_checked_transaction::<S, Self>(
+                .new_session_with_evm_ref(&txn_view, SessionId::txn(txn), cross_space_handler);
+        if let Err(err) = validate_signature_checked_transaction(
             self,
             &mut session,
-            storage,
+            &txn_view,
             txn,
             false,
             log_context,
@@ -743,30 +837,35 @@ impl AptosVM {
         //     session = self.0.new_session_with_evm_ref(storage, SessionId::txn(txn), cross_space_handler);
         // }
 
-        let gas_params = unwrap_or_discard!(self.0.get_gas_parameters(log_context));
-        let storage_gas_params = unwrap_or_discard!(self.0.get_storage_gas_parameters(log_context));
-        let txn_data = TransactionMetadata::new(txn);
         let mut gas_meter = AptosGasMeter::new(
             self.0.get_gas_feature_version(),
             gas_params.clone(),
             storage_gas_params.clone(),
             txn_data.max_gas_amount(),
         );
+        gas_meter.share_balance(move_gas_left);
 
         let result = match txn.payload() {
             payload @ TransactionPayload::Script(_)
             | payload @ TransactionPayload::EntryFunction(_) => self
                 .execute_script_or_entry_function(
-                    storage,
+                    &txn_view,
                     session,
+                    &call_move_handler,
                     &mut gas_meter,
                     &txn_data,
                     payload,
                     log_context,
                 ),
-            TransactionPayload::ModuleBundle(m) => {
-                self.execute_modules(storage, session, &mut gas_meter, &txn_data, m, log_context)
-            },
+            TransactionPayload::ModuleBundle(m) => self.execute_modules(
+                &txn_view,
+                session,
+                &call_move_handler,
+                &mut gas_meter,
+                &txn_data,
+                m,
+                log_context,
+            ),
             TransactionPayload::EthTransactionPayload(_) => {
                 unreachable!("AptosVM cannot process EVM transaction")
             },
@@ -778,18 +877,66 @@ impl AptosVM {
             .expect("Balance should always be less than or equal to max gas amount set");
         TXN_GAS_USAGE.observe(u64::from(gas_usage) as f64);
 
-        match result {
+        let (vm_status, output) = match result {
             Ok(output) => {
                 let (vm_status, output) = output.into();
                 let (delta_change_set, output) = output.into();
-                let (write_set, events, gas_used, status) = output.unpack();
+                let (write_set, mut events, gas_used, status) = output.unpack();
+
+                // Failing to read the EVM state discards the transaction, as for an EVM transaction.
+                macro_rules! unwrap_or_storage_error {
+                    ($res:expr) => {
+                        match $res {
+                            Ok(s) => s,
+                            Err(e) => {
+                                error!("Cannot read the EVM state of a Move transaction: {}", e);
+                                return discard_error_vm_status(VMStatus::Error(
+                                    StatusCode::STORAGE_ERROR,
+                                ));
+                            },
+                        }
+                    };
+                }
 
-                state
-                    .state
-                    .commit(Default::default(), None)
-                    .expect("no db error");
+                evm_substate.accrue(call_move_handler.take_substate());
+                let killed =
+                    unwrap_or_storage_error!(make_executor(&machine, &evm_context, &mut state)
+                        .cross_vm_postprocessing(&mut evm_substate));
+                if called_evm {
+                    let supply = NativeTokenSupplyChange {
+                        bridge_before: native_bridge_totals(storage),
+                        bridge_after: native_bridge_totals(
+                            &DeltaStateView::new(storage, &write_set).as_move_resolver(),
+                        ),
+                        evm_issued_before: evm_issued,
+                        evm_issued_after: state.state.total_issued_tokens(),
+                    };
+                    if let Err(e) = supply.check_conserved(killed) {
+                        log_context.alert();
+                        error!(*log_context, "{}", e);
+                        return discard_error_vm_status(VMStatus::Error(
+                            StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+                        ));
+                    }
+                }
+                unwrap_or_storage_error!(state.state.commit(Default::default(), None));
                 std::mem::drop(state);
 
+                events.extend(unwrap_or_storage_error!(evm_events_to_aptos_events(
+                    &mut view_wrapper,
+                    evm_substate.logs,
+                    evm_context.event_key_scheme(),
+                )));
+                let contracts_created: Vec<_> = evm_substate
+                    .contracts_created
+                    .iter()
+                    .map(|contract| contract.address)
+                    .collect();
+                events.extend(unwrap_or_storage_error!(contracts_created_to_aptos_events(
+                    &mut view_wrapper,
+                    &contracts_created
+                )));
+
                 let mut write_set_mut = write_set.into_mut();
                 for (key, op) in view_wrapper.drain() {
                     write_set_mut.insert((key, op));
@@ -805,16 +952,25 @@ impl AptosVM {
                 if txn_status.is_discarded() {
                     discard_error_vm_status(err)
                 } else {
-                    self.failed_transaction_cleanup_and_keep_vm_status(
+                    let (vm_status, output) = self.failed_transaction_cleanup_and_keep_vm_status(
                         err,
                         &mut gas_meter,
                         &txn_data,
                         storage,
                         log_context,
-                    )
+                    );
+                    let output = with_evm_abort_info(&vm_status, output, evm_abort_info);
+                    (vm_status, output)
                 }
             },
+        };
+
+        if called_evm {
+            let (delta_change_set, output) = output.into();
+            let output = TransactionOutputExt::new(delta_change_set, output.with_cross_vm_call());
+            return (vm_status, output);
         }
+        (vm_status, output)
     }
 
     fn execute_writeset<S: MoveResolverExt>(
@@ -1005,12 +1161,11 @@ impl AptosVM {
         let data_cache = state_view.as_move_resolver();
         let context_reader = ContextView::new(&vm, &data_cache, &log_context);
         if let Ok(eth_tx) = txn.try_into() {
-            let call_move_handler = vm.make_cross_space_handler(&data_cache, &log_context);
-            evm.execute_eth_transaction(&context_reader, &data_cache, call_move_handler, &eth_tx)
+            evm.execute_eth_transaction(&vm, &context_reader, &data_cache, &eth_tx, &log_context)
         } else {
             let vm = AptosVM::new(state_view);
             let simulation_vm = AptosSimulationVM(vm);
-            let evm_context = evm.machine.make_context(&context_reader);
+            let evm_context = evm.block_context(&context_reader);
             let (status, output) = simulation_vm.simulate_signed_transaction(
                 &state_view.as_move_resolver(),
                 txn,
@@ -1091,10 +1246,9 @@ impl AptosVM {
                 self.0.check_gas(storage, txn_data, log_context)?;
                 self.0.run_module_prologue(session, txn_data, log_context)
             },
-            TransactionPayload::EthTransactionPayload(_) => {
-                // FIXME(vm)
-                Ok(())
-            },
+            // EVM transactions are checked by `validate_eth_transaction` before they enter
+            // mempool, and by the EVM executor before they run.
+            TransactionPayload::EthTransactionPayload(_) => Ok(()),
         }
     }
 }
@@ -1189,10 +1343,56 @@ impl VMValidator for AptosVM {
         transaction: SignedTransaction,
         state_view: &impl StateView,
     ) -> VMValidatorResult {
+        if let TransactionPayload::EthTransactionPayload(_) = transaction.payload() {
+            return self.validate_eth_transaction(transaction, state_view);
+        }
         validate_signed_transaction(self, transaction, state_view)
     }
 }
 
+impl AptosVM {
+    /// Validates an EVM transaction, which does not run the Move prologue. Its signature is
+    /// checked like the others, then `AptosEVM::check_eth_transaction` checks it against the EVM
+    /// state. A nonce ahead of the account is accepted, as the Move prologue does.
+    fn validate_eth_transaction(
+        &self,
+        transaction: SignedTransaction,
+        state_view: &impl StateView,
+    ) -> VMValidatorResult {
+        let _timer = TXN_VALIDATION_SECONDS.start_timer();
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let txn = match Self::check_signature(transaction) {
+            Ok(t) => t.into_inner(),
+            _ => {
+                return VMValidatorResult::error(StatusCode::INVALID_SIGNATURE);
+            },
+        };
+        let evm_txn: EvmTransaction = (&txn).try_into().expect("an EVM transaction");
+
+        let data_cache = state_view.as_move_resolver();
+        let context_reader = ContextView::new(self, &data_cache, &log_context);
+        let validation_result = AptosEVM::new().check_eth_transaction(
+            &context_reader,
+            state_view,
+            &evm_txn,
+            txn.chain_id(),
+        );
+
+        let (counter_label, result) = match validation_result {
+            Ok(_) | Err(StatusCode::SEQUENCE_NUMBER_TOO_NEW) => (
+                "success",
+                VMValidatorResult::new(None, txn.gas_unit_price()),
+            ),
+            Err(status_code) => ("failure", VMValidatorResult::new(Some(status_code), 0)),
+        };
+        TRANSACTIONS_VALIDATED
+            .with_label_values(&[counter_label])
+            .inc();
+
+        result
+    }
+}
+
 impl VMAdapter for AptosVM {
     fn new_session<'r, R: MoveResolverExt>(
         &self,
@@ -1275,16 +1475,16 @@ pub fn execute_single_transaction<S: MoveResolverExt + StateView>(
                 // println!("Executing user eth tx");
                 // let evm_txn: EvmTransaction = evm_txn;
                 // debug!("(ETH) Transaction nonce: {}, sender: {}", sequence_number_to_be_executed, sender);
-                let call_move_handler = vm.make_cross_space_handler(data_cache, log_context);
                 let (status, output, _) = evm.execute_eth_transaction(
+                    vm,
                     &context_reader,
                     data_cache,
-                    call_move_handler,
                     &evm_txn,
+                    log_context,
                 );
                 (status, output)
             } else {
-                let evm_context = evm.machine.make_context(&context_reader);
+                let evm_context = evm.block_context(&context_reader);
                 // debug!("(Move) Transaction nonce: {}, sender: {}", sequence_number_to_be_executed, sender);
                 vm.execute_user_transaction(
                     data_cache,
@@ -1349,34 +1549,133 @@ pub fn execute_single_transaction<S: MoveResolverExt + StateView>(
 
 pub struct AptosEVM {
     machine: EvmMachine,
+    /// Context of the block the last transaction ran in, shared by the transactions of a block.
+    block_context: Mutex<Option<Arc<EvmContext>>>,
 }
 
 impl AptosEVM {
     pub fn new() -> Self {
         Self {
             machine: EvmMachine::new(),
+            block_context: Mutex::new(None),
+        }
+    }
+
+    /// Returns the EVM context of the current block, which is only made by the first transaction
+    /// of the block. Blocks are told apart by their hash, as two blocks can have the same height.
+    pub(crate) fn block_context(&self, context_reader: &impl EvmContextReader) -> Arc<EvmContext> {
+        let block_hash = context_reader.get_block_hash();
+        let mut block_context = self.block_context.lock();
+        match &*block_context {
+            Some(context) if context.block_hash() == block_hash => context.clone(),
+            _ => {
+                let context = Arc::new(self.machine.make_context(context_reader));
+                *block_context = Some(context.clone());
+                context
+            },
+        }
+    }
+
+    /// Makes the checks the EVM executor makes before it runs a transaction, on the state of
+    /// `storage`, and checks that the transaction is signed for the chain id of the EVM.
+    pub(crate) fn check_eth_transaction<S: StateView>(
+        &self,
+        context_reader: &impl EvmContextReader,
+        storage: &S,
+        txn: &EvmTransaction,
+        chain_id: ChainId,
+    ) -> Result<(), StatusCode> {
+        let context = self.block_context(context_reader);
+        if chain_id.id() as u64 != context.chain_id() {
+            return Err(StatusCode::BAD_CHAIN_ID);
+        }
+
+        let mut view_wrapper = ViewWrapper {
+            inner: storage,
+            cache: Default::default(),
+        };
+        let state = EvmState::new(&mut view_wrapper);
+        let sender = txn.sender();
+        let nonce = state
+            .state
+            .nonce(&sender)
+            .map_err(|_| StatusCode::STORAGE_ERROR)?;
+        if *txn.nonce() < nonce {
+            return Err(StatusCode::SEQUENCE_NUMBER_TOO_OLD);
+        } else if *txn.nonce() > nonce {
+            return Err(StatusCode::SEQUENCE_NUMBER_TOO_NEW);
+        }
+
+        let intrinsic_gas = context.intrinsic_gas(
+            *txn.action() == Action::Create,
+            &txn.data(),
+            txn.access_list().as_deref(),
+        );
+        if *txn.gas() < U256::from(intrinsic_gas) {
+            return Err(StatusCode::MAX_GAS_UNITS_BELOW_MIN_TRANSACTION_GAS_UNITS);
+        }
+
+        let base_fee = context.base_fee_per_gas();
+        if *txn.gas_price() < base_fee {
+            return Err(StatusCode::GAS_UNIT_PRICE_BELOW_MIN_BOUND);
+        }
+
+        // The gas fields fit in a u64, so only the value can overflow.
+        let cost = txn
+            .gas()
+            .saturating_mul(txn.effective_gas_price(&base_fee))
+            .checked_add(*txn.value());
+        let balance = state
+            .state
+            .balance(&sender)
+            .map_err(|_| StatusCode::STORAGE_ERROR)?;
+        match cost {
+            Some(cost) if cost <= balance => Ok(()),
+            _ => Err(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE),
         }
     }
 
     pub(crate) fn execute_eth_transaction<S: MoveResolverExt + StateView>(
         &self,
+        vm: &AptosVM,
         context_reader: &impl EvmContextReader,
         storage: &S,
-        mut call_move_handler: CrossSpaceHandler<S>,
         txn: &EvmTransaction,
+        log_context: &AdapterLogSchema,
     ) -> (VMStatus, TransactionOutputExt, Option<Vec<u8>>) {
-        // FIXME: Ideally, the context should be made every block, instead of every transaction.
-        let context = self.machine.make_context(context_reader);
+        let context = self.block_context(context_reader);
+        let call_move_handler =
+            vm.make_cross_space_handler(storage, &self.machine, &context, log_context);
         let mut view_wrapper = ViewWrapper {
             inner: storage,
             cache: Default::default(),
         };
-        let mut state = EvmState::new_with_move_vm(&mut view_wrapper, &mut call_move_handler);
+        let mut state = EvmState::new_with_move_vm(&mut view_wrapper, &call_move_handler);
+        let evm_issued = state.state.total_issued_tokens();
+        // A failed read is in the read set, so Block-STM re-executes the transaction if the read
+        // was speculative.
+        macro_rules! unwrap_or_storage_error {
+            ($res:expr) => {
+                match $res {
+                    Ok(s) => s,
+                    Err(e) => {
+                        error!("Cannot read the state of an EVM transaction: {}", e);
+                        return (
+                            VMStatus::Error(StatusCode::STORAGE_ERROR),
+                            discard_error_output(StatusCode::STORAGE_ERROR),
+                            None,
+                        );
+                    },
+                }
+            };
+        }
+
         let mut executor = make_executor(&self.machine, &context, &mut state);
-        let output = executor
-            .transact(txn, TransactOptions::exec_with_no_tracing())
-            .expect("no db error");
+        let output = unwrap_or_storage_error!(
+            executor.transact(txn, TransactOptions::exec_with_no_tracing())
+        );
 
+        let succeeded = matches!(output, ExecutionOutcome::Finished(_));
         let output_bytes = match &output {
             ExecutionOutcome::Finished(executed) => Some(executed.output.clone()),
             _ => None,
@@ -1386,29 +1685,83 @@ impl AptosEVM {
         let executed = extract_evm_executed(&output);
         let gas_used = executed.map_or(0u64, |x| x.gas_charged.as_u64());
 
-        state
-            .state
-            .commit(Default::default(), None)
-            .expect("no db error");
+        // The EVM calls of the Move calls are finalized together with the transaction.
+        let mut cross_vm_substate = call_move_handler.take_substate();
+        if succeeded {
+            unwrap_or_storage_error!(make_executor(&self.machine, &context, &mut state)
+                .cross_vm_postprocessing(&mut cross_vm_substate));
+        }
+        // Contracts self-destruct in EVM frames too, so only the value moved by the cross-VM
+        // calls is compared with the bridge.
+        let (cross_vm_issued, cross_vm_burned) = state.state.cross_vm_tokens();
+        unwrap_or_storage_error!(state.state.commit(Default::default(), None));
         std::mem::drop(state);
 
         let events = if let Some(executed) = output.successfully_executed() {
-            evm_events_to_aptos_events(&mut view_wrapper, executed.logs).expect("no db error")
+            let contracts_created: Vec<_> = executed
+                .contracts_created
+                .iter()
+                .chain(&cross_vm_substate.contracts_created)
+                .map(|contract| contract.address)
+                .collect();
+            let mut logs = executed.logs;
+            logs.extend(cross_vm_substate.logs);
+            let mut events = unwrap_or_storage_error!(evm_events_to_aptos_events(
+                &mut view_wrapper,
+                logs,
+                context.event_key_scheme(),
+            ));
+            events.extend(unwrap_or_storage_error!(contracts_created_to_aptos_events(
+                &mut view_wrapper,
+                &contracts_created
+            )));
+            events
         } else {
             vec![]
         };
 
-        // FIXME(0xg): we assume the finalization always success in demo.
-        let move_change_set = call_move_handler.finalize().unwrap();
-
         let mut write_set_mut = WriteSetMut::new(view_wrapper.drain());
-        move_change_set
-            .into_iter()
-            .for_each(|x| write_set_mut.insert(x));
+        let cross_vm_trace = call_move_handler.take_traces(succeeded);
+        let made_cross_vm_call = call_move_handler.called_move.get();
+        // The Move changes of reverted frames are already dropped by the checkpoints of the EVM
+        // state, and a failed transaction keeps none of them.
+        if succeeded {
+            call_move_handler
+                .finalize()
+                .into_iter()
+                .for_each(|x| write_set_mut.insert(x));
+        }
         let write_set = write_set_mut.freeze().unwrap();
         trace!("execution write set {:?}", write_set);
 
-        let transaction_output = TransactionOutput::new(write_set, events, gas_used, txn_status);
+        if succeeded && made_cross_vm_call {
+            let supply = NativeTokenSupplyChange {
+                bridge_before: native_bridge_totals(storage),
+                bridge_after: native_bridge_totals(
+                    &DeltaStateView::new(storage, &write_set).as_move_resolver(),
+                ),
+                evm_issued_before: evm_issued,
+                evm_issued_after: (evm_issued + cross_vm_issued).saturating_sub(cross_vm_burned),
+            };
+            if let Err(e) = supply.check_conserved(U256::zero()) {
+                log_context.alert();
+                error!(*log_context, "{}", e);
+                return (
+                    VMStatus::Error(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR),
+                    discard_error_output(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR),
+                    None,
+                );
+            }
+        }
+
+        let mut transaction_output =
+            TransactionOutput::new(write_set, events, gas_used, txn_status);
+        if let Some(cross_vm_trace) = cross_vm_trace {
+            transaction_output = transaction_output.with_cross_vm_trace(cross_vm_trace);
+        }
+        if made_cross_vm_call {
+            transaction_output = transaction_output.with_cross_vm_call();
+        }
         let transaction_output_ext =
             TransactionOutputExt::new(DeltaChangeSet::empty(), transaction_output);
 
@@ -1416,70 +1769,564 @@ impl AptosEVM {
     }
 }
 
-pub struct CrossSpaceHandler<'r, 'l, S: MoveResolverExt + StateView> {
-    session: SessionExt<'r, 'l, S>,
-    gas_meter: AptosGasMeter,
+/// Reports why `call_evm` aborted a Move transaction, e.g. the revert data of the EVM callee, in
+/// place of the abort info of the error map.
+fn with_evm_abort_info(
+    vm_status: &VMStatus,
+    output: TransactionOutputExt,
+    evm_abort_info: Option<AbortInfo>,
+) -> TransactionOutputExt {
+    let is_call_evm_abort = matches!(
+        vm_status,
+        VMStatus::MoveAbort(AbortLocation::Module(module), abort_codes::EVM_CALL_REVERT)
+            if module.address() == &CORE_CODE_ADDRESS && module.name().as_str() == "cross_vm"
+    );
+    let evm_abort_info = match evm_abort_info {
+        Some(evm_abort_info) if is_call_evm_abort => evm_abort_info,
+        _ => return output,
+    };
+    let (delta_change_set, output) = output.into();
+    let (write_set, events, gas_used, status) = output.unpack();
+    let status = match status {
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort { location, code, .. }) => {
+            TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+                location,
+                code,
+                info: Some(evm_abort_info),
+            })
+        },
+        status => status,
+    };
+    TransactionOutputExt::new(
+        delta_change_set,
+        TransactionOutput::new(write_set, events, gas_used, status),
+    )
 }
 
-impl<'r, 'l, S: MoveResolverExt + StateView> CrossSpaceHandler<'r, 'l, S> {
-    fn cross_space_transfer(&mut self, address: AccountAddress, value: u64) -> Result<(), String> {
-        {
-            let module = ModuleId::new(CORE_CODE_ADDRESS, ident_str!("aptos_coin").to_owned());
-            let function_name = ident_str!("mint");
-            let args = vec![
-                bcs::to_bytes(&CORE_CODE_ADDRESS).unwrap(),
-                bcs::to_bytes(&CORE_CODE_ADDRESS).unwrap(),
-                bcs::to_bytes(&value).unwrap(),
-            ];
-            self.session
-                .execute_function_bypass_visibility(
-                    &module,
-                    function_name,
-                    vec![],
-                    args,
-                    &mut self.gas_meter,
-                )
-                .map_err(|e| format!("Cannot mint: {}", e))?;
+/// `0x1::cross_vm::native_bridge_totals`, or zeros before the bridge is initialized.
+fn native_bridge_totals<S: ResourceResolver>(storage: &S) -> (u128, u128) {
+    storage
+        .get_resource(&CORE_CODE_ADDRESS, &NativeBridgeResource::struct_tag())
+        .ok()
+        .flatten()
+        .and_then(|blob| bcs::from_bytes::<NativeBridgeResource>(&blob).ok())
+        .map_or((0, 0), |bridge| {
+            (bridge.total_from_evm(), bridge.total_to_evm())
+        })
+}
+
+/// The native token moved between the VMs by the cross-VM calls of one transaction, as
+/// recorded by the bridge in Move and by the issuance of the EVM.
+pub(crate) struct NativeTokenSupplyChange {
+    /// `native_bridge_totals` before and after the transaction.
+    pub(crate) bridge_before: (u128, u128),
+    pub(crate) bridge_after: (u128, u128),
+    /// Total issued tokens of the EVM state before and after the transaction.
+    pub(crate) evm_issued_before: U256,
+    pub(crate) evm_issued_after: U256,
+}
+
+impl NativeTokenSupplyChange {
+    /// Checks that the calls have neither created nor destroyed any native token, besides the
+    /// `killed` balances of the contracts which self-destructed.
+    pub(crate) fn check_conserved(&self, killed: U256) -> Result<(), String> {
+        let (from_evm_before, to_evm_before) = self.bridge_before;
+        let (from_evm_after, to_evm_after) = self.bridge_after;
+        let to_evm = to_evm_after
+            .checked_sub(to_evm_before)
+            .ok_or("Octas sent to the EVM decreased")?;
+        // The bridge only records what it minted and burned, so the octas burned for the EVM
+        // count towards the Move supply before the transaction.
+        let before = NativeTokenSupply {
+            move_supply: from_evm_before + to_evm,
+            evm_issued: self.evm_issued_before,
+        };
+        let after = NativeTokenSupply {
+            move_supply: from_evm_after,
+            evm_issued: self.evm_issued_after,
+        };
+        after.check_conserved(&before, killed)
+    }
+}
+
+/// Keeps the status, sub status and location of a Move VM error for the EVM caller.
+fn move_vm_error(err: VMError) -> CallMoveVMError {
+    let location = match err.location() {
+        Location::Module(module_id) => module_id.to_string(),
+        Location::Script => "script".to_string(),
+        Location::Undefined => "undefined".to_string(),
+    };
+    CallMoveVMError::Vm {
+        status: err.major_status() as u64,
+        sub_status: err.sub_status(),
+        location,
+        message: err.message().cloned().unwrap_or_default(),
+    }
+}
+
+/// Runs the Move calls of a transaction from the EVM: the `callMove`s of an EVM transaction, or
+/// the calls back into Move of the `call_evm`s of a Move transaction. Every call runs in its own
+/// session on top of the changes of the earlier calls, so that its changes follow the checkpoints
+/// of the EVM state and are dropped together with a reverted EVM frame. The Move code of a call
+/// can call the EVM again on the state of its caller, so the calls of a transaction nest.
+pub struct CrossSpaceHandler<'a, S: MoveResolverExt + StateView> {
+    vm: &'a AptosVMImpl,
+    storage: &'a S,
+    machine: &'a EvmMachine,
+    evm_context: &'a EvmContext,
+    gas_params: &'a AptosGasParameters,
+    storage_gas_params: &'a StorageGasParameters,
+    /// Changes of the finished calls which have not been reverted.
+    changes: RefCell<CallChanges>,
+    /// Keys loaded by the calls and written by the finished calls.
+    accesses: RefCell<Accesses>,
+    /// Logs, suicides and created contracts of the EVM calls of the finished calls.
+    substates: RefCell<Vec<Substate>>,
+    /// Journal lengths of `changes`, lengths of `traces` and lengths of `substates` at the
+    /// checkpoints of the EVM state.
+    checkpoints: RefCell<Vec<(usize, usize, usize)>>,
+    /// Modules of the running calls and of the Move transaction, innermost last.
+    call_stack: RefCell<Vec<ModuleId>>,
+    /// Id of the next call. A call nested in another one has a greater id.
+    next_call: Cell<usize>,
+    /// Move gas used by the last call, in internal gas units.
+    last_call_gas_used: Cell<u64>,
+    /// Traces of the calls, if cross-VM tracing is enabled.
+    traces: RefCell<Option<CrossVmTrace>>,
+    /// Whether the EVM transaction has called into Move, even if the call was reverted.
+    called_move: Cell<bool>,
+}
+
+/// Call id of the Move transaction whose `call_evm`s call back into Move.
+const TRANSACTION_CALL: usize = 0;
+
+/// A load of a key by a call, or a write of a key by a finished call.
+struct Access {
+    call: usize,
+    write: bool,
+}
+
+/// The accesses of the calls to each key, in order.
+#[derive(Default)]
+struct Accesses(BTreeMap<StateKey, Vec<Access>>);
+
+impl Accesses {
+    fn record(&mut self, key: &StateKey, call: usize, write: bool) {
+        let access = Access { call, write };
+        match self.0.get_mut(key) {
+            Some(accesses) => accesses.push(access),
+            None => {
+                self.0.insert(key.clone(), vec![access]);
+            },
         }
-        {
-            let module = ModuleId::new(CORE_CODE_ADDRESS, ident_str!("aptos_account").to_owned());
-            let function_name = ident_str!("transfer");
-            let args = vec![
-                bcs::to_bytes(&CORE_CODE_ADDRESS).unwrap(),
-                bcs::to_bytes(&address).unwrap(),
-                bcs::to_bytes(&value).unwrap(),
-            ];
-            self.session
-                .execute_function_bypass_visibility(
-                    &module,
-                    function_name,
-                    vec![],
-                    args,
-                    &mut self.gas_meter,
-                )
-                .map_err(|e| format!("Cannot transfer: {}", e))?;
+    }
+}
+
+/// The state a call of a `CrossSpaceHandler` runs on: the storage with the changes of the finished
+/// calls on top. Records the keys the call loads.
+pub struct CallChangesView<'b, S> {
+    base: &'b S,
+    changes: &'b RefCell<CallChanges>,
+    accesses: &'b RefCell<Accesses>,
+    call: usize,
+}
+
+impl<'b, S> TStateView for CallChangesView<'b, S>
+where
+    S: TStateView<Key = StateKey>,
+{
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<Vec<u8>>> {
+        self.accesses
+            .borrow_mut()
+            .record(state_key, self.call, false);
+        match self.changes.borrow().write_set().get(state_key) {
+            Some(WriteOp::Creation(data) | WriteOp::Modification(data)) => Ok(Some(data.clone())),
+            Some(WriteOp::Deletion) => Ok(None),
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base.is_genesis()
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base.get_usage()
+    }
+}
+
+/// The calls a Move transaction makes back into Move through its `call_evm`s.
+trait CrossVmCalls {
+    /// Squashes the changes of the Move transaction on top of the changes of the calls, and aborts
+    /// the transaction if they conflict.
+    fn merge_into_transaction(
+        &self,
+        change_set_ext: ChangeSetExt,
+        configs: &ChangeSetConfigs,
+    ) -> Result<ChangeSetExt, VMStatus>;
+}
+
+/// Changes of the cross-space calls of an EVM transaction, squashed into one write set as the
+/// calls finish. A journal of what the calls overwrite undoes the calls of a reverted EVM frame.
+#[derive(Default)]
+pub(crate) struct CallChanges {
+    changes: WriteSet,
+    /// What `changes` held for a key before a call wrote it, in write order.
+    journal: Vec<(StateKey, Option<WriteOp>)>,
+}
+
+impl CallChanges {
+    pub(crate) fn write_set(&self) -> &WriteSet {
+        &self.changes
+    }
+
+    pub(crate) fn into_write_set(self) -> WriteSet {
+        self.changes
+    }
+
+    pub(crate) fn journal_len(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// The writes of `write_set` to keys which the changes do not write yet.
+    pub(crate) fn new_writes<'b>(
+        &'b self,
+        write_set: &'b WriteSet,
+    ) -> impl Iterator<Item = (&'b StateKey, &'b WriteOp)> {
+        write_set
+            .iter()
+            .filter(move |(key, _)| self.changes.get(key).is_none())
+    }
+
+    /// Squashes the write set of a finished call into the changes. If the write sets cannot be
+    /// squashed, the changes are left as they were.
+    pub(crate) fn apply(&mut self, write_set: &WriteSet) -> Result<(), String> {
+        let journal_len = self.journal.len();
+        let mut changes = std::mem::take(&mut self.changes).into_mut();
+        let result = write_set.iter().try_for_each(|(key, op)| {
+            let prev = changes.get(key).cloned();
+            let squashed = WriteSetMut::new(prev.clone().map(|prev| (key.clone(), prev)))
+                .squash(WriteSetMut::new(vec![(key.clone(), op.clone())]))
+                .map_err(|e| format!("Cannot squash write sets: {}", e))?;
+            match squashed.get(key) {
+                Some(op) => changes.insert((key.clone(), op.clone())),
+                None => {
+                    changes.as_inner_mut().remove(key);
+                },
+            }
+            self.journal.push((key.clone(), prev));
+            Ok(())
+        });
+        self.changes = changes.freeze().expect("freezing a write set cannot fail");
+        if result.is_err() {
+            self.undo(journal_len);
+        }
+        result
+    }
+
+    /// Restores the changes to what they were when the journal had `journal_len` entries.
+    pub(crate) fn undo(&mut self, journal_len: usize) {
+        let mut changes = std::mem::take(&mut self.changes).into_mut();
+        for (key, prev) in self.journal.drain(journal_len..).rev() {
+            match prev {
+                Some(op) => changes.insert((key, op)),
+                None => {
+                    changes.as_inner_mut().remove(&key);
+                },
+            }
+        }
+        self.changes = changes.freeze().expect("freezing a write set cannot fail");
+    }
+}
+
+/// Arguments of a cross-space call.
+enum CallData {
+    /// BCS encoded arguments, see `cross_space_call`.
+    Bcs(Vec<Vec<u8>>),
+    /// ABI encoded arguments, see `cross_space_call_typed`.
+    Abi(Vec<u8>),
+    /// ABI encoded arguments of an entry function called with a signer, see
+    /// `cross_space_call_as_signer`.
+    AbiAsSigner(Vec<u8>),
+}
+
+/// Gas used between two balances of the gas meter.
+fn gas_used(before: Gas, after: Gas) -> u64 {
+    before.checked_sub(after).map_or(0, Into::into)
+}
+
+impl<'a, S: MoveResolverExt + StateView> CrossSpaceHandler<'a, S> {
+    /// The state the call with id `call` runs on.
+    fn view(&self, call: usize) -> StorageAdapterOwned<CallChangesView<'_, S>> {
+        CallChangesView {
+            base: self.storage,
+            changes: &self.changes,
+            accesses: &self.accesses,
+            call,
+        }
+        .into_move_resolver()
+    }
+
+    /// The state the Move transaction runs on, if its `call_evm`s call back into Move.
+    pub(crate) fn transaction_view(&self) -> StorageAdapterOwned<CallChangesView<'_, S>> {
+        self.view(TRANSACTION_CALL)
+    }
+
+    /// Records the entry function module of the Move transaction for the re-entrancy guard.
+    pub(crate) fn enter_transaction(&self, payload: &TransactionPayload) {
+        if let TransactionPayload::EntryFunction(entry_function) = payload {
+            self.call_stack
+                .borrow_mut()
+                .push(entry_function.module().clone());
+        }
+    }
+
+    /// Refuses a call into a module which guards itself against re-entrant calls from the EVM
+    /// while the module is still running further up the call stack.
+    fn check_reentrancy<R: ResourceResolver>(
+        &self,
+        resolver: &R,
+        module: &ModuleId,
+    ) -> Result<(), String> {
+        if !self.call_stack.borrow().contains(module) {
+            return Ok(());
+        }
+        let guarded = resolver
+            .get_resource(module.address(), &ReentrancyGuardResource::struct_tag())
+            .ok()
+            .flatten()
+            .and_then(|blob| bcs::from_bytes::<ReentrancyGuardResource>(&blob).ok())
+            .map_or(false, |guard| guard.is_guarded(module.name().as_str()));
+        if guarded {
+            return Err(format!("Re-entrant call into guarded module {}", module));
+        }
+        Ok(())
+    }
+
+    /// Checks the changes of the finished call `call` against the calls nested in it, since a
+    /// session caches what it loads: a nested call cannot see what the call has written, and the
+    /// call cannot see what a nested call writes to a key it has loaded before. Returns the first
+    /// key the call and a nested call access in a conflicting order.
+    ///
+    /// The writes of a running call stay in the data cache of its Move VM session, which the
+    /// natives cannot reach, so they can neither be flushed to the calls nested in a `call_evm`
+    /// nor be replaced in the cache by what these calls write. The conflicting orders are refused
+    /// instead, until the session exposes its pending writes.
+    fn check_conflicts<'k>(
+        &self,
+        call: usize,
+        writes: impl IntoIterator<Item = &'k StateKey>,
+    ) -> Result<(), StateKey> {
+        // The calls nested in `call` have the ids after it.
+        if self.next_call.get() == call + 1 {
+            return Ok(());
+        }
+        let accesses = self.accesses.borrow();
+        let first_load = |accesses: &[Access]| {
+            accesses
+                .iter()
+                .position(|access| access.call == call && !access.write)
+        };
+        let nested_after = |accesses: &[Access], from: usize, writes_only: bool| {
+            accesses[from..]
+                .iter()
+                .any(|access| access.call > call && (access.write || !writes_only))
+        };
+        for (key, key_accesses) in &accesses.0 {
+            if let Some(position) = first_load(key_accesses) {
+                if nested_after(key_accesses, position, true) {
+                    return Err(key.clone());
+                }
+            }
+        }
+        for key in writes {
+            if let Some(key_accesses) = accesses.0.get(key) {
+                let from = first_load(key_accesses).unwrap_or(0);
+                if nested_after(key_accesses, from, false) {
+                    return Err(key.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A gas meter for a call holding the EVM gas forwarded to it, converted to internal gas.
+    fn new_gas_meter(&self, gas: U256) -> AptosGasMeter {
+        let gas = if gas >= U256::from(u64::MAX) {
+            u64::MAX
+        } else {
+            gas.as_u64()
+        };
+        let mut gas_meter = AptosGasMeter::new(
+            self.vm.get_gas_feature_version(),
+            self.gas_params.clone(),
+            self.storage_gas_params.clone(),
+            0,
+        );
+        gas_meter.cross_space_topup(InternalGas::new(
+            gas.saturating_mul(INTERNAL_GAS_PER_EVM_GAS),
+        ));
+        gas_meter
+    }
+
+    /// Finishes the session of a call and charges its storage gas. The keys written by the earlier
+    /// calls have been paid for already. Returns the changes of the call.
+    fn finish_session<R: MoveResolverExt>(
+        &self,
+        mut session: SessionExt<R>,
+        gas_meter: &mut AptosGasMeter,
+    ) -> Result<ChangeSet, String> {
+        if session.extract_publish_request().is_some() {
+            return Err("Can not init module in cross-space call".to_string());
+        }
+
+        let session_output = session.finish().map_err(|e| format!("{:?}", e))?;
+        let change_set_ext = session_output
+            .into_change_set(&mut (), gas_meter.change_set_configs())
+            .map_err(|e| format!("Cannot get change set: {:?}", e))?;
+
+        let (_, change_set) = change_set_ext.into_inner();
+
+        gas_meter
+            .charge_write_set_gas(self.changes.borrow().new_writes(change_set.write_set()))
+            .map_err(|e| format!("Cannot charge storage gas: {:?}", e))?;
+        Ok(change_set)
+    }
+
+    /// Takes the traces of the calls, if cross-VM tracing is enabled. All the calls of a failed
+    /// transaction are reverted.
+    fn take_traces(&self, succeeded: bool) -> Option<CrossVmTrace> {
+        let mut traces = self.traces.take()?;
+        if !succeeded {
+            traces.iter_mut().for_each(|trace| trace.reverted = true);
+        }
+        Some(traces)
+    }
+
+    /// Takes the logs, suicides and created contracts of the EVM calls of the calls which have
+    /// not been reverted.
+    pub(crate) fn take_substate(&self) -> Substate {
+        let mut substate = Substate::new();
+        for call_substate in self.substates.take() {
+            substate.accrue(call_substate);
+        }
+        substate
+    }
+
+    /// Moves the value sent along with the call to `address`, then calls the function if
+    /// `module_name` is not empty. A call with a signer moves the value to the signer instead.
+    fn execute_call<R: MoveResolverExt>(
+        session: &mut SessionExt<R>,
+        gas_meter: &mut TracingGasMeter,
+        caller: Address,
+        address: AccountAddress,
+        module_name: String,
+        func_name: String,
+        data: CallData,
+        types: Vec<TypeTag>,
+        value: U256,
+    ) -> Result<Vec<u8>, CallMoveVMError> {
+        if !matches!(data, CallData::AbiAsSigner(_)) {
+            Self::prepare_cross_space_call(session, gas_meter, address, value)?;
+            if module_name.is_empty() {
+                return Ok(vec![]);
+            }
         }
+        match data {
+            CallData::Bcs(data) => Self::cross_space_call(
+                session,
+                gas_meter,
+                address,
+                module_name,
+                func_name,
+                caller,
+                data,
+                types,
+            ),
+            CallData::Abi(data) => Self::cross_space_call_typed(
+                session,
+                gas_meter,
+                address,
+                module_name,
+                func_name,
+                caller,
+                data,
+                types,
+            ),
+            CallData::AbiAsSigner(data) => Self::cross_space_call_as_signer(
+                session,
+                gas_meter,
+                address,
+                module_name,
+                func_name,
+                caller,
+                data,
+                types,
+                value,
+            ),
+        }
+    }
 
+    /// Issues the value sent along with the call to `address`. The EVM has burned it already, and
+    /// checked that it is a whole number of octas.
+    fn prepare_cross_space_call<R: MoveResolverExt>(
+        session: &mut SessionExt<R>,
+        gas_meter: &mut TracingGasMeter,
+        address: AccountAddress,
+        value: U256,
+    ) -> Result<(), String> {
+        let value = wei_to_octas(value)
+            .ok_or_else(|| format!("Value {} is not a whole number of octas", value))?;
+
+        if value > 0 {
+            Self::cross_space_transfer(session, gas_meter, address, value)?;
+        }
+        Ok(())
+    }
+
+    fn cross_space_transfer<R: MoveResolverExt>(
+        session: &mut SessionExt<R>,
+        gas_meter: &mut TracingGasMeter,
+        address: AccountAddress,
+        value: u64,
+    ) -> Result<(), String> {
+        let module = ModuleId::new(CORE_CODE_ADDRESS, ident_str!("cross_vm").to_owned());
+        let function_name = ident_str!("deposit_from_evm");
+        let args = vec![
+            bcs::to_bytes(&address).unwrap(),
+            bcs::to_bytes(&value).unwrap(),
+        ];
+        gas_meter.enter(&module, function_name.as_str(), &[]);
+        session
+            .execute_function_bypass_visibility(&module, function_name, vec![], args, gas_meter)
+            .map_err(|e| format!("Cannot deposit: {}", e))?;
         Ok(())
     }
 
-    fn cross_space_call(
-        &mut self,
+    fn cross_space_call<R: MoveResolverExt>(
+        session: &mut SessionExt<R>,
+        gas_meter: &mut TracingGasMeter,
         address: AccountAddress,
         module_name: String,
         func_name: String,
         caller: Address,
         data: Vec<Vec<u8>>,
         ty_args: Vec<TypeTag>,
-    ) -> Result<Vec<u8>, String> {
+    ) -> Result<Vec<u8>, CallMoveVMError> {
         let module = ModuleId::new(
             address,
             Identifier::new(module_name).map_err(|e| format!("{}", e))?,
         );
         let function = Identifier::new(func_name).map_err(|e| format!("{}", e))?;
 
-        let res = self
-            .session
+        gas_meter.enter(&module, function.as_str(), &ty_args);
+        let res = session
             .execute_function_bypass_visibility(
                 &module,
                 &function,
@@ -1488,9 +2335,9 @@ impl<'r, 'l, S: MoveResolverExt + StateView> CrossSpaceHandler<'r, 'l, S> {
                     bcs::to_bytes(&caller.0.to_vec()).unwrap(),
                     bcs::to_bytes(&data).unwrap(),
                 ],
-                &mut self.gas_meter,
+                gas_meter,
             )
-            .map_err(|e| format!("{}", e))?;
+            .map_err(move_vm_error)?;
 
         let (raw, ty) = res
             .return_values
@@ -1510,35 +2357,391 @@ impl<'r, 'l, S: MoveResolverExt + StateView> CrossSpaceHandler<'r, 'l, S> {
                 .into_iter()
                 .map(extract_byte)
                 .collect::<Option<Vec<u8>>>()
-                .ok_or("Incorrect return type".to_string())
+                .ok_or_else(|| "Incorrect return type".to_string().into())
         } else {
-            Err("Incorrect return type".to_string())
+            Err("Incorrect return type".to_string().into())
         }
     }
 
-    fn finalize(mut self) -> Result<WriteSet, String> {
-        if self.session.extract_publish_request().is_some() {
-            return Err("Can not init module in cross-space call".to_string());
+    /// Calls a Move function whose first parameter takes the EVM caller as `vector<u8>`. The other
+    /// parameters are ABI decoded from `data`, and the return values are ABI encoded.
+    fn cross_space_call_typed<R: MoveResolverExt>(
+        session: &mut SessionExt<R>,
+        gas_meter: &mut TracingGasMeter,
+        address: AccountAddress,
+        module_name: String,
+        func_name: String,
+        caller: Address,
+        data: Vec<u8>,
+        ty_args: Vec<TypeTag>,
+    ) -> Result<Vec<u8>, CallMoveVMError> {
+        let module = ModuleId::new(
+            address,
+            Identifier::new(module_name).map_err(|e| format!("{}", e))?,
+        );
+        let function = Identifier::new(func_name).map_err(|e| format!("{}", e))?;
+
+        let func_inst = session
+            .load_function(&module, &function, &ty_args)
+            .map_err(move_vm_error)?;
+        let mut args = vec![bcs::to_bytes(&caller.0.to_vec()).unwrap()];
+        args.extend(Self::decode_abi_args(session, &func_inst, &data)?);
+
+        gas_meter.enter(&module, function.as_str(), &ty_args);
+        let res = session
+            .execute_function_bypass_visibility(&module, &function, ty_args, args, gas_meter)
+            .map_err(move_vm_error)?;
+
+        let mut return_types = vec![];
+        let mut return_values = vec![];
+        for (raw, layout) in res.return_values.iter() {
+            let ty = layout_to_abi_type(layout).map_err(|e| format!("{}", e))?;
+            let value = MoveValue::simple_deserialize(raw, layout).map_err(|e| format!("{}", e))?;
+            return_values.push(move_value_to_abi(value, &ty).map_err(|e| format!("{}", e))?);
+            return_types.push(ty);
         }
+        encode_list(&return_types, &return_values).map_err(|e| e.0.to_string().into())
+    }
 
-        let session_output = self.session.finish().map_err(|e| format!("{:?}", e))?;
-        let change_set_ext = session_output
-            .into_change_set(&mut (), self.gas_meter.change_set_configs())
-            .map_err(|e| format!("Cannot get change set: {:?}", e))?;
+    /// Calls an entry function with the signer of the Move account linked to `caller`, which must
+    /// have delegated its signer for the function with `0x1::signer_delegation`, and issues the
+    /// value to that account. The parameters after the signer are ABI decoded from `data` and
+    /// validated like the arguments of a transaction. Entry functions return nothing.
+    fn cross_space_call_as_signer<R: MoveResolverExt>(
+        session: &mut SessionExt<R>,
+        gas_meter: &mut TracingGasMeter,
+        address: AccountAddress,
+        module_name: String,
+        func_name: String,
+        caller: Address,
+        data: Vec<u8>,
+        ty_args: Vec<TypeTag>,
+        value: U256,
+    ) -> Result<Vec<u8>, CallMoveVMError> {
+        let module = ModuleId::new(
+            address,
+            Identifier::new(module_name).map_err(|e| format!("{}", e))?,
+        );
+        let function = Identifier::new(func_name).map_err(|e| format!("{}", e))?;
 
-        let write_set = change_set_ext.write_set().clone();
+        let account = Self::authorize_signer(session, gas_meter, caller, &module, &function)?;
+        Self::prepare_cross_space_call(session, gas_meter, account, value)?;
 
-        self.gas_meter
-            .charge_write_set_gas(write_set.iter())
-            .map_err(|e| format!("Cannot charge storage gas: {:?}", e))?;
+        let func_inst = session
+            .load_function(&module, &function, &ty_args)
+            .map_err(move_vm_error)?;
+        let takes_signer = match func_inst.parameters.first() {
+            Some(Type::Signer) => true,
+            Some(Type::Reference(inner_type)) => matches!(&**inner_type, Type::Signer),
+            _ => false,
+        };
+        if !takes_signer {
+            return Err("Entry function does not take a signer".to_string().into());
+        }
+        let args = Self::decode_abi_args(session, &func_inst, &data)?;
+        let args = verifier::transaction_arg_validation::validate_combine_signer_and_txn_args(
+            session,
+            vec![account],
+            args,
+            &func_inst,
+        )
+        .map_err(|e| format!("Invalid entry function call: {:?}", e))?;
+
+        gas_meter.enter(&module, function.as_str(), &ty_args);
+        session
+            .execute_entry_function(&module, &function, ty_args, args, gas_meter)
+            .map_err(move_vm_error)?;
+        Ok(vec![])
+    }
+
+    /// Returns the Move account linked to `caller` if it has delegated its signer for the
+    /// function, and aborts otherwise.
+    fn authorize_signer<R: MoveResolverExt>(
+        session: &mut SessionExt<R>,
+        gas_meter: &mut TracingGasMeter,
+        caller: Address,
+        module: &ModuleId,
+        function: &Identifier,
+    ) -> Result<AccountAddress, CallMoveVMError> {
+        let delegation = ModuleId::new(
+            CORE_CODE_ADDRESS,
+            ident_str!("signer_delegation").to_owned(),
+        );
+        let function_name = ident_str!("authorize");
+        let args = vec![
+            bcs::to_bytes(&caller.0.to_vec()).unwrap(),
+            bcs::to_bytes(module.address()).unwrap(),
+            bcs::to_bytes(module.name().as_str()).unwrap(),
+            bcs::to_bytes(function.as_str()).unwrap(),
+        ];
+        gas_meter.enter(&delegation, function_name.as_str(), &[]);
+        let res = session
+            .execute_function_bypass_visibility(&delegation, function_name, vec![], args, gas_meter)
+            .map_err(move_vm_error)?;
+        let (raw, _) = res
+            .return_values
+            .first()
+            .ok_or("Incorrect numbers of return value".to_string())?;
+        bcs::from_bytes(raw).map_err(|e| format!("{}", e).into())
+    }
+
+    /// ABI decodes the parameters of a function after the first one from `data`, and BCS encodes
+    /// them as its arguments.
+    fn decode_abi_args<R: MoveResolverExt>(
+        session: &SessionExt<R>,
+        func_inst: &LoadedFunctionInstantiation,
+        data: &[u8],
+    ) -> Result<Vec<Vec<u8>>, CallMoveVMError> {
+        let param_layouts = func_inst
+            .parameters
+            .iter()
+            .skip(1)
+            .map(|ty| {
+                let tag = session.get_type_tag(ty).map_err(|e| format!("{}", e))?;
+                session.get_type_layout(&tag).map_err(|e| format!("{}", e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let param_types = param_layouts
+            .iter()
+            .map(layout_to_abi_type)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| format!("{}", e))?;
+
+        let values = decode_list(&param_types, data).map_err(|e| e.0.to_string())?;
+        let mut args = vec![];
+        for (value, layout) in values.into_iter().zip(&param_layouts) {
+            let move_value = abi_to_move_value(value, layout).map_err(|e| format!("{}", e))?;
+            args.push(
+                move_value
+                    .simple_serialize()
+                    .ok_or("Cannot serialize argument".to_string())?,
+            );
+        }
+        Ok(args)
+    }
+
+    /// Resolves the name the EVM calls a function by to the Move function which the module
+    /// registers under it with `#[cross_vm]`, so that the EVM can only call the functions a module
+    /// exposes to it. Entry functions are called by their own name, since the signer delegation
+    /// guards them instead. The function is validated again, since modules published before the
+    /// validation was tightened may register functions taking a signer.
+    fn resolve_function<R: MoveResolverExt>(
+        resolver: &R,
+        address: AccountAddress,
+        module_name: &str,
+        func_name: String,
+        data: &CallData,
+    ) -> Result<String, CallMoveVMError> {
+        if module_name.is_empty() || matches!(data, CallData::AbiAsSigner(_)) {
+            return Ok(func_name);
+        }
+        let module_id = ModuleId::new(
+            address,
+            Identifier::new(module_name).map_err(|e| format!("{}", e))?,
+        );
+        let bytes = resolver
+            .get_module(&module_id)
+            .map_err(|e| format!("Cannot load module {}: {:?}", module_id, e))?
+            .ok_or_else(|| format!("Module {} does not exist", module_id))?;
+        let module = CompiledModule::deserialize(&bytes)
+            .map_err(|e| format!("Cannot deserialize module {}: {}", module_id, e))?;
+        let resolved = aptos_framework::get_module_metadata(&module)
+            .and_then(|metadata| metadata.cross_vm_function(&func_name))
+            .ok_or_else(|| {
+                format!(
+                    "{}::{} is not registered as a cross-VM function",
+                    module_id, func_name
+                )
+            })?;
+        let functions = module
+            .function_defs
+            .iter()
+            .map(|func_def| Function::new(&module, func_def))
+            .collect::<BTreeMap<_, _>>();
+        aptos_framework::is_valid_cross_vm_function(&functions, &resolved).map_err(|_| {
+            format!(
+                "{}::{} is not a valid cross-VM function",
+                module_id, resolved
+            )
+        })?;
+        Ok(resolved)
+    }
+
+    /// Returns the Move changes of the calls which have not been reverted.
+    fn finalize(self) -> WriteSet {
+        self.changes.into_inner().into_write_set()
+    }
+
+    /// Calls a Move function, or just transfers the value if `module_name` is empty, and traces
+    /// the call if cross-VM tracing is enabled. The Move code runs at call depth `depth`, and can
+    /// call the EVM again on `state`.
+    fn call(
+        &self,
+        state: &mut dyn StateTrait,
+        depth: usize,
+        caller: Address,
+        address: Vec<u8>,
+        module_name: String,
+        func_name: String,
+        data: CallData,
+        types: Vec<TypeTag>,
+        value: U256,
+        gas: U256,
+    ) -> std::result::Result<Vec<u8>, CallMoveVMError> {
+        self.called_move.set(true);
+        let call = self.next_call.get();
+        self.next_call.set(call + 1);
+        let resolver = self.view(call);
+
+        let address = AccountAddress::new(address.try_into().unwrap());
+        let module = Identifier::new(module_name.as_str())
+            .ok()
+            .map(|name| ModuleId::new(address, name));
+        let trace = self.traces.borrow().is_some().then(|| CrossVmCallTrace {
+            caller,
+            address,
+            module: module_name.clone(),
+            function: func_name.clone(),
+            ty_args: types.clone(),
+            calls: vec![],
+            events: vec![],
+            write_set: WriteSet::default(),
+            execution_gas_used: 0,
+            storage_gas_used: 0,
+            error: None,
+            reverted: false,
+        });
+
+        let mut gas_meter = self.new_gas_meter(gas);
+        let internal_balance = gas_meter.internal_balance();
+        let balance = gas_meter.balance();
+        let move_gas_left = MoveGasLeft::default();
+        gas_meter.share_balance(move_gas_left.clone());
+        let mut substate = Substate::new();
+        let mut called_evm = false;
+        let mut evm_abort_info = None;
+        let cross_vm_context = CrossVMContext::new(
+            make_executor_with_state(self.machine, self.evm_context, state),
+            move_gas_left,
+            &mut substate,
+            depth,
+            &mut called_evm,
+            &mut evm_abort_info,
+        );
+        let mut session =
+            self.vm
+                .new_session_with_evm_ref(&resolver, SessionId::Void, cross_vm_context);
+
+        let entered = match &module {
+            Some(module) => self.check_reentrancy(&resolver, module),
+            None => Ok(()),
+        };
+        if let Some(module) = &module {
+            self.call_stack.borrow_mut().push(module.clone());
+        }
+        let mut tracing_meter = TracingGasMeter::new(&mut gas_meter, trace.is_some());
+        let result = entered
+            .map_err(CallMoveVMError::from)
+            .and_then(|()| {
+                Self::resolve_function(&resolver, address, &module_name, func_name, &data)
+            })
+            .and_then(|func_name| {
+                Self::execute_call(
+                    &mut session,
+                    &mut tracing_meter,
+                    caller,
+                    address,
+                    module_name,
+                    func_name,
+                    data,
+                    types,
+                    value,
+                )
+            });
+        let calls = tracing_meter.finish();
+        let execution_balance = gas_meter.balance();
+        let result = result.and_then(|return_value| {
+            let change_set = self.finish_session(session, &mut gas_meter)?;
+            Ok((return_value, change_set))
+        });
+        if module.is_some() {
+            self.call_stack.borrow_mut().pop();
+        }
+        let result = result.and_then(|(return_value, change_set)| {
+            let writes = change_set.write_set().iter().map(|(key, _)| key);
+            if let Err(key) = self.check_conflicts(call, writes) {
+                return Err(format!("Conflicting access to {:?} by a nested call", key).into());
+            }
+            self.changes.borrow_mut().apply(change_set.write_set())?;
+            let mut accesses = self.accesses.borrow_mut();
+            for (key, _) in change_set.write_set().iter() {
+                accesses.record(key, call, true);
+            }
+            self.substates.borrow_mut().push(substate);
+            Ok((return_value, change_set))
+        });
+        self.last_call_gas_used.set(
+            internal_balance
+                .checked_sub(gas_meter.internal_balance())
+                .map_or(0, Into::into),
+        );
 
-        Ok(write_set)
+        if let Some(mut trace) = trace {
+            trace.calls = calls;
+            trace.execution_gas_used = gas_used(balance, execution_balance);
+            trace.storage_gas_used = gas_used(execution_balance, gas_meter.balance());
+            match &result {
+                Ok((_, change_set)) => {
+                    trace.write_set = change_set.write_set().clone();
+                    trace.events = change_set.events().to_vec();
+                },
+                Err(e) => trace.error = Some(e.to_string()),
+            }
+            self.traces
+                .borrow_mut()
+                .get_or_insert_with(Vec::new)
+                .push(trace);
+        }
+        result.map(|(return_value, _)| return_value)
     }
 }
 
-impl<'r, 'l, S: MoveResolverExt + StateView> CallMoveVMTrait for CrossSpaceHandler<'r, 'l, S> {
+impl<'a, S: MoveResolverExt + StateView> CrossVmCalls for CrossSpaceHandler<'a, S> {
+    fn merge_into_transaction(
+        &self,
+        change_set_ext: ChangeSetExt,
+        configs: &ChangeSetConfigs,
+    ) -> Result<ChangeSetExt, VMStatus> {
+        let writes = change_set_ext.write_set().iter().map(|(key, _)| key);
+        if let Err(key) = self.check_conflicts(TRANSACTION_CALL, writes) {
+            debug!("Conflicting access to {:?} by a call from the EVM", key);
+            return Err(VMStatus::MoveAbort(
+                AbortLocation::Module(ModuleId::new(
+                    CORE_CODE_ADDRESS,
+                    ident_str!("cross_vm").to_owned(),
+                )),
+                abort_codes::WRITE_CONFLICT,
+            ));
+        }
+        let changes = self.changes.take().into_write_set();
+        if changes.iter().next().is_none() {
+            return Ok(change_set_ext);
+        }
+        ChangeSetExt::new(
+            DeltaChangeSet::empty(),
+            ChangeSet::new(changes, vec![], configs)?,
+            Arc::new(configs.clone()),
+        )
+        .squash(change_set_ext)
+        .map_err(|_err| VMStatus::Error(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR))
+    }
+}
+
+impl<'a, S: MoveResolverExt + StateView> CallMoveVMTrait for CrossSpaceHandler<'a, S> {
     fn call_move_vm(
-        &mut self,
+        &self,
+        state: &mut dyn StateTrait,
+        depth: usize,
         caller: Address,
         address: Vec<u8>,
         module_name: String,
@@ -1547,35 +2750,108 @@ impl<'r, 'l, S: MoveResolverExt + StateView> CallMoveVMTrait for CrossSpaceHandl
         types: Vec<TypeTag>,
         value: U256,
         gas: U256,
-    ) -> std::result::Result<Vec<u8>, String> {
+    ) -> std::result::Result<Vec<u8>, CallMoveVMError> {
         // IMPORTANT (Vlad): call move vm
-        let gas = if gas >= U256::from(u64::MAX) {
-            u64::MAX
-        } else {
-            gas.as_u64()
-        };
+        let data = CallData::Bcs(data);
+        self.call(
+            state,
+            depth,
+            caller,
+            address,
+            module_name,
+            func_name,
+            data,
+            types,
+            value,
+            gas,
+        )
+    }
 
-        let value = if value >= U256::from(u64::MAX) * U256::from(10_000_000_000u64) {
-            u64::MAX
-        } else {
-            (value / U256::from(10_000_000_000u64)).as_u64()
-        };
+    fn call_move_vm_typed(
+        &self,
+        state: &mut dyn StateTrait,
+        depth: usize,
+        caller: Address,
+        address: Vec<u8>,
+        module_name: String,
+        func_name: String,
+        data: Vec<u8>,
+        types: Vec<TypeTag>,
+        value: U256,
+        gas: U256,
+    ) -> std::result::Result<Vec<u8>, CallMoveVMError> {
+        let data = CallData::Abi(data);
+        self.call(
+            state,
+            depth,
+            caller,
+            address,
+            module_name,
+            func_name,
+            data,
+            types,
+            value,
+            gas,
+        )
+    }
 
-        let address = AccountAddress::new(address.try_into().unwrap());
+    fn call_move_vm_as_signer(
+        &self,
+        state: &mut dyn StateTrait,
+        depth: usize,
+        caller: Address,
+        address: Vec<u8>,
+        module_name: String,
+        func_name: String,
+        data: Vec<u8>,
+        types: Vec<TypeTag>,
+        value: U256,
+        gas: U256,
+    ) -> std::result::Result<Vec<u8>, CallMoveVMError> {
+        let data = CallData::AbiAsSigner(data);
+        self.call(
+            state,
+            depth,
+            caller,
+            address,
+            module_name,
+            func_name,
+            data,
+            types,
+            value,
+            gas,
+        )
+    }
 
-        self.gas_meter.cross_space_topup(gas);
+    fn last_call_gas_used(&self) -> u64 {
+        self.last_call_gas_used.get()
+    }
 
-        if value > 0 {
-            self.cross_space_transfer(address, value)?;
-        }
+    fn checkpoint(&self) {
+        let num_traces = self.traces.borrow().as_ref().map_or(0, Vec::len);
+        self.checkpoints.borrow_mut().push((
+            self.changes.borrow().journal_len(),
+            num_traces,
+            self.substates.borrow().len(),
+        ));
+    }
 
-        let return_value = if !module_name.is_empty() {
-            self.cross_space_call(address, module_name, func_name, caller, data, types)?
-        } else {
-            vec![]
-        };
+    fn discard_checkpoint(&self) {
+        self.checkpoints.borrow_mut().pop();
+    }
 
-        Ok(return_value)
+    fn revert_to_checkpoint(&self) {
+        let checkpoint = self.checkpoints.borrow_mut().pop();
+        if let Some((journal_len, num_traces, num_substates)) = checkpoint {
+            self.changes.borrow_mut().undo(journal_len);
+            self.substates.borrow_mut().truncate(num_substates);
+            // The traces of the reverted calls are kept for debugging.
+            if let Some(traces) = self.traces.borrow_mut().as_mut() {
+                traces[num_traces..]
+                    .iter_mut()
+                    .for_each(|trace| trace.reverted = true);
+            }
+        }
     }
 }
 
@@ -1627,20 +2903,49 @@ impl AptosSimulationVM {
             return discard_error_vm_status(VMStatus::Error(StatusCode::INVALID_SIGNATURE));
         }
 
+        let gas_params = match self.0 .0.get_gas_parameters(log_context) {
+            Err(err) => return discard_error_vm_status(err),
+            Ok(s) => s,
+        };
+        let storage_gas_params = match self.0 .0.get_storage_gas_parameters(log_context) {
+            Err(err) => return discard_error_vm_status(err),
+            Ok(s) => s,
+        };
+        let txn_data = TransactionMetadata::new(txn);
+
+        let call_move_handler =
+            self.0
+                .make_cross_space_handler(storage, machine, evm_context, log_context);
+        call_move_handler.enter_transaction(txn.payload());
+        let txn_view = call_move_handler.transaction_view();
+        let mut evm_substate = Substate::new();
         let mut view_wrapper = ViewWrapper {
             inner: storage,
             cache: Default::default(),
         };
-        let mut state = EvmState::new(&mut view_wrapper);
+        let mut state = EvmState::new_with_move_vm(&mut view_wrapper, &call_move_handler);
         let executor = make_executor(&machine, &evm_context, &mut state);
-        let cross_space_handler = CrossVMContext { executor };
+        let mut called_evm = false;
+        let mut evm_abort_info = None;
+        let move_gas_left = MoveGasLeft::default();
+        let cross_space_handler = CrossVMContext::new(
+            executor,
+            move_gas_left.clone(),
+            &mut evm_substate,
+            0,
+            &mut called_evm,
+            &mut evm_abort_info,
+        );
 
         // Revalidate the transaction.
-        let txn_data = TransactionMetadata::new(txn);
-        let mut session = self.0.0.new_session_with_evm_ref(storage, SessionId::txn_meta(&txn_data), cross_space_handler);
-        if let Err(err) = self.validate_simulated_transaction::<S>(
+        let mut session = self.0 .0.new_session_with_evm_ref(
+            &txn_view,
+            SessionId::txn_meta(&txn_data),
+            cross_space_handler,
+        );
+        if let Err(err) = self.validate_simulated_transaction(
             &mut session,
-            storage,
+            &txn_view,
             txn,
             &txn_data,
             log_context,
@@ -1648,28 +2953,21 @@ impl AptosSimulationVM {
             return discard_error_vm_status(err);
         };
 
-        let gas_params = match self.0 .0.get_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
-            Ok(s) => s,
-        };
-        let storage_gas_params = match self.0 .0.get_storage_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
-            Ok(s) => s,
-        };
-
         let mut gas_meter = AptosGasMeter::new(
             self.0 .0.get_gas_feature_version(),
             gas_params.clone(),
             storage_gas_params.clone(),
             txn_data.max_gas_amount(),
         );
+        gas_meter.share_balance(move_gas_left);
 
         let result = match txn.payload() {
             payload @ TransactionPayload::Script(_)
             | payload @ TransactionPayload::EntryFunction(_) => {
                 self.0.execute_script_or_entry_function(
-                    storage,
+                    &txn_view,
                     session,
+                    &call_move_handler,
                     &mut gas_meter,
                     &txn_data,
                     payload,
@@ -1677,8 +2975,15 @@ impl AptosSimulationVM {
                 )
             },
             TransactionPayload::ModuleBundle(m) => {
-                self.0
-                    .execute_modules(storage, session, &mut gas_meter, &txn_data, m, log_context)
+                self.0.execute_modules(
+                    &txn_view,
+                    session,
+                    &call_move_handler,
+                    &mut gas_meter,
+                    &txn_data,
+                    m,
+                    log_context,
+                )
             },
             TransactionPayload::EthTransactionPayload(_) => {
                 // FIXME(vm)
@@ -1712,6 +3017,7 @@ impl AptosSimulationVM {
                         storage,
                         log_context,
                     );
+                    let output = with_evm_abort_info(&vm_status, output, evm_abort_info);
                     (vm_status, output)
                 }
             },
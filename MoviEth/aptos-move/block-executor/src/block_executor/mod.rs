@@ -11,6 +11,7 @@ use crate::{
     executor::{BlockExecutor, PreprocessedTransaction, RAYON_EXEC_POOL},
     output_delta_resolver::OutputDeltaResolver,
     task::TransactionOutput as BlockExecutorTransactionOutput,
+    transaction_classifier::TransactionClassifier,
     view::ResolvedData,
 };
 use aptos_logger::debug;
@@ -123,13 +124,24 @@ impl BlockAptosVM {
 
 
     pub fn execute_block_prototype(
-        transactions: Vec<Transaction>,
+        mut transactions: Vec<Transaction>,
         state_view: Arc<CachedStateView>,
         concurrency_level: usize,
         channels: &TwoWayChannels,
         previous_nonce_per_address: &mut HashMap<AccountAddress, u64>
     ) -> Result<Vec<TransactionOutput>, VMStatus>
     {
+        if !AptosVM::get_prototype_workers() {
+            // Block-STM runs the EVM and cross-VM transactions like the Move ones: the reads of
+            // both VMs go through the multi-version view of the transaction, so a cross-VM
+            // transaction is re-executed when any of them is invalidated.
+            return Self::execute_block(transactions, &*state_view, concurrency_level);
+        }
+
+        // The workers are picked by the type of the transactions, which is inferred from the
+        // state before the block.
+        TransactionClassifier::new(&*state_view).classify_block(&mut transactions);
+
         // Verify the signatures of all the transactions in parallel.
         // This is time consuming so don't wait and do the checking
         // sequentially while executing the transactions.
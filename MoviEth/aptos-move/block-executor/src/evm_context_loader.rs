@@ -1,10 +1,12 @@
 use crate::{
     aptos_vm::AptosVM, errors::expect_only_successful_execution, logging::AdapterLogSchema, move_vm_ext::{MoveResolverExt, SessionId}, system_module_names::{
-        BLOCK_MODULE, GET_BLOCK_HEIGHT_NAME, GET_TIMESTAMP_NAME, TIMESTAMP_MODULE,
+        BLOCK_MODULE, EVM_BLOCK_MODULE, GET_BLOCK_HEIGHT_NAME, GET_EVM_BLOCK_GAS_LIMIT_NAME,
+        GET_EVENT_KEY_PER_TOPIC_NAME, GET_EVM_BLOCK_HASH_NAME, GET_EVM_BLOCK_PROPOSER_NAME,
+        GET_EVM_CHAIN_ID_NAME, GET_RECENT_BLOCK_HASHES_NAME, GET_TIMESTAMP_NAME, TIMESTAMP_MODULE,
     }
 };
-use aptos_evm::EvmContextReader;
-use aptos_types::vm_status::VMStatus;
+use aptos_evm::{Address, EvmContextReader, H256};
+use aptos_types::{transaction::aptos_address_to_eth_address, vm_status::VMStatus};
 use move_core_types::{identifier::IdentStr, language_storage::ModuleId, value::MoveValue};
 use move_vm_types::gas::UnmeteredGasMeter;
 
@@ -63,6 +65,21 @@ fn convert_to_u64(move_value: MoveValue) -> u64 {
     }
 }
 
+/// Maps a Move address to an EVM address, the inverse of `eth_address_to_aptos_address`.
+fn convert_to_evm_address(move_value: MoveValue) -> Address {
+    match move_value {
+        MoveValue::Address(address) => aptos_address_to_eth_address(&address),
+        _ => unreachable!(),
+    }
+}
+
+fn convert_to_hash(move_value: MoveValue) -> H256 {
+    match move_value {
+        MoveValue::Address(address) => H256::from(address.into_bytes()),
+        _ => unreachable!(),
+    }
+}
+
 impl<'a, S: MoveResolverExt> EvmContextReader for ContextView<'a, S> {
     fn get_timestamp(&self) -> u64 {
         convert_to_u64(
@@ -77,4 +94,62 @@ impl<'a, S: MoveResolverExt> EvmContextReader for ContextView<'a, S> {
                 .unwrap(),
         )
     }
+
+    fn get_block_hash(&self) -> H256 {
+        convert_to_hash(
+            self.view_framework(&EVM_BLOCK_MODULE, GET_EVM_BLOCK_HASH_NAME)
+                .unwrap(),
+        )
+    }
+
+    fn get_chain_id(&self) -> u64 {
+        convert_to_u64(
+            self.view_framework(&EVM_BLOCK_MODULE, GET_EVM_CHAIN_ID_NAME)
+                .unwrap(),
+        )
+    }
+
+    fn get_block_gas_limit(&self) -> u64 {
+        convert_to_u64(
+            self.view_framework(&EVM_BLOCK_MODULE, GET_EVM_BLOCK_GAS_LIMIT_NAME)
+                .unwrap(),
+        )
+    }
+
+    fn get_block_author(&self) -> Address {
+        convert_to_evm_address(
+            self.view_framework(&EVM_BLOCK_MODULE, GET_EVM_BLOCK_PROPOSER_NAME)
+                .unwrap(),
+        )
+    }
+
+    fn get_recent_block_hashes(&self) -> Vec<H256> {
+        match self
+            .view_framework(&EVM_BLOCK_MODULE, GET_RECENT_BLOCK_HASHES_NAME)
+            .unwrap()
+        {
+            MoveValue::Vector(hashes) => hashes.into_iter().map(convert_to_hash).collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn get_event_key_per_topic(&self) -> bool {
+        match self
+            .view_framework(&EVM_BLOCK_MODULE, GET_EVENT_KEY_PER_TOPIC_NAME)
+            .unwrap()
+        {
+            MoveValue::Bool(per_topic) => per_topic,
+            _ => unreachable!(),
+        }
+    }
+    /// The EVM base fee follows the minimum gas unit price of the Aptos gas
+    /// schedule, which every transaction must pay anyway.
+    fn get_base_fee_per_gas(&self) -> u64 {
+        self.aptos_vm
+            .0
+            .get_gas_parameters(self.log_context)
+            .map_or(0, |gas_params| {
+                u64::from(gas_params.txn.min_price_per_gas_unit)
+            })
+    }
 }
@@ -2,16 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_executor::{vm_wrapper::AptosExecutorTaskArc, AptosTransactionOutput}, cached_state_view::CachedStateView, counters, errors2::{Error2, Result2}, output_delta_resolver::OutputDeltaResolver, scheduler::{Scheduler, SchedulerTask, TaskGuard, TxnIndex, Version}, task::{ExecutionStatus, ExecutorTask, Transaction, TransactionOutput}, txn_last_input_output::TxnLastInputOutput, view::{LatestView, MVHashMapView}
+    block_executor::{vm_wrapper::AptosExecutorTaskArc, AptosTransactionOutput}, cached_state_view::CachedStateView, counters, errors2::{Error2, Result2}, output_delta_resolver::OutputDeltaResolver, scheduler::{Scheduler, SchedulerTask, TaskGuard, TxnIndex, Version}, task::{ExecutionStatus, ExecutorTask, Transaction, TransactionOutput}, txn_last_input_output::TxnLastInputOutput, view::{CapturedReads, LatestView, LatestViewArc, MVHashMapView}
 };
 use aptos_logger::debug;
 use aptos_mvhashmap::{MVHashMap, MVHashMapError, MVHashMapOutput};
 use aptos_state_view::TStateView;
-use aptos_types::{account_address::AccountAddress, block_metadata::BlockMetadata, state_store::state_key::StateKey, transaction::{SignatureCheckedTransaction, WriteSetPayload}, vm_status::VMStatus, write_set::WriteOp};
+use aptos_types::{account_address::AccountAddress, block_metadata::BlockMetadata, state_store::state_key::StateKey, transaction::{SignatureCheckedTransaction, WriteSetPayload}, vm_status::VMStatus, write_set::{TransactionWrite, WriteOp}};
 use cfx_primitives::transaction::TxType;
 use num_cpus;
 use once_cell::sync::Lazy;
-use std::{collections::{btree_map::BTreeMap, HashMap}, hash::Hash, marker::PhantomData, sync::{mpsc::{Receiver, Sender}, Arc, RwLock}};
+use std::{collections::{btree_map::BTreeMap, HashMap, HashSet}, hash::Hash, marker::PhantomData, sync::{mpsc::{self, Receiver, Sender}, Arc, RwLock}, thread};
 
 /// Transactions after signature checking:
 /// Waypoints and BlockPrologues are not signed and are unaffected by signature checking,
@@ -28,6 +28,10 @@ pub enum PreprocessedTransaction {
 pub struct ExecutionStatusMultiWorker {
     pub status: ExecutionStatus<AptosTransactionOutput, VMStatus>,
     pub worker_type: WorkerType,
+    /// The index of the transaction in the block, which is where its output goes.
+    pub idx: TxnIndex,
+    /// The values the transaction read, which are validated against the state before it.
+    pub reads: CapturedReads<StateKey>,
 }
 
 pub type ManagerSender = Sender<ExecutionContext>;
@@ -64,13 +68,36 @@ pub enum TxScanPointer {
 
 pub type WorkerType = TxScanPointer;
 
+/// Whether a transaction executed by `worker_type` called into the other VM without having been
+/// scheduled as a cross-VM one, in which case it must be re-executed on the cross worker.
+fn is_misclassified(
+    worker_type: WorkerType,
+    status: &ExecutionStatus<AptosTransactionOutput, VMStatus>,
+) -> bool {
+    match status {
+        ExecutionStatus::Success(output) | ExecutionStatus::SkipRest(output) => {
+            worker_type != WorkerType::Cross && output.as_ref().txn_output().made_cross_vm_call()
+        },
+        ExecutionStatus::Abort(_) => false,
+    }
+}
+
+/// The type a misclassified transaction is re-scheduled with.
+fn cross_tx_type(tx_type: TxType) -> TxType {
+    match tx_type {
+        TxType::EthereumTx => TxType::CrossTxEthereumOrigin,
+        TxType::AptosTx => TxType::CrossTxAptosOrigin,
+        tx_type => tx_type,
+    }
+}
+
 pub struct ExecutionContext
 {
     pub base_view: Arc<CachedStateView>,
     pub executor: Arc<AptosExecutorTaskArc<CachedStateView>>, 
     pub execution_state_cache: Arc<RwLock<BTreeMap<StateKey, WriteOp>>>, 
-    pub idx: TxnIndex,
-    pub txn_batch: Vec<PreprocessedTransaction>
+    /// The transactions to execute in order, with their indices in the block.
+    pub txn_batch: Vec<(TxnIndex, PreprocessedTransaction)>
 }
 
 impl ExecutionContext
@@ -79,14 +106,12 @@ impl ExecutionContext
         base_view: &Arc<CachedStateView>, 
         executor: &Arc<AptosExecutorTaskArc<CachedStateView>>, 
         execution_state_cache: &Arc<RwLock<BTreeMap<StateKey, WriteOp>>>, 
-        idx: &TxnIndex, 
-        txn_batch: Vec<PreprocessedTransaction>
+        txn_batch: Vec<(TxnIndex, PreprocessedTransaction)>
     ) -> Self {
         ExecutionContext {
             base_view: base_view.clone(),
             executor: executor.clone(),
             execution_state_cache: execution_state_cache.clone(),
-            idx: *idx,
             txn_batch: txn_batch,
         }
     }
@@ -395,7 +420,26 @@ where
         let block_size = signature_verified_block.len();
         
         let executed_state_cache = Arc::new(RwLock::new(BTreeMap::new()));
-        let mut ret = Vec::with_capacity(block_size);
+        // The outputs are slotted by block index, since the workers reply in any order.
+        let mut outputs: Vec<Option<AptosTransactionOutput>> = (0..block_size).map(|_| None).collect();
+        let mut skip_rest_from = block_size;
+        // The values each transaction read, validated once the whole block is executed.
+        let mut reads: Vec<CapturedReads<StateKey>> = (0..block_size).map(|_| Vec::new()).collect();
+        // The worker running the latest dispatched transaction of each sender, until it replies.
+        let mut in_flight_senders: HashMap<AccountAddress, WorkerType> = HashMap::new();
+        // The types of the transactions, updated when a transaction turns out to be a cross-VM one.
+        let mut tx_types: Vec<TxType> = signature_verified_block
+            .iter()
+            .map(|txn| match txn {
+                PreprocessedTransaction::UserTransaction(inner_tx) => inner_tx.transaction_type,
+                _ => TxType::CrossTxAptosOrigin,
+            })
+            .collect();
+        // Whether a transaction was sent to a worker, since the pointers of a misclassified
+        // transaction move back over the transactions dispatched after it.
+        let mut dispatched = vec![false; block_size];
+        // The indices of the batch each worker runs.
+        let mut pointer_type_to_batch: [Vec<TxnIndex>; 3] = [vec![], vec![], vec![]];
         
         let executor = Arc::new(<AptosExecutorTaskArc<CachedStateView>>::init(base_view.clone()));
        
@@ -425,19 +469,12 @@ where
                 sender_handle = &cross_channel.0; // default
 
                 while *pointer_idx < block_size && !batch_end_flag {
-                    let tx_of_potentially_correct_type = &signature_verified_block[*pointer_idx];
-                    let tx_type: &TxType;
-                    match tx_of_potentially_correct_type {
-                        PreprocessedTransaction::StateCheckpoint => {
-                            tx_type = &TxType::CrossTxAptosOrigin;
-                        },
-                        PreprocessedTransaction::UserTransaction(inner_tx) => {
-                            tx_type = self.fetch_tx_type(inner_tx);
-                        }
-                        _ => {
-                            panic!("THIS SHOULD NEVER HAPPEN");
-                        }
+                    if dispatched[*pointer_idx] {
+                        *pointer_idx += 1;
+                        continue;
                     }
+                    let tx_of_potentially_correct_type = &signature_verified_block[*pointer_idx];
+                    let tx_type = &tx_types[*pointer_idx];
                     
                     match tx_of_potentially_correct_type {
                         PreprocessedTransaction::UserTransaction(_) | PreprocessedTransaction::StateCheckpoint => {
@@ -447,6 +484,7 @@ where
                                 pointer_idx,
                                 &mut pointer_type_to_whether_worker_is_loaded,
                                 previous_nonce_per_address,
+                                &mut in_flight_senders,
                                 *pointer_type,
                                 processed_transactions,
                                 block_size,
@@ -455,7 +493,8 @@ where
                             );
                             
                             if let Ok(handle) = check_result {
-                                tx_batch.push(tx_of_potentially_correct_type.clone());
+                                dispatched[*pointer_idx] = true;
+                                tx_batch.push((*pointer_idx, tx_of_potentially_correct_type.clone()));
                                 sender_handle = handle;
                                 *pointer_idx += 1;
                             } else {
@@ -470,12 +509,13 @@ where
                 }
 
                 if tx_batch.len() > 0 {
+                    pointer_type_to_batch[*pointer_type as usize] =
+                        tx_batch.iter().map(|(idx, _)| *idx).collect();
                     self.send_txns_to_worker(
                         tx_batch,
                         &base_view,
                         &executor,
                         &executed_state_cache,
-                        pointer_idx,
                         *pointer_type,
                         sender_handle,
                         &mut pointer_type_to_whether_worker_is_loaded,
@@ -492,22 +532,60 @@ where
                     
                     match response {
                         Ok(execution_results) => {
-                            for ExecutionStatusMultiWorker {status: res, worker_type} in execution_results {
+                            let num_results = execution_results.len();
+                            for (result_idx, ExecutionStatusMultiWorker {status: res, worker_type, idx, reads: txn_reads}) in execution_results.into_iter().enumerate() {
                             
                                 // debug!("Recieved reply from {:?} job queue!", *pointer_type);
                                 pointer_type_to_whether_worker_is_loaded[worker_type as usize] = false;
+                                in_flight_senders.retain(|_, worker| *worker != worker_type);
+
+                                if is_misclassified(worker_type, &res) {
+                                    // The worker stopped at this transaction without applying its
+                                    // writes. It is re-scheduled on the cross worker, and the rest
+                                    // of the batch on the same worker.
+                                    debug_assert_eq!(result_idx + 1, num_results);
+                                    let requeued = &pointer_type_to_batch[worker_type as usize][result_idx..];
+                                    self.roll_back_nonces(
+                                        requeued,
+                                        signature_verified_block,
+                                        previous_nonce_per_address,
+                                    );
+                                    for requeued_idx in requeued {
+                                        dispatched[*requeued_idx] = false;
+                                    }
+                                    tx_types[idx] = cross_tx_type(tx_types[idx]);
+                                    pointer_type_to_txn_idx_in_block[worker_type as usize] = idx;
+                                    let cross_pointer = &mut pointer_type_to_txn_idx_in_block[TxScanPointer::Cross as usize];
+                                    *cross_pointer = (*cross_pointer).min(idx);
+                                    continue;
+                                }
 
                                 match res {
-                                    ExecutionStatus::Success(output) | ExecutionStatus::SkipRest(output) => {
+                                    ExecutionStatus::Success(output) => {
+                                        assert_eq!(
+                                            output.get_deltas().len(),
+                                            0,
+                                            "Sequential execution must materialize deltas"
+                                        );
+                                        outputs[idx] = Some(output);
+                                        reads[idx] = txn_reads;
+                                    },
+                                    ExecutionStatus::SkipRest(output) => {
                                         assert_eq!(
                                             output.get_deltas().len(),
                                             0,
                                             "Sequential execution must materialize deltas"
                                         );
-                                        ret.push(output);
+                                        outputs[idx] = Some(output);
+                                        reads[idx] = txn_reads;
+                                        skip_rest_from = skip_rest_from.min(idx + 1);
                                     },
                                     ExecutionStatus::Abort(err) => {
                                         // Record the status indicating abort.
+                                        self.wait_for_loaded_workers(
+                                            &pointer_type_to_channel_endpoints,
+                                            &pointer_type_to_whether_worker_is_loaded,
+                                        );
                                         return Err(Error2::UserError(err));
                                     },
                                 }
@@ -531,12 +609,157 @@ where
             }
         }
 
+        // The workers share one state cache, so a transaction may have read the writes of a
+        // transaction after it in the block, or missed those of a transaction before it that ran
+        // on another worker. The block is executed sequentially from the first such transaction.
+        let mut committed = BTreeMap::new();
+        if let Some(first_invalid) = self.first_invalid_transaction(
+            &outputs[..skip_rest_from],
+            &reads,
+            &base_view,
+            &mut committed,
+        ) {
+            debug!(
+                "Transaction {} read a stale value, executing the rest of the block sequentially",
+                first_invalid
+            );
+            skip_rest_from = self.execute_sequentially_from(
+                first_invalid,
+                signature_verified_block,
+                &base_view,
+                &executor,
+                committed,
+                cross_channel,
+                &mut outputs,
+            )?;
+        }
+
+        // As in sequential execution, the transactions after one that skips the rest of the block
+        // are skipped, whichever worker executed them.
+        let mut ret: Vec<_> = outputs
+            .into_iter()
+            .take(skip_rest_from)
+            .map(|output| output.expect("Every transaction of the block must be executed"))
+            .collect();
         ret.resize_with(block_size, AptosTransactionOutput::skip_output);
         Ok(ret)
     }
 
-    fn fetch_tx_type<'a>(&self, inner_tx: &'a Box<SignatureCheckedTransaction>) -> &'a TxType {
-        &(*(*inner_tx)).transaction_type
+    /// Waits for the replies of the workers that still run a batch, so that they are not
+    /// received as the replies for the next block.
+    fn wait_for_loaded_workers(
+        &self,
+        channel_endpoints: &[&TwoWayChannelEndpoints; 3],
+        are_workers_loaded: &[bool; 3],
+    ) {
+        for (endpoints, is_loaded) in channel_endpoints.iter().zip(are_workers_loaded) {
+            if *is_loaded {
+                let _ = endpoints.1.recv();
+            }
+        }
+    }
+
+    /// Returns the first of the executed transactions that read a value other than the one the
+    /// transactions before it in the block left, after applying the writes of the transactions
+    /// before it to `committed`.
+    fn first_invalid_transaction(
+        &self,
+        outputs: &[Option<AptosTransactionOutput>],
+        reads: &[CapturedReads<StateKey>],
+        base_view: &CachedStateView,
+        committed: &mut BTreeMap<StateKey, WriteOp>,
+    ) -> Option<TxnIndex> {
+        for (idx, output) in outputs.iter().enumerate() {
+            let valid = reads[idx].iter().all(|(key, value)| {
+                let expected = match committed.get(key) {
+                    Some(write_op) => write_op.extract_raw_bytes(),
+                    None => match base_view.get_state_value(key) {
+                        Ok(expected) => expected,
+                        Err(_) => return false,
+                    },
+                };
+                expected == *value
+            });
+            if !valid {
+                return Some(idx);
+            }
+            let output = output
+                .as_ref()
+                .expect("Every transaction of the block must be executed");
+            for (key, write_op) in output.get_writes() {
+                committed.insert(key, write_op);
+            }
+        }
+        None
+    }
+
+    /// Executes the transactions of the block from `first_idx` in order on the cross worker, on
+    /// top of the `committed` writes of the transactions before it, and returns the index the
+    /// rest of the block is skipped from.
+    fn execute_sequentially_from(
+        &self,
+        first_idx: TxnIndex,
+        signature_verified_block: &[PreprocessedTransaction],
+        base_view: &Arc<CachedStateView>,
+        executor: &Arc<AptosExecutorTaskArc<CachedStateView>>,
+        committed: BTreeMap<StateKey, WriteOp>,
+        cross_channel: &TwoWayChannelEndpoints,
+        outputs: &mut [Option<AptosTransactionOutput>],
+    ) -> Result<TxnIndex, Error2<VMStatus>> {
+        let txn_batch = signature_verified_block[first_idx..]
+            .iter()
+            .enumerate()
+            .map(|(offset, txn)| (first_idx + offset, txn.clone()))
+            .collect();
+        cross_channel
+            .0
+            .send(ExecutionContext::new(
+                base_view,
+                executor,
+                &Arc::new(RwLock::new(committed)),
+                txn_batch,
+            ))
+            .expect("Cross channel stopped accepting tx processing requests");
+        // The other workers are idle once every transaction of the block is processed.
+        let execution_results = cross_channel
+            .1
+            .recv()
+            .expect("Cross worker stopped replying");
+
+        for ExecutionStatusMultiWorker { status, idx, .. } in execution_results {
+            match status {
+                ExecutionStatus::Success(output) => outputs[idx] = Some(output),
+                ExecutionStatus::SkipRest(output) => {
+                    outputs[idx] = Some(output);
+                    return Ok(idx + 1);
+                },
+                ExecutionStatus::Abort(err) => return Err(Error2::UserError(err)),
+            }
+        }
+        Ok(signature_verified_block.len())
+    }
+
+    /// Restores the previous nonces of the senders of `requeued` transactions, which must be
+    /// dispatched again.
+    fn roll_back_nonces(
+        &self,
+        requeued: &[TxnIndex],
+        signature_verified_block: &[PreprocessedTransaction],
+        previous_nonce_per_address: &mut HashMap<AccountAddress, u64>,
+    ) {
+        let mut rolled_back = HashSet::new();
+        for idx in requeued {
+            if let PreprocessedTransaction::UserTransaction(inner_tx) = &signature_verified_block[*idx] {
+                // The first transaction of a sender in the batch has its lowest nonce.
+                if !rolled_back.insert(inner_tx.sender()) {
+                    continue;
+                }
+                match inner_tx.sequence_number() {
+                    0 => previous_nonce_per_address.remove(&inner_tx.sender()),
+                    nonce => previous_nonce_per_address.insert(inner_tx.sender(), nonce - 1),
+                };
+            }
+        }
     }
 
     fn check_whether_need_to_wait_or_skip_txn<'a>(
@@ -546,6 +769,7 @@ where
         tx_pointer_idx: &mut usize,
         are_workers_loaded: &mut [bool; 3],
         previous_nonce_per_address:  &mut HashMap<AccountAddress, u64>,
+        in_flight_senders: &mut HashMap<AccountAddress, WorkerType>,
         pointer_type: TxScanPointer,
         processed_transactions: usize,
         block_size: usize,
@@ -621,6 +845,16 @@ where
                 }
 
 
+                // The previous transaction of the sender may still run on another worker, which
+                // writes the sender state this transaction reads. Conflicts on other keys are
+                // caught when the reads are validated after the block.
+                if in_flight_senders
+                    .get(transaction_sender)
+                    .map_or(false, |worker| *worker != pointer_type)
+                {
+                    return Result::Err(())
+                }
+
                 // let loaded_worker_error_text: String = format!(
                 //     "UserTransaction: {:?} pointer has to wait since one of the workers is currently busy",  
                 //     actual_pointer_type
@@ -662,6 +896,7 @@ where
                         }
 
                         // debug!("Ethereum tx will soon be sent to the thread...");
+                        in_flight_senders.insert(*transaction_sender, pointer_type);
                         Result::Ok(&eth_channel.0)
                     },
                     TxType::AptosTx => {
@@ -692,6 +927,7 @@ where
                         }
 
                         // debug!("Aptos tx will soon be sent to the thread...");
+                        in_flight_senders.insert(*transaction_sender, pointer_type);
                         Result::Ok(&move_channel.0)
                     },
                     TxType::CrossTxAptosOrigin | TxType::CrossTxEthereumOrigin => {
@@ -735,6 +971,7 @@ where
 
 
                         // debug!("Cross tx will soon be sent to the thread...");
+                        in_flight_senders.insert(*transaction_sender, pointer_type);
                         Result::Ok(&cross_channel.0)
                     },
                     TxType::PlaceholderTypeTx => {
@@ -749,17 +986,16 @@ where
 
     fn send_txns_to_worker(
         &self,
-        txn_batch: Vec<PreprocessedTransaction>,
+        txn_batch: Vec<(TxnIndex, PreprocessedTransaction)>,
         base_view: &Arc<CachedStateView>,
         executor: &Arc<AptosExecutorTaskArc<CachedStateView>>,
         executed_state_cache: &Arc<RwLock<BTreeMap<StateKey, WriteOp>>>, 
-        tx_pointer_idx: &mut usize,
         pointer_type: TxScanPointer,
         sender_handle: &ManagerSender,
         are_workers_loaded: &mut [bool; 3],
     ) {
 
-        match txn_batch.last().unwrap() {
+        match &txn_batch.last().unwrap().1 {
             PreprocessedTransaction::UserTransaction(_) => {
                 // invariant: once here, safe to send for execution 
                 let request_status = sender_handle.send(
@@ -767,7 +1003,6 @@ where
                         base_view,
                         executor,
                         executed_state_cache,
-                        tx_pointer_idx,
                         txn_batch
                     )
                 );
@@ -804,7 +1039,6 @@ where
                         base_view,
                         executor,
                         executed_state_cache,
-                        tx_pointer_idx,
                         txn_batch
                     )
                 );
@@ -880,3 +1114,87 @@ where
 
     }
 }
+
+/// The view a prototype worker executes a transaction of a batch on.
+pub type PrototypeView = LatestViewArc<PreprocessedTransaction, CachedStateView>;
+
+/// Executes a transaction of a batch sent to a prototype worker with the block executor of the
+/// batch, on top of the writes of the transactions executed before it.
+pub fn execute_prototype_transaction(
+    context: &ExecutionContext,
+    view: &PrototypeView,
+    idx: TxnIndex,
+    txn: &PreprocessedTransaction,
+) -> ExecutionStatus<AptosTransactionOutput, VMStatus> {
+    context.executor.execute_transaction(view, txn, idx, true)
+}
+
+/// Spawns a worker of the prototype executor. The worker executes the batches it receives in
+/// order with `execute_transaction`, applies their writes to the shared state cache, and replies
+/// with the status and the reads of each transaction tagged with its index in the block. It stops
+/// at the first abort or misclassified transaction of a batch, and when the manager side of its
+/// channel is dropped.
+pub fn spawn_prototype_worker<F>(
+    worker_type: WorkerType,
+    worker_sender: WorkerSender,
+    execute_transaction: F,
+) -> ManagerSender
+where
+    F: Fn(&ExecutionContext, &PrototypeView, TxnIndex, &PreprocessedTransaction) -> ExecutionStatus<AptosTransactionOutput, VMStatus>
+        + Send
+        + 'static,
+{
+    let (manager_sender, worker_receiver) = mpsc::channel::<ExecutionContext>();
+    let thread_name = match worker_type {
+        WorkerType::Aptos => "move_execution_thread",
+        WorkerType::Ethereum => "eth_execution_thread",
+        WorkerType::Cross => "cross_execution_thread",
+    };
+    thread::Builder::new()
+        .name(thread_name.to_string())
+        .spawn(move || {
+            for context in worker_receiver.iter() {
+                let mut execution_results = Vec::with_capacity(context.txn_batch.len());
+                for (idx, txn) in &context.txn_batch {
+                    let view = PrototypeView::new_btree_view(
+                        context.base_view.clone(),
+                        context.execution_state_cache.clone(),
+                        *idx,
+                    );
+                    let status = execute_transaction(&context, &view, *idx, txn);
+                    let aborted = match &status {
+                        // The writes of a misclassified transaction are dropped, and the manager
+                        // re-schedules it with the rest of the batch.
+                        _ if is_misclassified(worker_type, &status) => true,
+                        ExecutionStatus::Success(output) | ExecutionStatus::SkipRest(output) => {
+                            // Apply the writes.
+                            let mut execution_state_cache =
+                                context.execution_state_cache.write().unwrap();
+                            for (key, write_op) in output.get_writes() {
+                                execution_state_cache.insert(key, write_op);
+                            }
+                            false
+                        },
+                        ExecutionStatus::Abort(_) => true,
+                    };
+                    execution_results.push(ExecutionStatusMultiWorker {
+                        status,
+                        worker_type,
+                        idx: *idx,
+                        reads: view.take_captured_reads(),
+                    });
+                    if aborted {
+                        break;
+                    }
+                }
+                // Release the state views of the block before replying.
+                drop(context);
+
+                if worker_sender.send(execution_results).is_err() {
+                    break;
+                }
+            }
+        })
+        .expect("Failed to spawn a prototype worker");
+    manager_sender
+}
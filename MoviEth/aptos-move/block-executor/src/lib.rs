@@ -171,6 +171,7 @@ pub mod block_executor;
 pub mod natives;
 mod verifier;
 pub mod read_write_set_analysis;
+pub mod transaction_classifier;
 
 #[cfg(test)]
 mod unit_tests;
@@ -2,12 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    errors2::Error, executor::BlockExecutor, proptest_types::types::{
-        DeltaDataView, EmptyDataView, ExpectedOutput, KeyType, Task, Transaction, TransactionGen,
+    block_executor::AptosTransactionOutput,
+    cached_state_view::CachedStateView,
+    errors2::Error,
+    executor::{
+        spawn_prototype_worker, BlockExecutor, PreprocessedTransaction, TwoWayChannels, WorkerType,
+    },
+    proptest_types::types::{
+        empty_cached_state_view, execute_mixed_prototype_transaction, DeltaDataView, EmptyDataView,
+        ExpectedOutput, KeyType, MixedTask, MixedTransactionGen, Task, Transaction, TransactionGen,
         TransactionGenParams, ValueType, STORAGE_AGGREGATOR_VALUE,
-    }
+    },
 };
 use aptos_aggregator::delta_change_set::serialize;
+use aptos_types::{
+    account_address::AccountAddress, state_store::state_key::StateKey, write_set::WriteOp,
+};
 use claims::assert_ok;
 use num_cpus;
 use proptest::{
@@ -17,7 +27,13 @@ use proptest::{
     strategy::{Strategy, ValueTree},
     test_runner::TestRunner,
 };
-use std::{fmt::Debug, hash::Hash, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    sync::{mpsc, Arc},
+};
 
 fn run_transactions<K, V>(
     key_universe: &[K],
@@ -68,8 +84,102 @@ fn run_transactions<K, V>(
     }
 }
 
+fn final_state(outputs: &[AptosTransactionOutput]) -> BTreeMap<StateKey, WriteOp> {
+    let mut state = BTreeMap::new();
+    for output in outputs {
+        for (key, write_op) in output.as_ref().txn_output().write_set() {
+            state.insert(key.clone(), write_op.clone());
+        }
+    }
+    state
+}
+
+fn run_mixed_transactions(
+    senders: &[AccountAddress],
+    transaction_gens: Vec<MixedTransactionGen>,
+    num_repeat: usize,
+) {
+    let block = MixedTransactionGen::materialize_block(senders, transaction_gens);
+    let base_view = Arc::new(empty_cached_state_view());
+
+    let baseline =
+        BlockExecutor::<PreprocessedTransaction, MixedTask, CachedStateView>::new(num_cpus::get())
+            .execute_transactions_sequential((), &block, &*base_view)
+            .unwrap();
+
+    for _ in 0..num_repeat {
+        let (worker_sender, manager_receiver) = mpsc::channel();
+        let channels = TwoWayChannels {
+            move_channel: (
+                spawn_prototype_worker(
+                    WorkerType::Aptos,
+                    worker_sender.clone(),
+                    execute_mixed_prototype_transaction,
+                ),
+                &manager_receiver,
+            ),
+            eth_channel: (
+                spawn_prototype_worker(
+                    WorkerType::Ethereum,
+                    worker_sender.clone(),
+                    execute_mixed_prototype_transaction,
+                ),
+                &manager_receiver,
+            ),
+            cross_channel: (
+                spawn_prototype_worker(
+                    WorkerType::Cross,
+                    worker_sender,
+                    execute_mixed_prototype_transaction,
+                ),
+                &manager_receiver,
+            ),
+        };
+
+        let output = BlockExecutor::<PreprocessedTransaction, MixedTask, CachedStateView>::new(
+            num_cpus::get(),
+        )
+        .execute_transactions_parallel_prototype(
+            &block,
+            base_view.clone(),
+            &channels,
+            &mut HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(output.len(), baseline.len());
+        for (idx, (output, expected)) in output.iter().zip(baseline.iter()).enumerate() {
+            assert_eq!(
+                output.as_ref().txn_output(),
+                expected.as_ref().txn_output(),
+                "Output of transaction {} differs from sequential execution",
+                idx
+            );
+        }
+        assert_eq!(final_state(&output), final_state(&baseline));
+
+        // Block-STM runs the same block without the prototype workers.
+        let (output, _) =
+            BlockExecutor::<PreprocessedTransaction, MixedTask, CachedStateView>::new(
+                num_cpus::get(),
+            )
+            .execute_transactions_parallel((), &block, &*base_view)
+            .unwrap();
+        assert_eq!(final_state(&output), final_state(&baseline));
+    }
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(32))]
+    #[test]
+    fn prototype_matches_sequential(
+        senders in vec(any::<[u8; 32]>(), 1..8),
+        transaction_gen in vec(any::<MixedTransactionGen>(), 1..200).no_shrink(),
+    ) {
+        let senders: Vec<_> = senders.into_iter().map(AccountAddress::new).collect();
+        run_mixed_transactions(&senders, transaction_gen, 2);
+    }
+
     #[test]
     fn no_early_termination(
         universe in vec(any::<[u8; 32]>(), 100),
@@ -2,24 +2,41 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_executor::AptosTransactionOutput,
+    cached_state_view::CachedStateView,
     errors2::Error2,
+    executor::{ExecutionContext, PreprocessedTransaction, PrototypeView},
+    proof_fetcher::ProofFetcher,
     scheduler::TxnIndex,
+    state_view::DbReader,
     task::{
         ExecutionStatus, ExecutorTask, ModulePath, Transaction as TransactionType,
         TransactionOutput,
     },
 };
 use aptos_aggregator::{
-    delta_change_set::{delta_add, delta_sub, deserialize, serialize, DeltaOp},
-    transaction::AggregatorValue,
+    delta_change_set::{delta_add, delta_sub, deserialize, serialize, DeltaChangeSet, DeltaOp},
+    transaction::{AggregatorValue, TransactionOutputExt},
 };
+use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey};
+use aptos_scratchpad::SparseMerkleTree;
 use aptos_state_view::{StateViewId, TStateView};
 use aptos_types::{
     access_path::AccessPath,
     account_address::AccountAddress,
-    state_store::state_storage_usage::StateStorageUsage,
-    write_set::{TransactionWrite, WriteOp},
+    chain_id::ChainId,
+    proof::SparseMerkleProofExt,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::{
+        ExecutionStatus as KeptStatus, RawTransaction, Script, TransactionOutput as AptosOutput,
+        TransactionPayload, TransactionStatus, Version,
+    },
+    vm_status::VMStatus,
+    write_set::{TransactionWrite, WriteOp, WriteSetMut},
 };
+use cfx_primitives::transaction::TxType;
 use claims::assert_none;
 use proptest::{arbitrary::Arbitrary, collection::vec, prelude::*, proptest, sample::Index};
 use proptest_derive::Arbitrary;
@@ -669,3 +686,201 @@ impl<V: Debug + Clone + PartialEq + Eq + TransactionWrite> ExpectedOutput<V> {
         }
     }
 }
+
+///////////////////////////////////////////////////////////////////////////
+// Mixed Move, EVM and cross-VM blocks for the prototype executor.
+///////////////////////////////////////////////////////////////////////////
+
+/// A user transaction of a mixed block. The sequence numbers of a sender follow the block order.
+/// `tx_type` is the type the transaction is classified with, `calls_other_vm` whether it
+/// actually calls into the other VM when executed, and `shared_key` the key it reads and writes
+/// besides the state of its sender, as EVM and Move transactions of different senders do.
+#[derive(Arbitrary, Debug, Clone)]
+pub struct MixedTransactionGen {
+    sender: Index,
+    #[proptest(strategy = "prop::bool::weighted(0.1)")]
+    calls_other_vm: bool,
+    #[proptest(strategy = "prop::option::weighted(0.5, 0..4u8)")]
+    shared_key: Option<u8>,
+    #[proptest(strategy = "prop_oneof![
+        Just(TxType::AptosTx),
+        Just(TxType::EthereumTx),
+        Just(TxType::CrossTxAptosOrigin),
+        Just(TxType::CrossTxEthereumOrigin),
+    ]")]
+    tx_type: TxType,
+}
+
+impl MixedTransactionGen {
+    /// Materializes the transactions of a block, which ends with a state checkpoint as the
+    /// prototype executor expects.
+    pub fn materialize_block(
+        senders: &[AccountAddress],
+        transaction_gens: Vec<Self>,
+    ) -> Vec<PreprocessedTransaction> {
+        let private_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+        let mut sequence_numbers = HashMap::new();
+        let mut block: Vec<_> = transaction_gens
+            .into_iter()
+            .map(|txn_gen| {
+                let sender = senders[txn_gen.sender.index(senders.len())];
+                let sequence_number = sequence_numbers.entry(sender).or_insert(0u64);
+                let raw_txn = RawTransaction::new_script(
+                    sender,
+                    *sequence_number,
+                    Script::new(
+                        std::iter::once(txn_gen.calls_other_vm as u8)
+                            .chain(txn_gen.shared_key)
+                            .collect(),
+                        vec![],
+                        vec![],
+                    ),
+                    0,
+                    0,
+                    0,
+                    ChainId::test(),
+                );
+                *sequence_number += 1;
+                let mut txn = raw_txn
+                    .sign(&private_key, private_key.public_key())
+                    .unwrap()
+                    .into_inner();
+                txn.transaction_type = txn_gen.tx_type;
+                PreprocessedTransaction::UserTransaction(Box::new(txn.check_signature().unwrap()))
+            })
+            .collect();
+        block.push(PreprocessedTransaction::StateCheckpoint);
+        block
+    }
+}
+
+/// Executes a transaction of a mixed block: it chains the state of its sender with its sequence
+/// number and type, so that its output depends on the transactions of the sender before it. A
+/// transaction whose script code starts with `1` reports a call into the other VM, and one whose
+/// script code has a second byte also chains the shared key of that byte with the state of its
+/// sender, so that its output depends on the transactions of any sender before it.
+fn execute_mixed_transaction(
+    view: &impl TStateView<Key = StateKey>,
+    txn: &PreprocessedTransaction,
+) -> ExecutionStatus<AptosTransactionOutput, VMStatus> {
+    let code = match txn {
+        PreprocessedTransaction::UserTransaction(txn) => match txn.payload() {
+            TransactionPayload::Script(script) => script.code().to_vec(),
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+    let calls_other_vm = code.first() == Some(&1);
+    let write_set = match txn {
+        PreprocessedTransaction::UserTransaction(txn) => {
+            let key = StateKey::Raw(txn.sender().to_vec());
+            let mut preimage = view.get_state_value(&key).unwrap().unwrap_or_default();
+            preimage.extend_from_slice(&txn.sequence_number().to_le_bytes());
+            preimage.push(txn.transaction_type as u8);
+            let mut writes = vec![];
+            if let Some(shared_key) = code.get(1) {
+                let shared_key = StateKey::Raw(vec![*shared_key]);
+                let mut shared_preimage = view
+                    .get_state_value(&shared_key)
+                    .unwrap()
+                    .unwrap_or_default();
+                preimage.extend_from_slice(&shared_preimage);
+                shared_preimage.extend_from_slice(txn.sender().as_ref());
+                let shared_value = HashValue::sha3_256_of(&shared_preimage).to_vec();
+                writes.push((shared_key, WriteOp::Modification(shared_value)));
+            }
+            let value = HashValue::sha3_256_of(&preimage).to_vec();
+            writes.push((key, WriteOp::Modification(value)));
+            WriteSetMut::new(writes)
+        },
+        PreprocessedTransaction::StateCheckpoint => WriteSetMut::default(),
+        _ => unreachable!("Mixed blocks only have user transactions and a state checkpoint"),
+    };
+    let mut output = AptosOutput::new(
+        write_set.freeze().unwrap(),
+        vec![],
+        0,
+        TransactionStatus::Keep(KeptStatus::Success),
+    );
+    if calls_other_vm {
+        output = output.with_cross_vm_call();
+    }
+    ExecutionStatus::Success(AptosTransactionOutput::new(TransactionOutputExt::new(
+        DeltaChangeSet::empty(),
+        output,
+    )))
+}
+
+/// Executes the transactions sent to a prototype worker with `execute_mixed_transaction`.
+pub(crate) fn execute_mixed_prototype_transaction(
+    _context: &ExecutionContext,
+    view: &PrototypeView,
+    _idx: TxnIndex,
+    txn: &PreprocessedTransaction,
+) -> ExecutionStatus<AptosTransactionOutput, VMStatus> {
+    execute_mixed_transaction(view, txn)
+}
+
+/// Executes mixed blocks sequentially, as the baseline of the prototype executor.
+pub struct MixedTask;
+
+impl ExecutorTask for MixedTask {
+    type Argument = ();
+    type Error = VMStatus;
+    type Output = AptosTransactionOutput;
+    type Txn = PreprocessedTransaction;
+
+    fn init(_argument: Self::Argument) -> Self {
+        Self
+    }
+
+    fn execute_transaction(
+        &self,
+        view: &impl TStateView<Key = StateKey>,
+        txn: &PreprocessedTransaction,
+        _txn_idx: TxnIndex,
+        _materialize_deltas: bool,
+    ) -> ExecutionStatus<Self::Output, Self::Error> {
+        execute_mixed_transaction(view, txn)
+    }
+}
+
+/// A storage without any state snapshot, for a `CachedStateView` of the empty state.
+struct EmptyDbReader;
+
+impl DbReader for EmptyDbReader {
+    fn get_state_snapshot_before(
+        &self,
+        _next_version: Version,
+    ) -> anyhow::Result<Option<(Version, HashValue)>> {
+        Ok(None)
+    }
+}
+
+struct EmptyProofFetcher;
+
+impl ProofFetcher for EmptyProofFetcher {
+    fn fetch_state_value_and_proof(
+        &self,
+        _state_key: &StateKey,
+        _version: Version,
+        _root_hash: Option<HashValue>,
+    ) -> anyhow::Result<(Option<StateValue>, Option<SparseMerkleProofExt>)> {
+        unreachable!("The empty state has no snapshot to read from");
+    }
+
+    fn get_proof_cache(&self) -> HashMap<HashValue, SparseMerkleProofExt> {
+        HashMap::new()
+    }
+}
+
+pub(crate) fn empty_cached_state_view() -> CachedStateView {
+    CachedStateView::new(
+        StateViewId::Miscellaneous,
+        Arc::new(EmptyDbReader),
+        0,
+        SparseMerkleTree::new_empty(),
+        Arc::new(EmptyProofFetcher),
+    )
+    .unwrap()
+}
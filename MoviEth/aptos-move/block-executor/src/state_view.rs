@@ -5,7 +5,7 @@ use anyhow::{anyhow, Result, format_err};
 use aptos_crypto::HashValue;
 use aptos_state_view::TStateView;
 use aptos_types::{
-    access_path::AccessPath, account_address::AccountAddress, account_config::{NewBlockEvent, CORE_CODE_ADDRESS}, contract_event::{ContractEvent, EventWithVersion}, epoch_change::EpochChangeProof, epoch_state::EpochState, event::EventKey, ledger_info::LedgerInfoWithSignatures, move_resource::MoveStorage, on_chain_config::{access_path_for_config, ConfigID}, proof::{AccumulatorConsistencyProof, SparseMerkleProof, SparseMerkleProofExt, SparseMerkleRangeProof, TransactionAccumulatorRangeProof, TransactionAccumulatorSummary}, state_proof::StateProof, state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_storage_usage::StateStorageUsage, state_value::{StateValue, StateValueChunkWithProof}, table::{TableHandle, TableInfo}}, transaction::{AccountTransactionsWithProof, Transaction, TransactionInfo, TransactionListWithProof, TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version}, write_set::WriteSet
+    access_path::AccessPath, account_address::AccountAddress, account_config::{NewBlockEvent, CORE_CODE_ADDRESS}, contract_event::{ContractEvent, EventWithVersion}, epoch_change::EpochChangeProof, epoch_state::EpochState, event::EventKey, ledger_info::LedgerInfoWithSignatures, move_resource::MoveStorage, on_chain_config::{access_path_for_config, ConfigID}, proof::{AccumulatorConsistencyProof, SparseMerkleProof, SparseMerkleProofExt, SparseMerkleRangeProof, TransactionAccumulatorRangeProof, TransactionAccumulatorSummary}, state_proof::StateProof, state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_storage_usage::StateStorageUsage, state_value::{StateValue, StateValueChunkWithProof}, table::{TableHandle, TableInfo}}, transaction::{AccountTransactionsWithProof, CrossVmTrace, Transaction, TransactionInfo, TransactionListWithProof, TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version}, write_set::WriteSet
 };
 use std::sync::Arc;
 
@@ -142,6 +142,22 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns the sequence number of the first event of `event_key` emitted at or after
+    /// `version`, or `None` if there is no such event.
+    fn get_event_seq_num_at_or_after_version(
+        &self,
+        event_key: &EventKey,
+        version: Version,
+    ) -> Result<Option<u64>> {
+        unimplemented!()
+    }
+
+    /// Returns the cross-VM trace of the EVM transaction at `version`, if the node saved it when
+    /// it executed the transaction with cross-VM tracing enabled.
+    fn get_cross_vm_trace(&self, version: Version) -> Result<Option<CrossVmTrace>> {
+        unimplemented!()
+    }
+
     fn get_transaction_iterator(
         &self,
         start_version: Version,
@@ -22,6 +22,13 @@ pub static TIMESTAMP_MODULE: Lazy<ModuleId> = Lazy::new(|| {
     )
 });
 
+pub static EVM_BLOCK_MODULE: Lazy<ModuleId> = Lazy::new(|| {
+    ModuleId::new(
+        account_config::CORE_CODE_ADDRESS,
+        ident_str!("evm_block").to_owned(),
+    )
+});
+
 // TZ: TODO: remove these except for the block-related names
 // Names for special functions and structs
 pub const SCRIPT_PROLOGUE_NAME: &IdentStr = ident_str!("script_prologue");
@@ -31,3 +38,9 @@ pub const USER_EPILOGUE_NAME: &IdentStr = ident_str!("epilogue");
 pub const BLOCK_PROLOGUE: &IdentStr = ident_str!("block_prologue");
 pub const GET_BLOCK_HEIGHT_NAME: &IdentStr = ident_str!("get_current_block_height");
 pub const GET_TIMESTAMP_NAME: &IdentStr = ident_str!("now_seconds");
+pub const GET_EVM_CHAIN_ID_NAME: &IdentStr = ident_str!("chain_id");
+pub const GET_EVM_BLOCK_GAS_LIMIT_NAME: &IdentStr = ident_str!("block_gas_limit");
+pub const GET_EVM_BLOCK_PROPOSER_NAME: &IdentStr = ident_str!("proposer");
+pub const GET_EVM_BLOCK_HASH_NAME: &IdentStr = ident_str!("block_hash");
+pub const GET_RECENT_BLOCK_HASHES_NAME: &IdentStr = ident_str!("recent_block_hashes");
+pub const GET_EVENT_KEY_PER_TOPIC_NAME: &IdentStr = ident_str!("event_key_per_topic");
@@ -0,0 +1,282 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Infers the `TxType` of user transactions, which the prototype executor uses to pick the worker
+//! of a transaction. A transaction is a cross-VM one if it can call into the other VM: a Move
+//! transaction reaching a native of `0x1::cross_vm`, or an EVM transaction reaching one of the
+//! contracts which call into Move, including the ERC-20 facades of Move coins. The analysis
+//! over-approximates what it can see statically; the calls it misses, e.g. to an EVM address
+//! computed at runtime, are caught by the executor, which re-executes such transactions on the
+//! cross worker.
+
+use aptos_evm::{
+    is_coin_facade_address, Action, Address, AddressWithSpace, EvmState, Space, ViewWrapper,
+};
+use aptos_state_view::StateView;
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::CORE_CODE_ADDRESS,
+    state_store::state_key::StateKey,
+    transaction::{SignedTransaction, Transaction, TransactionPayload},
+};
+use cfx_parameters::internal_contract_addresses::{
+    COIN_BRIDGE_CONTRACT_ADDRESS, CROSS_SPACE_CONTRACT_ADDRESS,
+};
+use cfx_primitives::transaction::TxType;
+use cfx_state::state_trait::StateOpsTrait;
+use move_binary_format::{
+    access::ModuleAccess,
+    binary_views::BinaryIndexedView,
+    file_format::{Bytecode, CompiledModule, CompiledScript},
+};
+use move_core_types::{ident_str, identifier::Identifier, language_storage::ModuleId};
+use once_cell::sync::Lazy;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// The module whose natives call into the EVM.
+static CROSS_VM_MODULE: Lazy<ModuleId> =
+    Lazy::new(|| ModuleId::new(CORE_CODE_ADDRESS, ident_str!("cross_vm").to_owned()));
+
+const PUSH1: u8 = 0x60;
+const PUSH20: u8 = 0x73;
+const PUSH32: u8 = 0x7f;
+
+type FunctionKey = (ModuleId, Identifier);
+
+pub struct TransactionClassifier<'a, S> {
+    state_view: &'a S,
+    modules: HashMap<ModuleId, Option<CompiledModule>>,
+    /// Whether a Move function transitively reaches a native of `0x1::cross_vm`.
+    move_functions: HashMap<FunctionKey, bool>,
+    /// Whether the code of an EVM contract transitively reaches a contract calling into Move.
+    evm_contracts: HashMap<Address, bool>,
+}
+
+impl<'a, S: StateView> TransactionClassifier<'a, S> {
+    pub fn new(state_view: &'a S) -> Self {
+        Self {
+            state_view,
+            modules: HashMap::new(),
+            move_functions: HashMap::new(),
+            evm_contracts: HashMap::new(),
+        }
+    }
+
+    /// Sets the type of the user transactions of a block.
+    pub fn classify_block(&mut self, transactions: &mut [Transaction]) {
+        for txn in transactions.iter_mut() {
+            if let Transaction::UserTransaction(txn) = txn {
+                txn.transaction_type = self.classify(txn);
+            }
+        }
+    }
+
+    pub fn classify(&mut self, txn: &SignedTransaction) -> TxType {
+        match txn.payload() {
+            TransactionPayload::EthTransactionPayload(payload) => {
+                let calls_move = match payload.action.to_eth_action() {
+                    Action::Call(address) => self.evm_contract_calls_move(address),
+                    Action::Create => self.evm_code_calls_move(&payload.data),
+                };
+                if calls_move {
+                    TxType::CrossTxEthereumOrigin
+                } else {
+                    TxType::EthereumTx
+                }
+            },
+            TransactionPayload::EntryFunction(entry_function) => {
+                let function = (
+                    entry_function.module().clone(),
+                    entry_function.function().to_owned(),
+                );
+                if self.move_function_calls_evm(function) {
+                    TxType::CrossTxAptosOrigin
+                } else {
+                    TxType::AptosTx
+                }
+            },
+            TransactionPayload::Script(script) => {
+                if self.script_calls_evm(script.code()) {
+                    TxType::CrossTxAptosOrigin
+                } else {
+                    TxType::AptosTx
+                }
+            },
+            // Publishing only runs the `init_module` of the new modules, which cannot be looked
+            // up before they are published.
+            TransactionPayload::ModuleBundle(_) => TxType::AptosTx,
+        }
+    }
+
+    fn move_function_calls_evm(&mut self, function: FunctionKey) -> bool {
+        if let Some(calls_evm) = self.move_functions.get(&function) {
+            return *calls_evm;
+        }
+        let calls_evm = self.move_functions_call_evm(vec![function.clone()]);
+        self.move_functions.insert(function, calls_evm);
+        calls_evm
+    }
+
+    fn script_calls_evm(&mut self, code: &[u8]) -> bool {
+        match CompiledScript::deserialize(code) {
+            Ok(script) => {
+                let view = BinaryIndexedView::Script(&script);
+                let callees = callees(&view, &script.code.code);
+                self.move_functions_call_evm(callees)
+            },
+            // The VM rejects the script before running any of it.
+            Err(_) => false,
+        }
+    }
+
+    fn move_functions_call_evm(&mut self, functions: Vec<FunctionKey>) -> bool {
+        let Self {
+            state_view,
+            modules,
+            move_functions,
+            ..
+        } = self;
+        reaches_target(move_functions, functions, |(module_id, name)| {
+            let module = match modules
+                .entry(module_id.clone())
+                .or_insert_with(|| load_module(*state_view, module_id))
+            {
+                Some(module) => &*module,
+                None => return Some(vec![]),
+            };
+            let definition = module.function_defs().iter().find(|definition| {
+                module.identifier_at(module.function_handle_at(definition.function).name)
+                    == name.as_ident_str()
+            });
+            match definition.map(|definition| &definition.code) {
+                Some(Some(code)) => Some(callees(&BinaryIndexedView::Module(module), &code.code)),
+                Some(None) if module_id == &*CROSS_VM_MODULE => None,
+                _ => Some(vec![]),
+            }
+        })
+    }
+
+    fn evm_contract_calls_move(&mut self, address: Address) -> bool {
+        if let Some(calls_move) = self.evm_contracts.get(&address) {
+            return *calls_move;
+        }
+        let calls_move = self.evm_contracts_call_move(vec![address]);
+        self.evm_contracts.insert(address, calls_move);
+        calls_move
+    }
+
+    fn evm_code_calls_move(&mut self, code: &[u8]) -> bool {
+        self.evm_contracts_call_move(pushed_addresses(code))
+    }
+
+    fn evm_contracts_call_move(&mut self, addresses: Vec<Address>) -> bool {
+        let Self {
+            state_view,
+            evm_contracts,
+            ..
+        } = self;
+        let mut view_wrapper = ViewWrapper {
+            inner: *state_view,
+            cache: Default::default(),
+        };
+        let state = EvmState::new(&mut view_wrapper);
+        reaches_target(evm_contracts, addresses, |address| {
+            if calls_into_move(address) {
+                return None;
+            }
+            let address = AddressWithSpace {
+                address: *address,
+                space: Space::Ethereum,
+            };
+            Some(match state.state.code(&address) {
+                Ok(Some(code)) => pushed_addresses(&code),
+                _ => vec![],
+            })
+        })
+    }
+}
+
+/// Whether the internal contract at `address` calls into Move.
+fn calls_into_move(address: &Address) -> bool {
+    *address == *CROSS_SPACE_CONTRACT_ADDRESS
+        || *address == *COIN_BRIDGE_CONTRACT_ADDRESS
+        || is_coin_facade_address(address)
+}
+
+fn load_module<S: StateView>(state_view: &S, module_id: &ModuleId) -> Option<CompiledModule> {
+    let key = StateKey::AccessPath(AccessPath::from(module_id));
+    let bytes = state_view.get_state_value(&key).ok()??;
+    CompiledModule::deserialize(&bytes).ok()
+}
+
+/// The functions called by `code`.
+fn callees(view: &BinaryIndexedView, code: &[Bytecode]) -> Vec<FunctionKey> {
+    code.iter()
+        .filter_map(|instruction| match instruction {
+            Bytecode::Call(handle) => Some(*handle),
+            Bytecode::CallGeneric(instantiation) => {
+                Some(view.function_instantiation_at(*instantiation).handle)
+            },
+            _ => None,
+        })
+        .map(|handle| {
+            let handle = view.function_handle_at(handle);
+            let module = view.module_handle_at(handle.module);
+            let module_id = ModuleId::new(
+                *view.address_identifier_at(module.address),
+                view.identifier_at(module.name).to_owned(),
+            );
+            (module_id, view.identifier_at(handle.name).to_owned())
+        })
+        .collect()
+}
+
+/// The addresses pushed by EVM `code`, which are all the contracts it can call without reading
+/// their address from storage or input.
+pub(crate) fn pushed_addresses(code: &[u8]) -> Vec<Address> {
+    let mut addresses = vec![];
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        if opcode == PUSH20 && pc + 21 <= code.len() {
+            addresses.push(Address::from_slice(&code[pc + 1..pc + 21]));
+        }
+        pc += match opcode {
+            PUSH1..=PUSH32 => (opcode - PUSH1) as usize + 2,
+            _ => 1,
+        };
+    }
+    addresses
+}
+
+/// Whether a node reachable from `roots` is a target, i.e. one for which `successors` returns
+/// `None`. The nodes seen by a search finding no target cannot reach any, and are recorded in
+/// `memo`.
+pub(crate) fn reaches_target<K: Clone + Eq + Hash>(
+    memo: &mut HashMap<K, bool>,
+    roots: Vec<K>,
+    mut successors: impl FnMut(&K) -> Option<Vec<K>>,
+) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack = roots;
+    while let Some(node) = stack.pop() {
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+        match memo.get(&node) {
+            Some(true) => return true,
+            Some(false) => continue,
+            None => match successors(&node) {
+                Some(next) => stack.extend(next),
+                None => return true,
+            },
+        }
+    }
+    for node in visited {
+        memo.insert(node, false);
+    }
+    false
+}
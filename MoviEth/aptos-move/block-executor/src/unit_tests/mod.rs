@@ -2,15 +2,38 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    executor::BlockExecutor,
-    proptest_types::types::{DeltaDataView, ExpectedOutput, KeyType, Task, Transaction, ValueType},
+    aptos_vm::{AptosEVM, CallChanges, NativeTokenSupplyChange},
+    cached_state_view::CachedStateView,
+    executor::{BlockExecutor, PreprocessedTransaction},
+    proptest_types::types::{
+        empty_cached_state_view, DeltaDataView, ExpectedOutput, KeyType, Task, Transaction,
+        ValueType,
+    },
     scheduler::{Scheduler, SchedulerTask, TaskGuard},
     task::ModulePath,
+    transaction_classifier::{pushed_addresses, reaches_target, TransactionClassifier},
+    view::{LatestView, MVHashMapView},
 };
 use aptos_aggregator::delta_change_set::{delta_add, delta_sub, DeltaOp, DeltaUpdate};
-use aptos_types::write_set::TransactionWrite;
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+use aptos_evm::{
+    coin_facade_address, contracts_created_to_aptos_events, created_contract, is_evm_event,
+    make_executor, Action, Address, AddressWithSpace, EvmContextReader, EvmMachine, EvmState,
+    Space, StorageKey, Substate, ViewWrapper, H256, U256,
+};
+use aptos_mvhashmap::MVHashMap;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    state_store::state_key::StateKey,
+    transaction::{EthTransactionPayload, RawTransaction, SignedTransaction},
+    write_set::{TransactionWrite, WriteOp, WriteSet, WriteSetMut},
+};
+use cfx_primitives::transaction::TxType;
+use cfx_state::{native_token::octas_to_wei, state_trait::StateOpsTrait, CleanupMode, StateTrait};
 use rand::random;
 use std::{
+    collections::HashMap,
     fmt::Debug,
     hash::Hash,
     marker::PhantomData,
@@ -565,3 +588,325 @@ fn scheduler_drain_idx() {
 
     assert!(matches!(s.next_task(), SchedulerTask::Done));
 }
+
+#[test]
+fn pushed_addresses_skip_push_data() {
+    let callee = Address::repeat_byte(0xaa);
+    let mut code = vec![0x60, 0x73]; // PUSH1 0x73
+    code.push(0x73); // PUSH20 callee
+    code.extend_from_slice(callee.as_bytes());
+    code.push(0x7f); // PUSH32 with a PUSH20 opcode in its data
+    code.push(0x73);
+    code.extend_from_slice(&[0x11; 31]);
+    code.extend_from_slice(&[0x73, 0x22]); // truncated PUSH20
+
+    assert_eq!(pushed_addresses(&code), vec![callee]);
+}
+
+#[test]
+fn reaches_target_memoizes_dead_ends() {
+    // 0 -> 1 -> 2 -> 0, 3 -> 4 where 4 is the target.
+    let successors = |node: &u32| match node {
+        0 => Some(vec![1]),
+        1 => Some(vec![2]),
+        2 => Some(vec![0]),
+        3 => Some(vec![4]),
+        _ => None,
+    };
+    let mut memo = HashMap::new();
+
+    assert!(!reaches_target(&mut memo, vec![0], successors));
+    assert_eq!(memo.len(), 3);
+    assert!(memo.values().all(|reaches| !reaches));
+
+    assert!(reaches_target(&mut memo, vec![1, 3], successors));
+    assert_eq!(memo.get(&3), None);
+
+    // Memoized nodes are not expanded again.
+    assert!(!reaches_target(&mut memo, vec![2], |_| unreachable!()));
+}
+
+fn eth_transaction(action: Action, data: Vec<u8>) -> SignedTransaction {
+    let private_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+    RawTransaction::new_eth(
+        AccountAddress::ONE,
+        0,
+        EthTransactionPayload::new(U256::zero(), action.into(), data),
+        0,
+        0,
+        0,
+        ChainId::test(),
+    )
+    .sign(&private_key, private_key.public_key())
+    .unwrap()
+    .into_inner()
+}
+
+#[test]
+fn coin_facade_transfers_are_cross_vm() {
+    let base_view = empty_cached_state_view();
+    let mut classifier = TransactionClassifier::new(&base_view);
+    let facade = coin_facade_address("0x1::aptos_coin::AptosCoin");
+    // transfer(address,uint256)
+    let transfer = [&[0xa9, 0x05, 0x9c, 0xbb][..], &[0; 64]].concat();
+
+    assert_eq!(
+        classifier.classify(&eth_transaction(Action::Call(facade), transfer.clone())),
+        TxType::CrossTxEthereumOrigin
+    );
+    // The code of a new contract calling the facade.
+    let code = [&[0x73][..], facade.as_bytes()].concat(); // PUSH20 facade
+    assert_eq!(
+        classifier.classify(&eth_transaction(Action::Create, code)),
+        TxType::CrossTxEthereumOrigin
+    );
+    assert_eq!(
+        classifier.classify(&eth_transaction(
+            Action::Call(Address::repeat_byte(0xaa)),
+            transfer
+        )),
+        TxType::EthereumTx
+    );
+}
+
+#[test]
+fn evm_state_reads_are_versioned() {
+    let slot = |byte| -> StateKey {
+        StorageKey::storage_key(Address::repeat_byte(0x11), &H256::repeat_byte(byte)).into()
+    };
+    let versioned_data_cache = MVHashMap::new();
+    versioned_data_cache.add_write(&slot(1), (0, 0), WriteOp::Modification(vec![1]));
+    versioned_data_cache.add_write(&slot(2), (0, 0), WriteOp::Modification(vec![2]));
+    let scheduler = Scheduler::new(2);
+    let speculative_view = MVHashMapView::new(&versioned_data_cache, &scheduler);
+    let base_view = empty_cached_state_view();
+    let latest_view = LatestView::<PreprocessedTransaction, CachedStateView>::new_mv_view(
+        &base_view,
+        &speculative_view,
+        1,
+    );
+
+    // Slot 1 is written back unchanged, slot 2 is modified and slot 3 created.
+    let mut view_wrapper = ViewWrapper {
+        inner: &latest_view,
+        cache: Default::default(),
+    };
+    for (byte, value) in [(1, 1), (2, 4), (3, 3)] {
+        let key = StorageKey::storage_key(Address::repeat_byte(0x11), &H256::repeat_byte(byte));
+        view_wrapper
+            .cache
+            .insert(key, Some(vec![value].into_boxed_slice()));
+    }
+    let mut writes = view_wrapper.drain();
+    writes.sort_by(|(a, _), (b, _)| a.cmp(b));
+    assert_eq!(
+        writes,
+        vec![
+            (slot(2), WriteOp::Modification(vec![4])),
+            (slot(3), WriteOp::Creation(vec![3])),
+        ]
+    );
+
+    let reads = speculative_view.take_reads();
+    assert_eq!(reads.len(), 3);
+    for read in reads {
+        if *read.path() == slot(3) {
+            assert!(read.validate_storage());
+        } else {
+            assert!(read.validate_version((0, 0)));
+        }
+    }
+}
+
+/// Context of a block at height 1, with the given block hash.
+struct TestEvmContext(H256);
+
+impl EvmContextReader for TestEvmContext {
+    fn get_timestamp(&self) -> u64 {
+        0
+    }
+
+    fn get_block_height(&self) -> u64 {
+        1
+    }
+
+    fn get_block_hash(&self) -> H256 {
+        self.0
+    }
+
+    fn get_chain_id(&self) -> u64 {
+        1
+    }
+
+    fn get_block_gas_limit(&self) -> u64 {
+        30_000_000
+    }
+
+    fn get_block_author(&self) -> Address {
+        Address::zero()
+    }
+
+    fn get_recent_block_hashes(&self) -> Vec<H256> {
+        vec![]
+    }
+
+    fn get_event_key_per_topic(&self) -> bool {
+        false
+    }
+
+    fn get_base_fee_per_gas(&self) -> u64 {
+        0
+    }
+}
+
+#[test]
+fn cross_vm_postprocessing_kills_suicided_contracts() {
+    let machine = EvmMachine::new();
+    let evm_context = machine.make_context(&TestEvmContext(H256::zero()));
+    let base_view = empty_cached_state_view();
+    let mut view_wrapper = ViewWrapper {
+        inner: &base_view,
+        cache: Default::default(),
+    };
+    let contract = AddressWithSpace {
+        address: Address::repeat_byte(0x22),
+        space: Space::Ethereum,
+    };
+
+    let mut state = EvmState::new(&mut view_wrapper);
+    state
+        .state
+        .add_balance(
+            &contract,
+            &U256::from(5),
+            CleanupMode::ForceCreate,
+            0.into(),
+        )
+        .unwrap();
+    state.state.add_total_issued(U256::from(5));
+    state.state.commit(Default::default(), None).unwrap();
+    drop(state);
+
+    // The contract self-destructed in a `call_evm` of the Move transaction.
+    let mut substate = Substate::new();
+    substate.suicides.insert(contract);
+    let mut state = EvmState::new(&mut view_wrapper);
+    let killed = make_executor(&machine, &evm_context, &mut state)
+        .cross_vm_postprocessing(&mut substate)
+        .unwrap();
+    assert_eq!(killed, U256::from(5));
+    assert!(!state.state.exists(&contract).unwrap());
+    assert_eq!(state.state.total_issued_tokens(), U256::zero());
+}
+
+#[test]
+fn native_token_supply_change_is_conserved() {
+    // 30 octas sent to the EVM and 10 octas sent back, 5 wei of which a killed contract burned.
+    let supply = NativeTokenSupplyChange {
+        bridge_before: (100, 200),
+        bridge_after: (110, 230),
+        evm_issued_before: octas_to_wei(50),
+        evm_issued_after: octas_to_wei(70) - 5,
+    };
+    assert!(supply.check_conserved(U256::from(5)).is_ok());
+    assert!(supply.check_conserved(U256::zero()).is_err());
+
+    // Value issued in the EVM without being burned in Move.
+    let supply = NativeTokenSupplyChange {
+        bridge_before: (100, 200),
+        bridge_after: (100, 200),
+        evm_issued_before: octas_to_wei(50),
+        evm_issued_after: octas_to_wei(51),
+    };
+    assert!(supply.check_conserved(U256::zero()).is_err());
+}
+
+#[test]
+fn evm_block_context_is_cached_by_block_hash() {
+    let evm = AptosEVM::new();
+    let context = evm.block_context(&TestEvmContext(H256::repeat_byte(1)));
+    assert!(Arc::ptr_eq(
+        &context,
+        &evm.block_context(&TestEvmContext(H256::repeat_byte(1)))
+    ));
+
+    // Another block of the same height gets a context of its own.
+    let other_context = evm.block_context(&TestEvmContext(H256::repeat_byte(2)));
+    assert!(!Arc::ptr_eq(&context, &other_context));
+    assert_eq!(other_context.block_hash(), H256::repeat_byte(2));
+}
+
+#[test]
+fn cross_space_call_changes_are_journaled() {
+    use WriteOp::*;
+
+    let key = |byte| StateKey::Raw(vec![byte]);
+    let write_set =
+        |ops: Vec<(StateKey, WriteOp)>| -> WriteSet { WriteSetMut::new(ops).freeze().unwrap() };
+    let mut changes = CallChanges::default();
+
+    changes
+        .apply(&write_set(vec![
+            (key(1), Creation(vec![1])),
+            (key(2), Modification(vec![2])),
+        ]))
+        .unwrap();
+    let first_call = changes.write_set().clone();
+    let checkpoint = changes.journal_len();
+
+    // Only the keys the first call has not written are charged for storage again.
+    let second_call = write_set(vec![
+        (key(1), Modification(vec![3])),
+        (key(2), Deletion),
+        (key(3), Creation(vec![4])),
+    ]);
+    let new_keys: Vec<_> = changes
+        .new_writes(&second_call)
+        .map(|(key, _)| key.clone())
+        .collect();
+    assert_eq!(new_keys, vec![key(3)]);
+    changes.apply(&second_call).unwrap();
+    assert_eq!(
+        changes.write_set(),
+        &write_set(vec![
+            (key(1), Creation(vec![3])),
+            (key(2), Deletion),
+            (key(3), Creation(vec![4])),
+        ])
+    );
+
+    // The EVM frame of the second call reverts.
+    changes.undo(checkpoint);
+    assert_eq!(changes.write_set(), &first_call);
+    assert_eq!(changes.journal_len(), checkpoint);
+
+    // A write set which cannot be squashed is not applied, even partially.
+    assert!(changes
+        .apply(&write_set(vec![
+            (key(0), Creation(vec![5])),
+            (key(1), Creation(vec![6])),
+        ]))
+        .is_err());
+    assert_eq!(changes.write_set(), &first_call);
+    assert_eq!(changes.journal_len(), checkpoint);
+}
+
+#[test]
+fn contracts_created_become_events() {
+    let base_view = empty_cached_state_view();
+    let mut view_wrapper = ViewWrapper {
+        inner: &base_view,
+        cache: Default::default(),
+    };
+    let contract = Address::repeat_byte(0x33);
+
+    // A contract re-created at the same address, e.g. with CREATE2 after a self-destruct, gets
+    // the next sequence number of its creation event stream.
+    let events = contracts_created_to_aptos_events(&mut view_wrapper, &[contract]).unwrap();
+    let recreated = contracts_created_to_aptos_events(&mut view_wrapper, &[contract]).unwrap();
+    assert_eq!(events[0].key(), recreated[0].key());
+    assert_eq!(events[0].sequence_number(), 0);
+    assert_eq!(recreated[0].sequence_number(), 1);
+    assert_eq!(created_contract(&events[0]), Some(contract));
+    assert!(!is_evm_event(&events[0]));
+}
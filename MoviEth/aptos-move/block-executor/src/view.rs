@@ -19,7 +19,7 @@ use aptos_types::{
     write_set::TransactionWrite,
 };
 use move_binary_format::errors::Location;
-use std::{cell::RefCell, collections::BTreeMap, hash::Hash, sync::{Arc, RwLock}};
+use std::{cell::RefCell, collections::BTreeMap, hash::Hash, sync::{Arc, Mutex, RwLock}};
 
 /// Resolved and serialized data for WriteOps, None means deletion.
 pub type ResolvedData = Option<Vec<u8>>;
@@ -243,10 +243,17 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>> TStateView for LatestView<
     }
 }
 
+/// The values a transaction read through a `LatestViewArc`, in the order it read them.
+pub type CapturedReads<K> = Vec<(K, ResolvedData)>;
+
+/// A view of the state cache shared by the prototype workers. The cache may already hold the
+/// writes of transactions after `idx` in the block, so the view captures the values it resolved
+/// for them to be validated against the state before `idx`.
 pub struct LatestViewArc<T: Transaction, S: TStateView<Key = T::Key>> {
     base_view: Arc<S>,
     latest_view: ViewMapKindArc<T>,
-    idx: TxnIndex
+    idx: TxnIndex,
+    captured_reads: Mutex<CapturedReads<T::Key>>,
 }
 
 impl<T: Transaction, S: TStateView<Key = T::Key>> LatestViewArc<T, S> {
@@ -259,8 +266,14 @@ impl<T: Transaction, S: TStateView<Key = T::Key>> LatestViewArc<T, S> {
             base_view,
             latest_view: ViewMapKindArc::BTree(map),
             idx,
+            captured_reads: Mutex::new(Vec::new()),
         }
     }
+
+    /// Takes the values resolved through the view so far.
+    pub fn take_captured_reads(&self) -> CapturedReads<T::Key> {
+        std::mem::take(&mut *self.captured_reads.lock().unwrap())
+    }
 }
 
 impl<T: Transaction, S: TStateView<Key = T::Key>> TStateView for LatestViewArc<T, S> {
@@ -268,7 +281,7 @@ impl<T: Transaction, S: TStateView<Key = T::Key>> TStateView for LatestViewArc<T
 
     fn get_state_value(&self, state_key: &T::Key) -> anyhow::Result<Option<Vec<u8>>> {
         // debug!("Get state_value call!");
-        match &self.latest_view {
+        let ret = match &self.latest_view {
             ViewMapKindArc::BTree(map_arc) => {
                     let map = map_arc.read().unwrap();
                     (*map).get(state_key).map_or_else(
@@ -291,7 +304,14 @@ impl<T: Transaction, S: TStateView<Key = T::Key>> TStateView for LatestViewArc<T
                     |v| Ok(v.extract_raw_bytes()),
                 )
             },
+        };
+        if let Ok(value) = &ret {
+            self.captured_reads
+                .lock()
+                .unwrap()
+                .push((state_key.clone(), value.clone()));
         }
+        ret
     }
 
     fn id(&self) -> StateViewId {
@@ -5,6 +5,7 @@ use crate::{assert_success, AptosPackageHooks};
 use aptos::move_tool::MemberId;
 use aptos_cached_packages::aptos_stdlib;
 use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use aptos_evm::{Action, Address, EvmState, ViewWrapper, U256};
 use aptos_framework::{natives::code::PackageMetadata, BuildOptions, BuiltPackage};
 use aptos_gas::{AptosGasParameters, InitialGasSchedule, ToOnChainGasSchedule};
 use aptos_language_e2e_tests::{
@@ -19,9 +20,15 @@ use aptos_types::{
     on_chain_config::{FeatureFlag, GasScheduleV2},
     state_store::state_key::StateKey,
     transaction::{
-        EntryFunction, SignedTransaction, TransactionOutput, TransactionPayload, TransactionStatus,
+        eth_address_to_aptos_address, EntryFunction, SignedTransaction, TransactionOutput,
+        TransactionPayload, TransactionStatus,
     },
+    write_set::WriteSetMut,
 };
+use cfx_primitives::{Eip155Transaction, Transaction as EthRawTransaction};
+use cfx_state::{state_trait::StateOpsTrait, CleanupMode, StateTrait};
+use cfx_types::AddressSpaceUtil;
+use cfxkey::{Generator, KeyPair, Random};
 use move_core_types::{
     language_storage::{ResourceKey, StructTag, TypeTag},
     move_resource::MoveStructType,
@@ -34,8 +41,12 @@ use rand::{
     Rng, SeedableRng,
 };
 use serde::{de::DeserializeOwned, Serialize};
+use solidity_abi::{function_selector, parse_signature, ABIEncodable};
 use std::{collections::BTreeMap, path::Path};
 
+/// The chain id the EVM runs with unless `evm_block::set_config` changes it.
+const EVM_CHAIN_ID: u32 = 129;
+
 /// A simple test harness for defining Move e2e tests.
 ///
 /// Tests defined via this harness typically live in the `<crate>/tests` directory, the standard
@@ -446,6 +457,150 @@ impl MoveHarness {
             .unwrap()
             .sequence_number()
     }
+
+    /// Creates an EVM account with a randomly generated key pair and the given balance in wei.
+    pub fn new_eth_account(&mut self, balance: U256) -> KeyPair {
+        let key_pair = Random.generate().unwrap();
+        self.add_eth_balance(&key_pair.address(), balance);
+        key_pair
+    }
+
+    /// Adds `balance` wei to an EVM account. Like the genesis EVM balance, it counts toward the
+    /// native tokens issued in the EVM.
+    pub fn add_eth_balance(&mut self, address: &Address, balance: U256) {
+        let mut view_wrapper = ViewWrapper {
+            inner: self.executor.get_state_view(),
+            cache: Default::default(),
+        };
+        let mut state = EvmState::new(&mut view_wrapper);
+        state
+            .state
+            .add_balance(
+                &address.with_evm_space(),
+                &balance,
+                CleanupMode::ForceCreate,
+                0.into(),
+            )
+            .unwrap();
+        state.state.add_total_issued(balance);
+        state.state.commit(Default::default(), None).unwrap();
+        drop(state);
+        let write_set = WriteSetMut::new(view_wrapper.drain()).freeze().unwrap();
+        self.executor.apply_write_set(&write_set);
+    }
+
+    /// Deploys `code` as the code of an EVM account.
+    pub fn set_eth_code(&mut self, address: &Address, code: Vec<u8>) {
+        let mut view_wrapper = ViewWrapper {
+            inner: self.executor.get_state_view(),
+            cache: Default::default(),
+        };
+        let mut state = EvmState::new(&mut view_wrapper);
+        let address = address.with_evm_space();
+        state
+            .state
+            .add_balance(&address, &U256::zero(), CleanupMode::ForceCreate, 0.into())
+            .unwrap();
+        state.state.init_code(&address, code).unwrap();
+        state.state.commit(Default::default(), None).unwrap();
+        drop(state);
+        let write_set = WriteSetMut::new(view_wrapper.drain()).freeze().unwrap();
+        self.executor.apply_write_set(&write_set);
+    }
+
+    /// Reads the balance of an EVM account, in wei.
+    pub fn eth_balance(&self, address: &Address) -> U256 {
+        let mut view_wrapper = ViewWrapper {
+            inner: self.executor.get_state_view(),
+            cache: Default::default(),
+        };
+        let state = EvmState::new(&mut view_wrapper);
+        state.state.balance(&address.with_evm_space()).unwrap()
+    }
+
+    /// Reads the nonce of an EVM account.
+    pub fn eth_nonce(&self, address: &Address) -> u64 {
+        let mut view_wrapper = ViewWrapper {
+            inner: self.executor.get_state_view(),
+            cache: Default::default(),
+        };
+        let state = EvmState::new(&mut view_wrapper);
+        state
+            .state
+            .nonce(&address.with_evm_space())
+            .unwrap()
+            .as_u64()
+    }
+
+    /// Reads the total native tokens issued in the EVM, in wei.
+    pub fn eth_total_issued(&self) -> U256 {
+        let mut view_wrapper = ViewWrapper {
+            inner: self.executor.get_state_view(),
+            cache: Default::default(),
+        };
+        let state = EvmState::new(&mut view_wrapper);
+        state.state.total_issued_tokens()
+    }
+
+    /// Creates an EVM transaction which calls `to` with `value` wei and `data`, signed by
+    /// `sender` for the default EVM chain id.
+    pub fn create_eth_transaction(
+        &mut self,
+        sender: &KeyPair,
+        to: Address,
+        value: U256,
+        data: Vec<u8>,
+    ) -> SignedTransaction {
+        let on_chain_nonce = self.eth_nonce(&sender.address());
+        let nonce_ref = self
+            .txn_seq_no
+            .entry(eth_address_to_aptos_address(&sender.address()))
+            .or_default();
+        let nonce = std::cmp::max(on_chain_nonce, *nonce_ref);
+        *nonce_ref = nonce + 1;
+        let txn = EthRawTransaction::Ethereum(Eip155Transaction {
+            nonce: nonce.into(),
+            gas_price: 1.into(),
+            gas: 2_000_000.into(),
+            action: Action::Call(to),
+            value,
+            chain_id: Some(EVM_CHAIN_ID),
+            data,
+        })
+        .sign(sender.secret());
+        SignedTransaction::try_from(txn).unwrap()
+    }
+
+    /// Runs an EVM transaction which calls `to` with `value` wei and `data`. If the transaction
+    /// is kept, its write set is applied to storage.
+    pub fn run_eth_transaction(
+        &mut self,
+        sender: &KeyPair,
+        to: Address,
+        value: U256,
+        data: Vec<u8>,
+    ) -> TransactionStatus {
+        let txn = self.create_eth_transaction(sender, to, value, data);
+        self.run(txn)
+    }
+}
+
+/// Encodes a call to `callMove` of the cross space contract with BCS encoded arguments.
+pub fn encode_call_move(
+    address: AccountAddress,
+    module: &str,
+    function: &str,
+    args: Vec<Vec<u8>>,
+) -> Vec<u8> {
+    let (name, types) = parse_signature("callMove(bytes32,string,string,bytes[],bytes[])").unwrap();
+    let params = (
+        address.into_bytes(),
+        module.to_string(),
+        function.to_string(),
+        args,
+        Vec::<Vec<u8>>::new(),
+    );
+    [&function_selector(&name, &types)[..], &params.abi_encode()].concat()
 }
 
 impl Default for MoveHarness {
@@ -0,0 +1,31 @@
+module 0xcafe::linked_caller {
+    use std::option;
+    use std::signer;
+    use std::string;
+    use aptos_framework::cross_vm;
+
+    /// What the EVM contract returned to the last `call_as` of the account.
+    struct LastCall has key {
+        returned: vector<vector<u8>>,
+    }
+
+    /// Calls `contract`, which returns an `address`, as the EVM address linked to the account.
+    entry fun call_as(account: &signer, contract: vector<u8>) acquires LastCall {
+        let returned = cross_vm::call_evm_typed_as(
+            account,
+            option::none(),
+            contract,
+            string::utf8(b"sender()"),
+            vector[],
+            string::utf8(b"address"),
+            1000000,
+            1,
+        );
+        let addr = signer::address_of(account);
+        if (exists<LastCall>(addr)) {
+            borrow_global_mut<LastCall>(addr).returned = returned;
+        } else {
+            move_to(account, LastCall { returned });
+        }
+    }
+}
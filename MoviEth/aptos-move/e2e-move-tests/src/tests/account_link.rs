@@ -0,0 +1,323 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::cross_vm::move_error;
+use crate::{assert_abort, assert_success, encode_call_move, tests::common, MoveHarness};
+use aptos_crypto::SigningKey;
+use aptos_evm::{Address, H256, U256};
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{ChainIdResource, EvmLinkResource, MoveLinkResource},
+    transaction::{eth_address_to_aptos_address, TransactionStatus},
+};
+use cfx_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+use cfxkey::{Generator, KeyPair, Random};
+use move_core_types::{
+    move_resource::MoveStructType, parser::parse_struct_tag, vm_status::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+/// `error::invalid_argument(EINVALID_EVM_SIGNATURE)` of `0x1::account_link`.
+const EINVALID_EVM_SIGNATURE: u64 = 0x10004;
+/// `error::invalid_argument(EINVALID_MOVE_SIGNATURE)` of `0x1::account_link`.
+const EINVALID_MOVE_SIGNATURE: u64 = 0x10005;
+/// `error::not_found(ENOT_LINKED)` of `0x1::account_link`.
+const ENOT_LINKED: u64 = 0x60006;
+/// `error::not_found(EACCOUNT_NOT_LINKED)` of `0x1::cross_vm`.
+const EACCOUNT_NOT_LINKED: u64 = 0x60002;
+
+/// Mimics `0x1::account_link::LinkProof`.
+#[derive(Serialize)]
+struct LinkProof {
+    chain_id: u8,
+    account: AccountAddress,
+    evm_address: Vec<u8>,
+    nonce: u64,
+}
+
+/// Mimics `0xcafe::linked_caller::LastCall`.
+#[derive(Deserialize)]
+struct LastCall {
+    returned: Vec<Vec<u8>>,
+}
+
+/// `0x1::account_link::link_message` for the link number `nonce` of `account`.
+pub(super) fn link_message(
+    h: &MoveHarness,
+    account: &AccountAddress,
+    evm_address: &Address,
+    nonce: u64,
+) -> Vec<u8> {
+    let chain_id = h
+        .read_resource::<ChainIdResource>(&AccountAddress::ONE, ChainIdResource::struct_tag())
+        .unwrap()
+        .chain_id();
+    let proof = LinkProof {
+        chain_id: chain_id.id(),
+        account: *account,
+        evm_address: evm_address.as_bytes().to_vec(),
+        nonce,
+    };
+    [
+        &b"MoviEth account link:"[..],
+        &bcs::to_bytes(&proof).unwrap(),
+    ]
+    .concat()
+}
+
+/// Signs `message` with `key` as `personal_sign` does, returning `r || s || v`.
+pub(super) fn personal_sign(key: &KeyPair, message: &[u8]) -> Vec<u8> {
+    let mut keccak = Keccak::v256();
+    let mut digest = [0u8; 32];
+    keccak.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+    keccak.update(message);
+    keccak.finalize(&mut digest);
+    let mut signature = cfxkey::sign(key.secret(), &H256::from(digest))
+        .unwrap()
+        .to_vec();
+    signature[64] += 27;
+    signature
+}
+
+pub(super) fn link_evm_address(
+    h: &mut MoveHarness,
+    account: &Account,
+    key: &KeyPair,
+    signature: Vec<u8>,
+) -> TransactionStatus {
+    h.run_entry_function(
+        account,
+        str::parse("0x1::account_link::link_evm_address").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&key.address().as_bytes().to_vec()).unwrap(),
+            bcs::to_bytes(&signature).unwrap(),
+        ],
+    )
+}
+
+/// Calls `0x1::account_link::ihe_link_move_account` from `key` with the Ed25519 signature of
+/// `signer` over `message`, claiming the public key of `account`.
+fn link_move_account(
+    h: &mut MoveHarness,
+    key: &KeyPair,
+    account: &Account,
+    signer: &Account,
+    message: &[u8],
+) -> TransactionStatus {
+    let signature = signer.privkey.sign_arbitrary_message(message);
+    let data = encode_call_move(
+        AccountAddress::ONE,
+        "account_link",
+        "link_move_account",
+        vec![
+            bcs::to_bytes(account.address()).unwrap(),
+            bcs::to_bytes(&account.pubkey.to_bytes().to_vec()).unwrap(),
+            bcs::to_bytes(&signature.to_bytes().to_vec()).unwrap(),
+        ],
+    );
+    h.run_eth_transaction(key, CROSS_SPACE_CONTRACT_ADDRESS, U256::zero(), data)
+}
+
+fn evm_link(h: &MoveHarness, account: &AccountAddress) -> Option<EvmLinkResource> {
+    h.read_resource::<EvmLinkResource>(account, EvmLinkResource::struct_tag())
+}
+
+fn linked_account(h: &MoveHarness, evm_address: &Address) -> Option<AccountAddress> {
+    h.read_resource::<MoveLinkResource>(
+        &eth_address_to_aptos_address(evm_address),
+        MoveLinkResource::struct_tag(),
+    )
+    .map(|link| link.account())
+}
+
+/// Asserts that an EVM transaction reverted with the abort `code` of `0x1::account_link`.
+fn assert_account_link_revert(status: &TransactionStatus, code: u64) {
+    let (status_code, abort_code, location) = move_error(status);
+    assert_eq!(status_code, StatusCode::ABORTED as u64);
+    assert_eq!(abort_code, code);
+    assert!(
+        location.ends_with("::account_link"),
+        "location {}",
+        location
+    );
+}
+
+#[test]
+fn test_link_evm_address() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_with_key_pair();
+    let key = Random.generate().unwrap();
+
+    let message = link_message(&h, account.address(), &key.address(), 0);
+    let signature = personal_sign(&key, &message);
+    assert_success!(link_evm_address(&mut h, &account, &key, signature.clone()));
+    let link = evm_link(&h, account.address()).unwrap();
+    assert_eq!(link.evm_address(), Some(key.address()));
+    assert_eq!(link.nonce(), 1);
+    assert_eq!(linked_account(&h, &key.address()), Some(*account.address()));
+
+    assert_success!(h.run_entry_function(
+        &account,
+        str::parse("0x1::account_link::unlink").unwrap(),
+        vec![],
+        vec![],
+    ));
+    let link = evm_link(&h, account.address()).unwrap();
+    assert_eq!(link.evm_address(), None);
+    assert_eq!(link.nonce(), 2);
+    assert_eq!(linked_account(&h, &key.address()), None);
+    assert_abort!(
+        h.run_entry_function(
+            &account,
+            str::parse("0x1::account_link::unlink").unwrap(),
+            vec![],
+            vec![],
+        ),
+        ENOT_LINKED
+    );
+
+    // The proof of the first link is bound to its nonce, so it cannot link the account again.
+    assert_abort!(
+        link_evm_address(&mut h, &account, &key, signature),
+        EINVALID_EVM_SIGNATURE
+    );
+    let message = link_message(&h, account.address(), &key.address(), 2);
+    assert_success!(link_evm_address(
+        &mut h,
+        &account,
+        &key,
+        personal_sign(&key, &message)
+    ));
+    assert_eq!(evm_link(&h, account.address()).unwrap().nonce(), 3);
+}
+
+#[test]
+fn test_link_evm_address_rejects_forged_signatures() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_with_key_pair();
+    let key = Random.generate().unwrap();
+    let message = link_message(&h, account.address(), &key.address(), 0);
+
+    // Signed by another key.
+    let other = Random.generate().unwrap();
+    assert_abort!(
+        link_evm_address(&mut h, &account, &key, personal_sign(&other, &message)),
+        EINVALID_EVM_SIGNATURE
+    );
+    // Signed for another account.
+    let other_message = link_message(&h, &AccountAddress::ONE, &key.address(), 0);
+    assert_abort!(
+        link_evm_address(&mut h, &account, &key, personal_sign(&key, &other_message)),
+        EINVALID_EVM_SIGNATURE
+    );
+    // Tampered with, or with an invalid recovery id or length.
+    let signature = personal_sign(&key, &message);
+    let mut tampered = signature.clone();
+    tampered[10] ^= 1;
+    let mut bad_recovery_id = signature.clone();
+    bad_recovery_id[64] = 29;
+    for signature in [tampered, bad_recovery_id, signature[..64].to_vec()] {
+        assert_abort!(
+            link_evm_address(&mut h, &account, &key, signature),
+            EINVALID_EVM_SIGNATURE
+        );
+    }
+    assert!(evm_link(&h, account.address()).is_none());
+    assert_eq!(linked_account(&h, &key.address()), None);
+
+    // The raw recovery id is accepted as well as the one of `personal_sign`.
+    let mut signature = signature;
+    signature[64] -= 27;
+    assert_success!(link_evm_address(&mut h, &account, &key, signature));
+}
+
+#[test]
+fn test_link_move_account_from_evm() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_with_key_pair();
+    let key = h.new_eth_account(U256::exp10(18));
+
+    // Signed by another key than the one of the account.
+    let message = link_message(&h, account.address(), &key.address(), 0);
+    let other = h.new_account_with_key_pair();
+    let status = link_move_account(&mut h, &key, &account, &other, &message);
+    assert_account_link_revert(&status, EINVALID_MOVE_SIGNATURE);
+    // Signed for another EVM address.
+    let other_key = Random.generate().unwrap();
+    let other_message = link_message(&h, account.address(), &other_key.address(), 0);
+    let status = link_move_account(&mut h, &key, &account, &account, &other_message);
+    assert_account_link_revert(&status, EINVALID_MOVE_SIGNATURE);
+    assert_eq!(linked_account(&h, &key.address()), None);
+
+    assert_success!(link_move_account(
+        &mut h, &key, &account, &account, &message
+    ));
+    assert_eq!(linked_account(&h, &key.address()), Some(*account.address()));
+    assert_eq!(
+        evm_link(&h, account.address()).unwrap().evm_address(),
+        Some(key.address())
+    );
+
+    // The EVM side unlinks on its own, which invalidates the proof of the link.
+    let data = encode_call_move(AccountAddress::ONE, "account_link", "unlink", vec![]);
+    assert_success!(h.run_eth_transaction(&key, CROSS_SPACE_CONTRACT_ADDRESS, U256::zero(), data));
+    assert_eq!(linked_account(&h, &key.address()), None);
+    assert_eq!(evm_link(&h, account.address()).unwrap().evm_address(), None);
+    let status = link_move_account(&mut h, &key, &account, &account, &message);
+    assert_account_link_revert(&status, EINVALID_MOVE_SIGNATURE);
+}
+
+#[test]
+fn test_call_evm_typed_as_linked_address() {
+    let mut h = MoveHarness::new();
+    let publisher = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&publisher, &common::test_dir_path("account_link.data/pack")));
+
+    // A contract which returns its caller:
+    // CALLER PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let contract = Random.generate().unwrap().address();
+    h.set_eth_code(
+        &contract,
+        vec![0x33, 0x60, 0, 0x52, 0x60, 32, 0x60, 0, 0xf3],
+    );
+    let call_as = |h: &mut MoveHarness, account: &Account| {
+        h.run_entry_function(
+            account,
+            str::parse("0xcafe::linked_caller::call_as").unwrap(),
+            vec![],
+            vec![bcs::to_bytes(&contract.as_bytes().to_vec()).unwrap()],
+        )
+    };
+
+    let account = h.new_account_with_key_pair();
+    assert_abort!(call_as(&mut h, &account), EACCOUNT_NOT_LINKED);
+
+    let key = Random.generate().unwrap();
+    let message = link_message(&h, account.address(), &key.address(), 0);
+    assert_success!(link_evm_address(
+        &mut h,
+        &account,
+        &key,
+        personal_sign(&key, &message)
+    ));
+    assert_success!(call_as(&mut h, &account));
+    let last_call = h
+        .read_resource::<LastCall>(
+            account.address(),
+            parse_struct_tag("0xcafe::linked_caller::LastCall").unwrap(),
+        )
+        .unwrap();
+    let sender: Vec<u8> = bcs::from_bytes(&last_call.returned[0]).unwrap();
+    assert_eq!(sender, key.address().as_bytes());
+
+    assert_success!(h.run_entry_function(
+        &account,
+        str::parse("0x1::account_link::unlink").unwrap(),
+        vec![],
+        vec![],
+    ));
+    assert_abort!(call_as(&mut h, &account), EACCOUNT_NOT_LINKED);
+}
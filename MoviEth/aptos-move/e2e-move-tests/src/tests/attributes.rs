@@ -192,6 +192,178 @@ fn test_bad_view_attribute_in_compiled_module() {
     assert_vm_status!(result, StatusCode::CONSTRAINT_NOT_SATISFIED);
 }
 
+#[test]
+fn test_cross_vm_attribute() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xf00d").unwrap());
+
+    let mut builder = PackageBuilder::new("Package");
+    builder.add_source(
+        "m.move",
+        r#"
+        module 0xf00d::M {
+            #[cross_vm]
+            fun echo(_caller: vector<u8>, value: u64): u64 { value }
+
+            #[cross_vm(deposit)]
+            fun deposit_from_evm(_caller: vector<u8>, _amount: u64) { }
+
+            public entry fun deposit(_account: &signer, _amount: u64) { }
+        }
+        "#,
+    );
+    let path = builder.write_to_temp().unwrap();
+    assert_success!(h.publish_package(&account, path.path()));
+}
+
+#[test]
+#[should_panic]
+fn test_cross_vm_attribute_without_caller() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xf00d").unwrap());
+
+    let mut builder = PackageBuilder::new("Package");
+    builder.add_source(
+        "m.move",
+        r#"
+        module 0xf00d::M {
+            #[cross_vm]
+            fun echo(value: u64): u64 { value }
+        }
+        "#,
+    );
+    let path = builder.write_to_temp().unwrap();
+    assert_success!(h.publish_package(&account, path.path()));
+}
+
+#[test]
+fn test_bad_cross_vm_attribute_in_compiled_module() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xf00d").unwrap());
+
+    let mut builder = PackageBuilder::new("Package");
+    builder.add_source(
+        "m.move",
+        r#"
+        module 0xf00d::M {
+            public entry fun transfer(_account: &signer, _amount: u64) { }
+        }
+        "#,
+    );
+    let path = builder.write_to_temp().unwrap();
+
+    let package = BuiltPackage::build(path.path().to_path_buf(), BuildOptions::default())
+        .expect("building package must succeed");
+    let code = package.extract_code();
+    // There should only be the above module
+    assert!(code.len() == 1);
+    let mut compiled_module = CompiledModule::deserialize(&code[0]).unwrap();
+    let mut value = aptos_framework::RuntimeModuleMetadataV1 {
+        error_map: BTreeMap::new(),
+        struct_attributes: BTreeMap::new(),
+        fun_attributes: BTreeMap::new(),
+    };
+    // An entry function cannot be called with a forged caller.
+    value.fun_attributes.insert("transfer".to_string(), vec![
+        aptos_framework::KnownAttribute::cross_vm_function("transfer".to_string()),
+    ]);
+
+    let metadata = Metadata {
+        key: aptos_framework::APTOS_METADATA_KEY_V1.to_vec(),
+        value: bcs::to_bytes(&value).unwrap(),
+    };
+
+    compiled_module.metadata = vec![metadata];
+    let mut code = vec![];
+    compiled_module.serialize(&mut code).unwrap();
+    let metadata = package
+        .extract_metadata()
+        .expect("extracting package metadata must succeed");
+    let result = h.run_transaction_payload(
+        &account,
+        aptos_stdlib::code_publish_package_txn(
+            bcs::to_bytes(&metadata).expect("PackageMetadata has BCS"),
+            vec![code],
+        ),
+    );
+
+    assert_vm_status!(result, StatusCode::CONSTRAINT_NOT_SATISFIED);
+}
+
+#[test]
+fn test_cross_vm_attribute_on_signer_function_in_compiled_module() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xf00d").unwrap());
+
+    let mut builder = PackageBuilder::new("Package");
+    builder.add_source(
+        "m.move",
+        r#"
+        module 0xf00d::M {
+            fun withdraw(_caller: vector<u8>, _account: &signer, _amount: u64) { }
+        }
+        "#,
+    );
+    let path = builder.write_to_temp().unwrap();
+
+    let package = BuiltPackage::build(path.path().to_path_buf(), BuildOptions::default())
+        .expect("building package must succeed");
+    let code = package.extract_code();
+    // There should only be the above module
+    assert!(code.len() == 1);
+    let mut compiled_module = CompiledModule::deserialize(&code[0]).unwrap();
+    let mut value = aptos_framework::RuntimeModuleMetadataV1 {
+        error_map: BTreeMap::new(),
+        struct_attributes: BTreeMap::new(),
+        fun_attributes: BTreeMap::new(),
+    };
+    // A signer would let any EVM caller act for the account.
+    value.fun_attributes.insert("withdraw".to_string(), vec![
+        aptos_framework::KnownAttribute::cross_vm_function("withdraw".to_string()),
+    ]);
+
+    let metadata = Metadata {
+        key: aptos_framework::APTOS_METADATA_KEY_V1.to_vec(),
+        value: bcs::to_bytes(&value).unwrap(),
+    };
+
+    compiled_module.metadata = vec![metadata];
+    let mut code = vec![];
+    compiled_module.serialize(&mut code).unwrap();
+    let metadata = package
+        .extract_metadata()
+        .expect("extracting package metadata must succeed");
+    let result = h.run_transaction_payload(
+        &account,
+        aptos_stdlib::code_publish_package_txn(
+            bcs::to_bytes(&metadata).expect("PackageMetadata has BCS"),
+            vec![code],
+        ),
+    );
+
+    assert_vm_status!(result, StatusCode::CONSTRAINT_NOT_SATISFIED);
+}
+
+#[test]
+#[should_panic]
+fn test_cross_vm_attribute_with_signer() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xf00d").unwrap());
+
+    let mut builder = PackageBuilder::new("Package");
+    builder.add_source(
+        "m.move",
+        r#"
+        module 0xf00d::M {
+            #[cross_vm]
+            fun withdraw(_caller: vector<u8>, _account: &signer, _amount: u64) { }
+        }
+        "#,
+    );
+    let path = builder.write_to_temp().unwrap();
+    assert_success!(h.publish_package(&account, path.path()));
+}
+
 // We need this because we cannot produce a KnownAttribute directly.
 #[derive(Serialize)]
 pub struct FakeKnownAttribute {
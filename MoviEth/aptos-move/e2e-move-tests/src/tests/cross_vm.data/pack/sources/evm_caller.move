@@ -0,0 +1,106 @@
+module 0xcafe::evm_caller {
+    use std::bcs;
+    use std::option;
+    use std::string;
+    use std::vector;
+    use aptos_std::from_bcs;
+    use aptos_framework::aptos_coin::AptosCoin;
+    use aptos_framework::coin;
+    use aptos_framework::cross_vm;
+
+    /// Number of times `relay` and the entry functions calling it have been run.
+    struct Counter has key {
+        value: u64,
+    }
+
+    fun init_module(account: &signer) {
+        move_to(account, Counter { value: 0 });
+    }
+
+    fun bump(): u64 acquires Counter {
+        let counter = borrow_global_mut<Counter>(@0xcafe);
+        counter.value = counter.value + 1;
+        counter.value
+    }
+
+    /// Calls `relay` from the EVM through the cross space contract at `cross_space`.
+    fun call_relay(cross_space: vector<u8>, hops: u64) {
+        let args = vector[
+            bcs::to_bytes(&bcs::to_bytes(&@0xcafe)),
+            bcs::to_bytes(&b"evm_caller"),
+            bcs::to_bytes(&b"relay"),
+            bcs::to_bytes(&vector[cross_space, bcs::to_bytes(&hops)]),
+            bcs::to_bytes(&vector<vector<u8>>[]),
+        ];
+        cross_vm::call_evm_typed(
+            option::none(),
+            cross_space,
+            string::utf8(b"callMove(bytes32,string,string,bytes[],bytes[])"),
+            args,
+            string::utf8(b"bytes"),
+            2000000,
+            1,
+            &cross_vm::default_cap(),
+        );
+    }
+
+    /// Calls itself through the EVM `hops` more times, then bumps the counter and returns its value. The
+    /// cross space contract and `hops` are the two elements of `data`.
+    #[cross_vm]
+    fun relay(_caller: vector<u8>, data: vector<vector<u8>>): vector<u8> acquires Counter {
+        let cross_space = *vector::borrow(&data, 0);
+        let hops = from_bcs::to_u64(*vector::borrow(&data, 1));
+        if (hops > 0) {
+            call_relay(cross_space, hops - 1);
+        };
+        bcs::to_bytes(&bump())
+    }
+
+    /// Calls `relay` through the EVM with `hops` hops, then bumps the counter.
+    entry fun relay_from_move(_account: &signer, cross_space: vector<u8>, hops: u64) acquires Counter {
+        call_relay(cross_space, hops);
+        bump();
+    }
+
+    /// Bumps the counter without calling the EVM.
+    entry fun bump_counter(_account: &signer) acquires Counter {
+        bump();
+    }
+
+    /// Bumps the counter, then calls `relay`, which bumps it again.
+    entry fun bump_then_relay(_account: &signer, cross_space: vector<u8>) acquires Counter {
+        bump();
+        call_relay(cross_space, 0);
+    }
+
+    /// Returns the address of the EVM caller.
+    #[cross_vm]
+    fun echo_caller(caller: vector<u8>, _data: vector<vector<u8>>): vector<u8> {
+        caller
+    }
+
+    /// Calls `contract` with at most `gas_limit` EVM gas.
+    entry fun call_with_gas(_account: &signer, contract: vector<u8>, gas_limit: u64) {
+        cross_vm::call_evm_with_gas(
+            option::none(),
+            contract,
+            string::utf8(b"run()"),
+            vector[],
+            gas_limit,
+            1,
+            &cross_vm::default_cap(),
+        );
+    }
+
+    /// Sends `amount` octas of the account to the EVM address `to`.
+    entry fun send_to_evm(account: &signer, to: vector<u8>, amount: u64) {
+        let coin = coin::withdraw<AptosCoin>(account, amount);
+        cross_vm::call_evm(
+            option::some(coin),
+            to,
+            string::utf8(b"fallback()"),
+            vector[],
+            &cross_vm::default_cap(),
+        );
+    }
+}
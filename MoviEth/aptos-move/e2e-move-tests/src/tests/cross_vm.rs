@@ -0,0 +1,503 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, encode_call_move, tests::common, MoveHarness};
+use aptos_block_executor::{block_executor::BlockAptosVM, counters::SPECULATIVE_ABORT_COUNT};
+use aptos_evm::{created_contract, is_evm_event, U256};
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{CoinStoreResource, NativeBridgeResource},
+    transaction::{ExecutionStatus, Transaction, TransactionStatus},
+    vm_status::AbortLocation,
+};
+use cfx_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+use cfx_state::native_token::{octas_to_wei, NativeTokenSupply};
+use cfxkey::{Generator, KeyPair, Random};
+use move_core_types::move_resource::MoveStructType;
+use move_core_types::parser::parse_struct_tag;
+use move_core_types::vm_status::StatusCode;
+use serde::Deserialize;
+use solidity_abi::{function_selector, parse_signature, ABIDecodable};
+
+/// Abort code of an EVM transaction which reverted.
+const REVERT_CODE: u64 = 1031;
+
+/// Abort code of `call_evm` when the EVM callee reverts.
+const EVM_CALL_REVERT: u64 = 2;
+
+/// Abort code of a Move transaction which wrote state that the Move calls of its EVM calls
+/// accessed.
+const WRITE_CONFLICT: u64 = 7;
+
+/// Decodes the `MoveError(uint64,uint64,string)` an EVM transaction reverted with.
+pub(super) fn move_error(status: &TransactionStatus) -> (u64, u64, String) {
+    let description = match status {
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+            code: REVERT_CODE,
+            info: Some(info),
+            ..
+        }) => &info.description,
+        _ => panic!("expect an EVM revert, got {:?}", status),
+    };
+    let data = hex::decode(description.trim_start_matches("0x")).unwrap();
+    let (name, types) = parse_signature("MoveError(uint64,uint64,string)").unwrap();
+    assert_eq!(data[..4], function_selector(&name, &types));
+    <(u64, u64, String)>::abi_decode(&data[4..]).unwrap()
+}
+
+/// Mimics `0xcafe::evm_caller::Counter`.
+#[derive(Deserialize)]
+struct Counter {
+    value: u64,
+}
+
+fn counter(h: &MoveHarness) -> u64 {
+    h.read_resource::<Counter>(
+        &AccountAddress::from_hex_literal("0xcafe").unwrap(),
+        parse_struct_tag("0xcafe::evm_caller::Counter").unwrap(),
+    )
+    .unwrap()
+    .value
+}
+
+/// Calls `0xcafe::evm_caller::relay` from the EVM, which calls itself through the EVM `hops` times.
+fn relay_from_evm(h: &mut MoveHarness, sender: &KeyPair, hops: u64) -> TransactionStatus {
+    let data = encode_call_move(
+        AccountAddress::from_hex_literal("0xcafe").unwrap(),
+        "evm_caller",
+        "relay",
+        vec![
+            CROSS_SPACE_CONTRACT_ADDRESS.as_bytes().to_vec(),
+            bcs::to_bytes(&hops).unwrap(),
+        ],
+    );
+    h.run_eth_transaction(sender, CROSS_SPACE_CONTRACT_ADDRESS, U256::zero(), data)
+}
+
+/// Runs `0xcafe::evm_caller::relay_from_move`, which calls `relay` through the EVM.
+fn relay_from_move(h: &mut MoveHarness, account: &Account, hops: u64) -> TransactionStatus {
+    h.run_entry_function(
+        account,
+        str::parse("0xcafe::evm_caller::relay_from_move").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&CROSS_SPACE_CONTRACT_ADDRESS.as_bytes().to_vec()).unwrap(),
+            bcs::to_bytes(&hops).unwrap(),
+        ],
+    )
+}
+
+/// Asserts that a Move transaction aborted in `0x1::cross_vm` with `code`.
+fn assert_cross_vm_abort(status: &TransactionStatus, code: u64) {
+    match status {
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+            location: AbortLocation::Module(module),
+            code: abort_code,
+            ..
+        }) if module.name().as_str() == "cross_vm" => assert_eq!(*abort_code, code),
+        _ => panic!("expect a cross_vm abort, got {:?}", status),
+    }
+}
+
+#[test]
+fn test_move_called_from_evm_can_call_evm() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+
+    // EVM -> Move -> EVM -> Move: the inner call bumps the counter first, and the outer one sees
+    // it bumped.
+    let sender = h.new_eth_account(U256::exp10(18));
+    assert_success!(relay_from_evm(&mut h, &sender, 1));
+    assert_eq!(counter(&h), 2);
+
+    // Move -> EVM -> Move: the transaction sees what the call from the EVM wrote.
+    assert_success!(relay_from_move(&mut h, &account, 0));
+    assert_eq!(counter(&h), 4);
+}
+
+#[test]
+fn test_three_hop_cross_vm_calls() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+
+    // Move -> EVM -> Move -> EVM -> Move, each bumping the counter after the calls it made.
+    assert_success!(relay_from_move(&mut h, &account, 1));
+    assert_eq!(counter(&h), 3);
+
+    // EVM -> Move -> EVM -> Move -> EVM -> Move.
+    let sender = h.new_eth_account(U256::exp10(18));
+    assert_success!(relay_from_evm(&mut h, &sender, 2));
+    assert_eq!(counter(&h), 6);
+}
+
+#[test]
+fn test_cross_vm_call_depth_limit() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+
+    // Every switch between the VMs counts as 128 of the 1024 call depth levels, so an EVM
+    // transaction reaches Move at most 4 times.
+    let sender = h.new_eth_account(U256::exp10(18));
+    assert_success!(relay_from_evm(&mut h, &sender, 3));
+    assert_eq!(counter(&h), 4);
+
+    // The innermost `call_evm` aborts, which reverts the calls around it.
+    let status = relay_from_evm(&mut h, &sender, 4);
+    let (status_code, abort_code, location) = move_error(&status);
+    assert_eq!(status_code, StatusCode::ABORTED as u64);
+    assert_eq!(abort_code, EVM_CALL_REVERT);
+    assert!(location.ends_with("::cross_vm"), "location {}", location);
+    assert_eq!(counter(&h), 4);
+
+    // A Move transaction starts at depth 0, so it reaches Move from the EVM at most 3 times.
+    assert_success!(relay_from_move(&mut h, &account, 2));
+    assert_eq!(counter(&h), 8);
+    let status = relay_from_move(&mut h, &account, 3);
+    assert_cross_vm_abort(&status, EVM_CALL_REVERT);
+    assert_eq!(counter(&h), 8);
+}
+
+#[test]
+fn test_reentrancy_guard_blocks_reentrant_call() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+    assert_success!(h.run_entry_function(
+        &account,
+        str::parse("0x1::cross_vm::set_reentrancy_guard").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes("evm_caller").unwrap(),
+            bcs::to_bytes(&true).unwrap(),
+        ],
+    ));
+
+    // A call into the guarded module which does not re-enter it still succeeds.
+    let sender = h.new_eth_account(U256::exp10(18));
+    assert_success!(relay_from_evm(&mut h, &sender, 0));
+    assert_eq!(counter(&h), 1);
+
+    // The module calls itself back through the EVM, which the guard refuses.
+    let status = relay_from_evm(&mut h, &sender, 1);
+    let (_, abort_code, _) = move_error(&status);
+    assert_eq!(abort_code, EVM_CALL_REVERT);
+    let status = relay_from_move(&mut h, &account, 0);
+    assert_cross_vm_abort(&status, EVM_CALL_REVERT);
+    assert_eq!(counter(&h), 1);
+}
+
+#[test]
+fn test_write_conflict_with_nested_call_aborts() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+
+    // The transaction bumps the counter before the call from the EVM bumps it again, which the
+    // call cannot see, so the transaction aborts instead of losing a bump.
+    let status = h.run_entry_function(
+        &account,
+        str::parse("0xcafe::evm_caller::bump_then_relay").unwrap(),
+        vec![],
+        vec![bcs::to_bytes(&CROSS_SPACE_CONTRACT_ADDRESS.as_bytes().to_vec()).unwrap()],
+    );
+    assert_cross_vm_abort(&status, WRITE_CONFLICT);
+    assert_eq!(counter(&h), 0);
+}
+
+/// `native_bridge_totals` of the harness.
+fn native_bridge_totals(h: &MoveHarness) -> (u128, u128) {
+    let bridge = h
+        .read_resource::<NativeBridgeResource>(
+            &AccountAddress::ONE,
+            NativeBridgeResource::struct_tag(),
+        )
+        .unwrap();
+    (bridge.total_from_evm(), bridge.total_to_evm())
+}
+
+fn apt_balance(h: &MoveHarness, address: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(address, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
+
+#[test]
+fn test_native_token_round_trip_is_conserved() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+    let balance = apt_balance(&h, account.address());
+    let (from_evm, to_evm) = native_bridge_totals(&h);
+    let evm_issued = h.eth_total_issued();
+
+    // EVM -> Move: the value sent along with `callMove` is burned in the EVM and minted to the
+    // module account.
+    let sender = h.new_eth_account(U256::exp10(18));
+    let evm_issued = evm_issued + U256::exp10(18);
+    let data = encode_call_move(*account.address(), "evm_caller", "echo_caller", vec![]);
+    let status = h.run_eth_transaction(
+        &sender,
+        CROSS_SPACE_CONTRACT_ADDRESS,
+        octas_to_wei(1000),
+        data,
+    );
+    assert_success!(status);
+    assert_eq!(apt_balance(&h, account.address()), balance + 1000);
+    assert_eq!(native_bridge_totals(&h), (from_evm + 1000, to_evm));
+    assert_eq!(h.eth_total_issued(), evm_issued - octas_to_wei(1000));
+
+    // Move -> EVM: the coin is burned in Move and issued to the receiver in the EVM.
+    let receiver = Random.generate().unwrap().address();
+    let status = h.run_entry_function(
+        &account,
+        str::parse("0xcafe::evm_caller::send_to_evm").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&receiver.as_bytes().to_vec()).unwrap(),
+            bcs::to_bytes(&400u64).unwrap(),
+        ],
+    );
+    assert_success!(status);
+    assert_eq!(h.eth_balance(&receiver), octas_to_wei(400));
+    assert_eq!(native_bridge_totals(&h), (from_evm + 1000, to_evm + 400));
+    assert_eq!(h.eth_total_issued(), evm_issued - octas_to_wei(600));
+
+    // Only the bridge minted and burned for the round trip, so the supply it accounts for is
+    // conserved. Gas fees do not count towards the issuance of the EVM.
+    let (from_evm_after, to_evm_after) = native_bridge_totals(&h);
+    let before = NativeTokenSupply {
+        move_supply: from_evm + (to_evm_after - to_evm),
+        evm_issued,
+    };
+    let after = NativeTokenSupply {
+        move_supply: from_evm_after,
+        evm_issued: h.eth_total_issued(),
+    };
+    after.check_conserved(&before, U256::zero()).unwrap();
+}
+
+#[test]
+fn test_eth_transaction_with_nested_cross_vm_calls_conserves_native_token() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+    let balance = apt_balance(&h, account.address());
+    let (from_evm, to_evm) = native_bridge_totals(&h);
+    let sender = h.new_eth_account(U256::exp10(18));
+    let evm_issued = h.eth_total_issued();
+
+    // The value is moved into Move by the outer call of an EVM transaction which reaches Move
+    // twice, so the supply is checked against the bridge after all of its calls.
+    let data = encode_call_move(
+        *account.address(),
+        "evm_caller",
+        "relay",
+        vec![
+            CROSS_SPACE_CONTRACT_ADDRESS.as_bytes().to_vec(),
+            bcs::to_bytes(&1u64).unwrap(),
+        ],
+    );
+    let status = h.run_eth_transaction(
+        &sender,
+        CROSS_SPACE_CONTRACT_ADDRESS,
+        octas_to_wei(1000),
+        data,
+    );
+    assert_success!(status);
+    assert_eq!(counter(&h), 2);
+    assert_eq!(apt_balance(&h, account.address()), balance + 1000);
+    assert_eq!(native_bridge_totals(&h), (from_evm + 1000, to_evm));
+    assert_eq!(h.eth_total_issued(), evm_issued - octas_to_wei(1000));
+}
+
+#[test]
+fn test_contracts_created_by_call_evm_are_recorded() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+
+    // A factory which creates an empty contract whenever it is called:
+    // PUSH1 0 PUSH1 0 PUSH1 0 CREATE STOP
+    let factory = Random.generate().unwrap().address();
+    h.set_eth_code(&factory, vec![0x60, 0, 0x60, 0, 0x60, 0, 0xf0, 0x00]);
+
+    let txn = h.create_entry_function(
+        &account,
+        str::parse("0xcafe::evm_caller::send_to_evm").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&factory.as_bytes().to_vec()).unwrap(),
+            bcs::to_bytes(&0u64).unwrap(),
+        ],
+    );
+    let (status, events) = h.run_with_events(txn);
+    assert_success!(status);
+    let created: Vec<_> = events.iter().filter_map(created_contract).collect();
+    assert_eq!(created.len(), 1);
+    assert_ne!(created[0], factory);
+    // The creation is not an EVM log, so it does not show up in `eth_getLogs`.
+    assert!(events
+        .iter()
+        .filter(|event| created_contract(event).is_some())
+        .all(|event| !is_evm_event(event)));
+}
+
+#[test]
+fn test_call_evm_reports_revert_data() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+
+    // A contract which reverts with the custom error 0xdeadbeef:
+    // PUSH4 0xdeadbeef PUSH1 0 MSTORE PUSH1 4 PUSH1 28 REVERT
+    let reverter = Random.generate().unwrap().address();
+    h.set_eth_code(
+        &reverter,
+        vec![
+            0x63, 0xde, 0xad, 0xbe, 0xef, 0x60, 0, 0x52, 0x60, 4, 0x60, 28, 0xfd,
+        ],
+    );
+
+    let status = h.run_entry_function(
+        &account,
+        str::parse("0xcafe::evm_caller::send_to_evm").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&reverter.as_bytes().to_vec()).unwrap(),
+            bcs::to_bytes(&0u64).unwrap(),
+        ],
+    );
+    match status {
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+            code: EVM_CALL_REVERT,
+            info: Some(info),
+            ..
+        }) => {
+            assert_eq!(info.reason_name, "EVM_CALL_REVERT");
+            assert_eq!(info.description, "0xdeadbeef");
+        },
+        _ => panic!("expect a call_evm revert, got {:?}", status),
+    }
+}
+
+#[test]
+fn test_call_evm_out_of_gas_limit_reverts() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+
+    // A contract which loops forever: JUMPDEST PUSH1 0 JUMP
+    let looper = Random.generate().unwrap().address();
+    h.set_eth_code(&looper, vec![0x5b, 0x60, 0, 0x56]);
+    let call_with_gas = |h: &mut MoveHarness, gas_limit: u64| {
+        h.run_entry_function(
+            &account,
+            str::parse("0xcafe::evm_caller::call_with_gas").unwrap(),
+            vec![],
+            vec![
+                bcs::to_bytes(&looper.as_bytes().to_vec()).unwrap(),
+                bcs::to_bytes(&gas_limit).unwrap(),
+            ],
+        )
+    };
+
+    // The callee runs past the limit the caller chose, which the transaction could pay for.
+    let status = call_with_gas(&mut h, 50_000);
+    match status {
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+            code: EVM_CALL_REVERT,
+            info: Some(info),
+            ..
+        }) => assert_eq!(info.reason_name, "EVM_CALL_REVERT"),
+        _ => panic!("expect a call_evm revert, got {:?}", status),
+    }
+
+    // The limit is capped by the Move gas left, so the transaction runs out of gas.
+    let status = call_with_gas(&mut h, u64::MAX);
+    assert!(matches!(
+        status,
+        TransactionStatus::Keep(ExecutionStatus::OutOfGas)
+    ));
+}
+
+#[test]
+fn test_conflicting_cross_vm_block_runs_in_parallel_as_sequentially() {
+    let mut h = MoveHarness::new();
+    let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&account, &common::test_dir_path("cross_vm.data/pack")));
+    let receiver = Random.generate().unwrap().address();
+
+    // EVM transfers conflict on the balance of the receiver, with each other and with the coins
+    // sent from Move, and the calls from both VMs conflict on the counter with the Move bumps.
+    let mut txns = vec![];
+    for _ in 0..8 {
+        let sender = h.new_eth_account(U256::exp10(18));
+        let move_sender = h.new_account_with_key_pair();
+        txns.push(h.create_eth_transaction(&sender, receiver, U256::from(1000), vec![]));
+        let data = encode_call_move(
+            *account.address(),
+            "evm_caller",
+            "relay",
+            vec![
+                CROSS_SPACE_CONTRACT_ADDRESS.as_bytes().to_vec(),
+                bcs::to_bytes(&0u64).unwrap(),
+            ],
+        );
+        txns.push(h.create_eth_transaction(
+            &sender,
+            CROSS_SPACE_CONTRACT_ADDRESS,
+            U256::zero(),
+            data,
+        ));
+        txns.push(h.create_entry_function(
+            &move_sender,
+            str::parse("0xcafe::evm_caller::send_to_evm").unwrap(),
+            vec![],
+            vec![
+                bcs::to_bytes(&receiver.as_bytes().to_vec()).unwrap(),
+                bcs::to_bytes(&10u64).unwrap(),
+            ],
+        ));
+        txns.push(h.create_entry_function(
+            &move_sender,
+            str::parse("0xcafe::evm_caller::bump_counter").unwrap(),
+            vec![],
+            vec![],
+        ));
+    }
+    let block: Vec<_> = txns.into_iter().map(Transaction::UserTransaction).collect();
+
+    let sequential =
+        BlockAptosVM::execute_block(block.clone(), h.executor.get_state_view(), 1).unwrap();
+    for output in &sequential {
+        assert_success!(output.status().clone());
+    }
+
+    // Whether a validation fails depends on the scheduling, so the block is run until Block-STM
+    // re-executes a transaction that read a value before the transactions before it wrote it.
+    let mut re_executed = false;
+    for _ in 0..20 {
+        let aborts = SPECULATIVE_ABORT_COUNT.get();
+        let parallel =
+            BlockAptosVM::execute_block(block.clone(), h.executor.get_state_view(), 4).unwrap();
+        assert_eq!(parallel, sequential);
+        if SPECULATIVE_ABORT_COUNT.get() > aborts {
+            re_executed = true;
+            break;
+        }
+    }
+    assert!(re_executed, "no transaction of the block was re-executed");
+
+    for output in &sequential {
+        h.executor.apply_write_set(output.write_set());
+    }
+    assert_eq!(counter(&h), 16);
+    assert_eq!(
+        h.eth_balance(&receiver),
+        U256::from(8000) + octas_to_wei(80)
+    );
+}
@@ -1,11 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+mod account_link;
 mod aggregator;
 mod attributes;
 mod chain_id;
 mod code_publishing;
 mod common;
+mod cross_vm;
 mod error_map;
 mod framework_compatibility;
 mod gas;
@@ -21,6 +23,7 @@ mod new_integer_types;
 mod offer_signer_capability;
 mod rotate_auth_key;
 mod scripts;
+mod signer_delegation;
 mod simple_defi;
 mod stake;
 mod string_args;
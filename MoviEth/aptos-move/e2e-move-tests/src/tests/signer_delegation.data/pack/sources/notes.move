@@ -0,0 +1,20 @@
+module 0xcafe::notes {
+    use std::signer;
+
+    struct Note has key {
+        value: u64,
+    }
+
+    entry fun set(account: &signer, value: u64) acquires Note {
+        let addr = signer::address_of(account);
+        if (exists<Note>(addr)) {
+            borrow_global_mut<Note>(addr).value = value;
+        } else {
+            move_to(account, Note { value });
+        }
+    }
+
+    entry fun clear(account: &signer) acquires Note {
+        let Note { value: _ } = move_from<Note>(signer::address_of(account));
+    }
+}
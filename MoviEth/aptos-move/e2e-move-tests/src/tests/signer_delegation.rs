@@ -0,0 +1,178 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    account_link::{link_evm_address, link_message, personal_sign},
+    cross_vm::move_error,
+};
+use crate::{assert_success, tests::common, MoveHarness};
+use aptos_evm::U256;
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{account_address::AccountAddress, transaction::TransactionStatus};
+use cfx_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+use cfxkey::KeyPair;
+use move_core_types::{parser::parse_struct_tag, vm_status::StatusCode};
+use serde::Deserialize;
+use solidity_abi::{function_selector, parse_signature, ABIEncodable};
+
+/// `error::permission_denied(ENOT_DELEGATED)` of `0x1::signer_delegation`.
+const ENOT_DELEGATED: u64 = 0x50003;
+
+/// Mimics `0x1::timestamp::CurrentTimeMicroseconds`.
+#[derive(Deserialize)]
+struct CurrentTimeMicroseconds {
+    microseconds: u64,
+}
+
+/// Mimics `0xcafe::notes::Note`.
+#[derive(Deserialize)]
+struct Note {
+    value: u64,
+}
+
+fn now_seconds(h: &MoveHarness) -> u64 {
+    h.read_resource::<CurrentTimeMicroseconds>(
+        &AccountAddress::ONE,
+        parse_struct_tag("0x1::timestamp::CurrentTimeMicroseconds").unwrap(),
+    )
+    .unwrap()
+    .microseconds
+        / 1_000_000
+}
+
+fn note(h: &MoveHarness, account: &AccountAddress) -> Option<u64> {
+    h.read_resource::<Note>(account, parse_struct_tag("0xcafe::notes::Note").unwrap())
+        .map(|note| note.value)
+}
+
+/// Publishes `0xcafe::notes` and links a new Move account to a new EVM account.
+fn setup(h: &mut MoveHarness) -> (Account, KeyPair) {
+    let publisher = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(
+        &publisher,
+        &common::test_dir_path("signer_delegation.data/pack")
+    ));
+    let account = h.new_account_with_key_pair();
+    let key = h.new_eth_account(U256::exp10(18));
+    let message = link_message(h, account.address(), &key.address(), 0);
+    assert_success!(link_evm_address(
+        h,
+        &account,
+        &key,
+        personal_sign(&key, &message)
+    ));
+    (account, key)
+}
+
+/// Runs `0x1::signer_delegation::delegate` for `0xcafe::notes::<function>`.
+fn delegate(
+    h: &mut MoveHarness,
+    account: &Account,
+    function: &str,
+    expiration_secs: u64,
+) -> TransactionStatus {
+    h.run_entry_function(
+        account,
+        str::parse("0x1::signer_delegation::delegate").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&AccountAddress::from_hex_literal("0xcafe").unwrap()).unwrap(),
+            bcs::to_bytes("notes").unwrap(),
+            bcs::to_bytes(function).unwrap(),
+            bcs::to_bytes(&expiration_secs).unwrap(),
+        ],
+    )
+}
+
+/// Calls `0xcafe::notes::<function>` from `key` with `callMoveAsSigner`, with the ABI encoded
+/// arguments `data`.
+fn call_as_signer(
+    h: &mut MoveHarness,
+    key: &KeyPair,
+    function: &str,
+    data: Vec<u8>,
+) -> TransactionStatus {
+    let (name, types) =
+        parse_signature("callMoveAsSigner(bytes32,string,string,bytes,bytes[])").unwrap();
+    let params = (
+        AccountAddress::from_hex_literal("0xcafe")
+            .unwrap()
+            .into_bytes(),
+        "notes".to_string(),
+        function.to_string(),
+        data,
+        Vec::<Vec<u8>>::new(),
+    );
+    let data = [&function_selector(&name, &types)[..], &params.abi_encode()].concat();
+    h.run_eth_transaction(key, CROSS_SPACE_CONTRACT_ADDRESS, U256::zero(), data)
+}
+
+/// Asserts that an EVM transaction reverted since the signer was not delegated to it.
+fn assert_not_delegated(status: &TransactionStatus) {
+    let (status_code, abort_code, location) = move_error(status);
+    assert_eq!(status_code, StatusCode::ABORTED as u64);
+    assert_eq!(abort_code, ENOT_DELEGATED);
+    assert!(
+        location.ends_with("::signer_delegation"),
+        "location {}",
+        location
+    );
+}
+
+#[test]
+fn test_call_move_as_delegated_signer() {
+    let mut h = MoveHarness::new();
+    let (account, key) = setup(&mut h);
+
+    assert_not_delegated(&call_as_signer(&mut h, &key, "set", 7u64.abi_encode()));
+    assert_eq!(note(&h, account.address()), None);
+
+    let expiration_secs = now_seconds(&h) + 3600;
+    assert_success!(delegate(&mut h, &account, "set", expiration_secs));
+    assert_success!(call_as_signer(&mut h, &key, "set", 7u64.abi_encode()));
+    assert_eq!(note(&h, account.address()), Some(7));
+
+    // The delegation covers `set` only.
+    assert_not_delegated(&call_as_signer(&mut h, &key, "clear", vec![]));
+    assert_eq!(note(&h, account.address()), Some(7));
+    // A module delegation covers `clear` too.
+    assert_success!(delegate(&mut h, &account, "", expiration_secs));
+    assert_success!(call_as_signer(&mut h, &key, "clear", vec![]));
+    assert_eq!(note(&h, account.address()), None);
+}
+
+#[test]
+fn test_revoked_delegation_is_rejected() {
+    let mut h = MoveHarness::new();
+    let (account, key) = setup(&mut h);
+
+    let expiration_secs = now_seconds(&h) + 3600;
+    assert_success!(delegate(&mut h, &account, "set", expiration_secs));
+    assert_success!(h.run_entry_function(
+        &account,
+        str::parse("0x1::signer_delegation::revoke").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&AccountAddress::from_hex_literal("0xcafe").unwrap()).unwrap(),
+            bcs::to_bytes("notes").unwrap(),
+            bcs::to_bytes("set").unwrap(),
+        ],
+    ));
+    assert_not_delegated(&call_as_signer(&mut h, &key, "set", 7u64.abi_encode()));
+    assert_eq!(note(&h, account.address()), None);
+}
+
+#[test]
+fn test_expired_delegation_is_rejected() {
+    let mut h = MoveHarness::new();
+    let (account, key) = setup(&mut h);
+
+    let expiration_secs = now_seconds(&h) + 3600;
+    assert_success!(delegate(&mut h, &account, "set", expiration_secs));
+    assert_success!(call_as_signer(&mut h, &key, "set", 7u64.abi_encode()));
+
+    h.new_epoch();
+    assert!(now_seconds(&h) >= expiration_secs);
+    assert_not_delegated(&call_as_signer(&mut h, &key, "set", 8u64.abi_encode()));
+    assert_eq!(note(&h, account.address()), Some(7));
+}
@@ -1,33 +1,104 @@
-use cfx_evm::{Env, Machine, Spec};
+use crate::events::EventKeyScheme;
+use cfx_evm::{execution::gas_required_for, Env, Machine, Spec};
+use cfx_primitives::AccessList;
 use cfx_types::{Address, H256, U256};
+use std::sync::Arc;
 
 pub trait ContextReader {
     fn get_timestamp(&self) -> u64;
     fn get_block_height(&self) -> u64;
+    /// The hash of the current block, which tells blocks of the same height apart.
+    fn get_block_hash(&self) -> H256;
+    fn get_chain_id(&self) -> u64;
+    fn get_block_gas_limit(&self) -> u64;
+    /// The proposer of the current block, as an EVM address.
+    fn get_block_author(&self) -> Address;
+    /// The hashes of the most recent blocks before the current one, the oldest first.
+    fn get_recent_block_hashes(&self) -> Vec<H256>;
+    /// Whether the logs of a contract go to one event stream per first topic.
+    fn get_event_key_per_topic(&self) -> bool;
+    /// The base fee per gas of the current block (EIP-1559).
+    fn get_base_fee_per_gas(&self) -> u64;
 }
 
 pub struct EvmContext {
     pub(crate) env: Env,
     pub(crate) spec: Spec,
+    event_key_scheme: EventKeyScheme,
+    block_hash: H256,
 }
 
-// Question: stubs?
 impl EvmContext {
     pub(crate) fn make_for_new_block(reader: &impl ContextReader, machine: &Machine) -> Self {
         // FIXME(vm): panics here.
         let block_height = reader.get_block_height();
         let timestamp = reader.get_timestamp();
+        let last_hashes = reader.get_recent_block_hashes();
         let env = Env {
             number: block_height,
-            author: Address::zero(),
+            author: reader.get_block_author(),
             timestamp,
             difficulty: U256::zero(),
-            gas_limit: U256::zero(),
-            last_hash: H256::zero(),
+            gas_limit: U256::from(reader.get_block_gas_limit()),
+            last_hash: last_hashes.last().cloned().unwrap_or_default(),
+            last_hashes: Arc::new(last_hashes),
             accumulated_gas_used: U256::zero(),
             epoch_height: block_height,
+            chain_id: reader.get_chain_id(),
+            base_fee_per_gas: U256::from(reader.get_base_fee_per_gas()),
         };
         let spec = machine.params().spec(block_height);
-        EvmContext { env, spec }
+        let event_key_scheme = if reader.get_event_key_per_topic() {
+            EventKeyScheme::PerTopic0
+        } else {
+            EventKeyScheme::PerContract
+        };
+        EvmContext {
+            env,
+            spec,
+            event_key_scheme,
+            block_hash: reader.get_block_hash(),
+        }
+    }
+
+    /// The height of the block this context is made for.
+    pub fn block_height(&self) -> u64 {
+        self.env.number
+    }
+
+    /// The hash of the block this context is made for.
+    pub fn block_hash(&self) -> H256 {
+        self.block_hash
+    }
+
+    /// The gas limit of the block this context is made for.
+    pub fn block_gas_limit(&self) -> U256 {
+        self.env.gas_limit
+    }
+
+    /// The EIP-155 chain id of the EVM.
+    pub fn chain_id(&self) -> u64 {
+        self.env.chain_id
+    }
+
+    /// The base fee per gas of the block this context is made for.
+    pub fn base_fee_per_gas(&self) -> U256 {
+        self.env.base_fee_per_gas
+    }
+
+    /// The gas a transaction pays before it runs, for its kind, its data and
+    /// its access list.
+    pub fn intrinsic_gas(
+        &self,
+        is_create: bool,
+        data: &[u8],
+        access_list: Option<&AccessList>,
+    ) -> u64 {
+        gas_required_for(is_create, data, access_list, &self.spec)
+    }
+
+    /// How the logs of the block are assigned to event streams.
+    pub fn event_key_scheme(&self) -> EventKeyScheme {
+        self.event_key_scheme
     }
 }
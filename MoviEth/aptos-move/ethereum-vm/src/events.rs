@@ -9,21 +9,119 @@ use cfx_evm::StorageTrait;
 use cfx_primitives::LogEntry;
 use cfx_storage::Result as DbResult;
 use cfx_types::{Address, Space, H256};
-use move_core_types::language_storage::TypeTag;
+use move_core_types::{account_address::AccountAddress, language_storage::TypeTag};
+use serde::{Deserialize, Serialize};
 use solidity_abi::{ABIDecodable, ABIEncodable};
 use solidity_abi_derive::ABIVariable;
 use std::collections::BTreeMap;
 
+/// Creation number of the event stream shared by all the logs of a contract.
+const CONTRACT_EVENT_CREATION_NUM: u64 = 0;
+
+/// Creation number of the event stream recording the creations of a contract. It has the top bit
+/// unset, so it never clashes with the event streams of the topics either.
+const CONTRACT_CREATION_EVENT_CREATION_NUM: u64 = 1;
+
+/// Length of the first event nonce layout, which stored the next sequence number as 8 big-endian
+/// bytes without a version.
+const LEGACY_EVENT_NONCE_LEN: usize = 8;
+
+/// How the logs of EVM contracts are assigned to Aptos event streams.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKeyScheme {
+    /// All the logs of a contract share one event stream.
+    PerContract,
+    /// The logs of a contract with the same first topic, i.e. of the same Solidity event, share
+    /// one event stream. Logs without topics stay on the event stream of the contract.
+    PerTopic0,
+}
+
+/// On-chain layout of the next sequence number of an EVM event stream.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum EventNonce {
+    V1(u64),
+}
+
+/// Returns the key of the event stream which takes the logs of `address` with the first topic
+/// `topic0`.
+pub fn evm_event_key(address: &Address, topic0: Option<&H256>, scheme: EventKeyScheme) -> EventKey {
+    let creation_num = match (scheme, topic0) {
+        (EventKeyScheme::PerTopic0, Some(topic0)) => topic_creation_num(topic0),
+        _ => CONTRACT_EVENT_CREATION_NUM,
+    };
+    EventKey::new(creation_num, eth_address_to_aptos_address(address))
+}
+
+/// The first 8 bytes of the topic with the top bit set, so that it never clashes with the event
+/// stream of the contract.
+fn topic_creation_num(topic0: &H256) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&topic0[..8]);
+    u64::from_be_bytes(bytes) | (1 << 63)
+}
+
 pub fn evm_events_to_aptos_events(
     storage: impl StorageTrait<StorageKey = StorageKey>,
     events: Vec<LogEntry>,
+    scheme: EventKeyScheme,
 ) -> DbResult<Vec<ContractEvent>> {
-    Ok(assign_event_nonce(storage, events)?
+    Ok(assign_event_nonce(storage, events, scheme)?
         .into_iter()
         .map(Into::into)
         .collect())
 }
 
+/// Whether an event was converted from an EVM log, as opposed to emitted by a Move module in the
+/// same transaction.
+pub fn is_evm_event(event: &ContractEvent) -> bool {
+    let creator = event.key().get_creator_address();
+    *event.type_tag() == TypeTag::Vector(Box::new(TypeTag::U8)) && is_evm_address(&creator)
+}
+
+/// Records the contracts created by a transaction as events on their creation event streams, so
+/// that they outlive the execution like the logs do.
+pub fn contracts_created_to_aptos_events(
+    mut storage: impl StorageTrait<StorageKey = StorageKey>,
+    contracts: &[Address],
+) -> DbResult<Vec<ContractEvent>> {
+    contracts
+        .iter()
+        .map(|address| {
+            let storage_key = StorageKey::creation_event_nonce_key(*address);
+            let nonce = read_nonce(&storage, &storage_key)?;
+            write_nonce(&mut storage, storage_key, nonce + 1)?;
+            let aptos_address = eth_address_to_aptos_address(address);
+            Ok(ContractEvent::new(
+                EventKey::new(CONTRACT_CREATION_EVENT_CREATION_NUM, aptos_address),
+                nonce,
+                TypeTag::Address,
+                bcs::to_bytes(&aptos_address).expect("Unexpected Serialization Error"),
+            ))
+        })
+        .collect()
+}
+
+/// The contract whose creation an event records, if it was emitted by
+/// `contracts_created_to_aptos_events`.
+pub fn created_contract(event: &ContractEvent) -> Option<Address> {
+    if event.key().get_creation_number() != CONTRACT_CREATION_EVENT_CREATION_NUM
+        || *event.type_tag() != TypeTag::Address
+    {
+        return None;
+    }
+    let creator = event.key().get_creator_address();
+    let recorded = bcs::from_bytes::<AccountAddress>(event.event_data()).ok()?;
+    (recorded == creator && is_evm_address(&creator))
+        .then(|| aptos_address_to_eth_address(&creator))
+}
+
+/// Whether an Aptos address is the image of an EVM address.
+fn is_evm_address(address: &AccountAddress) -> bool {
+    address.as_ref()[Address::len_bytes()..]
+        .iter()
+        .all(|byte| *byte == 0)
+}
+
 pub fn aptos_events_to_evm_events(events: Vec<ContractEvent>) -> anyhow::Result<Vec<LogEntry>> {
     events
         .into_iter()
@@ -33,6 +131,7 @@ pub fn aptos_events_to_evm_events(events: Vec<ContractEvent>) -> anyhow::Result<
 
 struct LogEntryWithNonce {
     log: LogEntry,
+    key: EventKey,
     nonce: u64,
 }
 
@@ -46,21 +145,13 @@ struct LogEntryPayload {
 impl From<LogEntryWithNonce> for ContractEvent {
     fn from(value: LogEntryWithNonce) -> Self {
         use TypeTag::{Vector, U8};
-        // TODO: currently, all the events from the same evm contract shares one event key. Maybe better solution later.
         let LogEntryWithNonce {
-            log:
-                LogEntry {
-                    address,
-                    topics,
-                    data,
-                    ..
-                },
+            log: LogEntry { topics, data, .. },
+            key,
             nonce,
         } = value;
-        let account_address = eth_address_to_aptos_address(&address);
-        let event_key = EventKey::new(0, account_address);
         let payload = LogEntryPayload { topics, data };
-        Self::new(event_key, nonce, Vector(Box::new(U8)), payload.abi_encode())
+        Self::new(key, nonce, Vector(Box::new(U8)), payload.abi_encode())
     }
 }
 
@@ -79,6 +170,7 @@ impl TryFrom<ContractEvent> for LogEntryWithNonce {
         };
         Ok(Self {
             log,
+            key: *value.key(),
             nonce: value.sequence_number(),
         })
     }
@@ -87,52 +179,133 @@ impl TryFrom<ContractEvent> for LogEntryWithNonce {
 fn assign_event_nonce(
     mut storage: impl StorageTrait<StorageKey = StorageKey>,
     logs: Vec<LogEntry>,
+    scheme: EventKeyScheme,
 ) -> DbResult<Vec<LogEntryWithNonce>> {
-    let mut nonce_map: BTreeMap<Address, u64> = BTreeMap::new();
+    let mut nonce_map: BTreeMap<EventKey, (StorageKey, u64)> = BTreeMap::new();
     let answer: Vec<LogEntryWithNonce> = logs
         .into_iter()
         .map(|log| -> DbResult<LogEntryWithNonce> {
-            let address = log.address;
+            let topic0 = match scheme {
+                EventKeyScheme::PerContract => None,
+                EventKeyScheme::PerTopic0 => log.topics.first(),
+            };
+            let key = evm_event_key(&log.address, topic0, scheme);
             let nonce;
-            if !nonce_map.contains_key(&address) {
-                let current_nonce = read_nonce(&storage, address)?;
-                nonce_map.insert(address, current_nonce + 1);
+            if !nonce_map.contains_key(&key) {
+                let storage_key = match topic0 {
+                    Some(topic0) => StorageKey::topic_event_nonce_key(log.address, topic0),
+                    None => StorageKey::event_nonce_key(log.address),
+                };
+                let current_nonce = read_nonce(&storage, &storage_key)?;
+                nonce_map.insert(key, (storage_key, current_nonce + 1));
                 nonce = current_nonce;
             } else {
-                let nonce_mut = nonce_map.get_mut(&address).unwrap();
+                let (_, nonce_mut) = nonce_map.get_mut(&key).unwrap();
                 nonce = *nonce_mut;
                 *nonce_mut += 1;
             }
 
-            Ok(LogEntryWithNonce { log, nonce })
+            Ok(LogEntryWithNonce { log, key, nonce })
         })
         .collect::<DbResult<_>>()?;
 
-    for (address, nonce) in nonce_map {
-        write_nonce(&mut storage, address, nonce)?;
+    for (_, (storage_key, nonce)) in nonce_map {
+        write_nonce(&mut storage, storage_key, nonce)?;
     }
 
     Ok(answer)
 }
 
+/// Reads the next sequence number of an event stream. Values in the legacy layout are read as
+/// well, and are rewritten in the current layout by the next `write_nonce`.
 fn read_nonce(
     storage: &impl StorageTrait<StorageKey = StorageKey>,
-    address: Address,
+    storage_key: &StorageKey,
 ) -> DbResult<u64> {
-    let nonce = storage
-        .get(StorageKey::event_nonce_key(address))?
-        .map_or(0, |x| {
-            let mut encoded = [0u8; 8];
-            encoded.copy_from_slice(&x);
-            u64::from_le_bytes(encoded)
-        });
-    Ok(nonce)
+    match storage.get(storage_key.clone())? {
+        Some(encoded) => decode_nonce(&encoded),
+        None => Ok(0),
+    }
 }
+
 fn write_nonce(
     storage: &mut impl StorageTrait<StorageKey = StorageKey>,
-    address: Address,
+    storage_key: StorageKey,
     nonce: u64,
 ) -> DbResult<()> {
-    let encoded: Box<[u8]> = nonce.to_be_bytes().to_vec().into_boxed_slice();
-    storage.set(StorageKey::event_nonce_key(address), encoded)
+    let encoded = bcs::to_bytes(&EventNonce::V1(nonce))
+        .map_err(|e| format!("Cannot encode event nonce: {}", e))?;
+    storage.set(storage_key, encoded.into_boxed_slice())
+}
+
+fn decode_nonce(encoded: &[u8]) -> DbResult<u64> {
+    if encoded.len() == LEGACY_EVENT_NONCE_LEN {
+        let mut bytes = [0u8; LEGACY_EVENT_NONCE_LEN];
+        bytes.copy_from_slice(encoded);
+        return Ok(u64::from_be_bytes(bytes));
+    }
+    match bcs::from_bytes(encoded) {
+        Ok(EventNonce::V1(nonce)) => Ok(nonce),
+        Err(e) => Err(format!("Cannot decode event nonce: {}", e).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_nonce() {
+        assert_eq!(decode_nonce(&5u64.to_be_bytes()).unwrap(), 5);
+        let encoded = bcs::to_bytes(&EventNonce::V1(5)).unwrap();
+        assert_ne!(encoded.len(), LEGACY_EVENT_NONCE_LEN);
+        assert_eq!(decode_nonce(&encoded).unwrap(), 5);
+        assert!(decode_nonce(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_evm_event_key() {
+        let address = Address::repeat_byte(0x11);
+        let topic0 = H256::repeat_byte(0x22);
+        let contract_key = evm_event_key(&address, None, EventKeyScheme::PerTopic0);
+        assert_eq!(
+            contract_key,
+            evm_event_key(&address, Some(&topic0), EventKeyScheme::PerContract)
+        );
+
+        let topic_key = evm_event_key(&address, Some(&topic0), EventKeyScheme::PerTopic0);
+        assert_ne!(topic_key, contract_key);
+        assert_eq!(
+            topic_key.get_creator_address(),
+            contract_key.get_creator_address()
+        );
+        assert_ne!(
+            evm_event_key(&address, Some(&H256::zero()), EventKeyScheme::PerTopic0),
+            contract_key
+        );
+    }
+
+    #[test]
+    fn test_is_evm_event() {
+        let address = Address::repeat_byte(0x11);
+        let log = LogEntry {
+            address,
+            topics: vec![H256::repeat_byte(0x22)],
+            data: vec![1, 2, 3],
+            space: Space::Ethereum,
+        };
+        let key = evm_event_key(&address, None, EventKeyScheme::PerContract);
+        let event: ContractEvent = LogEntryWithNonce { log, key, nonce: 0 }.into();
+        assert!(is_evm_event(&event));
+
+        let move_event = ContractEvent::new(
+            EventKey::new(0, AccountAddress::ONE),
+            0,
+            TypeTag::Vector(Box::new(TypeTag::U8)),
+            vec![],
+        );
+        assert!(!is_evm_event(&move_event));
+        let move_event = ContractEvent::new(key, 0, TypeTag::U64, vec![]);
+        assert!(!is_evm_event(&move_event));
+    }
 }
@@ -1,21 +1,27 @@
 mod context;
 mod events;
 mod machine;
+mod move_abi;
 mod outcome;
 mod state;
 mod storage_key;
 mod transaction;
 
 pub use cfx_evm::{
-    vm::Error as EvmError, ExecutionOutcome, TXExecutor, TransactOptions, TransactionInfo,
+    coin_facade_address, is_coin_facade_address, vm::Error as EvmError, ExecutionOutcome,
+    StateTrait, Substate, TXExecutor, TransactOptions, TransactionInfo,
 };
 pub use cfx_primitives::Action;
-pub use cfx_types::{Address, AddressWithSpace, Space, U256};
+pub use cfx_types::{Address, AddressWithSpace, Space, H256, U256};
 pub use context::{ContextReader as EvmContextReader, EvmContext};
-pub use events::{aptos_events_to_evm_events, evm_events_to_aptos_events};
+pub use events::{
+    aptos_events_to_evm_events, contracts_created_to_aptos_events, created_contract,
+    evm_event_key, evm_events_to_aptos_events, is_evm_event, EventKeyScheme,
+};
 pub use machine::EvmMachine;
+pub use move_abi::{abi_to_move_value, abi_type_to_layout, layout_to_abi_type, move_value_to_abi};
 pub use outcome::{convert_exeuction_outcome, extract_evm_executed};
-pub use state::{EvmState, ViewWrapper};
+pub use state::{EvmState, StorageKey, ViewWrapper};
 pub use transaction::EvmTransaction;
 
 pub fn make_executor<'a>(
@@ -23,10 +29,15 @@ pub fn make_executor<'a>(
     context: &'a EvmContext,
     state: &'a mut EvmState,
 ) -> TXExecutor<'a> {
-    TXExecutor::new(
-        &mut state.state,
-        &context.env,
-        &machine.inner,
-        &context.spec,
-    )
+    make_executor_with_state(machine, context, &mut state.state)
+}
+
+/// Like `make_executor`, on a state borrowed from a running executor, e.g. the state a Move call
+/// from the EVM calls back into the EVM on.
+pub fn make_executor_with_state<'a>(
+    machine: &'a EvmMachine,
+    context: &'a EvmContext,
+    state: &'a mut dyn StateTrait,
+) -> TXExecutor<'a> {
+    TXExecutor::new(state, &context.env, &machine.inner, &context.spec)
 }
@@ -0,0 +1,250 @@
+use anyhow::{bail, Result};
+use cfx_types::{Address, U256};
+use move_core_types::{
+    account_address::AccountAddress,
+    u256::U256 as MoveU256,
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+use solidity_abi::{ABIType, ABIValue};
+
+// Mapping between Solidity ABI values and Move values:
+//   uint<M>                 <-> the smallest of u8/u16/u32/u64/u128/u256 holding M bits
+//   bool                    <-> bool
+//   address                 <-> vector<u8> of 20 bytes (from Move) / address (to Move)
+//   bytes<M>, bytes, string <-> vector<u8>
+//   T[], T[k]               <-> vector<T>
+//   (T1,...,Tn)             <-> a struct with fields T1,...,Tn
+
+/// The Move layout of the BCS bytes a Move caller provides for an ABI type.
+pub fn abi_type_to_layout(ty: &ABIType) -> MoveTypeLayout {
+    use MoveTypeLayout as L;
+    match ty {
+        ABIType::Uint(bits) if *bits <= 8 => L::U8,
+        ABIType::Uint(bits) if *bits <= 16 => L::U16,
+        ABIType::Uint(bits) if *bits <= 32 => L::U32,
+        ABIType::Uint(bits) if *bits <= 64 => L::U64,
+        ABIType::Uint(bits) if *bits <= 128 => L::U128,
+        ABIType::Uint(_) => L::U256,
+        ABIType::Bool => L::Bool,
+        ABIType::Address | ABIType::FixedBytes(_) | ABIType::Bytes | ABIType::String => {
+            L::Vector(Box::new(L::U8))
+        },
+        ABIType::Array(item) | ABIType::FixedArray(item, _) => {
+            L::Vector(Box::new(abi_type_to_layout(item)))
+        },
+        ABIType::Tuple(items) => L::Struct(MoveStructLayout::new(
+            items.iter().map(abi_type_to_layout).collect(),
+        )),
+    }
+}
+
+/// The ABI type a Move value of `layout` is exposed as to the EVM.
+pub fn layout_to_abi_type(layout: &MoveTypeLayout) -> Result<ABIType> {
+    use MoveTypeLayout as L;
+    Ok(match layout {
+        L::U8 => ABIType::Uint(8),
+        L::U16 => ABIType::Uint(16),
+        L::U32 => ABIType::Uint(32),
+        L::U64 => ABIType::Uint(64),
+        L::U128 => ABIType::Uint(128),
+        L::U256 => ABIType::Uint(256),
+        L::Bool => ABIType::Bool,
+        L::Address => ABIType::FixedBytes(32),
+        L::Vector(item) if matches!(**item, L::U8) => ABIType::Bytes,
+        L::Vector(item) => ABIType::Array(Box::new(layout_to_abi_type(item)?)),
+        L::Struct(s) => ABIType::Tuple(
+            s.fields()
+                .iter()
+                .map(layout_to_abi_type)
+                .collect::<Result<_>>()?,
+        ),
+        L::Signer => bail!("signer has no ABI representation"),
+    })
+}
+
+pub fn move_value_to_abi(value: MoveValue, ty: &ABIType) -> Result<ABIValue> {
+    Ok(match (ty, value) {
+        (ABIType::Uint(_), MoveValue::U8(v)) => ABIValue::Uint(v.into()),
+        (ABIType::Uint(_), MoveValue::U16(v)) => ABIValue::Uint(v.into()),
+        (ABIType::Uint(_), MoveValue::U32(v)) => ABIValue::Uint(v.into()),
+        (ABIType::Uint(_), MoveValue::U64(v)) => ABIValue::Uint(v.into()),
+        (ABIType::Uint(_), MoveValue::U128(v)) => ABIValue::Uint(v.into()),
+        (ABIType::Uint(_), MoveValue::U256(v)) => {
+            ABIValue::Uint(U256::from_little_endian(&v.to_le_bytes()))
+        },
+        (ABIType::Bool, MoveValue::Bool(v)) => ABIValue::Bool(v),
+        (ABIType::FixedBytes(32), MoveValue::Address(v)) => ABIValue::FixedBytes(v.to_vec()),
+        (ABIType::Address, MoveValue::Vector(v)) => {
+            let bytes = into_bytes(v)?;
+            if bytes.len() != Address::len_bytes() {
+                bail!("EVM address must be 20 bytes");
+            }
+            ABIValue::Address(Address::from_slice(&bytes))
+        },
+        (ABIType::FixedBytes(_), MoveValue::Vector(v)) => ABIValue::FixedBytes(into_bytes(v)?),
+        (ABIType::Bytes, MoveValue::Vector(v)) => ABIValue::Bytes(into_bytes(v)?),
+        (ABIType::String, MoveValue::Vector(v)) => ABIValue::String(String::from_utf8(
+            into_bytes(v)?,
+        )?),
+        (ABIType::Array(item) | ABIType::FixedArray(item, _), MoveValue::Vector(v)) => {
+            ABIValue::Array(
+                v.into_iter()
+                    .map(|x| move_value_to_abi(x, item))
+                    .collect::<Result<_>>()?,
+            )
+        },
+        (ABIType::Tuple(items), MoveValue::Struct(s)) => {
+            let fields = s.into_fields();
+            if fields.len() != items.len() {
+                bail!("Incorrect number of tuple fields");
+            }
+            ABIValue::Tuple(
+                fields
+                    .into_iter()
+                    .zip(items)
+                    .map(|(x, ty)| move_value_to_abi(x, ty))
+                    .collect::<Result<_>>()?,
+            )
+        },
+        (ty, value) => bail!("Move value {} does not match ABI type {:?}", value, ty),
+    })
+}
+
+pub fn abi_to_move_value(value: ABIValue, layout: &MoveTypeLayout) -> Result<MoveValue> {
+    use MoveTypeLayout as L;
+    let uint_overflow = || anyhow::anyhow!("ABI integer overflows {}", layout);
+    Ok(match (layout, value) {
+        (L::U8, ABIValue::Uint(v)) => MoveValue::U8(v.try_into().map_err(|_| uint_overflow())?),
+        (L::U16, ABIValue::Uint(v)) => {
+            MoveValue::U16(v.try_into().map_err(|_| uint_overflow())?)
+        },
+        (L::U32, ABIValue::Uint(v)) => {
+            MoveValue::U32(v.try_into().map_err(|_| uint_overflow())?)
+        },
+        (L::U64, ABIValue::Uint(v)) => {
+            MoveValue::U64(v.try_into().map_err(|_| uint_overflow())?)
+        },
+        (L::U128, ABIValue::Uint(v)) => {
+            MoveValue::U128(v.try_into().map_err(|_| uint_overflow())?)
+        },
+        (L::U256, ABIValue::Uint(v)) => {
+            let mut bytes = [0u8; 32];
+            v.to_little_endian(&mut bytes);
+            MoveValue::U256(MoveU256::from_le_bytes(&bytes))
+        },
+        (L::Bool, ABIValue::Bool(v)) => MoveValue::Bool(v),
+        (L::Address, ABIValue::FixedBytes(v)) if v.len() == 32 => {
+            MoveValue::Address(AccountAddress::new(v.try_into().expect("length checked")))
+        },
+        (L::Vector(item), ABIValue::Bytes(v) | ABIValue::FixedBytes(v))
+            if matches!(**item, L::U8) =>
+        {
+            MoveValue::vector_u8(v)
+        },
+        (L::Vector(item), ABIValue::String(v)) if matches!(**item, L::U8) => {
+            MoveValue::vector_u8(v.into_bytes())
+        },
+        (L::Vector(item), ABIValue::Address(v)) if matches!(**item, L::U8) => {
+            MoveValue::vector_u8(v.as_bytes().to_vec())
+        },
+        (L::Vector(item), ABIValue::Array(v)) => MoveValue::Vector(
+            v.into_iter()
+                .map(|x| abi_to_move_value(x, item))
+                .collect::<Result<_>>()?,
+        ),
+        (L::Struct(s), ABIValue::Tuple(v)) => {
+            if s.fields().len() != v.len() {
+                bail!("Incorrect number of tuple fields");
+            }
+            MoveValue::Struct(MoveStruct::new(
+                v.into_iter()
+                    .zip(s.fields())
+                    .map(|(x, layout)| abi_to_move_value(x, layout))
+                    .collect::<Result<_>>()?,
+            ))
+        },
+        (layout, value) => bail!("ABI value {:?} does not match Move type {}", value, layout),
+    })
+}
+
+fn into_bytes(values: Vec<MoveValue>) -> Result<Vec<u8>> {
+    values
+        .into_iter()
+        .map(|x| match x {
+            MoveValue::U8(byte) => Ok(byte),
+            _ => bail!("Expect a byte"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solidity_abi::parse_type_list;
+
+    fn round_trip(ty: &ABIType, value: MoveValue) {
+        let layout = abi_type_to_layout(ty);
+        let abi_value = move_value_to_abi(value.clone(), ty).unwrap();
+        assert_eq!(abi_to_move_value(abi_value, &layout).unwrap(), value);
+    }
+
+    #[test]
+    fn test_uint_layouts() {
+        let types = parse_type_list("uint8,uint24,uint64,uint72,uint256").unwrap();
+        let layouts: Vec<_> = types.iter().map(abi_type_to_layout).collect();
+        assert_eq!(layouts, vec![
+            MoveTypeLayout::U8,
+            MoveTypeLayout::U32,
+            MoveTypeLayout::U64,
+            MoveTypeLayout::U128,
+            MoveTypeLayout::U256,
+        ]);
+
+        round_trip(&ABIType::Uint(64), MoveValue::U64(u64::MAX));
+        round_trip(&ABIType::Uint(256), MoveValue::U256(MoveU256::from_le_bytes(&[0xff; 32])));
+        assert!(abi_to_move_value(ABIValue::Uint(U256::from(256)), &MoveTypeLayout::U8).is_err());
+    }
+
+    #[test]
+    fn test_address_and_bytes() {
+        let address = Address::repeat_byte(0x11);
+        let value = MoveValue::vector_u8(address.as_bytes().to_vec());
+        assert_eq!(
+            move_value_to_abi(value.clone(), &ABIType::Address).unwrap(),
+            ABIValue::Address(address)
+        );
+        round_trip(&ABIType::Address, value);
+        assert!(move_value_to_abi(MoveValue::vector_u8(vec![1; 19]), &ABIType::Address).is_err());
+
+        round_trip(&ABIType::Bytes, MoveValue::vector_u8(vec![1, 2, 3]));
+        round_trip(&ABIType::String, MoveValue::vector_u8(b"abc".to_vec()));
+        assert!(move_value_to_abi(MoveValue::vector_u8(vec![0xff]), &ABIType::String).is_err());
+
+        let move_address = AccountAddress::new([0x22; 32]);
+        let ty = layout_to_abi_type(&MoveTypeLayout::Address).unwrap();
+        let abi_value = move_value_to_abi(MoveValue::Address(move_address), &ty).unwrap();
+        assert_eq!(
+            abi_to_move_value(abi_value, &MoveTypeLayout::Address).unwrap(),
+            MoveValue::Address(move_address)
+        );
+    }
+
+    #[test]
+    fn test_arrays_and_tuples() {
+        let ty = ABIType::parse("(uint64,bool[])[]").unwrap();
+        let value = MoveValue::Vector(vec![MoveValue::Struct(MoveStruct::new(vec![
+            MoveValue::U64(7),
+            MoveValue::Vector(vec![MoveValue::Bool(true), MoveValue::Bool(false)]),
+        ]))]);
+        round_trip(&ty, value);
+        assert_eq!(
+            layout_to_abi_type(&abi_type_to_layout(&ty)).unwrap(),
+            ABIType::parse("(uint64,bool[])[]").unwrap()
+        );
+
+        let tuple = ABIType::parse("(uint64,bool)").unwrap();
+        let value = MoveValue::Struct(MoveStruct::new(vec![MoveValue::U64(7)]));
+        assert!(move_value_to_abi(value, &tuple).is_err());
+        assert!(layout_to_abi_type(&MoveTypeLayout::Signer).is_err());
+    }
+}
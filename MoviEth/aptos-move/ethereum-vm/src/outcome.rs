@@ -102,6 +102,7 @@ impl Into<ConvertedOutcome> for &TxDropError {
             TxDropError::NotEnoughBaseGas { .. } => {
                 MAX_GAS_UNITS_BELOW_MIN_TRANSACTION_GAS_UNITS.into()
             },
+            TxDropError::GasPriceBelowBaseFee { .. } => GAS_UNIT_PRICE_BELOW_MIN_BOUND.into(),
         }
     }
 }
@@ -160,6 +161,13 @@ impl Into<ConvertedOutcome> for &VmError {
                     description: msg.into(),
                 }),
             },
+            VmError::InternalContractReverted(data) => ConvertedOutcome {
+                status: VMStatus::MoveAbort(Script, REVERT_CODE),
+                reason: Some(AbortInfo {
+                    reason_name: REVERT_DESC.into(),
+                    description: revert_reason_decode(data),
+                }),
+            },
             VmError::Reverted => ConvertedOutcome {
                 status: VMStatus::MoveAbort(Script, REVERT_CODE),
                 reason: Some(AbortInfo {
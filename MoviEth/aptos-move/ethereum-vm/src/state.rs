@@ -2,10 +2,13 @@ pub use crate::storage_key::StorageKey;
 use aptos_state_view::StateView;
 use aptos_types::{state_store::state_key::StateKey, write_set::WriteOp};
 use cfx_evm::{CallMoveVMTrait, State, StateDb, StorageTrait};
-use cfx_storage::Result;
+use cfx_storage::{Error, Result};
 use cfx_types::H256;
 use std::collections::HashMap;
 
+/// The storage of the EVM state over an Aptos state view. The writes are buffered in `cache` until
+/// `drain`, and the reads go to `inner`, which under Block-STM is the multi-version view of the
+/// transaction, so the EVM accounts, code and storage slots are versioned like Move resources.
 pub struct ViewWrapper<'a, S: StateView> {
     pub inner: &'a S,
     pub cache: HashMap<StorageKey, Option<Box<[u8]>>>,
@@ -21,11 +24,12 @@ impl<'a, S: StateView> StorageTrait for &mut ViewWrapper<'a, S> {
             },
             None => {},
         };
-        Ok(self
+        // A speculative read can fail, in which case Block-STM re-executes the transaction.
+        let value = self
             .inner
             .get_state_value(&key.into())
-            .unwrap()
-            .map(|value| value.into_boxed_slice()))
+            .map_err(|e| Error::from(format!("Cannot read the EVM state: {}", e)))?;
+        Ok(value.map(|value| value.into_boxed_slice()))
     }
 
     fn set(&mut self, access_key: StorageKey, value: Box<[u8]>) -> Result<()> {
@@ -49,16 +53,16 @@ impl<'a, S: StateView> ViewWrapper<'a, S> {
             .drain()
             .filter_map(|(key, value)| {
                 let state_key: StateKey = key.into();
-                let has_old_value = self
-                    .inner
-                    .get_state_value(&state_key)
-                    .unwrap_or(None)
-                    .is_some();
-                match (has_old_value, value) {
-                    (true, Some(v)) => Some(WriteOp::Modification(v.to_vec())),
-                    (true, None) => Some(WriteOp::Deletion),
-                    (false, Some(v)) => Some(WriteOp::Creation(v.to_vec())),
-                    (false, None) => None,
+                let old_value = self.inner.get_state_value(&state_key).unwrap_or(None);
+                match (old_value, value) {
+                    // The EVM state commits some values even when they are unchanged, e.g. the
+                    // total issued tokens. Writing them would make every EVM transaction of a
+                    // block conflict with the others under Block-STM.
+                    (Some(old), Some(v)) if old[..] == v[..] => None,
+                    (Some(_), Some(v)) => Some(WriteOp::Modification(v.to_vec())),
+                    (Some(_), None) => Some(WriteOp::Deletion),
+                    (None, Some(v)) => Some(WriteOp::Creation(v.to_vec())),
+                    (None, None) => None,
                 }
                 .map(|op| (state_key, op))
             })
@@ -79,7 +83,7 @@ impl<'a> EvmState<'a> {
 
     pub fn new_with_move_vm<S: StateView>(
         view_wrapper: &'a mut ViewWrapper<S>,
-        call_move_handler: &'a mut dyn CallMoveVMTrait,
+        call_move_handler: &'a dyn CallMoveVMTrait,
     ) -> Self {
         let state_db = StateDb::new(view_wrapper);
         let state = State::new_with_move_vm(state_db, call_move_handler).unwrap();
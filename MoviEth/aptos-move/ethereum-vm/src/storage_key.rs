@@ -1,23 +1,80 @@
 use aptos_types::{
     access_path::{AccessPath, EvmPath, Path},
     state_store::state_key::StateKey as AptosStateKey,
-    transaction::eth_address_to_aptos_address,
+    transaction::{aptos_address_to_eth_address, eth_address_to_aptos_address},
 };
 use cfx_primitives::OwnedStateKey;
-use cfx_types::Address;
-#[derive(PartialEq, Eq, Hash)]
+use cfx_types::{Address, H256};
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct StorageKey {
     address: Address,
     path: EvmPath,
 }
 
 impl StorageKey {
+    pub fn account_key(address: Address) -> StorageKey {
+        StorageKey {
+            address,
+            path: EvmPath::Account,
+        }
+    }
+
+    /// The key of the storage slot `slot` of the contract `address`.
+    pub fn storage_key(address: Address, slot: &H256) -> StorageKey {
+        StorageKey {
+            address,
+            path: EvmPath::Storage(slot.as_bytes().to_vec()),
+        }
+    }
+
     pub fn event_nonce_key(address: Address) -> StorageKey {
         StorageKey {
             address,
             path: EvmPath::EventNonce,
         }
     }
+
+    pub fn topic_event_nonce_key(address: Address, topic0: &H256) -> StorageKey {
+        StorageKey {
+            address,
+            path: EvmPath::TopicEventNonce(topic0.as_bytes().to_vec()),
+        }
+    }
+
+    pub fn creation_event_nonce_key(address: Address) -> StorageKey {
+        StorageKey {
+            address,
+            path: EvmPath::CreationEventNonce,
+        }
+    }
+
+    /// Converts an Aptos state key back, or returns `None` if it is not a key of the EVM state.
+    pub fn from_state_key(key: &AptosStateKey) -> Option<StorageKey> {
+        match key {
+            AptosStateKey::AccessPath(access_path) => match bcs::from_bytes(&access_path.path) {
+                Ok(Path::Evm(path)) => Some(StorageKey {
+                    address: aptos_address_to_eth_address(&access_path.address),
+                    path,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// The storage slot of a contract storage key.
+    pub fn storage_slot(&self) -> Option<H256> {
+        match &self.path {
+            EvmPath::Storage(slot) if slot.len() == H256::len_bytes() => {
+                Some(H256::from_slice(slot))
+            },
+            _ => None,
+        }
+    }
 }
 
 impl From<OwnedStateKey> for StorageKey {
@@ -64,3 +121,28 @@ impl From<StorageKey> for AptosStateKey {
         AptosStateKey::AccessPath(key.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::access_path::AccessPath;
+    use move_core_types::account_address::AccountAddress;
+
+    #[test]
+    fn test_from_state_key() {
+        let address = Address::repeat_byte(0x11);
+        let slot = H256::repeat_byte(0x22);
+        let key = StorageKey::storage_key(address, &slot);
+        let state_key: AptosStateKey = key.clone().into();
+        let parsed = StorageKey::from_state_key(&state_key).unwrap();
+        assert!(parsed == key);
+        assert_eq!(parsed.address(), address);
+        assert_eq!(parsed.storage_slot(), Some(slot));
+
+        let account_key = StorageKey::from_state_key(&StorageKey::account_key(address).into());
+        assert_eq!(account_key.unwrap().storage_slot(), None);
+
+        let move_key = AptosStateKey::AccessPath(AccessPath::new(AccountAddress::ONE, vec![0]));
+        assert!(StorageKey::from_state_key(&move_key).is_none());
+    }
+}
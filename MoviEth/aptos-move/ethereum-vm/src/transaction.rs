@@ -2,7 +2,7 @@ use aptos_types::transaction::{
     aptos_address_to_eth_address, EthTransactionPayload, SignedTransaction, TransactionPayload,
 };
 use cfx_evm::TransactionInfo;
-use cfx_primitives::Action;
+use cfx_primitives::{AccessList, Action};
 use cfx_types::{AddressSpaceUtil, AddressWithSpace, U256};
 use std::borrow::Cow;
 use Cow::{Borrowed, Owned};
@@ -58,4 +58,16 @@ impl<'a> TransactionInfo for EvmTransaction<'a> {
     fn value(&self) -> Cow<U256> {
         Borrowed(&self.payload.value)
     }
+
+    fn max_priority_fee_per_gas(&self) -> Option<Cow<U256>> {
+        self.payload
+            .max_priority_fee_per_gas()
+            .map(|fee| Owned(U256::from(fee)))
+    }
+
+    fn access_list(&self) -> Option<Cow<AccessList>> {
+        self.payload
+            .access_list()
+            .map(|access_list| Owned(access_list.iter().map(Into::into).collect()))
+    }
 }
@@ -14,8 +14,11 @@ module aptos_framework::account {
     use aptos_std::from_bcs;
     use aptos_std::multi_ed25519;
 
+    friend aptos_framework::account_link;
     friend aptos_framework::aptos_account;
     friend aptos_framework::coin;
+    friend aptos_framework::coin_bridge;
+    friend aptos_framework::evm_block;
     friend aptos_framework::genesis;
     friend aptos_framework::resource_account;
     friend aptos_framework::transaction_validation;
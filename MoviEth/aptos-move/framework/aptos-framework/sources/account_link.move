@@ -0,0 +1,288 @@
+/// Links a Move account and an EVM address, so that whoever holds both keys acts as one identity in both VMs.
+///
+/// A link is proven by both sides: either by a transaction of the Move account carrying a secp256k1 signature of
+/// the EVM key, or by a call from the EVM address carrying an Ed25519 signature of the Move account's key. Both keys
+/// sign the same message, `link_message`, which the EVM key signs as an Ethereum signed message (`personal_sign`).
+/// Either side can unlink on its own.
+///
+/// Without a link, the Move account of an EVM address is its default account: the EVM address followed by 12 zero
+/// bytes, which is also the sender of its Ethereum transactions. With a link, the Move functions called from the EVM
+/// translate their EVM caller with `move_account_of`, and `cross_vm::call_evm_typed_as` calls the EVM with the
+/// linked EVM address as `msg.sender`.
+module aptos_framework::account_link {
+    use std::bcs;
+    use std::error;
+    use std::option::{Self, Option};
+    use std::signer;
+    use std::vector;
+    use aptos_std::aptos_hash;
+    use aptos_std::ed25519;
+    use aptos_std::from_bcs;
+    use aptos_std::secp256k1;
+    use aptos_framework::account;
+    use aptos_framework::chain_id;
+
+    /// An EVM address must be 20 bytes long.
+    const EINVALID_EVM_ADDRESS: u64 = 1;
+    /// The Move account is linked already.
+    const EACCOUNT_ALREADY_LINKED: u64 = 2;
+    /// The EVM address is linked already.
+    const EEVM_ADDRESS_ALREADY_LINKED: u64 = 3;
+    /// The secp256k1 signature is not one of the EVM address over the link message.
+    const EINVALID_EVM_SIGNATURE: u64 = 4;
+    /// The Ed25519 public key is not the one of the Move account, or its signature of the link message is invalid.
+    const EINVALID_MOVE_SIGNATURE: u64 = 5;
+    /// The account or EVM address is not linked.
+    const ENOT_LINKED: u64 = 6;
+
+    /// Prefix of the link message, so that a link proof cannot be mistaken for another signed message.
+    const LINK_MESSAGE_PREFIX: vector<u8> = b"MoviEth account link:";
+    /// Prefix of the messages signed with `personal_sign`, followed by the decimal message length.
+    const ETHEREUM_MESSAGE_PREFIX: vector<u8> = b"\x19Ethereum Signed Message:\n";
+
+    /// What both keys sign to link `account` and `evm_address`.
+    struct LinkProof has drop {
+        chain_id: u8,
+        account: address,
+        evm_address: vector<u8>,
+        nonce: u64,
+    }
+
+    /// Stored at a Move account which has been linked once. `evm_address` is empty while the account is not linked,
+    /// and `nonce` counts the links and unlinks of the account, so that a link proof cannot be replayed.
+    struct EvmLink has key {
+        evm_address: vector<u8>,
+        nonce: u64,
+    }
+
+    /// Stored at the default account of a linked EVM address.
+    struct MoveLink has key {
+        account: address,
+    }
+
+    #[view]
+    /// The default Move account of `evm_address`, i.e. the address followed by 12 zero bytes. Should be in-sync with
+    /// `eth_address_to_aptos_address`.
+    public fun default_account(evm_address: vector<u8>): address {
+        assert!(vector::length(&evm_address) == 20, error::invalid_argument(EINVALID_EVM_ADDRESS));
+        let i = 0;
+        while (i < 12) {
+            vector::push_back(&mut evm_address, 0);
+            i = i + 1;
+        };
+        from_bcs::to_address(evm_address)
+    }
+
+    #[view]
+    /// The Move account acting for `evm_address`: the linked account if any, or else its default account.
+    public fun move_account_of(evm_address: vector<u8>): address acquires MoveLink {
+        let default = default_account(evm_address);
+        if (exists<MoveLink>(default)) {
+            borrow_global<MoveLink>(default).account
+        } else {
+            default
+        }
+    }
+
+    #[view]
+    public fun linked_account(evm_address: vector<u8>): Option<address> acquires MoveLink {
+        let default = default_account(evm_address);
+        if (exists<MoveLink>(default)) {
+            option::some(borrow_global<MoveLink>(default).account)
+        } else {
+            option::none()
+        }
+    }
+
+    #[view]
+    public fun linked_evm_address(account: address): Option<vector<u8>> acquires EvmLink {
+        if (exists<EvmLink>(account)) {
+            let evm_address = borrow_global<EvmLink>(account).evm_address;
+            if (!vector::is_empty(&evm_address)) {
+                return option::some(evm_address)
+            }
+        };
+        option::none()
+    }
+
+    #[view]
+    /// The message both keys sign to link `account` and `evm_address` next.
+    public fun link_message(account: address, evm_address: vector<u8>): vector<u8> acquires EvmLink {
+        let nonce = if (exists<EvmLink>(account)) {
+            borrow_global<EvmLink>(account).nonce
+        } else {
+            0
+        };
+        let message = LINK_MESSAGE_PREFIX;
+        vector::append(&mut message, bcs::to_bytes(&LinkProof {
+            chain_id: chain_id::get(),
+            account,
+            evm_address,
+            nonce,
+        }));
+        message
+    }
+
+    /// Links the signer to `evm_address`. `signature` is the 65 bytes `r || s || v` signature of the link message
+    /// by the EVM key, as returned by `personal_sign`.
+    public entry fun link_evm_address(
+        account: &signer,
+        evm_address: vector<u8>,
+        signature: vector<u8>,
+    ) acquires EvmLink {
+        let account = signer::address_of(account);
+        let message = link_message(account, evm_address);
+        assert!(
+            recover_evm_address(message, signature) == option::some(evm_address),
+            error::invalid_argument(EINVALID_EVM_SIGNATURE),
+        );
+        link(account, evm_address);
+    }
+
+    #[cross_vm(link_move_account)]
+    /// Links the EVM caller to `account`. `signature` is the Ed25519 signature of the link message by the key of
+    /// `account`, whose `public_key` must match the authentication key of the account.
+    fun ihe_link_move_account(
+        caller: vector<u8>,
+        account: address,
+        public_key: vector<u8>,
+        signature: vector<u8>,
+    ) acquires EvmLink {
+        let message = link_message(account, caller);
+        let public_key = ed25519::new_unvalidated_public_key_from_bytes(public_key);
+        assert!(
+            ed25519::unvalidated_public_key_to_authentication_key(&public_key) ==
+                account::get_authentication_key(account),
+            error::invalid_argument(EINVALID_MOVE_SIGNATURE),
+        );
+        assert!(
+            ed25519::signature_verify_strict(&ed25519::new_signature_from_bytes(signature), &public_key, message),
+            error::invalid_argument(EINVALID_MOVE_SIGNATURE),
+        );
+        link(account, caller);
+    }
+
+    public entry fun unlink(account: &signer) acquires EvmLink, MoveLink {
+        let account = signer::address_of(account);
+        let evm_address = linked_evm_address(account);
+        assert!(option::is_some(&evm_address), error::not_found(ENOT_LINKED));
+        remove_link(account, option::destroy_some(evm_address));
+    }
+
+    #[cross_vm(unlink)]
+    /// Unlinks the EVM caller.
+    fun ihe_unlink(caller: vector<u8>) acquires EvmLink, MoveLink {
+        let account = linked_account(caller);
+        assert!(option::is_some(&account), error::not_found(ENOT_LINKED));
+        remove_link(option::destroy_some(account), caller);
+    }
+
+    #[cross_vm(move_account_of)]
+    /// The Move account acting for `evm_address`, for EVM contracts.
+    fun ihe_move_account_of(_caller: vector<u8>, evm_address: vector<u8>): address acquires MoveLink {
+        move_account_of(evm_address)
+    }
+
+    #[cross_vm(linked_evm_address)]
+    /// The EVM address linked to `account`, or an empty address if it is not linked.
+    fun ihe_linked_evm_address(_caller: vector<u8>, account: address): vector<u8> acquires EvmLink {
+        option::destroy_with_default(linked_evm_address(account), vector::empty())
+    }
+
+    fun link(account: address, evm_address: vector<u8>) acquires EvmLink {
+        let default = default_account(evm_address);
+        assert!(!exists<MoveLink>(default), error::already_exists(EEVM_ADDRESS_ALREADY_LINKED));
+        assert!(option::is_none(&linked_evm_address(account)), error::already_exists(EACCOUNT_ALREADY_LINKED));
+
+        if (exists<EvmLink>(account)) {
+            let link = borrow_global_mut<EvmLink>(account);
+            link.evm_address = evm_address;
+            link.nonce = link.nonce + 1;
+        } else {
+            move_to(&account::create_signer(account), EvmLink { evm_address, nonce: 1 });
+        };
+        move_to(&account::create_signer(default), MoveLink { account });
+    }
+
+    fun remove_link(account: address, evm_address: vector<u8>) acquires EvmLink, MoveLink {
+        let link = borrow_global_mut<EvmLink>(account);
+        link.evm_address = vector::empty();
+        link.nonce = link.nonce + 1;
+        let MoveLink { account: _ } = move_from<MoveLink>(default_account(evm_address));
+    }
+
+    /// The EVM address which signed `message` with `personal_sign`, if `signature` is valid.
+    fun recover_evm_address(message: vector<u8>, signature: vector<u8>): Option<vector<u8>> {
+        if (vector::length(&signature) != 65) {
+            return option::none()
+        };
+        let v = vector::pop_back(&mut signature);
+        let recovery_id = if (v >= 27) { v - 27 } else { v };
+        if (recovery_id > 1) {
+            return option::none()
+        };
+
+        let digest = ETHEREUM_MESSAGE_PREFIX;
+        vector::append(&mut digest, to_decimal(vector::length(&message)));
+        vector::append(&mut digest, message);
+        let digest = aptos_hash::keccak256(digest);
+
+        let public_key = secp256k1::ecdsa_recover(
+            digest,
+            recovery_id,
+            &secp256k1::ecdsa_signature_from_bytes(signature),
+        );
+        if (option::is_none(&public_key)) {
+            return option::none()
+        };
+        let public_key = secp256k1::ecdsa_raw_public_key_to_bytes(&option::destroy_some(public_key));
+        let hash = aptos_hash::keccak256(public_key);
+        let evm_address = vector::empty();
+        let i = 12;
+        while (i < 32) {
+            vector::push_back(&mut evm_address, *vector::borrow(&hash, i));
+            i = i + 1;
+        };
+        option::some(evm_address)
+    }
+
+    fun to_decimal(n: u64): vector<u8> {
+        let digits = vector[((48 + n % 10) as u8)];
+        n = n / 10;
+        while (n > 0) {
+            vector::push_back(&mut digits, ((48 + n % 10) as u8));
+            n = n / 10;
+        };
+        vector::reverse(&mut digits);
+        digits
+    }
+
+    #[test_only]
+    /// Links `account` and `evm_address` without the proofs of their keys.
+    public fun link_for_test(account: address, evm_address: vector<u8>) acquires EvmLink {
+        link(account, evm_address);
+    }
+
+    #[test]
+    fun test_default_account() {
+        assert!(
+            default_account(x"00112233445566778899aabbccddeeff00112233") ==
+                @0x00112233445566778899aabbccddeeff00112233000000000000000000000000,
+            0,
+        );
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0x10001, location = Self)]
+    fun test_default_account_invalid_length() {
+        default_account(x"0011");
+    }
+
+    #[test]
+    fun test_to_decimal() {
+        assert!(to_decimal(0) == b"0", 0);
+        assert!(to_decimal(7) == b"7", 0);
+        assert!(to_decimal(95) == b"95", 0);
+        assert!(to_decimal(1024) == b"1024", 0);
+    }
+}
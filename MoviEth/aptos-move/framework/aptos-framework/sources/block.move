@@ -7,6 +7,7 @@ module aptos_framework::block {
 
     use aptos_framework::account;
     use aptos_framework::event::{Self, EventHandle};
+    use aptos_framework::evm_block;
     use aptos_framework::reconfiguration;
     use aptos_framework::stake;
     use aptos_framework::state_storage;
@@ -135,6 +136,7 @@ module aptos_framework::block {
             time_microseconds: timestamp,
         };
         emit_new_block_event(&vm, &mut block_metadata_ref.new_block_events, new_block_event);
+        evm_block::on_new_block(hash, proposer);
 
         if (features::collect_and_distribute_gas_fees()) {
             // Assign the fees collected from the previous block to the previous block proposer.
@@ -0,0 +1,519 @@
+/// This module makes a token usable from both VMs without a second copy of its liquidity.
+///
+/// A registered `Coin<CoinType>` gets an ERC-20 facade in the EVM, at an address derived from the coin type. The
+/// facade reads and moves the balances of the `CoinStore<CoinType>`s directly, an EVM account holding the coins of
+/// its Move account, i.e. the account linked to it or else its default account (see `account_link`).
+///
+/// An ERC-20 bound to a marker type is wrapped into `Coin<Wrapped<Marker>>`: the coin bridge contract of the EVM
+/// locks the tokens and mints the wrapped coin, and unlocks them when the wrapped coin is burned.
+module aptos_framework::coin_bridge {
+    use std::bcs;
+    use std::error;
+    use std::option;
+    use std::signer;
+    use std::string::{Self, String};
+    use std::vector;
+    use aptos_std::aptos_hash;
+    use aptos_std::table::{Self, Table};
+    use aptos_std::type_info;
+    use aptos_framework::account;
+    use aptos_framework::account_link;
+    use aptos_framework::aptos_account;
+    use aptos_framework::coin::{Self, BurnCapability, Coin, MintCapability};
+    use aptos_framework::cross_vm::{Self, CallEvmCap};
+    use aptos_framework::system_addresses;
+
+    friend aptos_framework::genesis;
+
+    /// The coin type has not been initialized.
+    const ECOIN_NOT_INITIALIZED: u64 = 1;
+    /// No coin is registered for the facade address.
+    const EFACADE_NOT_REGISTERED: u64 = 2;
+    /// Only the facade of the coin can move it on behalf of EVM accounts.
+    const ENOT_FACADE: u64 = 3;
+    /// The spender is not allowed to transfer that much from the owner.
+    const EINSUFFICIENT_ALLOWANCE: u64 = 4;
+    /// An EVM address must be 20 bytes long.
+    const EINVALID_EVM_ADDRESS: u64 = 5;
+    /// The marker type must be declared by the signer.
+    const EMARKER_ADDRESS_MISMATCH: u64 = 6;
+    /// The ERC-20 is wrapped already.
+    const EERC20_ALREADY_WRAPPED: u64 = 7;
+    /// The ERC-20 is not wrapped, or not by this marker type.
+    const EERC20_NOT_WRAPPED: u64 = 8;
+    /// Only the coin bridge contract of the EVM can mint and burn wrapped coins on behalf of EVM accounts.
+    const ENOT_COIN_BRIDGE: u64 = 9;
+
+    /// The first 8 bytes of the facade addresses. Should be in-sync with `COIN_FACADE_BASE_ADDRESS` in cfx-evm.
+    const FACADE_ADDRESS_PREFIX: vector<u8> = x"0889000000000000";
+    /// Address of the coin bridge contract of the EVM. Should be in-sync with `COIN_BRIDGE_CONTRACT_ADDRESS` in
+    /// cfx-evm.
+    const COIN_BRIDGE_ADDRESS: vector<u8> = x"0888000000000000000000000000000000000003";
+    /// An allowance which `transferFrom` does not decrease, the `uint256` maximum of the EVM.
+    const UNLIMITED_ALLOWANCE: u64 = 18446744073709551615;
+
+    /// The EVM calls of this module. The coin bridge contract only releases locked tokens for calls of this type.
+    struct BridgeCall {}
+
+    /// A coin wrapping the ERC-20 bound to `Marker`.
+    struct Wrapped<phantom Marker> {}
+
+    struct CoinBridge has key {
+        /// Type names of the registered coins, by facade address.
+        facades: Table<vector<u8>, String>,
+        /// Allowances of the facades, by facade address, owner and spender.
+        allowances: Table<vector<u8>, u64>,
+        /// Type names of the markers, by the address of the ERC-20 they wrap.
+        markers: Table<vector<u8>, String>,
+        call_cap: CallEvmCap<BridgeCall>,
+    }
+
+    struct WrappedErc20<phantom Marker> has key {
+        erc20: vector<u8>,
+        mint_cap: MintCapability<Wrapped<Marker>>,
+        burn_cap: BurnCapability<Wrapped<Marker>>,
+    }
+
+    /// Only called during genesis.
+    public(friend) fun initialize(aptos_framework: &signer) {
+        system_addresses::assert_aptos_framework(aptos_framework);
+        move_to(aptos_framework, CoinBridge {
+            facades: table::new(),
+            allowances: table::new(),
+            markers: table::new(),
+            call_cap: cross_vm::initialize_cap<BridgeCall>(aptos_framework),
+        });
+    }
+
+    /// Registers the ERC-20 facade of `CoinType`. Any initialized coin can be registered, by anyone.
+    public entry fun register_coin<CoinType>() acquires CoinBridge {
+        assert!(coin::is_coin_initialized<CoinType>(), error::not_found(ECOIN_NOT_INITIALIZED));
+        let type_name = type_info::type_name<CoinType>();
+        let facade = facade_address_of(&type_name);
+        let bridge = borrow_global_mut<CoinBridge>(@aptos_framework);
+        if (!table::contains(&bridge.facades, facade)) {
+            table::add(&mut bridge.facades, facade, type_name);
+        }
+    }
+
+    #[view]
+    /// The EVM address of the ERC-20 facade of `CoinType`.
+    public fun facade_address<CoinType>(): vector<u8> {
+        facade_address_of(&type_info::type_name<CoinType>())
+    }
+
+    #[view]
+    public fun is_registered<CoinType>(): bool acquires CoinBridge {
+        let bridge = borrow_global<CoinBridge>(@aptos_framework);
+        table::contains(&bridge.facades, facade_address<CoinType>())
+    }
+
+    /// The facade address is `FACADE_ADDRESS_PREFIX` followed by the first 12 bytes of the Keccak-256 hash of the
+    /// coin type name.
+    fun facade_address_of(type_name: &String): vector<u8> {
+        let hash = aptos_hash::keccak256(*string::bytes(type_name));
+        let address = FACADE_ADDRESS_PREFIX;
+        let i = 0;
+        while (i < 12) {
+            vector::push_back(&mut address, *vector::borrow(&hash, i));
+            i = i + 1;
+        };
+        address
+    }
+
+    fun assert_facade<CoinType>(caller: &vector<u8>) {
+        assert!(*caller == facade_address<CoinType>(), error::permission_denied(ENOT_FACADE));
+    }
+
+    fun allowance_key(facade: vector<u8>, owner: vector<u8>, spender: vector<u8>): vector<u8> {
+        vector::append(&mut facade, owner);
+        vector::append(&mut facade, spender);
+        facade
+    }
+
+    #[cross_vm(facade_coin)]
+    /// The type name of the coin of a facade. Called by the facades.
+    fun ihe_facade_coin(_caller: vector<u8>, facade: vector<u8>): vector<u8> acquires CoinBridge {
+        let bridge = borrow_global<CoinBridge>(@aptos_framework);
+        assert!(table::contains(&bridge.facades, facade), error::not_found(EFACADE_NOT_REGISTERED));
+        *string::bytes(table::borrow(&bridge.facades, facade))
+    }
+
+    #[cross_vm(name)]
+    fun ihe_name<CoinType>(_caller: vector<u8>): vector<u8> {
+        *string::bytes(&coin::name<CoinType>())
+    }
+
+    #[cross_vm(symbol)]
+    fun ihe_symbol<CoinType>(_caller: vector<u8>): vector<u8> {
+        *string::bytes(&coin::symbol<CoinType>())
+    }
+
+    #[cross_vm(decimals)]
+    fun ihe_decimals<CoinType>(_caller: vector<u8>): u8 {
+        coin::decimals<CoinType>()
+    }
+
+    #[cross_vm(total_supply)]
+    /// The supply of a coin which does not track it is reported as 0.
+    fun ihe_total_supply<CoinType>(_caller: vector<u8>): u128 {
+        option::destroy_with_default(coin::supply<CoinType>(), 0)
+    }
+
+    #[cross_vm(balance_of)]
+    fun ihe_balance_of<CoinType>(_caller: vector<u8>, owner: vector<u8>): u64 {
+        let owner = account_link::move_account_of(owner);
+        if (coin::is_account_registered<CoinType>(owner)) {
+            coin::balance<CoinType>(owner)
+        } else {
+            0
+        }
+    }
+
+    #[cross_vm(allowance)]
+    fun ihe_allowance<CoinType>(
+        _caller: vector<u8>,
+        owner: vector<u8>,
+        spender: vector<u8>,
+    ): u64 acquires CoinBridge {
+        let bridge = borrow_global<CoinBridge>(@aptos_framework);
+        let key = allowance_key(facade_address<CoinType>(), owner, spender);
+        if (table::contains(&bridge.allowances, key)) {
+            *table::borrow(&bridge.allowances, key)
+        } else {
+            0
+        }
+    }
+
+    #[cross_vm(transfer)]
+    fun ihe_transfer<CoinType>(caller: vector<u8>, from: vector<u8>, to: vector<u8>, amount: u64) {
+        assert_facade<CoinType>(&caller);
+        let from = account::create_signer(account_link::move_account_of(from));
+        let coins = coin::withdraw<CoinType>(&from, amount);
+        aptos_account::deposit_coins(account_link::move_account_of(to), coins);
+    }
+
+    #[cross_vm(approve)]
+    fun ihe_approve<CoinType>(
+        caller: vector<u8>,
+        owner: vector<u8>,
+        spender: vector<u8>,
+        amount: u64,
+    ) acquires CoinBridge {
+        assert_facade<CoinType>(&caller);
+        let bridge = borrow_global_mut<CoinBridge>(@aptos_framework);
+        table::upsert(&mut bridge.allowances, allowance_key(caller, owner, spender), amount);
+    }
+
+    #[cross_vm(transfer_from)]
+    fun ihe_transfer_from<CoinType>(
+        caller: vector<u8>,
+        spender: vector<u8>,
+        from: vector<u8>,
+        to: vector<u8>,
+        amount: u64,
+    ) acquires CoinBridge {
+        assert_facade<CoinType>(&caller);
+        let bridge = borrow_global_mut<CoinBridge>(@aptos_framework);
+        let allowance = table::borrow_mut_with_default(
+            &mut bridge.allowances,
+            allowance_key(caller, from, spender),
+            0,
+        );
+        assert!(*allowance >= amount, error::permission_denied(EINSUFFICIENT_ALLOWANCE));
+        if (*allowance != UNLIMITED_ALLOWANCE) {
+            *allowance = *allowance - amount;
+        };
+        ihe_transfer<CoinType>(caller, from, to, amount);
+    }
+
+    /// Binds `erc20` to `Marker`, and initializes `Coin<Wrapped<Marker>>` with the given name, symbol and decimals,
+    /// which should be those of the ERC-20 since the wrapped coin has the same amounts. An ERC-20 can be bound only
+    /// once, by the account declaring the marker type.
+    public fun register_erc20<Marker>(
+        account: &signer,
+        erc20: vector<u8>,
+        name: String,
+        symbol: String,
+        decimals: u8,
+    ) acquires CoinBridge {
+        assert!(
+            type_info::account_address(&type_info::type_of<Marker>()) == signer::address_of(account),
+            error::invalid_argument(EMARKER_ADDRESS_MISMATCH),
+        );
+        assert!(vector::length(&erc20) == 20, error::invalid_argument(EINVALID_EVM_ADDRESS));
+        let bridge = borrow_global_mut<CoinBridge>(@aptos_framework);
+        assert!(!table::contains(&bridge.markers, erc20), error::already_exists(EERC20_ALREADY_WRAPPED));
+        table::add(&mut bridge.markers, erc20, type_info::type_name<Marker>());
+
+        // The coin info of `Wrapped<Marker>` lives at the address of the `Wrapped` type.
+        let aptos_framework = account::create_signer(@aptos_framework);
+        let (burn_cap, freeze_cap, mint_cap) = coin::initialize<Wrapped<Marker>>(
+            &aptos_framework,
+            name,
+            symbol,
+            decimals,
+            true,
+        );
+        coin::destroy_freeze_cap(freeze_cap);
+        move_to(&aptos_framework, WrappedErc20<Marker> { erc20, mint_cap, burn_cap });
+    }
+
+    #[view]
+    /// The address of the ERC-20 wrapped by `Coin<Wrapped<Marker>>`.
+    public fun wrapped_erc20<Marker>(): vector<u8> acquires WrappedErc20 {
+        borrow_global<WrappedErc20<Marker>>(@aptos_framework).erc20
+    }
+
+    /// Burns `coins`, and sends as many of the wrapped ERC-20 tokens to the EVM account `to`. Cannot be called from
+    /// Move code which is itself called from the EVM, use `unwrap` of the coin bridge contract there instead.
+    public fun unwrap<Marker>(coins: Coin<Wrapped<Marker>>, to: vector<u8>) acquires CoinBridge, WrappedErc20 {
+        assert!(vector::length(&to) == 20, error::invalid_argument(EINVALID_EVM_ADDRESS));
+        let wrapped = borrow_global<WrappedErc20<Marker>>(@aptos_framework);
+        let amount = coin::value(&coins);
+        coin::burn(coins, &wrapped.burn_cap);
+        let params = vector[bcs::to_bytes(&wrapped.erc20), bcs::to_bytes(&to), bcs::to_bytes(&amount)];
+        let bridge = borrow_global<CoinBridge>(@aptos_framework);
+        cross_vm::call_evm(option::none(), COIN_BRIDGE_ADDRESS, string::utf8(b"release"), params, &bridge.call_cap);
+    }
+
+    public entry fun unwrap_to_evm<Marker>(
+        account: &signer,
+        amount: u64,
+        to: vector<u8>,
+    ) acquires CoinBridge, WrappedErc20 {
+        unwrap(coin::withdraw<Wrapped<Marker>>(account, amount), to);
+    }
+
+    fun assert_coin_bridge<Marker>(caller: &vector<u8>, erc20: &vector<u8>) acquires WrappedErc20 {
+        assert!(*caller == COIN_BRIDGE_ADDRESS, error::permission_denied(ENOT_COIN_BRIDGE));
+        assert!(
+            exists<WrappedErc20<Marker>>(@aptos_framework) && wrapped_erc20<Marker>() == *erc20,
+            error::not_found(EERC20_NOT_WRAPPED),
+        );
+    }
+
+    #[cross_vm(wrapped_marker)]
+    /// The type name of the marker wrapping an ERC-20. Called by the coin bridge contract.
+    fun ihe_wrapped_marker(_caller: vector<u8>, erc20: vector<u8>): vector<u8> acquires CoinBridge {
+        let bridge = borrow_global<CoinBridge>(@aptos_framework);
+        assert!(table::contains(&bridge.markers, erc20), error::not_found(EERC20_NOT_WRAPPED));
+        *string::bytes(table::borrow(&bridge.markers, erc20))
+    }
+
+    #[cross_vm(mint_wrapped)]
+    /// Mints the wrapped coin for tokens locked by the coin bridge contract.
+    fun ihe_mint_wrapped<Marker>(
+        caller: vector<u8>,
+        erc20: vector<u8>,
+        to: address,
+        amount: u64,
+    ) acquires WrappedErc20 {
+        assert_coin_bridge<Marker>(&caller, &erc20);
+        let wrapped = borrow_global<WrappedErc20<Marker>>(@aptos_framework);
+        aptos_account::deposit_coins(to, coin::mint(amount, &wrapped.mint_cap));
+    }
+
+    #[cross_vm(burn_wrapped)]
+    /// Burns the wrapped coin of an EVM account, for which the coin bridge contract unlocks the tokens.
+    fun ihe_burn_wrapped<Marker>(
+        caller: vector<u8>,
+        erc20: vector<u8>,
+        from: vector<u8>,
+        amount: u64,
+    ) acquires WrappedErc20 {
+        assert_coin_bridge<Marker>(&caller, &erc20);
+        let wrapped = borrow_global<WrappedErc20<Marker>>(@aptos_framework);
+        let from = account::create_signer(account_link::move_account_of(from));
+        coin::burn(coin::withdraw<Wrapped<Marker>>(&from, amount), &wrapped.burn_cap);
+    }
+
+    #[test_only]
+    struct TestCoin {}
+
+    #[test_only]
+    struct TestMarker {}
+
+    #[test_only]
+    const ALICE: vector<u8> = x"00000000000000000000000000000000000a11ce";
+    #[test_only]
+    const BOB: vector<u8> = x"0000000000000000000000000000000000000b0b";
+    #[test_only]
+    const ERC20: vector<u8> = x"00000000000000000000000000000000000e3c20";
+
+    #[test_only]
+    /// Initializes the coin bridge and `TestCoin`, registers its facade and gives 100 coins to `ALICE`.
+    fun initialize_for_test(aptos_framework: &signer) acquires CoinBridge {
+        initialize(aptos_framework);
+        let (burn_cap, freeze_cap, mint_cap) = coin::initialize<TestCoin>(
+            aptos_framework,
+            string::utf8(b"Test Coin"),
+            string::utf8(b"TC"),
+            6,
+            true,
+        );
+        register_coin<TestCoin>();
+        aptos_account::deposit_coins(account_link::default_account(ALICE), coin::mint(100, &mint_cap));
+        coin::destroy_burn_cap(burn_cap);
+        coin::destroy_freeze_cap(freeze_cap);
+        coin::destroy_mint_cap(mint_cap);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    fun test_register_coin(aptos_framework: &signer) acquires CoinBridge {
+        initialize(aptos_framework);
+        let (burn_cap, freeze_cap, mint_cap) = coin::initialize<TestCoin>(
+            aptos_framework,
+            string::utf8(b"Test Coin"),
+            string::utf8(b"TC"),
+            6,
+            true,
+        );
+        assert!(!is_registered<TestCoin>(), 0);
+        register_coin<TestCoin>();
+        register_coin<TestCoin>();
+        assert!(is_registered<TestCoin>(), 1);
+
+        let facade = facade_address<TestCoin>();
+        assert!(vector::length(&facade) == 20, 2);
+        let prefix = FACADE_ADDRESS_PREFIX;
+        let i = 0;
+        while (i < 8) {
+            assert!(*vector::borrow(&facade, i) == *vector::borrow(&prefix, i), 3);
+            i = i + 1;
+        };
+        let type_name = type_info::type_name<TestCoin>();
+        assert!(ihe_facade_coin(vector[], facade) == *string::bytes(&type_name), 4);
+        assert!(ihe_name<TestCoin>(vector[]) == b"Test Coin", 5);
+        assert!(ihe_symbol<TestCoin>(vector[]) == b"TC", 6);
+        assert!(ihe_decimals<TestCoin>(vector[]) == 6, 7);
+        assert!(ihe_total_supply<TestCoin>(vector[]) == 0, 8);
+
+        coin::destroy_burn_cap(burn_cap);
+        coin::destroy_freeze_cap(freeze_cap);
+        coin::destroy_mint_cap(mint_cap);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x60001, location = Self)]
+    fun test_register_uninitialized_coin(aptos_framework: &signer) acquires CoinBridge {
+        initialize(aptos_framework);
+        register_coin<TestCoin>();
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x60002, location = Self)]
+    fun test_facade_not_registered(aptos_framework: &signer) acquires CoinBridge {
+        initialize(aptos_framework);
+        ihe_facade_coin(vector[], facade_address<TestCoin>());
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    fun test_transfer(aptos_framework: &signer) acquires CoinBridge {
+        initialize_for_test(aptos_framework);
+        ihe_transfer<TestCoin>(facade_address<TestCoin>(), ALICE, BOB, 30);
+
+        assert!(ihe_balance_of<TestCoin>(vector[], ALICE) == 70, 0);
+        assert!(ihe_balance_of<TestCoin>(vector[], BOB) == 30, 1);
+        assert!(coin::balance<TestCoin>(account_link::default_account(BOB)) == 30, 2);
+        assert!(ihe_total_supply<TestCoin>(vector[]) == 100, 3);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x50003, location = Self)]
+    fun test_transfer_not_facade(aptos_framework: &signer) acquires CoinBridge {
+        initialize_for_test(aptos_framework);
+        ihe_transfer<TestCoin>(BOB, ALICE, BOB, 30);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    fun test_transfer_from(aptos_framework: &signer) acquires CoinBridge {
+        initialize_for_test(aptos_framework);
+        let facade = facade_address<TestCoin>();
+        ihe_approve<TestCoin>(facade, ALICE, BOB, 50);
+        assert!(ihe_allowance<TestCoin>(vector[], ALICE, BOB) == 50, 0);
+        assert!(ihe_allowance<TestCoin>(vector[], BOB, ALICE) == 0, 1);
+
+        ihe_transfer_from<TestCoin>(facade, BOB, ALICE, BOB, 30);
+        assert!(ihe_allowance<TestCoin>(vector[], ALICE, BOB) == 20, 2);
+        assert!(ihe_balance_of<TestCoin>(vector[], BOB) == 30, 3);
+
+        // An unlimited allowance is not decreased.
+        ihe_approve<TestCoin>(facade, ALICE, BOB, UNLIMITED_ALLOWANCE);
+        ihe_transfer_from<TestCoin>(facade, BOB, ALICE, BOB, 30);
+        assert!(ihe_allowance<TestCoin>(vector[], ALICE, BOB) == UNLIMITED_ALLOWANCE, 4);
+        assert!(ihe_balance_of<TestCoin>(vector[], ALICE) == 40, 5);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x50004, location = Self)]
+    fun test_transfer_from_insufficient_allowance(aptos_framework: &signer) acquires CoinBridge {
+        initialize_for_test(aptos_framework);
+        let facade = facade_address<TestCoin>();
+        ihe_approve<TestCoin>(facade, ALICE, BOB, 20);
+        ihe_transfer_from<TestCoin>(facade, BOB, ALICE, BOB, 30);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x50003, location = Self)]
+    fun test_approve_not_facade(aptos_framework: &signer) acquires CoinBridge {
+        initialize_for_test(aptos_framework);
+        ihe_approve<TestCoin>(BOB, ALICE, BOB, 20);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    fun test_wrapped_erc20(aptos_framework: &signer) acquires CoinBridge, WrappedErc20 {
+        initialize(aptos_framework);
+        register_erc20<TestMarker>(aptos_framework, ERC20, string::utf8(b"Wrapped"), string::utf8(b"W"), 18);
+        assert!(wrapped_erc20<TestMarker>() == ERC20, 0);
+        let marker = type_info::type_name<TestMarker>();
+        assert!(ihe_wrapped_marker(vector[], ERC20) == *string::bytes(&marker), 1);
+
+        ihe_mint_wrapped<TestMarker>(COIN_BRIDGE_ADDRESS, ERC20, account_link::default_account(ALICE), 100);
+        assert!(coin::balance<Wrapped<TestMarker>>(account_link::default_account(ALICE)) == 100, 2);
+        ihe_burn_wrapped<TestMarker>(COIN_BRIDGE_ADDRESS, ERC20, ALICE, 40);
+        assert!(coin::balance<Wrapped<TestMarker>>(account_link::default_account(ALICE)) == 60, 3);
+        assert!(coin::supply<Wrapped<TestMarker>>() == option::some(60), 4);
+    }
+
+    #[test(aptos_framework = @aptos_framework, alice = @0xa11ce)]
+    #[expected_failure(abort_code = 0x10006, location = Self)]
+    fun test_register_erc20_marker_mismatch(
+        aptos_framework: &signer,
+        alice: &signer,
+    ) acquires CoinBridge {
+        initialize(aptos_framework);
+        register_erc20<TestMarker>(alice, ERC20, string::utf8(b"Wrapped"), string::utf8(b"W"), 18);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x10005, location = Self)]
+    fun test_register_erc20_invalid_address(aptos_framework: &signer) acquires CoinBridge {
+        initialize(aptos_framework);
+        register_erc20<TestMarker>(aptos_framework, x"0011", string::utf8(b"Wrapped"), string::utf8(b"W"), 18);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x80007, location = Self)]
+    fun test_register_erc20_twice(aptos_framework: &signer) acquires CoinBridge {
+        initialize(aptos_framework);
+        register_erc20<TestMarker>(aptos_framework, ERC20, string::utf8(b"Wrapped"), string::utf8(b"W"), 18);
+        register_erc20<TestCoin>(aptos_framework, ERC20, string::utf8(b"Wrapped"), string::utf8(b"W"), 18);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x50009, location = Self)]
+    fun test_mint_wrapped_not_coin_bridge(aptos_framework: &signer) acquires CoinBridge, WrappedErc20 {
+        initialize(aptos_framework);
+        register_erc20<TestMarker>(aptos_framework, ERC20, string::utf8(b"Wrapped"), string::utf8(b"W"), 18);
+        ihe_mint_wrapped<TestMarker>(ALICE, ERC20, account_link::default_account(ALICE), 100);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x60008, location = Self)]
+    fun test_mint_wrapped_other_erc20(aptos_framework: &signer) acquires CoinBridge, WrappedErc20 {
+        initialize(aptos_framework);
+        register_erc20<TestMarker>(aptos_framework, ERC20, string::utf8(b"Wrapped"), string::utf8(b"W"), 18);
+        ihe_mint_wrapped<TestMarker>(COIN_BRIDGE_ADDRESS, BOB, account_link::default_account(ALICE), 100);
+    }
+}
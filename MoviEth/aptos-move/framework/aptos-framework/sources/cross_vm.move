@@ -2,17 +2,108 @@ module aptos_framework::cross_vm {
     use std::string;
     use std::error;
     use std::signer;
-    use std::option::Option;
+    use std::vector;
+    use std::option::{Self, Option};
     use aptos_std::type_info;
-    use aptos_framework::coin::Coin;
+    use aptos_framework::account_link;
+    use aptos_framework::aptos_account;
+    use aptos_framework::coin::{Self, BurnCapability, Coin, MintCapability};
     use aptos_framework::aptos_coin::AptosCoin;
+    use aptos_framework::system_addresses;
+
+    friend aptos_framework::genesis;
 
     const ECOIN_INFO_ADDRESS_MISMATCH: u64 = 1;
+    /// The account is not linked to an EVM address.
+    const EACCOUNT_NOT_LINKED: u64 = 2;
+
+    /// EVM gas limit used by `call_evm` when the caller does not pick one.
+    const DEFAULT_EVM_GAS_LIMIT: u64 = 10000000;
+    /// EVM gas price used by `call_evm` when the caller does not pick one.
+    const DEFAULT_EVM_GAS_PRICE: u64 = 1;
+
+    /// The EVM call succeeded.
+    const EVM_CALL_SUCCESS: u8 = 0;
+    /// The EVM callee reverted. The return data is the revert data, e.g. an ABI encoded `Error(string)`.
+    const EVM_CALL_REVERTED: u8 = 1;
+    /// The EVM callee halted exceptionally, e.g. it ran out of gas.
+    const EVM_CALL_FAILED: u8 = 2;
 
     struct CallEvmCap<phantom CallType> has copy, store, drop {}
 
+    /// Outcome of `try_call_evm`.
+    struct EvmCallResult has copy, store, drop {
+        status: u8,
+        return_data: vector<u8>,
+    }
+
     struct DefaultCallType has copy, store {}
 
+    /// The modules of an account which the EVM cannot re-enter, see `set_reentrancy_guard`.
+    struct ReentrancyGuard has key {
+        modules: vector<string::String>,
+    }
+
+    /// Ledger of the native token moved between Move and the EVM. A coin sent to the EVM is burned here and
+    /// issued in the EVM state, and value sent from the EVM is burned there and minted here, so that the Move
+    /// supply plus the EVM issuance only changes by fees.
+    struct NativeBridge has key {
+        mint_cap: MintCapability<AptosCoin>,
+        burn_cap: BurnCapability<AptosCoin>,
+        /// Octas minted for value sent from the EVM.
+        total_from_evm: u128,
+        /// Octas burned for value sent to the EVM.
+        total_to_evm: u128,
+    }
+
+    /// Only called during genesis.
+    public(friend) fun initialize_native_bridge(
+        aptos_framework: &signer,
+        burn_cap: BurnCapability<AptosCoin>,
+        mint_cap: MintCapability<AptosCoin>,
+    ) {
+        system_addresses::assert_aptos_framework(aptos_framework);
+        move_to(aptos_framework, NativeBridge {
+            mint_cap,
+            burn_cap,
+            total_from_evm: 0,
+            total_to_evm: 0,
+        });
+    }
+
+    /// Mints `amount` octas sent from the EVM to `to`. Called by the VM for the `callMove`s of EVM transactions,
+    /// after the value has been burned in the EVM.
+    fun deposit_from_evm(to: address, amount: u64) acquires NativeBridge {
+        let bridge = borrow_global_mut<NativeBridge>(@aptos_framework);
+        bridge.total_from_evm = bridge.total_from_evm + (amount as u128);
+        aptos_account::deposit_coins(to, coin::mint(amount, &bridge.mint_cap));
+    }
+
+    /// Burns the coin sent to the EVM, and returns its value in octas for the EVM to issue.
+    fun withdraw_to_evm(coin: Option<Coin<AptosCoin>>): u64 acquires NativeBridge {
+        if (option::is_none(&coin)) {
+            option::destroy_none(coin);
+            return 0
+        };
+        let coin = option::destroy_some(coin);
+        let amount = coin::value(&coin);
+        if (amount == 0) {
+            coin::destroy_zero(coin);
+            return 0
+        };
+        let bridge = borrow_global_mut<NativeBridge>(@aptos_framework);
+        bridge.total_to_evm = bridge.total_to_evm + (amount as u128);
+        coin::burn(coin, &bridge.burn_cap);
+        amount
+    }
+
+    #[view]
+    /// Octas moved from the EVM to Move, and from Move to the EVM, since genesis.
+    public fun native_bridge_totals(): (u128, u128) acquires NativeBridge {
+        let bridge = borrow_global<NativeBridge>(@aptos_framework);
+        (bridge.total_from_evm, bridge.total_to_evm)
+    }
+
     public fun initialize_cap<CallType>(
         account: &signer,
     ): CallEvmCap<CallType> {
@@ -30,5 +121,158 @@ module aptos_framework::cross_vm {
         CallEvmCap<DefaultCallType> {}
     }
 
-    public native fun call_evm<CallType>(coin: Option<Coin<AptosCoin>>, address: vector<u8>, function: string::String, params: vector<vector<u8>>, cap: &CallEvmCap<CallType>): vector<u8>;
-}
\ No newline at end of file
+    /// Calls an EVM contract with the default gas limit and gas price.
+    public fun call_evm<CallType>(coin: Option<Coin<AptosCoin>>, address: vector<u8>, function: string::String, params: vector<vector<u8>>, cap: &CallEvmCap<CallType>): vector<u8> acquires NativeBridge {
+        call_evm_with_gas(coin, address, function, params, DEFAULT_EVM_GAS_LIMIT, DEFAULT_EVM_GAS_PRICE, cap)
+    }
+
+    /// Calls an EVM contract with at most `gas_limit` EVM gas. The limit is capped by what is left of the
+    /// transaction's Move gas budget, and the EVM gas actually consumed is charged to the Move gas meter.
+    /// Aborts with `OUT_OF_GAS` if the remaining Move gas cannot pay for the EVM execution, and with
+    /// `EVM_CALL_REVERT` (0x2) if the callee runs out of a `gas_limit` below that.
+    /// The EVM frames of the call share the 1024 call depth limit of the EVM with the calls before it, and every
+    /// switch between Move and the EVM counts as 128 frames. A call past the limit aborts with
+    /// `CALL_DEPTH_EXCEEDED` (0x6). Move code called from the EVM can call back into the EVM, on the state of the
+    /// calling EVM transaction. Move code only sees the changes of the Move calls nested in its EVM calls if it
+    /// had not loaded the same state before, so a Move call or transaction which writes state its nested Move
+    /// calls accessed after it loaded it, or loaded state they write, fails, with `WRITE_CONFLICT` (0x7) for a
+    /// transaction.
+    /// If the callee fails, the transaction aborts with `EVM_CALL_REVERT` (0x2), and the abort info of the
+    /// transaction carries the hex encoded revert data of the callee.
+    /// The coin is burned, and its value is issued to the callee in the EVM.
+    public fun call_evm_with_gas<CallType>(coin: Option<Coin<AptosCoin>>, address: vector<u8>, function: string::String, params: vector<vector<u8>>, gas_limit: u64, gas_price: u64, cap: &CallEvmCap<CallType>): vector<u8> acquires NativeBridge {
+        let value = withdraw_to_evm(coin);
+        call_evm_with_value(value, address, function, params, gas_limit, gas_price, cap)
+    }
+
+    /// Calls an EVM contract sending along `value` octas, which the caller has burned.
+    native fun call_evm_with_value<CallType>(value: u64, address: vector<u8>, function: string::String, params: vector<vector<u8>>, gas_limit: u64, gas_price: u64, cap: &CallEvmCap<CallType>): vector<u8>;
+
+    /// Calls an EVM contract with the default gas limit and gas price, without aborting if the callee fails.
+    public fun try_call_evm<CallType>(address: vector<u8>, function: string::String, params: vector<vector<u8>>, cap: &CallEvmCap<CallType>): EvmCallResult {
+        try_call_evm_with_gas(address, function, params, DEFAULT_EVM_GAS_LIMIT, DEFAULT_EVM_GAS_PRICE, cap)
+    }
+
+    /// Like `call_evm_with_gas`, but a revert or an exceptional halt of the callee is returned to the caller
+    /// instead of aborting. The EVM state changes of a failed call are discarded, and its gas is still charged.
+    /// No coin can be sent along, since it could not be given back if the call fails.
+    public native fun try_call_evm_with_gas<CallType>(address: vector<u8>, function: string::String, params: vector<vector<u8>>, gas_limit: u64, gas_price: u64, cap: &CallEvmCap<CallType>): EvmCallResult;
+
+    public fun is_success(result: &EvmCallResult): bool {
+        result.status == EVM_CALL_SUCCESS
+    }
+
+    public fun is_reverted(result: &EvmCallResult): bool {
+        result.status == EVM_CALL_REVERTED
+    }
+
+    public fun status(result: &EvmCallResult): u8 {
+        result.status
+    }
+
+    /// The decoded return value of a successful call, or the raw revert data of a reverted call.
+    public fun return_data(result: &EvmCallResult): vector<u8> {
+        result.return_data
+    }
+
+    /// Calls the EVM function `signature`, e.g. `transfer(address,uint256)`, with ABI encoded arguments.
+    /// Each element of `args` is the BCS encoding of the Move value for the matching parameter: unsigned
+    /// integers map to the smallest Move integer that fits, `address` to a 20 byte `vector<u8>`, `bool` to `bool`,
+    /// `bytes<M>`/`bytes`/`string` to `vector<u8>`, arrays to vectors and tuples to structs. The return data is
+    /// decoded as the comma separated `return_types`, e.g. `uint256,bool`, and each value is returned BCS encoded.
+    public fun call_evm_typed<CallType>(coin: Option<Coin<AptosCoin>>, address: vector<u8>, signature: string::String, args: vector<vector<u8>>, return_types: string::String, gas_limit: u64, gas_price: u64, cap: &CallEvmCap<CallType>): vector<vector<u8>> acquires NativeBridge {
+        let value = withdraw_to_evm(coin);
+        call_evm_typed_with_value(value, address, signature, args, return_types, gas_limit, gas_price, cap)
+    }
+
+    /// Like `call_evm_typed`, sending along `value` octas, which the caller has burned.
+    native fun call_evm_typed_with_value<CallType>(value: u64, address: vector<u8>, signature: string::String, args: vector<vector<u8>>, return_types: string::String, gas_limit: u64, gas_price: u64, cap: &CallEvmCap<CallType>): vector<vector<u8>>;
+
+    /// Like `call_evm_typed`, with the EVM address linked to `account` with `account_link` as `msg.sender` instead
+    /// of the cross space contract, so that the account acts as its EVM identity. The value of the coin is issued to
+    /// the linked EVM address, which sends it along.
+    public fun call_evm_typed_as(account: &signer, coin: Option<Coin<AptosCoin>>, address: vector<u8>, signature: string::String, args: vector<vector<u8>>, return_types: string::String, gas_limit: u64, gas_price: u64): vector<vector<u8>> acquires NativeBridge {
+        let sender = account_link::linked_evm_address(signer::address_of(account));
+        assert!(option::is_some(&sender), error::not_found(EACCOUNT_NOT_LINKED));
+        let value = withdraw_to_evm(coin);
+        call_evm_typed_from(option::destroy_some(sender), value, address, signature, args, return_types, gas_limit, gas_price)
+    }
+
+    /// Like `call_evm_typed_with_value`, calling from the EVM address `sender`.
+    native fun call_evm_typed_from(sender: vector<u8>, value: u64, address: vector<u8>, signature: string::String, args: vector<vector<u8>>, return_types: string::String, gas_limit: u64, gas_price: u64): vector<vector<u8>>;
+
+    /// Guards the module `module_name` of the account against re-entrant calls from the EVM, or lifts the guard.
+    /// A call from the EVM into a guarded module fails while the module is already running further down the call
+    /// stack, as the entry function of the transaction or as the callee of an earlier call from the EVM.
+    public entry fun set_reentrancy_guard(account: &signer, module_name: string::String, guarded: bool) acquires ReentrancyGuard {
+        let addr = signer::address_of(account);
+        if (!exists<ReentrancyGuard>(addr)) {
+            move_to(account, ReentrancyGuard { modules: vector::empty() });
+        };
+        let modules = &mut borrow_global_mut<ReentrancyGuard>(addr).modules;
+        let (found, index) = vector::index_of(modules, &module_name);
+        if (guarded && !found) {
+            vector::push_back(modules, module_name);
+        } else if (!guarded && found) {
+            vector::remove(modules, index);
+        };
+    }
+
+    #[view]
+    /// Whether the module `module_name` of `account` is guarded against re-entrant calls from the EVM.
+    public fun is_reentrancy_guarded(account: address, module_name: string::String): bool acquires ReentrancyGuard {
+        exists<ReentrancyGuard>(account)
+            && vector::contains(&borrow_global<ReentrancyGuard>(account).modules, &module_name)
+    }
+
+    #[test_only]
+    use aptos_framework::aptos_coin;
+
+    #[test_only]
+    fun initialize_native_bridge_for_test(aptos_framework: &signer) {
+        let (burn_cap, mint_cap) = aptos_coin::initialize_for_test(aptos_framework);
+        initialize_native_bridge(aptos_framework, burn_cap, mint_cap);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    fun test_deposit_from_evm(aptos_framework: &signer) acquires NativeBridge {
+        initialize_native_bridge_for_test(aptos_framework);
+        deposit_from_evm(@0xa11ce, 100);
+        deposit_from_evm(@0xa11ce, 20);
+
+        assert!(coin::balance<AptosCoin>(@0xa11ce) == 120, 0);
+        assert!(coin::supply<AptosCoin>() == option::some(120), 1);
+        let (from_evm, to_evm) = native_bridge_totals();
+        assert!(from_evm == 120 && to_evm == 0, 2);
+    }
+
+    #[test(aptos_framework = @aptos_framework, alice = @0xa11ce)]
+    fun test_withdraw_to_evm(aptos_framework: &signer, alice: &signer) acquires NativeBridge {
+        initialize_native_bridge_for_test(aptos_framework);
+        deposit_from_evm(@0xa11ce, 100);
+        let coin = coin::withdraw<AptosCoin>(alice, 30);
+
+        assert!(withdraw_to_evm(option::some(coin)) == 30, 0);
+        assert!(withdraw_to_evm(option::some(coin::zero<AptosCoin>())) == 0, 1);
+        assert!(withdraw_to_evm(option::none()) == 0, 2);
+        assert!(coin::balance<AptosCoin>(@0xa11ce) == 70, 3);
+        assert!(coin::supply<AptosCoin>() == option::some(70), 4);
+        let (from_evm, to_evm) = native_bridge_totals();
+        assert!(from_evm == 100 && to_evm == 30, 5);
+    }
+
+    #[test(account = @0xcafe)]
+    fun test_set_reentrancy_guard(account: &signer) acquires ReentrancyGuard {
+        let name = string::utf8(b"vault");
+        assert!(!is_reentrancy_guarded(@0xcafe, name), 0);
+        set_reentrancy_guard(account, name, true);
+        set_reentrancy_guard(account, name, true);
+        set_reentrancy_guard(account, string::utf8(b"pool"), true);
+        assert!(is_reentrancy_guarded(@0xcafe, name), 1);
+        assert!(vector::length(&borrow_global<ReentrancyGuard>(@0xcafe).modules) == 2, 2);
+
+        set_reentrancy_guard(account, name, false);
+        assert!(!is_reentrancy_guarded(@0xcafe, name), 3);
+        assert!(is_reentrancy_guarded(@0xcafe, string::utf8(b"pool")), 4);
+    }
+}
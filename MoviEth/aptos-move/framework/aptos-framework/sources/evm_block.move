@@ -0,0 +1,218 @@
+/// This module defines the block environment seen by EVM code: the chain id and block gas limit,
+/// the proposer of the current block and the hashes of the most recent blocks.
+module aptos_framework::evm_block {
+    use std::error;
+    use std::vector;
+    use aptos_std::table::{Self, Table};
+
+    use aptos_framework::account;
+    use aptos_framework::system_addresses;
+
+    friend aptos_framework::block;
+
+    /// Number of recent block hashes visible to the EVM `BLOCKHASH` opcode.
+    const BLOCK_HASH_WINDOW: u64 = 256;
+    /// Chain id seen by EVM code until `set_config` is called. Should be in-sync with `EVM_CHAINID`
+    /// in cfx-evm.
+    const DEFAULT_CHAIN_ID: u64 = 129;
+    /// Block gas limit seen by EVM code until `set_config` is called.
+    const DEFAULT_BLOCK_GAS_LIMIT: u64 = 30000000;
+
+    /// The block gas limit cannot be 0.
+    const EZERO_BLOCK_GAS_LIMIT: u64 = 1;
+    /// The chain id must be between 1 and 255, the chain ids EVM transactions can be signed for.
+    const EINVALID_CHAIN_ID: u64 = 2;
+
+    /// Should be in-sync with EvmConfig rust struct in evm_config.rs
+    struct EvmConfig has key {
+        /// Value of the EVM `CHAINID` opcode, also used to sign EVM transactions.
+        chain_id: u64,
+        /// Value of the EVM `GASLIMIT` opcode.
+        block_gas_limit: u64,
+        /// Whether the logs of a contract go to one event stream per first topic, i.e. per
+        /// Solidity event, instead of one event stream per contract.
+        event_key_per_topic: bool,
+    }
+
+    struct EvmBlockInfo has key {
+        /// Proposer of the current block.
+        proposer: address,
+        /// Hash of the current block.
+        hash: address,
+        /// Number of blocks recorded before the current one.
+        height: u64,
+        /// Hashes of the last `BLOCK_HASH_WINDOW` blocks before the current one, keyed by their
+        /// height modulo `BLOCK_HASH_WINDOW`, so that a new block only writes one slot.
+        recent_hashes: Table<u64, address>,
+    }
+
+    /// Set the chain id and the block gas limit seen by EVM code, and how EVM logs map to event streams.
+    /// Can only be called as part of the Aptos governance proposal process established by the AptosGovernance module.
+    public fun set_config(
+        aptos_framework: &signer,
+        chain_id: u64,
+        block_gas_limit: u64,
+        event_key_per_topic: bool,
+    ) acquires EvmConfig {
+        system_addresses::assert_aptos_framework(aptos_framework);
+        assert!(block_gas_limit > 0, error::invalid_argument(EZERO_BLOCK_GAS_LIMIT));
+        assert!(chain_id > 0 && chain_id <= 255, error::invalid_argument(EINVALID_CHAIN_ID));
+
+        if (exists<EvmConfig>(@aptos_framework)) {
+            let config = borrow_global_mut<EvmConfig>(@aptos_framework);
+            config.chain_id = chain_id;
+            config.block_gas_limit = block_gas_limit;
+            config.event_key_per_topic = event_key_per_topic;
+        } else {
+            move_to(aptos_framework, EvmConfig { chain_id, block_gas_limit, event_key_per_topic });
+        }
+    }
+
+    /// Record the hash and the proposer of a new block.
+    /// The runtime runs this in the block prologue before executing the transactions in a block.
+    public(friend) fun on_new_block(hash: address, proposer: address) acquires EvmBlockInfo {
+        if (!exists<EvmBlockInfo>(@aptos_framework)) {
+            let aptos_framework = account::create_signer(@aptos_framework);
+            move_to(&aptos_framework, EvmBlockInfo {
+                proposer,
+                hash,
+                height: 0,
+                recent_hashes: table::new(),
+            });
+            return
+        };
+
+        // The hash of the block which just ended replaces the hash `BLOCK_HASH_WINDOW` blocks before it.
+        let info = borrow_global_mut<EvmBlockInfo>(@aptos_framework);
+        table::upsert(&mut info.recent_hashes, info.height % BLOCK_HASH_WINDOW, info.hash);
+        info.height = info.height + 1;
+        info.proposer = proposer;
+        info.hash = hash;
+    }
+
+    #[view]
+    /// Return the chain id seen by EVM code.
+    public fun chain_id(): u64 acquires EvmConfig {
+        if (exists<EvmConfig>(@aptos_framework)) {
+            borrow_global<EvmConfig>(@aptos_framework).chain_id
+        } else {
+            DEFAULT_CHAIN_ID
+        }
+    }
+
+    #[view]
+    /// Return the block gas limit seen by EVM code.
+    public fun block_gas_limit(): u64 acquires EvmConfig {
+        if (exists<EvmConfig>(@aptos_framework)) {
+            borrow_global<EvmConfig>(@aptos_framework).block_gas_limit
+        } else {
+            DEFAULT_BLOCK_GAS_LIMIT
+        }
+    }
+
+    #[view]
+    /// Return whether the logs of a contract go to one event stream per first topic.
+    public fun event_key_per_topic(): bool acquires EvmConfig {
+        exists<EvmConfig>(@aptos_framework) && borrow_global<EvmConfig>(@aptos_framework).event_key_per_topic
+    }
+
+    #[view]
+    /// Return the proposer of the current block, `@vm_reserved` for Nil blocks.
+    public fun proposer(): address acquires EvmBlockInfo {
+        if (exists<EvmBlockInfo>(@aptos_framework)) {
+            borrow_global<EvmBlockInfo>(@aptos_framework).proposer
+        } else {
+            @vm_reserved
+        }
+    }
+
+    #[view]
+    /// Return the hash of the current block, `@0x0` before the first block.
+    public fun block_hash(): address acquires EvmBlockInfo {
+        if (exists<EvmBlockInfo>(@aptos_framework)) {
+            borrow_global<EvmBlockInfo>(@aptos_framework).hash
+        } else {
+            @0x0
+        }
+    }
+
+    #[view]
+    /// Return the hashes of at most `BLOCK_HASH_WINDOW` blocks before the current one, the oldest
+    /// first.
+    public fun recent_block_hashes(): vector<address> acquires EvmBlockInfo {
+        let hashes = vector::empty();
+        if (!exists<EvmBlockInfo>(@aptos_framework)) {
+            return hashes
+        };
+
+        let info = borrow_global<EvmBlockInfo>(@aptos_framework);
+        let height = if (info.height > BLOCK_HASH_WINDOW) { info.height - BLOCK_HASH_WINDOW } else { 0 };
+        while (height < info.height) {
+            vector::push_back(&mut hashes, *table::borrow(&info.recent_hashes, height % BLOCK_HASH_WINDOW));
+            height = height + 1;
+        };
+        hashes
+    }
+
+    #[test_only]
+    fun hash_for_test(height: u64): address {
+        account::create_resource_address(&@aptos_framework, std::bcs::to_bytes(&height))
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    public entry fun test_set_config(aptos_framework: signer) acquires EvmConfig {
+        assert!(chain_id() == DEFAULT_CHAIN_ID, 0);
+        assert!(block_gas_limit() == DEFAULT_BLOCK_GAS_LIMIT, 1);
+        assert!(!event_key_per_topic(), 2);
+        set_config(&aptos_framework, 7, 1000, true);
+        assert!(chain_id() == 7, 3);
+        assert!(block_gas_limit() == 1000, 4);
+        assert!(event_key_per_topic(), 5);
+    }
+
+    #[test(account = @0x123)]
+    #[expected_failure(abort_code = 0x50003, location = aptos_framework::system_addresses)]
+    public entry fun test_set_config_unauthorized_should_fail(account: signer) acquires EvmConfig {
+        set_config(&account, 7, 1000, true);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x10002, location = Self)]
+    public entry fun test_set_config_chain_id_too_large_should_fail(aptos_framework: signer) acquires EvmConfig {
+        set_config(&aptos_framework, 256, 1000, true);
+    }
+
+    #[test(aptos_framework = @aptos_framework)]
+    #[expected_failure(abort_code = 0x10002, location = Self)]
+    public entry fun test_set_config_zero_chain_id_should_fail(aptos_framework: signer) acquires EvmConfig {
+        set_config(&aptos_framework, 0, 1000, true);
+    }
+
+    #[test]
+    fun test_recent_block_hashes() acquires EvmBlockInfo {
+        assert!(vector::is_empty(&recent_block_hashes()), 0);
+        assert!(block_hash() == @0x0, 1);
+
+        on_new_block(hash_for_test(0), @vm_reserved);
+        on_new_block(hash_for_test(1), @0x123);
+        on_new_block(hash_for_test(2), @0x456);
+        assert!(proposer() == @0x456, 2);
+        assert!(block_hash() == hash_for_test(2), 3);
+        assert!(recent_block_hashes() == vector[hash_for_test(0), hash_for_test(1)], 4);
+    }
+
+    #[test]
+    fun test_recent_block_hashes_wrap_around() acquires EvmBlockInfo {
+        let height = 0;
+        while (height <= BLOCK_HASH_WINDOW + 1) {
+            on_new_block(hash_for_test(height), @vm_reserved);
+            height = height + 1;
+        };
+
+        // The current block is `BLOCK_HASH_WINDOW + 1`, so the oldest hash kept is of block 1.
+        let hashes = recent_block_hashes();
+        assert!(vector::length(&hashes) == BLOCK_HASH_WINDOW, 0);
+        assert!(*vector::borrow(&hashes, 0) == hash_for_test(1), 1);
+        assert!(*vector::borrow(&hashes, BLOCK_HASH_WINDOW - 1) == hash_for_test(BLOCK_HASH_WINDOW), 2);
+    }
+}
@@ -0,0 +1,22 @@
+spec aptos_framework::evm_block {
+    spec on_new_block {
+        aborts_if exists<EvmBlockInfo>(@aptos_framework)
+            && global<EvmBlockInfo>(@aptos_framework).height + 1 > MAX_U64;
+    }
+
+    /// The caller is aptos_framework.
+    /// The block gas limit must be greater than 0.
+    spec set_config(
+        aptos_framework: &signer,
+        chain_id: u64,
+        block_gas_limit: u64,
+        event_key_per_topic: bool,
+    ) {
+        use std::signer;
+        aborts_if !system_addresses::is_aptos_framework_address(signer::address_of(aptos_framework));
+        aborts_if block_gas_limit == 0;
+        ensures global<EvmConfig>(@aptos_framework).chain_id == chain_id;
+        ensures global<EvmConfig>(@aptos_framework).block_gas_limit == block_gas_limit;
+        ensures global<EvmConfig>(@aptos_framework).event_key_per_topic == event_key_per_topic;
+    }
+}
@@ -13,7 +13,9 @@ module aptos_framework::genesis {
     use aptos_framework::chain_id;
     use aptos_framework::chain_status;
     use aptos_framework::coin;
+    use aptos_framework::coin_bridge;
     use aptos_framework::consensus_config;
+    use aptos_framework::cross_vm;
     use aptos_framework::gas_schedule;
     use aptos_framework::reconfiguration;
     use aptos_framework::stake;
@@ -125,12 +127,15 @@ module aptos_framework::genesis {
         reconfiguration::initialize(&aptos_framework_account);
         block::initialize(&aptos_framework_account, epoch_interval_microsecs);
         state_storage::initialize(&aptos_framework_account);
+        coin_bridge::initialize(&aptos_framework_account);
         timestamp::set_time_has_started(&aptos_framework_account);
     }
 
     /// Genesis step 2: Initialize Aptos coin.
     fun initialize_aptos_coin(aptos_framework: &signer) {
         let (burn_cap, mint_cap) = aptos_coin::initialize(aptos_framework);
+        // Give cross_vm module both capabilities so it can move value between Move and the EVM.
+        cross_vm::initialize_native_bridge(aptos_framework, burn_cap, mint_cap);
         // Give stake module MintCapability<AptosCoin> so it can mint rewards.
         stake::store_aptos_coin_mint_cap(aptos_framework, mint_cap);
         // Give transaction_fee module BurnCapability<AptosCoin> so it can burn gas.
@@ -144,6 +149,8 @@ module aptos_framework::genesis {
     ) {
         let (burn_cap, mint_cap) = aptos_coin::initialize(aptos_framework);
         coin::register<AptosCoin>(aptos_framework);
+        // Give cross_vm module both capabilities so it can move value between Move and the EVM.
+        cross_vm::initialize_native_bridge(aptos_framework, burn_cap, mint_cap);
         // Give stake module MintCapability<AptosCoin> so it can mint rewards.
         stake::store_aptos_coin_mint_cap(aptos_framework, mint_cap);
         // Give transaction_fee module BurnCapability<AptosCoin> so it can burn gas.
@@ -0,0 +1,285 @@
+/// Lets the EVM address linked to a Move account call Move entry functions with the signer of the account.
+///
+/// `callMoveAsSigner` of the cross space contract calls an entry function with the signer of the Move account linked
+/// to its EVM caller with `account_link`, so that existing Move modules can be called from Solidity as they are. The
+/// account must have delegated its signer to the EVM address it is linked to, for the function and until a given
+/// time. A delegation covers a single entry function, or all the entry functions of a module if its function name is
+/// empty. It lapses when it expires, or when the account is linked to another EVM address.
+module aptos_framework::signer_delegation {
+    use std::error;
+    use std::option;
+    use std::signer;
+    use std::string::{Self, String};
+    use std::vector;
+    use aptos_framework::account_link;
+    use aptos_framework::timestamp;
+
+    /// The account or EVM address is not linked.
+    const ENOT_LINKED: u64 = 1;
+    /// The expiration time has passed already.
+    const EALREADY_EXPIRED: u64 = 2;
+    /// The signer of the account is not delegated for the function.
+    const ENOT_DELEGATED: u64 = 3;
+    /// The delegations cannot cover this module, since they would let the EVM address manage them.
+    const EINVALID_SCOPE: u64 = 4;
+
+    /// The entry functions a delegation covers: `function_name`, or all of them if it is empty.
+    struct Scope has copy, drop, store {
+        module_address: address,
+        module_name: String,
+        function_name: String,
+    }
+
+    struct Delegation has drop, store {
+        evm_address: vector<u8>,
+        scope: Scope,
+        expiration_secs: u64,
+    }
+
+    /// Stored at a Move account which has delegated its signer.
+    struct SignerDelegations has key {
+        delegations: vector<Delegation>,
+    }
+
+    /// Delegates the signer to the linked EVM address for the entry functions of `scope`, until `expiration_secs`
+    /// (exclusive). Replaces the delegation of the same scope, if any, and drops the expired ones.
+    public entry fun delegate(
+        account: &signer,
+        module_address: address,
+        module_name: String,
+        function_name: String,
+        expiration_secs: u64,
+    ) acquires SignerDelegations {
+        let evm_address = account_link::linked_evm_address(signer::address_of(account));
+        assert!(option::is_some(&evm_address), error::not_found(ENOT_LINKED));
+        assert!(expiration_secs > timestamp::now_seconds(), error::invalid_argument(EALREADY_EXPIRED));
+        assert!(
+            module_address != @aptos_framework || module_name != string::utf8(b"signer_delegation"),
+            error::invalid_argument(EINVALID_SCOPE),
+        );
+
+        let scope = Scope { module_address, module_name, function_name };
+        if (!exists<SignerDelegations>(signer::address_of(account))) {
+            move_to(account, SignerDelegations { delegations: vector::empty() });
+        };
+        let delegations = &mut borrow_global_mut<SignerDelegations>(signer::address_of(account)).delegations;
+        remove_delegations(delegations, option::some(scope));
+        vector::push_back(delegations, Delegation {
+            evm_address: option::destroy_some(evm_address),
+            scope,
+            expiration_secs,
+        });
+    }
+
+    /// Revokes the delegation of the given scope.
+    public entry fun revoke(
+        account: &signer,
+        module_address: address,
+        module_name: String,
+        function_name: String,
+    ) acquires SignerDelegations {
+        let account = signer::address_of(account);
+        assert!(exists<SignerDelegations>(account), error::not_found(ENOT_DELEGATED));
+        let delegations = &mut borrow_global_mut<SignerDelegations>(account).delegations;
+        let num_delegations = vector::length(delegations);
+        remove_delegations(delegations, option::some(Scope { module_address, module_name, function_name }));
+        assert!(vector::length(delegations) < num_delegations, error::not_found(ENOT_DELEGATED));
+    }
+
+    public entry fun revoke_all(account: &signer) acquires SignerDelegations {
+        let account = signer::address_of(account);
+        if (exists<SignerDelegations>(account)) {
+            let SignerDelegations { delegations: _ } = move_from<SignerDelegations>(account);
+        }
+    }
+
+    #[view]
+    /// Whether `evm_address` can call the entry function with the signer of `account` now.
+    public fun is_delegated(
+        account: address,
+        evm_address: vector<u8>,
+        module_address: address,
+        module_name: String,
+        function_name: String,
+    ): bool acquires SignerDelegations {
+        if (!exists<SignerDelegations>(account) ||
+            account_link::linked_evm_address(account) != option::some(evm_address)) {
+            return false
+        };
+        let now = timestamp::now_seconds();
+        let delegations = &borrow_global<SignerDelegations>(account).delegations;
+        let i = 0;
+        while (i < vector::length(delegations)) {
+            let delegation = vector::borrow(delegations, i);
+            if (delegation.evm_address == evm_address &&
+                delegation.expiration_secs > now &&
+                covers(&delegation.scope, module_address, &module_name, &function_name)) {
+                return true
+            };
+            i = i + 1;
+        };
+        false
+    }
+
+    /// Called by the VM before an EVM caller calls an entry function with a signer. Returns the Move account linked
+    /// to `evm_address`, which must have delegated its signer for the function.
+    fun authorize(
+        evm_address: vector<u8>,
+        module_address: address,
+        module_name: String,
+        function_name: String,
+    ): address acquires SignerDelegations {
+        let account = account_link::linked_account(evm_address);
+        assert!(option::is_some(&account), error::not_found(ENOT_LINKED));
+        let account = option::destroy_some(account);
+        assert!(
+            is_delegated(account, evm_address, module_address, module_name, function_name),
+            error::permission_denied(ENOT_DELEGATED),
+        );
+        account
+    }
+
+    /// Removes the delegations of `scope` if any, and the expired ones.
+    fun remove_delegations(delegations: &mut vector<Delegation>, scope: option::Option<Scope>) {
+        let now = timestamp::now_seconds();
+        let i = 0;
+        while (i < vector::length(delegations)) {
+            let delegation = vector::borrow(delegations, i);
+            if (delegation.expiration_secs <= now || option::some(delegation.scope) == scope) {
+                vector::swap_remove(delegations, i);
+            } else {
+                i = i + 1;
+            }
+        }
+    }
+
+    fun covers(scope: &Scope, module_address: address, module_name: &String, function_name: &String): bool {
+        scope.module_address == module_address &&
+            &scope.module_name == module_name &&
+            (string::is_empty(&scope.function_name) || &scope.function_name == function_name)
+    }
+
+    #[test]
+    fun test_covers() {
+        let function_scope = Scope {
+            module_address: @0xcafe,
+            module_name: string::utf8(b"pool"),
+            function_name: string::utf8(b"swap"),
+        };
+        assert!(covers(&function_scope, @0xcafe, &string::utf8(b"pool"), &string::utf8(b"swap")), 0);
+        assert!(!covers(&function_scope, @0xcafe, &string::utf8(b"pool"), &string::utf8(b"add_liquidity")), 1);
+        assert!(!covers(&function_scope, @0xcafe, &string::utf8(b"router"), &string::utf8(b"swap")), 2);
+        assert!(!covers(&function_scope, @0xbeef, &string::utf8(b"pool"), &string::utf8(b"swap")), 3);
+
+        let module_scope = Scope {
+            module_address: @0xcafe,
+            module_name: string::utf8(b"pool"),
+            function_name: string::utf8(b""),
+        };
+        assert!(covers(&module_scope, @0xcafe, &string::utf8(b"pool"), &string::utf8(b"add_liquidity")), 4);
+        assert!(!covers(&module_scope, @0xcafe, &string::utf8(b"router"), &string::utf8(b"swap")), 5);
+    }
+
+    #[test_only]
+    const EVM_ADDRESS: vector<u8> = x"00000000000000000000000000000000000a11ce";
+    #[test_only]
+    const OTHER_EVM_ADDRESS: vector<u8> = x"0000000000000000000000000000000000000b0b";
+
+    #[test_only]
+    /// Starts the clock at 100 seconds and links `account` to `EVM_ADDRESS`.
+    fun setup(aptos_framework: &signer, account: &signer) {
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        timestamp::update_global_time_for_test_secs(100);
+        account_link::link_for_test(signer::address_of(account), EVM_ADDRESS);
+    }
+
+    #[test_only]
+    fun authorize_swap(evm_address: vector<u8>): address acquires SignerDelegations {
+        authorize(evm_address, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"))
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    fun test_delegate_and_authorize(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 200);
+        assert!(authorize_swap(EVM_ADDRESS) == @0x123, 0);
+
+        // A module scope covers all the functions of the module.
+        delegate(account, @0xcafe, string::utf8(b"router"), string::utf8(b""), 200);
+        assert!(authorize(EVM_ADDRESS, @0xcafe, string::utf8(b"router"), string::utf8(b"swap")) == @0x123, 1);
+        assert!(!is_delegated(@0x123, EVM_ADDRESS, @0xcafe, string::utf8(b"pool"), string::utf8(b"add_liquidity")), 2);
+        assert!(!is_delegated(@0x123, OTHER_EVM_ADDRESS, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap")), 3);
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x50003, location = Self)]
+    fun test_authorize_out_of_scope(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 200);
+        authorize(EVM_ADDRESS, @0xcafe, string::utf8(b"pool"), string::utf8(b"add_liquidity"));
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x50003, location = Self)]
+    fun test_authorize_expired(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 200);
+        timestamp::update_global_time_for_test_secs(199);
+        assert!(authorize_swap(EVM_ADDRESS) == @0x123, 0);
+        timestamp::update_global_time_for_test_secs(200);
+        authorize_swap(EVM_ADDRESS);
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x10002, location = Self)]
+    fun test_delegate_already_expired(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 100);
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x50003, location = Self)]
+    fun test_authorize_revoked(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 200);
+        delegate(account, @0xcafe, string::utf8(b"router"), string::utf8(b""), 200);
+        revoke(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"));
+        assert!(is_delegated(@0x123, EVM_ADDRESS, @0xcafe, string::utf8(b"router"), string::utf8(b"swap")), 0);
+        authorize_swap(EVM_ADDRESS);
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x60003, location = Self)]
+    fun test_revoke_not_delegated(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 200);
+        revoke(account, @0xcafe, string::utf8(b"pool"), string::utf8(b""));
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x50003, location = Self)]
+    fun test_delegation_lapses_on_relink(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 200);
+        account_link::unlink(account);
+        account_link::link_for_test(@0x123, OTHER_EVM_ADDRESS);
+        assert!(!is_delegated(@0x123, EVM_ADDRESS, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap")), 0);
+        authorize_swap(OTHER_EVM_ADDRESS);
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x60001, location = Self)]
+    fun test_authorize_after_unlink(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @0xcafe, string::utf8(b"pool"), string::utf8(b"swap"), 200);
+        account_link::unlink(account);
+        authorize_swap(EVM_ADDRESS);
+    }
+
+    #[test(aptos_framework = @aptos_framework, account = @0x123)]
+    #[expected_failure(abort_code = 0x10004, location = Self)]
+    fun test_delegate_signer_delegation(aptos_framework: &signer, account: &signer) acquires SignerDelegations {
+        setup(aptos_framework, account);
+        delegate(account, @aptos_framework, string::utf8(b"signer_delegation"), string::utf8(b""), 200);
+    }
+}
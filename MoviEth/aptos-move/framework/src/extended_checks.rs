@@ -17,10 +17,14 @@ use move_model::{
     symbol::Symbol,
     ty::{PrimitiveType, Type},
 };
-use std::{collections::BTreeMap, rc::Rc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
 
 const INIT_MODULE_FUN: &str = "init_module";
 const VIEW_FUN_ATTRIBUTE: &str = "view";
+const CROSS_VM_FUN_ATTRIBUTE: &str = "cross_vm";
 const LEGAC_ENTRY_FUN_ATTRIBUTE: &str = "legacy_entry_fun";
 const ERROR_PREFIX: &str = "E";
 
@@ -60,6 +64,7 @@ impl<'a> ExtendedChecker<'a> {
                 self.check_init_module(module);
                 self.check_entry_functions(module);
                 self.check_and_record_view_functions(module);
+                self.check_and_record_cross_vm_functions(module);
                 self.build_error_map(module)
             }
         }
@@ -190,6 +195,116 @@ impl<'a> ExtendedChecker<'a> {
     }
 }
 
+// ----------------------------------------------------------------------------------
+// Cross-VM Functions
+
+impl<'a> ExtendedChecker<'a> {
+    /// Records the functions marked `#[cross_vm]` or `#[cross_vm(name)]`, which the EVM can call
+    /// by their own name or by `name`. They take the EVM caller as `vector<u8>` first.
+    fn check_and_record_cross_vm_functions(&mut self, module: &ModuleEnv) {
+        let mut names = BTreeSet::new();
+        for ref fun in module.get_functions() {
+            let args = if let Some(args) = self.get_attribute_args(fun, CROSS_VM_FUN_ATTRIBUTE) {
+                args
+            } else {
+                continue;
+            };
+            let name = match args {
+                [] => fun.get_simple_name_string().to_string(),
+                [Attribute::Apply(_, name, args)] if args.is_empty() => {
+                    self.name_string(*name).to_string()
+                },
+                _ => {
+                    self.env.error(
+                        &fun.get_loc(),
+                        "cross-VM function attribute takes at most the name to call it by",
+                    );
+                    continue;
+                },
+            };
+            if fun.is_entry() {
+                self.env.error(
+                    &fun.get_loc(),
+                    "entry function cannot be a cross-VM function",
+                )
+            }
+            let params = fun.get_parameter_types();
+            let takes_caller = matches!(
+                params.first(),
+                Some(Type::Vector(ety)) if matches!(**ety, Type::Primitive(PrimitiveType::U8))
+            );
+            if !takes_caller {
+                self.env.error(
+                    &fun.get_loc(),
+                    "cross-VM function must take the EVM caller as `vector<u8>` first",
+                )
+            }
+            let takes_signer = params.iter().any(|ty| match ty {
+                Type::Primitive(PrimitiveType::Signer) => true,
+                Type::Reference(_, ty) => matches!(**ty, Type::Primitive(PrimitiveType::Signer)),
+                _ => false,
+            });
+            if takes_signer {
+                // The EVM caller could forge it from the address it passes.
+                self.env
+                    .error(&fun.get_loc(), "cross-VM function cannot take a signer")
+            }
+            self.check_transaction_args(&fun.get_loc(), params.get(1..).unwrap_or_default());
+            for idx in 0..fun.get_return_count() {
+                self.check_cross_vm_return_type(&fun.get_loc(), &fun.get_return_type(idx));
+            }
+            if !names.insert(name.clone()) {
+                self.env.error(
+                    &fun.get_loc(),
+                    &format!("duplicate cross-VM function name `{}`", name),
+                )
+            }
+            // Remember the runtime info that this is a cross-VM function
+            let module_id = self.get_runtime_module_id(module);
+            self.output
+                .entry(module_id)
+                .or_default()
+                .fun_attributes
+                .entry(fun.get_simple_name_string().to_string())
+                .or_default()
+                .push(KnownAttribute::cross_vm_function(name));
+        }
+    }
+
+    /// The return values of a cross-VM function are ABI encoded for the EVM caller, which cannot
+    /// hold references or signers.
+    fn check_cross_vm_return_type(&self, loc: &Loc, ty: &Type) {
+        use Type::*;
+        match ty {
+            Primitive(PrimitiveType::Signer) => {
+                self.env
+                    .error(loc, "cross-VM function cannot return a signer");
+            },
+            Primitive(_) | TypeParameter(_) => {
+                // Any other primitive type allowed, any parameter expected to instantiate with
+                // primitive
+            },
+            Vector(ety) => {
+                // Vectors are allowed if element type is allowed
+                self.check_cross_vm_return_type(loc, ety)
+            },
+            Struct(mid, sid, _) if self.is_allowed_input_struct(mid.qualified(*sid)) => {
+                // Specific struct types are allowed
+            },
+            _ => {
+                // Everything else, including references, is disallowed.
+                self.env.error(
+                    loc,
+                    &format!(
+                        "type `{}` is not supported as a cross-VM return type",
+                        ty.display(&self.env.get_type_display_ctx())
+                    ),
+                );
+            },
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------------
 // Error Map
 
@@ -238,12 +353,21 @@ impl<'a> ExtendedChecker<'a> {
 
 impl<'a> ExtendedChecker<'a> {
     fn has_attribute(&self, fun: &FunctionEnv, attr_name: &str) -> bool {
-        fun.get_attributes().iter().any(|attr| {
-            if let Attribute::Apply(_, name, _) = attr {
-                self.env.symbol_pool().string(*name).as_str() == attr_name
-            } else {
-                false
-            }
+        self.get_attribute_args(fun, attr_name).is_some()
+    }
+
+    fn get_attribute_args<'f>(
+        &self,
+        fun: &'f FunctionEnv,
+        attr_name: &str,
+    ) -> Option<&'f [Attribute]> {
+        fun.get_attributes().iter().find_map(|attr| match attr {
+            Attribute::Apply(_, name, args)
+                if self.env.symbol_pool().string(*name).as_str() == attr_name =>
+            {
+                Some(args.as_slice())
+            },
+            _ => None,
         })
     }
 
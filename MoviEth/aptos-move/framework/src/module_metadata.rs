@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_types::transaction::AbortInfo;
-use move_binary_format::{normalized::Function, CompiledModule};
+use move_binary_format::{
+    normalized::{Function, Type},
+    CompiledModule,
+};
 use move_core_types::{
     errmap::ErrorDescription, identifier::Identifier, language_storage::ModuleId,
     metadata::Metadata,
@@ -10,7 +13,7 @@ use move_core_types::{
 use move_vm_runtime::move_vm::MoveVM;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use thiserror::Error;
 
 /// The minimal file format version from which the V1 metadata is supported
@@ -57,6 +60,7 @@ pub struct KnownAttribute {
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum KnownAttributeKind {
     ViewFunction = 1,
+    CrossVmFunction = 2,
 }
 
 impl KnownAttribute {
@@ -70,6 +74,27 @@ impl KnownAttribute {
     pub fn is_view_function(&self) -> bool {
         self.kind == (KnownAttributeKind::ViewFunction as u16)
     }
+
+    /// A function the EVM can call by the name `name`.
+    pub fn cross_vm_function(name: String) -> Self {
+        Self {
+            kind: KnownAttributeKind::CrossVmFunction as u16,
+            args: vec![name],
+        }
+    }
+
+    pub fn is_cross_vm_function(&self) -> bool {
+        self.kind == (KnownAttributeKind::CrossVmFunction as u16)
+    }
+
+    /// The name the EVM calls a cross-VM function by.
+    pub fn cross_vm_name(&self) -> Option<&str> {
+        if self.is_cross_vm_function() {
+            self.args.first().map(String::as_str)
+        } else {
+            None
+        }
+    }
 }
 
 /// Extract metadata from the VM, upgrading V0 to V1 representation as needed
@@ -123,6 +148,41 @@ pub fn is_valid_view_function(
     })
 }
 
+/// A cross-VM function takes the EVM caller as `vector<u8>` first, and cannot be an entry
+/// function, which the EVM calls with a signer instead. It cannot take a signer either, which
+/// would let any EVM caller act for the account.
+pub fn is_valid_cross_vm_function(
+    functions: &BTreeMap<Identifier, Function>,
+    fun: &str,
+) -> Result<(), MetadataValidationError> {
+    if let Ok(ident_fun) = Identifier::new(fun) {
+        if let Some(mod_fun) = functions.get(&ident_fun) {
+            if !mod_fun.is_entry
+                && mod_fun.parameters.first() == Some(&Type::Vector(Box::new(Type::U8)))
+                && !mod_fun.parameters.iter().any(contains_signer)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(MetadataValidationError {
+        key: fun.to_string(),
+        attribute: KnownAttributeKind::CrossVmFunction as u16,
+    })
+}
+
+/// Whether a parameter of type `ty` is or holds a signer.
+pub fn contains_signer(ty: &Type) -> bool {
+    match ty {
+        Type::Signer => true,
+        Type::Reference(inner) | Type::MutableReference(inner) | Type::Vector(inner) => {
+            contains_signer(inner)
+        },
+        _ => false,
+    }
+}
+
 pub fn verify_module_metadata(module: &CompiledModule) -> Result<(), MetadataValidationError> {
     let metadata = if let Some(metadata) = get_module_metadata(module) {
         metadata
@@ -135,10 +195,20 @@ pub fn verify_module_metadata(module: &CompiledModule) -> Result<(), MetadataVal
         .iter()
         .map(|func_def| Function::new(module, func_def))
         .collect::<BTreeMap<_, _>>();
+    let mut cross_vm_names = BTreeSet::new();
     for (fun, attrs) in &metadata.fun_attributes {
         for attr in attrs {
             if attr.is_view_function() {
                 is_valid_view_function(&functions, fun)?
+            } else if let Some(name) = attr.cross_vm_name() {
+                is_valid_cross_vm_function(&functions, fun)?;
+                // Two functions cannot be called by the same name.
+                if !cross_vm_names.insert(name) {
+                    return Err(MetadataValidationError {
+                        key: fun.clone(),
+                        attribute: attr.kind,
+                    });
+                }
             } else {
                 return Err(MetadataValidationError {
                     key: fun.clone(),
@@ -186,6 +256,16 @@ impl RuntimeModuleMetadataV1 {
             && self.struct_attributes.is_empty()
     }
 
+    /// The function the EVM calls by `name`, if the module registers one with `#[cross_vm]`.
+    pub fn cross_vm_function(&self, name: &str) -> Option<String> {
+        self.fun_attributes.iter().find_map(|(fun, attrs)| {
+            attrs
+                .iter()
+                .any(|attr| attr.cross_vm_name() == Some(name))
+                .then(|| fun.clone())
+        })
+    }
+
     pub fn extract_abort_info(&self, code: u64) -> Option<AbortInfo> {
         self.error_map
             .get(&(code & 0xFFF))
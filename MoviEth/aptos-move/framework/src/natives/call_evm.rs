@@ -1,175 +1,653 @@
 use super::code::get_move_string;
-use aptos_types::vm_status::StatusCode;
+use aptos_evm::{abi_to_move_value, abi_type_to_layout, move_value_to_abi};
+use aptos_types::{transaction::AbortInfo, vm_status::StatusCode};
 use better_any::{Tid, TidAble};
-use cfx_evm::{execution::revert_reason_decode, CrossVMParams, FinalizationResult, TXExecutor};
+use cfx_evm::{
+    execution::revert_reason_decode, native_token::octas_to_wei, vm::ReturnData, CrossVMParams,
+    FinalizationResult, Substate, TXExecutor,
+};
+use cfx_parameters::{
+    cross_vm::CROSS_VM_CALL_DEPTH, internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS,
+};
 use ethereum_types::{Address, U256};
+use log::debug;
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
-use move_core_types::language_storage::{StructTag, TypeTag};
+use move_core_types::{
+    gas_algebra::InternalGas,
+    language_storage::{StructTag, TypeTag},
+    value::MoveValue,
+};
 use move_vm_runtime::native_functions::{NativeContext, NativeFunction};
 use move_vm_types::{
     loaded_data::runtime_types::Type,
     natives::function::NativeResult,
     pop_arg,
-    values::{Struct, Value},
+    values::{Struct, Value, Vector},
 };
 use smallvec::smallvec;
-use solidity_abi::ABIDecodable;
-use std::{collections::VecDeque, ops::Deref, sync::Arc};
+use solidity_abi::{
+    decode_list, encode_list, function_selector, parse_signature, parse_type_list, ABIDecodable,
+    ABIType,
+};
+use std::{
+    collections::VecDeque,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 pub mod abort_codes {
     pub const INCORRECT_TYPE_TAG: u64 = 0x1;
     pub const EVM_CALL_REVERT: u64 = 0x2;
     pub const CANNOT_CALL_EVM: u64 = 0x3;
     pub const MALFORMED_OUTPUT: u64 = 0x4;
+    pub const MALFORMED_INPUT: u64 = 0x5;
+    pub const CALL_DEPTH_EXCEEDED: u64 = 0x6;
+    /// Aborts a Move transaction which wrote state that Move calls nested in its EVM calls
+    /// accessed, or the other way around, see `CrossSpaceHandler` of the block executor.
+    pub const WRITE_CONFLICT: u64 = 0x7;
 }
+
+/// Reason name of the abort info of a Move transaction aborted by a failed `call_evm`.
+pub const EVM_CALL_REVERT_NAME: &str = "EVM_CALL_REVERT";
+
+/// Status of a `cross_vm::EvmCallResult`, must match the constants in `cross_vm.move`.
+pub mod evm_call_status {
+    pub const SUCCESS: u8 = 0;
+    pub const REVERTED: u8 = 1;
+    pub const FAILED: u8 = 2;
+}
+
+pub use cfx_parameters::cross_vm::INTERNAL_GAS_PER_EVM_GAS;
+
+/// Internal gas left in the Move gas meter. The meter publishes its balance before every native
+/// call, so that `call_evm` bounds the EVM gas by what the transaction can still pay for.
+#[derive(Clone, Debug, Default)]
+pub struct MoveGasLeft(Arc<AtomicU64>);
+
+impl MoveGasLeft {
+    pub fn set(&self, gas: InternalGas) {
+        self.0.store(u64::from(gas), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> InternalGas {
+        InternalGas::new(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Lets Move code call into the EVM, both in a Move transaction and in a Move call from the EVM,
+/// whose EVM calls run on the state of the calling EVM transaction. A session without one aborts
+/// with `CANNOT_CALL_EVM`.
 #[derive(Tid)]
 pub struct CrossVMContext<'a> {
     pub executor: TXExecutor<'a>,
+    /// Balance of the Move gas meter running the transaction.
+    pub move_gas_left: MoveGasLeft,
+    /// Logs, suicides and created contracts of the successful `call_evm`s, to be finalized
+    /// together with the enclosing Move transaction.
+    pub substate: &'a mut Substate,
+    /// Call depth of the Move code, 0 for a Move transaction. The EVM calls it makes share the
+    /// `max_depth` limit of the EVM frames with the calls before it.
+    pub depth: usize,
+    /// Set once the Move transaction calls into the EVM, even if the call fails.
+    pub called_evm: &'a mut bool,
+    /// Why the last `call_evm` aborted the Move transaction, e.g. the revert data of the callee,
+    /// to be reported in the abort info of the transaction.
+    pub evm_abort_info: &'a mut Option<AbortInfo>,
+}
+
+impl<'a> CrossVMContext<'a> {
+    pub fn new(
+        executor: TXExecutor<'a>,
+        move_gas_left: MoveGasLeft,
+        substate: &'a mut Substate,
+        depth: usize,
+        called_evm: &'a mut bool,
+        evm_abort_info: &'a mut Option<AbortInfo>,
+    ) -> Self {
+        Self {
+            executor,
+            move_gas_left,
+            substate,
+            depth,
+            called_evm,
+            evm_abort_info,
+        }
+    }
+
+    /// The depth of the EVM frame of a call made by the Move code, if it is within the depth
+    /// limit shared with the EVM frames. Switching to the EVM costs `CROSS_VM_CALL_DEPTH` levels.
+    fn callee_depth(&self) -> Option<usize> {
+        let depth = self.depth + CROSS_VM_CALL_DEPTH;
+        (depth < self.executor.max_call_depth()).then_some(depth)
+    }
+
+    /// EVM gas the Move transaction can still pay for at this point of its execution.
+    fn evm_gas_budget(&self) -> u64 {
+        internal_gas_to_evm_gas(self.move_gas_left.get())
+    }
+}
+
+fn internal_gas_to_evm_gas(gas: InternalGas) -> u64 {
+    u64::from(gas) / INTERNAL_GAS_PER_EVM_GAS
+}
+
+fn evm_gas_to_internal_gas(evm_gas: U256) -> InternalGas {
+    let evm_gas = if evm_gas >= U256::from(u64::MAX) {
+        u64::MAX
+    } else {
+        evm_gas.as_u64()
+    };
+    InternalGas::new(evm_gas.saturating_mul(INTERNAL_GAS_PER_EVM_GAS))
+}
+
+/// Pops `gas_limit` and `gas_price`, capping the limit by the remaining budget.
+fn pop_gas(arguments: &mut VecDeque<Value>, evm_gas_budget: u64) -> PartialVMResult<(U256, U256)> {
+    let gas_price = U256::from(pop_arg!(arguments, u64));
+    let gas = U256::from(std::cmp::min(pop_arg!(arguments, u64), evm_gas_budget));
+    Ok((gas, gas_price))
+}
+
+/// Pops the EVM receiver and, if `payable`, the octas sent along with the call, which the
+/// caller has burned with `cross_vm::withdraw_to_evm`. Returns the value in wei.
+fn pop_receiver_and_value(
+    arguments: &mut VecDeque<Value>,
+    payable: bool,
+) -> PartialVMResult<(Address, U256)> {
+    let receiver = pop_evm_address(arguments)?;
+    if !payable {
+        return Ok((receiver, U256::zero()));
+    }
+    let value = octas_to_wei(pop_arg!(arguments, u64));
+    Ok((receiver, value))
+}
+
+fn pop_evm_address(arguments: &mut VecDeque<Value>) -> PartialVMResult<Address> {
+    let raw = pop_arg!(arguments, Vec<u8>);
+    if raw.len() != 20 {
+        return Err(PartialVMError::new(
+            StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+        ));
+    }
+    Ok(Address::from_slice(&raw))
+}
+
+fn caller_info(struct_tag: &StructTag) -> String {
+    let address = struct_tag.address.into_bytes();
+    let module = struct_tag.module.as_ident_str().as_str();
+    let name = struct_tag.name.as_ident_str().as_str();
+    format!("0x{}::{module}::{name}", hex::encode(&address))
+}
+
+fn pop_bytes_list(arguments: &mut VecDeque<Value>) -> PartialVMResult<Vec<Vec<u8>>> {
+    pop_arg!(arguments, Vec<Value>)
+        .into_iter()
+        .map(|x| x.value_as::<Vec<u8>>())
+        .collect()
 }
 
 fn make_cross_vm_params(
     struct_tag: Box<StructTag>,
     arguments: &mut VecDeque<Value>,
+    evm_gas_budget: u64,
+    depth: usize,
+    payable: bool,
 ) -> PartialVMResult<CrossVMParams> {
     let err = PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR);
 
-    let gas = U256::from(10_000_000);
-    let gas_price = U256::from(1);
-
     let _call_cap = arguments.pop_back().ok_or_else(|| err.clone())?;
 
-    let evm_params = pop_arg!(arguments, Vec<Value>)
-        .into_iter()
-        .map(|x| x.value_as::<Vec<u8>>())
-        .collect::<PartialVMResult<Vec<Vec<u8>>>>()?;
+    let (gas, gas_price) = pop_gas(arguments, evm_gas_budget)?;
+
+    let evm_params = pop_bytes_list(arguments)?;
 
     let function_name_value = arguments.pop_back().ok_or_else(|| err.clone())?;
     let function_name = get_move_string(function_name_value)?;
 
-    let receiver_raw = pop_arg!(arguments, Vec<u8>);
-    let receiver = if receiver_raw.len() == 20 {
-        Address::from_slice(&receiver_raw)
-    } else {
-        return Err(err);
-    };
+    let (receiver, value) = pop_receiver_and_value(arguments, payable)?;
 
-    let mut maybe_coin = pop_arg!(arguments, Struct)
-        .unpack()?
-        .next()
-        .ok_or_else(|| err.clone())?
-        .value_as::<Vec<Value>>()?;
-    assert!(maybe_coin.len() <= 1);
-
-    let value = if let Some(coin) = maybe_coin.pop() {
-        let value = coin
-            .value_as::<Struct>()?
-            .unpack()?
-            .next()
-            .ok_or_else(|| err.clone())?
-            .value_as::<u64>()?;
-        U256::from(value) * U256::from(10_000_000_000u64)
-    } else {
-        U256::zero()
+    let params = CrossVMParams {
+        sender: *CROSS_SPACE_CONTRACT_ADDRESS,
+        receiver,
+        function_name,
+        gas,
+        gas_price,
+        value,
+        evm_params,
+        caller_info: caller_info(&struct_tag),
+        calldata: None,
+        depth,
     };
+    Ok(params)
+}
 
-    let caller_info = {
-        let address = struct_tag.address.into_bytes();
-        let module = struct_tag.module.as_ident_str().as_str();
-        let name = struct_tag.name.as_ident_str().as_str();
-        format!("0x{}::{module}::{name}", hex::encode(&address))
+/// The arguments of `cross_vm::call_evm_typed`, with the calldata already ABI encoded.
+struct TypedCall {
+    params: CrossVMParams,
+    return_types: Vec<ABIType>,
+}
+
+/// Pops the arguments of a typed call, from the value to the gas price. The
+/// call is from the cross space contract. Returns `Ok(None)` if the signature,
+/// the arguments or the return types are malformed.
+fn make_typed_call(
+    caller_info: String,
+    arguments: &mut VecDeque<Value>,
+    evm_gas_budget: u64,
+    depth: usize,
+) -> PartialVMResult<Option<TypedCall>> {
+    let err = PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR);
+
+    let (gas, gas_price) = pop_gas(arguments, evm_gas_budget)?;
+
+    let return_types_value = arguments.pop_back().ok_or_else(|| err.clone())?;
+    let return_types = get_move_string(return_types_value)?;
+
+    let args = pop_bytes_list(arguments)?;
+
+    let signature_value = arguments.pop_back().ok_or_else(|| err.clone())?;
+    let signature = get_move_string(signature_value)?;
+
+    let (receiver, value) = pop_receiver_and_value(arguments, true)?;
+
+    let encode = || -> anyhow::Result<(String, Vec<u8>, Vec<ABIType>)> {
+        let (function_name, types) =
+            parse_signature(&signature).map_err(|e| anyhow::anyhow!(e.0))?;
+        if types.len() != args.len() {
+            anyhow::bail!("Incorrect number of arguments");
+        }
+        let values = types
+            .iter()
+            .zip(&args)
+            .map(|(ty, arg)| {
+                let move_value = MoveValue::simple_deserialize(arg, &abi_type_to_layout(ty))?;
+                move_value_to_abi(move_value, ty)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let encoded = encode_list(&types, &values).map_err(|e| anyhow::anyhow!(e.0))?;
+        let selector = function_selector(&function_name, &types);
+        let return_types = parse_type_list(&return_types).map_err(|e| anyhow::anyhow!(e.0))?;
+        Ok((
+            function_name,
+            [&selector[..], &encoded[..]].concat(),
+            return_types,
+        ))
+    };
+    let (function_name, calldata, return_types) = match encode() {
+        Ok(res) => res,
+        Err(_) => return Ok(None),
     };
 
     let params = CrossVMParams {
+        sender: *CROSS_SPACE_CONTRACT_ADDRESS,
         receiver,
         function_name,
         gas,
         gas_price,
         value,
-        evm_params,
+        evm_params: vec![],
         caller_info,
+        calldata: Some(calldata),
+        depth,
     };
-    Ok(params)
+    Ok(Some(TypedCall {
+        params,
+        return_types,
+    }))
 }
-// cross_vm:: call_evm
-fn native_call_evm(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut arguments: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    let struct_tag = if let TypeTag::Struct(struct_tag) = context.type_to_type_tag(&ty_args[0])? {
-        struct_tag
-    } else {
-        return Ok(NativeResult::err(0.into(), abort_codes::INCORRECT_TYPE_TAG));
-    };
-    if !struct_tag.type_params.is_empty() {
-        return Ok(NativeResult::err(0.into(), abort_codes::INCORRECT_TYPE_TAG));
+
+fn struct_tag_of_call_type(
+    context: &NativeContext,
+    ty_args: &[Type],
+) -> PartialVMResult<Option<Box<StructTag>>> {
+    match context.type_to_type_tag(&ty_args[0])? {
+        TypeTag::Struct(struct_tag) if struct_tag.type_params.is_empty() => Ok(Some(struct_tag)),
+        _ => Ok(None),
     }
+}
 
-    let executor = if let Some(CrossVMContext { executor }) =
-        context.extensions_mut().get_mut::<Option<CrossVMContext>>()
-    {
-        executor
-    } else {
-        println!("Pick Executor Fail");
-        println!("Custom backtrace: {}", std::backtrace::Backtrace::force_capture());
-        return Ok(NativeResult::err(0.into(), abort_codes::CANNOT_CALL_EVM));
-    };
+/// How a call into the EVM ended.
+enum EvmCallOutcome {
+    Success(ReturnData),
+    /// The callee executed `REVERT`, with the revert data.
+    Reverted(ReturnData),
+    /// The callee halted exceptionally, e.g. it ran out of gas.
+    Failed(cfx_evm::vm::Error),
+}
 
-    let params = make_cross_vm_params(struct_tag, &mut arguments)?;
-    // println!("Call to EVM Params: {:?}", &params);
-    let output = executor.cross_vm_call(params).expect("no db error");
+/// Runs the EVM call and accounts its gas. Returns how the call ended and its cost, or a
+/// `STORAGE_ERROR` if the EVM state cannot be read.
+fn run_cross_vm_call(
+    cross_vm_context: &mut CrossVMContext,
+    params: CrossVMParams,
+) -> PartialVMResult<(EvmCallOutcome, InternalGas)> {
+    let gas_limit = params.gas;
+    *cross_vm_context.called_evm = true;
+    // The transaction fails as if its own read failed, and is retried if the read was speculative.
+    let output = cross_vm_context
+        .executor
+        .cross_vm_call(params)
+        .map_err(|e| {
+            PartialVMError::new(StatusCode::STORAGE_ERROR)
+                .with_message(format!("Cannot read the EVM state: {}", e))
+        })?;
 
-    // FIXME(zeroxg): we drop the substate in this demo
-    // substate.accrue(output.substate);
-    match output.result {
-        Err(err) => {
-            println!("EVM Execution Error: {:?}", err);
-            Ok(NativeResult::err(0.into(), abort_codes::EVM_CALL_REVERT))
-        },
+    // A failed call consumes the whole limit, as in a top-level EVM transaction.
+    let gas_used = match &output.result {
+        Ok(FinalizationResult { gas_left, .. }) => gas_limit - *gas_left,
+        Err(_) => gas_limit,
+    };
+    let cost = evm_gas_to_internal_gas(gas_used);
+
+    let outcome = match output.result {
+        Err(err) => EvmCallOutcome::Failed(err),
         Ok(FinalizationResult {
             apply_state: false,
             return_data,
             ..
-        }) => {
-            println!("{:?}", return_data);
-            println!(
-                "EVM Reverted {:?}",
+        }) => EvmCallOutcome::Reverted(return_data),
+        Ok(res) => {
+            cross_vm_context.substate.accrue(output.substate);
+            EvmCallOutcome::Success(res.return_data)
+        },
+    };
+    Ok((outcome, cost))
+}
+
+/// Runs the EVM call and aborts the Move transaction if it does not succeed. Returns the
+/// return data and the cost of a successful call, or the `NativeResult` the native aborts with.
+fn execute_cross_vm_call(
+    cross_vm_context: &mut CrossVMContext,
+    params: CrossVMParams,
+) -> PartialVMResult<Result<(ReturnData, InternalGas), NativeResult>> {
+    let gas_limit = params.gas;
+    // Only a limit capped by the budget means that the Move transaction ran out of gas; a callee
+    // running past the limit of the caller fails like any other.
+    let capped_by_budget = gas_limit >= U256::from(cross_vm_context.evm_gas_budget());
+    let (outcome, cost) = run_cross_vm_call(cross_vm_context, params)?;
+
+    match outcome {
+        EvmCallOutcome::Failed(cfx_evm::vm::Error::OutOfGas) if capped_by_budget => {
+            Err(PartialVMError::new(StatusCode::OUT_OF_GAS)
+                .with_message(format!("EVM call ran out of gas with limit {}", gas_limit)))
+        },
+        EvmCallOutcome::Failed(err) => {
+            debug!("EVM call failed: {:?}", err);
+            *cross_vm_context.evm_abort_info = Some(AbortInfo {
+                reason_name: EVM_CALL_REVERT_NAME.into(),
+                description: format!("EVM call failed: {}", err),
+            });
+            Ok(Err(NativeResult::err(cost, abort_codes::EVM_CALL_REVERT)))
+        },
+        EvmCallOutcome::Reverted(return_data) => {
+            debug!(
+                "EVM call reverted: {}",
                 revert_reason_decode(return_data.deref())
             );
-            Ok(NativeResult::err(0.into(), abort_codes::EVM_CALL_REVERT))
-        },
-        Ok(res) => {
-            let decoded = if res.return_data.is_empty() {
-                vec![]
-            } else {
-                match Vec::<u8>::abi_decode(res.return_data.as_ref()) {
-                    Err(_) => {
-                        return Ok(NativeResult::err(0.into(), abort_codes::MALFORMED_OUTPUT));
-                    },
-                    Ok(v) => v,
-                }
-            };
-            match String::from_utf8(decoded.clone()) {
-                Ok(string) => {
-                    // println!("Decode output success (string): {}", string);
-                },
-                Err(_) => {
-                    // println!("Decode output success (hex): 0x{}", hex::encode(&decoded));
-                },
-            }
-
-            Ok(NativeResult::ok(0.into(), smallvec![Value::vector_u8(
-                decoded
-            )]))
+            // The raw revert data, so that custom errors can be decoded as well.
+            *cross_vm_context.evm_abort_info = Some(AbortInfo {
+                reason_name: EVM_CALL_REVERT_NAME.into(),
+                description: format!("0x{}", hex::encode(return_data.deref())),
+            });
+            Ok(Err(NativeResult::err(cost, abort_codes::EVM_CALL_REVERT)))
         },
+        EvmCallOutcome::Success(return_data) => Ok(Ok((return_data, cost))),
     }
 }
 
+/// Decodes the `bytes` returned by the functions `call_evm` calls.
+fn decode_bytes_output(return_data: &ReturnData) -> Option<Vec<u8>> {
+    if return_data.is_empty() {
+        Some(vec![])
+    } else {
+        Vec::<u8>::abi_decode(return_data.as_ref()).ok()
+    }
+}
+
+/// Runs `call` with the struct tag of the call type `ty_args[0]`, like `with_cross_vm_context`.
+/// The native aborts with `INCORRECT_TYPE_TAG` if the call type is not a struct without type
+/// parameters.
+fn with_call_type(
+    context: &mut NativeContext,
+    ty_args: &[Type],
+    call: impl FnOnce(Box<StructTag>, &mut CrossVMContext, usize) -> PartialVMResult<NativeResult>,
+) -> PartialVMResult<NativeResult> {
+    let struct_tag = match struct_tag_of_call_type(context, ty_args)? {
+        Some(struct_tag) => struct_tag,
+        None => return Ok(NativeResult::err(0.into(), abort_codes::INCORRECT_TYPE_TAG)),
+    };
+    with_cross_vm_context(context, |cross_vm_context, depth| {
+        call(struct_tag, cross_vm_context, depth)
+    })
+}
+
+/// Runs `call` with the cross-VM context and the depth of the EVM frame of the call. The native
+/// aborts before calling into the EVM with `CANNOT_CALL_EVM` without a cross-VM context, and
+/// with `CALL_DEPTH_EXCEEDED` past the call depth limit.
+fn with_cross_vm_context(
+    context: &mut NativeContext,
+    call: impl FnOnce(&mut CrossVMContext, usize) -> PartialVMResult<NativeResult>,
+) -> PartialVMResult<NativeResult> {
+    let cross_vm_context = if let Some(cross_vm_context) =
+        context.extensions_mut().get_mut::<Option<CrossVMContext>>()
+    {
+        cross_vm_context
+    } else {
+        return Ok(NativeResult::err(0.into(), abort_codes::CANNOT_CALL_EVM));
+    };
+    let depth = match cross_vm_context.callee_depth() {
+        Some(depth) => depth,
+        None => {
+            return Ok(NativeResult::err(
+                0.into(),
+                abort_codes::CALL_DEPTH_EXCEEDED,
+            ))
+        },
+    };
+    call(cross_vm_context, depth)
+}
+
+// cross_vm::call_evm_with_value
+fn native_call_evm(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    with_call_type(context, &ty_args, |struct_tag, cross_vm_context, depth| {
+        let params = make_cross_vm_params(
+            struct_tag,
+            &mut arguments,
+            cross_vm_context.evm_gas_budget(),
+            depth,
+            true,
+        )?;
+        let (return_data, cost) = match execute_cross_vm_call(cross_vm_context, params)? {
+            Ok(res) => res,
+            Err(abort) => return Ok(abort),
+        };
+
+        let decoded = match decode_bytes_output(&return_data) {
+            Some(decoded) => decoded,
+            None => return Ok(NativeResult::err(cost, abort_codes::MALFORMED_OUTPUT)),
+        };
+
+        Ok(NativeResult::ok(cost, smallvec![Value::vector_u8(decoded)]))
+    })
+}
+
+// cross_vm::try_call_evm_with_gas
+fn native_try_call_evm(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    with_call_type(context, &ty_args, |struct_tag, cross_vm_context, depth| {
+        let params = make_cross_vm_params(
+            struct_tag,
+            &mut arguments,
+            cross_vm_context.evm_gas_budget(),
+            depth,
+            false,
+        )?;
+        let (outcome, cost) = run_cross_vm_call(cross_vm_context, params)?;
+
+        let (status, return_data) = match outcome {
+            EvmCallOutcome::Success(return_data) => match decode_bytes_output(&return_data) {
+                Some(decoded) => (evm_call_status::SUCCESS, decoded),
+                None => return Ok(NativeResult::err(cost, abort_codes::MALFORMED_OUTPUT)),
+            },
+            EvmCallOutcome::Reverted(return_data) => {
+                (evm_call_status::REVERTED, return_data.deref().to_vec())
+            },
+            EvmCallOutcome::Failed(_) => (evm_call_status::FAILED, vec![]),
+        };
+
+        Ok(NativeResult::ok(
+            cost,
+            smallvec![Value::struct_(Struct::pack(vec![
+                Value::u8(status),
+                Value::vector_u8(return_data)
+            ]))],
+        ))
+    })
+}
+
+// cross_vm::call_evm_typed_with_value
+fn native_call_evm_typed(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    with_call_type(context, &ty_args, |struct_tag, cross_vm_context, depth| {
+        let _call_cap = arguments
+            .pop_back()
+            .ok_or_else(|| PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR))?;
+        let call = match make_typed_call(
+            caller_info(&struct_tag),
+            &mut arguments,
+            cross_vm_context.evm_gas_budget(),
+            depth,
+        )? {
+            Some(call) => call,
+            None => return Ok(NativeResult::err(0.into(), abort_codes::MALFORMED_INPUT)),
+        };
+        execute_typed_call(cross_vm_context, call)
+    })
+}
+
+// cross_vm::call_evm_typed_from
+fn native_call_evm_typed_from(
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    with_cross_vm_context(context, |cross_vm_context, depth| {
+        let mut call = match make_typed_call(
+            String::new(),
+            &mut arguments,
+            cross_vm_context.evm_gas_budget(),
+            depth,
+        )? {
+            Some(call) => call,
+            None => return Ok(NativeResult::err(0.into(), abort_codes::MALFORMED_INPUT)),
+        };
+        call.params.sender = pop_evm_address(&mut arguments)?;
+        execute_typed_call(cross_vm_context, call)
+    })
+}
+
+/// Runs a typed call, and returns its BCS encoded return values.
+fn execute_typed_call(
+    cross_vm_context: &mut CrossVMContext,
+    call: TypedCall,
+) -> PartialVMResult<NativeResult> {
+    let TypedCall {
+        params,
+        return_types,
+    } = call;
+    let (return_data, cost) = match execute_cross_vm_call(cross_vm_context, params)? {
+        Ok(res) => res,
+        Err(abort) => return Ok(abort),
+    };
+
+    let decode = || -> anyhow::Result<Vec<Value>> {
+        let values =
+            decode_list(&return_types, return_data.as_ref()).map_err(|e| anyhow::anyhow!(e.0))?;
+        values
+            .into_iter()
+            .zip(&return_types)
+            .map(|(value, ty)| {
+                let move_value = abi_to_move_value(value, &abi_type_to_layout(ty))?;
+                Ok(Value::vector_u8(move_value.simple_serialize().ok_or_else(
+                    || anyhow::anyhow!("Cannot serialize Move value"),
+                )?))
+            })
+            .collect()
+    };
+    let outputs = match decode() {
+        Ok(outputs) => outputs,
+        Err(_) => return Ok(NativeResult::err(cost, abort_codes::MALFORMED_OUTPUT)),
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&Type::Vector(Box::new(Type::U8)), outputs)?],
+    ))
+}
+
 pub fn make_all() -> impl Iterator<Item = (String, NativeFunction)> {
-    let func: NativeFunction =
+    let call_evm: NativeFunction =
         Arc::new(move |context, ty_args, args| native_call_evm(context, ty_args, args));
+    let try_call_evm: NativeFunction =
+        Arc::new(move |context, ty_args, args| native_try_call_evm(context, ty_args, args));
+    let call_evm_typed: NativeFunction =
+        Arc::new(move |context, ty_args, args| native_call_evm_typed(context, ty_args, args));
+    let call_evm_typed_from: NativeFunction =
+        Arc::new(move |context, ty_args, args| native_call_evm_typed_from(context, ty_args, args));
 
-    let natives = [("call_evm", func)];
+    let natives = [
+        ("call_evm_with_value", call_evm),
+        ("try_call_evm_with_gas", try_call_evm),
+        ("call_evm_typed_with_value", call_evm_typed),
+        ("call_evm_typed_from", call_evm_typed_from),
+    ];
 
     crate::natives::helpers::make_module_natives(natives)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_limit_is_capped_by_move_gas_left() {
+        let move_gas_left = MoveGasLeft::default();
+        move_gas_left
+            .clone()
+            .set(InternalGas::new(50_000 * INTERNAL_GAS_PER_EVM_GAS + 99));
+        let budget = internal_gas_to_evm_gas(move_gas_left.get());
+        assert_eq!(budget, 50_000);
+
+        let mut arguments = VecDeque::from(vec![Value::u64(10_000_000), Value::u64(7)]);
+        let (gas, gas_price) = pop_gas(&mut arguments, budget).unwrap();
+        assert_eq!(gas, U256::from(50_000));
+        assert_eq!(gas_price, U256::from(7));
+
+        let mut arguments = VecDeque::from(vec![Value::u64(21_000), Value::u64(1)]);
+        let (gas, _) = pop_gas(&mut arguments, budget).unwrap();
+        assert_eq!(gas, U256::from(21_000));
+    }
+
+    #[test]
+    fn evm_gas_used_is_charged_in_internal_gas() {
+        let cost = evm_gas_to_internal_gas(U256::from(21_000));
+        assert_eq!(cost, InternalGas::new(21_000 * INTERNAL_GAS_PER_EVM_GAS));
+        assert_eq!(internal_gas_to_evm_gas(cost), 21_000);
+        assert_eq!(
+            evm_gas_to_internal_gas(U256::MAX),
+            InternalGas::new(u64::MAX)
+        );
+    }
+}
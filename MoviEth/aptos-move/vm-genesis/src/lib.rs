@@ -307,15 +307,20 @@ pub fn encode_genesis_change_set(
             cache: Default::default(),
         };
         let mut state = EvmState::new(&mut view_wrapper);
+        let evm_genesis_balance =
+            U256::from(5_000_000_000u64) * U256::from(1_000_000_000_000_000_000u64);
         state
             .state
             .add_balance(
                 &evm_genesis_account.with_evm_space(),
-                &(U256::from(5_000_000_000u64) * U256::from(1_000_000_000_000_000_000u64)),
+                &evm_genesis_balance,
                 CleanupMode::ForceCreate,
                 0.into(),
             )
             .unwrap();
+        // Counted in the EVM issuance, so that the native token supply of both VMs is checked
+        // against the genesis supply.
+        state.state.add_total_issued(evm_genesis_balance);
 
 
         state.state.commit(Default::default(), None).unwrap();
@@ -2,11 +2,12 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+pub mod native_token;
 pub mod state_trait;
 pub mod tracer;
 
 use cfx_types::AddressWithSpace;
-pub use state_trait::{CallMoveVMTrait, StateTrait};
+pub use state_trait::{CallMoveVMError, CallMoveVMTrait, StateTrait};
 use std::collections::HashSet;
 
 /// Mode of dealing with null accounts.
@@ -0,0 +1,93 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Accounting of the native token moved between the EVM, which counts it in
+//! wei, and the Move VM, which counts `AptosCoin` in octas. Value leaving one
+//! side is burned there and issued on the other side, so that the total supply
+//! is conserved.
+
+use cfx_types::U256;
+
+/// One APT is 10^8 octas in the Move VM and 10^18 wei in the EVM.
+pub const WEI_PER_OCTA: u64 = 10_000_000_000;
+
+/// The octas worth `wei`, or `None` if `wei` is not a whole number of octas
+/// or does not fit in a `u64`. Sub-octa dust is never truncated, since it
+/// would be burned in the EVM without being issued in the Move VM.
+pub fn wei_to_octas(wei: U256) -> Option<u64> {
+    let (octas, dust) = wei.div_mod(U256::from(WEI_PER_OCTA));
+    if !dust.is_zero() || octas > U256::from(u64::MAX) {
+        return None;
+    }
+    Some(octas.as_u64())
+}
+
+pub fn octas_to_wei(octas: u64) -> U256 {
+    U256::from(octas) * U256::from(WEI_PER_OCTA)
+}
+
+/// Native token supply of both VMs at some version.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NativeTokenSupply {
+    /// Supply of `AptosCoin`, in octas.
+    pub move_supply: u128,
+    /// Total issued tokens of the EVM state, in wei.
+    pub evm_issued: U256,
+}
+
+impl NativeTokenSupply {
+    /// The supply of both VMs, in wei.
+    pub fn total(&self) -> U256 {
+        U256::from(self.move_supply) * U256::from(WEI_PER_OCTA) + self.evm_issued
+    }
+
+    /// Checks that moving value between the VMs has neither created nor
+    /// destroyed any since `genesis`. `burned` is the value burned since then
+    /// on purpose, e.g. gas fees, in wei.
+    pub fn check_conserved(&self, genesis: &NativeTokenSupply, burned: U256) -> Result<(), String> {
+        let expected = genesis.total().checked_sub(burned);
+        if expected != Some(self.total()) {
+            return Err(format!(
+                "Native token supply is {} wei (Move {} octas, EVM {} wei), expected {} wei \
+                 from {} wei at genesis and {} wei burned",
+                self.total(),
+                self.move_supply,
+                self.evm_issued,
+                expected.unwrap_or_default(),
+                genesis.total(),
+                burned,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wei_to_octas() {
+        assert_eq!(wei_to_octas(U256::zero()), Some(0));
+        assert_eq!(wei_to_octas(octas_to_wei(42)), Some(42));
+        assert_eq!(wei_to_octas(octas_to_wei(42) + 1), None);
+        assert_eq!(wei_to_octas(octas_to_wei(u64::MAX)), Some(u64::MAX));
+        assert_eq!(wei_to_octas(octas_to_wei(u64::MAX) * 2), None);
+    }
+
+    #[test]
+    fn test_check_conserved() {
+        let genesis = NativeTokenSupply {
+            move_supply: 100,
+            evm_issued: octas_to_wei(50),
+        };
+        // 20 octas moved from the EVM to Move, and 5 octas of fees burned.
+        let current = NativeTokenSupply {
+            move_supply: 115,
+            evm_issued: octas_to_wei(30),
+        };
+        assert!(current.check_conserved(&genesis, octas_to_wei(5)).is_ok());
+        assert!(current.check_conserved(&genesis, U256::zero()).is_err());
+    }
+}
@@ -10,9 +10,58 @@ pub trait StateTrait: CheckpointTrait + AsStateOpsTrait {
     ) -> DbResult<()>;
 }
 
+/// Why a call into the Move VM failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallMoveVMError {
+    /// The Move VM stopped with a status code, e.g. `ABORTED` with the abort
+    /// code as `sub_status`. `location` is the module where it happened.
+    Vm {
+        status: u64,
+        sub_status: Option<u64>,
+        location: String,
+        message: String,
+    },
+    /// The call could not be made, e.g. the arguments are malformed.
+    Other(String),
+}
+
+impl From<String> for CallMoveVMError {
+    fn from(msg: String) -> Self {
+        CallMoveVMError::Other(msg)
+    }
+}
+
+impl std::fmt::Display for CallMoveVMError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CallMoveVMError::Vm {
+                status,
+                sub_status,
+                location,
+                message,
+            } => write!(
+                f,
+                "Move VM error {} (sub status {:?}) at {}: {}",
+                status, sub_status, location, message
+            ),
+            CallMoveVMError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Runs the calls of the EVM into the Move VM. The Move code of a call can
+/// call back into the EVM on the state of the caller, so the calls take `&self`
+/// and can be nested in each other.
 pub trait CallMoveVMTrait {
+    /// Calls a Move function with `gas` EVM gas as the Move gas allowance.
+    /// The gas actually used is reported by `last_call_gas_used`, and the rest
+    /// of the allowance is refunded to the caller. The Move code calls back
+    /// into the EVM on `state`, and `depth` is the call depth of the Move call,
+    /// which its calls into the EVM continue from.
     fn call_move_vm(
-        &mut self,
+        &self,
+        state: &mut dyn StateTrait,
+        depth: usize,
         caller: Address,
         address: Vec<u8>,
         module: String,
@@ -21,27 +70,68 @@ pub trait CallMoveVMTrait {
         types: Vec<TypeTag>,
         value: U256,
         gas: U256,
-    ) -> Result<Vec<u8>, String>;
-}
-
-impl CallMoveVMTrait for &mut dyn CallMoveVMTrait {
-    fn call_move_vm(
-        &mut self,
+    ) -> Result<Vec<u8>, CallMoveVMError>;
+
+    /// Like `call_move_vm`, but `data` is the ABI encoding of the Move
+    /// function parameters and the Move return values are ABI encoded.
+    fn call_move_vm_typed(
+        &self,
+        state: &mut dyn StateTrait,
+        depth: usize,
         caller: Address,
         address: Vec<u8>,
         module: String,
         function: String,
-        data: Vec<Vec<u8>>,
+        data: Vec<u8>,
         types: Vec<TypeTag>,
         value: U256,
         gas: U256,
-    ) -> Result<Vec<u8>, String> {
-        (*self).call_move_vm(caller, address, module, function, data, types, value, gas)
-    }
+    ) -> Result<Vec<u8>, CallMoveVMError>;
+
+    /// Like `call_move_vm_typed`, but calls an entry function with the signer
+    /// of the Move account linked to `caller`, which must have delegated its
+    /// signer for the function. The value is issued to that account.
+    fn call_move_vm_as_signer(
+        &self,
+        state: &mut dyn StateTrait,
+        depth: usize,
+        caller: Address,
+        address: Vec<u8>,
+        module: String,
+        function: String,
+        data: Vec<u8>,
+        types: Vec<TypeTag>,
+        value: U256,
+        gas: U256,
+    ) -> Result<Vec<u8>, CallMoveVMError>;
+
+    /// Move internal gas used by the last call, whether it succeeded or not,
+    /// including the storage gas of its write set. It never exceeds the
+    /// allowance of the call, at `INTERNAL_GAS_PER_EVM_GAS` internal gas per
+    /// EVM gas.
+    fn last_call_gas_used(&self) -> u64;
+
+    /// Called when the EVM state creates a checkpoint. Move changes made
+    /// after it are kept or dropped together with the EVM changes.
+    fn checkpoint(&self);
+
+    /// Merge the Move changes since the last checkpoint with the previous
+    /// one.
+    fn discard_checkpoint(&self);
+
+    /// Drop the Move changes since the last checkpoint and discard it.
+    fn revert_to_checkpoint(&self);
 }
 
 pub trait StateOpsTrait {
-    fn as_move_vm(&mut self) -> Option<&mut dyn CallMoveVMTrait>;
+    /// Runs `f` with the Move VM and the state itself, so that the Move code
+    /// can call back into the EVM on the state. Returns `false` without
+    /// running `f` if the state has no Move VM.
+    fn with_move_vm(
+        &mut self,
+        f: &mut dyn FnMut(&dyn CallMoveVMTrait, &mut dyn StateTrait),
+    ) -> bool;
+
     /// Maintain `total_issued_tokens`.s
     fn add_total_issued(&mut self, v: U256);
 
@@ -49,6 +139,14 @@ pub trait StateOpsTrait {
     /// unlikely case that there are a lot of partial invalid blocks.
     fn subtract_total_issued(&mut self, v: U256);
 
+    /// Issues `v` moved into the EVM from the Move VM. Unlike
+    /// `add_total_issued`, it can be called during execution, and is reverted
+    /// together with the checkpoint it is made in.
+    fn issue_cross_vm(&mut self, v: U256);
+
+    /// Burns `v` moved out of the EVM into the Move VM, see `issue_cross_vm`.
+    fn burn_cross_vm(&mut self, v: U256);
+
     fn new_contract(
         &mut self,
         contract: &AddressWithSpace,
@@ -104,6 +202,10 @@ pub trait StateOpsTrait {
 
     fn total_issued_tokens(&self) -> U256;
 
+    /// The tokens issued and burned by `issue_cross_vm` and `burn_cross_vm`
+    /// since the state was created, excluding reverted checkpoints.
+    fn cross_vm_tokens(&self) -> (U256, U256);
+
     fn remove_contract(&mut self, address: &AddressWithSpace) -> DbResult<()>;
 
     fn exists(&self, address: &AddressWithSpace) -> DbResult<bool>;
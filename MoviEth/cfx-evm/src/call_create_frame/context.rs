@@ -169,8 +169,16 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
 
     fn blockhash(&mut self, number: &U256) -> H256 {
         if self.local_part.space == Space::Ethereum && self.local_part.spec.cip98 {
-            return if U256::from(self.env().epoch_height) == number + 1 {
-                self.env().last_hash.clone()
+            let env = self.env();
+            let height = U256::from(env.epoch_height);
+            if *number >= height {
+                return H256::default();
+            }
+            let distance = height - number;
+            return if distance == U256::one() {
+                env.last_hash.clone()
+            } else if distance <= U256::from(env.last_hashes.len()) {
+                env.last_hashes[env.last_hashes.len() - distance.as_usize()].clone()
             } else {
                 H256::default()
             };
@@ -306,6 +314,27 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
         return Ok(Err(TrapKind::Call(params)));
     }
 
+    fn is_warm_address(&self, address: &Address) -> bool {
+        self.callstack.accessed().is_warm_address(address)
+    }
+
+    fn is_warm_storage_key(&self, key: &Vec<u8>) -> bool {
+        self.callstack
+            .accessed()
+            .is_warm_storage_key(&self.local_part.origin.address, &H256::from_slice(key))
+    }
+
+    fn warm_address(&mut self, address: &Address) {
+        self.callstack.accessed_mut().warm_address(*address);
+    }
+
+    fn warm_storage_key(&mut self, key: &Vec<u8>) {
+        let address = self.local_part.origin.address;
+        self.callstack
+            .accessed_mut()
+            .warm_storage_key(address, H256::from_slice(key));
+    }
+
     fn extcode(&self, address: &Address) -> vm::Result<Option<Arc<Bytes>>> {
         let address = address.with_space(self.local_part.space);
         if let Some(contract) = self
@@ -442,6 +471,9 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
 
     fn chain_id(&self) -> u64 {
         let space = self.local_part.space;
+        if space == Space::Ethereum {
+            return self.local_part.env.chain_id;
+        }
         self.local_part
             .machine
             .params()
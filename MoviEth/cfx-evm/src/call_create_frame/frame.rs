@@ -332,9 +332,11 @@ impl<'a> CallCreateFrame<'a> {
             maybe_substate = Some(self.context.substate);
 
             state.discard_checkpoint();
+            callstack.accessed_mut().discard_checkpoint();
         } else {
             maybe_substate = None;
             state.revert_to_checkpoint();
+            callstack.accessed_mut().revert_to_checkpoint();
         }
 
         let executive_result = finalized_result
@@ -401,11 +403,16 @@ impl<'a> CallCreateFrame<'a> {
             tracer.record_call(&params);
         }
 
+        // The created or called account is warm even if the frame reverts
+        // (EIP-2929).
+        let contract_address = self.get_recipient().clone();
+        callstack.accessed_mut().warm_address(contract_address);
+
         // Make checkpoint for this executive, callstack is always maintained
         // with checkpoint.
         state.checkpoint();
+        callstack.accessed_mut().checkpoint();
 
-        let contract_address = self.get_recipient().clone();
         callstack.push(contract_address.with_space(self.context.space), is_create);
 
         // Pre execution: transfer value and init contract.
@@ -6,7 +6,7 @@ use super::{
 use crate::{
     evm::FinalizationResult,
     observer::MultiObservers as Observer,
-    state::{FrameStackInfo, Substate},
+    state::{AccessedSet, FrameStackInfo, Substate},
     vm::{self, TrapResult},
 };
 use cfx_state::StateTrait;
@@ -50,11 +50,12 @@ impl<'a> FrameStack<'a> {
         top_substate: Substate,
         observer: Observer,
         base_gas_required: u64,
+        accessed: AccessedSet,
     ) -> Self {
         FrameStack {
             state,
             frame_stack: vec![],
-            callstack: FrameStackInfo::new(),
+            callstack: FrameStackInfo::with_accessed(accessed),
             tx_substate: top_substate,
             observer,
             base_gas_required,
@@ -139,19 +139,51 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                     let newval = stack.peek(1);
                     let val = context.storage_at(&key.to_vec())?;
 
-                    if val.is_zero() && !newval.is_zero() {
+                    let gas = if val.is_zero() && !newval.is_zero() {
                         spec.sstore_set_gas * spec.evm_gas_ratio
+                    } else if spec.eip2929 {
+                        spec.sstore_reset_gas - spec.cold_sload_gas
                     } else {
                         spec.sstore_reset_gas
+                    };
+                    if spec.eip2929 && !context.is_warm_storage_key(&key) {
+                        gas + spec.cold_sload_gas
+                    } else {
+                        gas
                     }
                 };
 
                 Request::Gas(Gas::from(gas))
             },
-            instructions::SLOAD => Request::Gas(Gas::from(spec.sload_gas)),
-            instructions::BALANCE => Request::Gas(Gas::from(spec.balance_gas)),
-            instructions::EXTCODESIZE => Request::Gas(Gas::from(spec.extcodesize_gas)),
-            instructions::EXTCODEHASH => Request::Gas(Gas::from(spec.extcodehash_gas)),
+            instructions::SLOAD => {
+                let gas = if spec.eip2929 {
+                    let mut key = vec![0; 32];
+                    stack.peek(0).to_big_endian(key.as_mut());
+                    if context.is_warm_storage_key(&key) {
+                        spec.warm_storage_read_gas
+                    } else {
+                        spec.cold_sload_gas
+                    }
+                } else {
+                    spec.sload_gas
+                };
+                Request::Gas(Gas::from(gas))
+            },
+            instructions::BALANCE => Request::Gas(Gas::from(account_access_gas(
+                context,
+                stack.peek(0),
+                spec.balance_gas,
+            ))),
+            instructions::EXTCODESIZE => Request::Gas(Gas::from(account_access_gas(
+                context,
+                stack.peek(0),
+                spec.extcodesize_gas,
+            ))),
+            instructions::EXTCODEHASH => Request::Gas(Gas::from(account_access_gas(
+                context,
+                stack.peek(0),
+                spec.extcodehash_gas,
+            ))),
             instructions::SUICIDE => {
                 let mut gas = Gas::from(spec.suicide_gas);
 
@@ -171,6 +203,9 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                         gas.overflow_add((spec.suicide_to_new_account_cost * ratio).into())
                     );
                 }
+                if spec.eip2929 && !context.is_warm_address(&address) {
+                    gas = overflowing!(gas.overflow_add(spec.cold_account_access_gas.into()));
+                }
 
                 Request::Gas(gas)
             },
@@ -200,7 +235,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 )
             },
             instructions::EXTCODECOPY => Request::GasMemCopy(
-                spec.extcodecopy_base_gas.into(),
+                account_access_gas(context, stack.peek(0), spec.extcodecopy_base_gas).into(),
                 mem_needed(stack.peek(1), stack.peek(3))?,
                 Gas::from_u256(*stack.peek(3))?,
             ),
@@ -221,7 +256,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 Request::GasMem(gas, mem_needed(stack.peek(0), stack.peek(1))?)
             },
             instructions::CALL | instructions::CALLCODE => {
-                let mut gas = Gas::from(spec.call_gas);
+                let mut gas = Gas::from(account_access_gas(context, stack.peek(1), spec.call_gas));
                 let mem = cmp::max(
                     mem_needed(stack.peek(5), stack.peek(6))?,
                     mem_needed(stack.peek(3), stack.peek(4))?,
@@ -254,7 +289,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 Request::GasMemProvide(gas, mem, Some(requested))
             },
             instructions::DELEGATECALL | instructions::STATICCALL => {
-                let gas = Gas::from(spec.call_gas);
+                let gas = Gas::from(account_access_gas(context, stack.peek(1), spec.call_gas));
                 let mem = cmp::max(
                     mem_needed(stack.peek(4), stack.peek(5))?,
                     mem_needed(stack.peek(2), stack.peek(3))?,
@@ -368,6 +403,20 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
     }
 }
 
+/// The price of an instruction accessing the account at `address`: `gas`
+/// before EIP-2929, and the cold or the warm access price after it.
+#[inline]
+fn account_access_gas(context: &dyn vm::Context, address: &U256, gas: usize) -> usize {
+    let spec = context.spec();
+    if !spec.eip2929 {
+        gas
+    } else if context.is_warm_address(&u256_to_address(address)) {
+        spec.warm_storage_read_gas
+    } else {
+        spec.cold_account_access_gas
+    }
+}
+
 #[inline]
 fn mem_needed_const<Gas: evm::CostType>(mem: &U256, add: usize) -> vm::Result<Gas> {
     Gas::from_u256(overflowing!(mem.overflowing_add(U256::from(add))))
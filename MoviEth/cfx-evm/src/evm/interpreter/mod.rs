@@ -768,6 +768,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                 let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`; qed");
                 let code_address = self.stack.pop_back();
                 let code_address = u256_to_address(&code_address);
+                context.warm_address(&code_address);
 
                 let value = if instruction == instructions::DELEGATECALL {
                     None
@@ -919,6 +920,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::SUICIDE => {
                 let address = self.stack.pop_back();
                 let refund_address = u256_to_address(&address);
+                context.warm_address(&refund_address);
                 context.suicide(&refund_address, tracer, context.spec().account_start_nonce)?;
                 return Ok(InstructionResult::StopExecution);
             },
@@ -1005,6 +1007,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::SLOAD => {
                 let mut key = vec![0; 32];
                 self.stack.pop_back().to_big_endian(key.as_mut());
+                context.warm_storage_key(&key);
                 let word = context.storage_at(&key)?;
                 self.stack.push(word);
             },
@@ -1013,6 +1016,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                 self.stack.pop_back().to_big_endian(key.as_mut());
                 let val = self.stack.pop_back();
 
+                context.warm_storage_key(&key);
                 context.set_storage(key, val)?;
             },
             instructions::PC => {
@@ -1031,6 +1035,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             },
             instructions::BALANCE => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.warm_address(&address);
                 let balance = context.balance(&address)?;
                 self.stack.push(balance);
             },
@@ -1069,11 +1074,13 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::RETURNDATASIZE => self.stack.push(U256::from(self.return_data.len())),
             instructions::EXTCODESIZE => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.warm_address(&address);
                 let len = context.extcodesize(&address)?.unwrap_or(0);
                 self.stack.push(U256::from(len));
             },
             instructions::EXTCODEHASH => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.warm_address(&address);
                 let hash = context.extcodehash(&address)?.unwrap_or_else(H256::zero);
                 self.stack.push(hash.into_uint());
             },
@@ -1104,6 +1111,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             },
             instructions::EXTCODECOPY => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.warm_address(&address);
                 let code = context.extcode(&address)?;
                 Self::copy_data_to_memory(
                     &mut self.mem,
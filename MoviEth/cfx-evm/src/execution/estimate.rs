@@ -2,15 +2,10 @@ use super::{
     executed::{ExecutionError, ExecutionOutcome},
     TXExecutor, TransactOptions,
 };
-use cfx_parameters::consensus::ONE_CFX_IN_DRIP;
-use cfx_state::CleanupMode;
 use cfx_statedb::Result as DbResult;
-use cfx_types::{Address, AddressSpaceUtil, U256};
+use cfx_types::Address;
 use primitives::SignedTransaction;
-use std::{
-    cmp::{max, min},
-    ops::Shl,
-};
+use std::cmp::{max, min};
 
 #[derive(Debug, Clone, Copy)]
 pub struct EstimateRequest {
@@ -38,28 +33,10 @@ impl<'a> TXExecutor<'a> {
         request: EstimateRequest,
     ) -> DbResult<ExecutionOutcome> {
         if !request.has_sender {
-            let random_hex = Address::random();
-
-            tx.sender = random_hex;
+            // As in geth, a call without a sender runs from the zero address
+            // with the balance it has.
+            tx.sender = Address::zero();
             tx.public = None;
-
-            // If the sender is not specified, give it enough balance: 1 billion
-            // CFX.
-            let balance_inc = min(
-                tx.value()
-                    .saturating_add(U256::from(1_000_000_000) * ONE_CFX_IN_DRIP),
-                U256::one().shl(128),
-            );
-
-            self.state.add_balance(
-                &random_hex.with_space(tx.space()),
-                &balance_inc,
-                CleanupMode::NoEmpty,
-                self.spec.account_start_nonce,
-            )?;
-            // Make sure statistics are also correct and will not violate any
-            // underlying assumptions.
-            self.state.add_total_issued(balance_inc);
         }
 
         if request.has_nonce {
@@ -109,7 +86,7 @@ impl<'a> TXExecutor<'a> {
                 estimated_gas_limit - estimated_gas_limit / 4,
                 executed.gas_used,
             );
-            executed.fee = executed.gas_charged.saturating_mul(*tx.gas_price());
+            executed.fee = executed.gas_charged.saturating_mul(tx.effective_gas_price(&self.env.base_fee_per_gas));
         }
 
         // If the request has a sender, recheck the balance requirement matched.
@@ -120,7 +97,7 @@ impl<'a> TXExecutor<'a> {
                 executed
                     .estimated_gas_limit
                     .unwrap()
-                    .saturating_mul(*tx.gas_price())
+                    .saturating_mul(tx.effective_gas_price(&self.env.base_fee_per_gas))
             } else {
                 0.into()
             };
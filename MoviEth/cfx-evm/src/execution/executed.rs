@@ -56,6 +56,8 @@ pub enum TxDropError {
     OldNonce(U256, U256),
     ///
     NotEnoughBaseGas { expected: u64, actual: u64 },
+    /// The max fee per gas of the transaction is below the block base fee
+    GasPriceBelowBaseFee { base_fee: U256, gas_price: U256 },
 }
 
 #[derive(Debug, PartialEq)]
@@ -93,14 +95,15 @@ impl ExecutionOutcome {
 impl Executed {
     pub fn not_enough_balance_fee_charged(
         tx: &impl TransactionInfo,
+        gas_price: &U256,
         fee: &U256,
         trace: Vec<ExecTrace>,
         _spec: &Spec,
     ) -> Self {
-        let gas_charged = if *tx.gas_price() == U256::zero() {
+        let gas_charged = if gas_price.is_zero() {
             U256::zero()
         } else {
-            fee / *tx.gas_price()
+            fee / gas_price
         };
         Self {
             gas_used: *tx.gas(),
@@ -116,13 +119,14 @@ impl Executed {
 
     pub fn execution_error_fully_charged(
         tx: &impl TransactionInfo,
+        gas_price: &U256,
         trace: Vec<ExecTrace>,
         _spec: &Spec,
     ) -> Self {
         Self {
             gas_used: *tx.gas(),
             gas_charged: *tx.gas(),
-            fee: tx.gas().saturating_mul(*tx.gas_price()),
+            fee: tx.gas().saturating_mul(*gas_price),
             logs: vec![],
             contracts_created: vec![],
 
@@ -8,15 +8,17 @@ use crate::{
     evm::FinalizationResult,
     machine::Machine,
     observer::{AddressPocket, MultiObservers, StateTracer},
-    state::{cleanup_mode, Substate},
+    state::{cleanup_mode, AccessedSet, Substate},
     vm::{self, ActionParams, ActionValue, CallType, CreateContractAddress, CreateType, Env, Spec},
     vm_factory::VmFactory,
 };
-use cfx_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+use cfx_parameters::internal_contract_addresses::{
+    COIN_BRIDGE_CONTRACT_ADDRESS, CROSS_SPACE_CONTRACT_ADDRESS,
+};
 use cfx_state::StateTrait;
 use cfx_statedb::Result as DbResult;
 use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, Space, U256, U512};
-use primitives::transaction::Action;
+use primitives::{transaction::Action, AccessList};
 use solidity_abi::ABIEncodable;
 use std::{
     collections::HashSet,
@@ -27,14 +29,19 @@ use std::{
 /// Transaction executor.
 pub struct TXExecutor<'a> {
     pub(super) state: &'a mut dyn StateTrait,
-    env: &'a Env,
+    pub(super) env: &'a Env,
     machine: &'a Machine,
     factory: VmFactory,
     pub(super) spec: &'a Spec,
 }
 
-pub fn gas_required_for(is_create: bool, data: &[u8], spec: &Spec) -> u64 {
-    data.iter().fold(
+pub fn gas_required_for(
+    is_create: bool,
+    data: &[u8],
+    access_list: Option<&AccessList>,
+    spec: &Spec,
+) -> u64 {
+    let data_gas = data.iter().fold(
         (if is_create {
             spec.tx_create_gas
         } else {
@@ -46,7 +53,17 @@ pub fn gas_required_for(is_create: bool, data: &[u8], spec: &Spec) -> u64 {
                 _ => spec.tx_data_non_zero_gas,
             }) as u64
         },
-    )
+    );
+    let access_list_gas = access_list.map_or(0, |access_list| {
+        access_list
+            .iter()
+            .map(|item| {
+                spec.tx_access_list_address_gas as u64
+                    + spec.tx_access_list_storage_key_gas as u64 * item.storage_keys.len() as u64
+            })
+            .sum()
+    });
+    data_gas + access_list_gas
 }
 
 enum PreCheckResult<'a> {
@@ -59,6 +76,9 @@ enum PreCheckResult<'a> {
 
 #[derive(Debug)]
 pub struct CrossVMParams {
+    /// `msg.sender` of the call: the cross space contract, or the EVM address
+    /// linked to the calling Move account.
+    pub sender: Address,
     pub receiver: Address,
     pub function_name: String,
     pub gas: U256,
@@ -66,6 +86,13 @@ pub struct CrossVMParams {
     pub value: U256,
     pub evm_params: Vec<Vec<u8>>,
     pub caller_info: String,
+    /// ABI encoded calldata. If set, it is sent as-is instead of the
+    /// `(string,bytes[])` call built from `function_name` and `evm_params`.
+    pub calldata: Option<Vec<u8>>,
+    /// Call depth of the EVM frame of the call. It continues from the depth
+    /// of the Move code making the call, so that the EVM frames and cross-VM
+    /// calls of a transaction share the `max_depth` limit.
+    pub depth: usize,
 }
 
 pub struct CrossVMReturn {
@@ -75,6 +102,9 @@ pub struct CrossVMReturn {
 
 impl CrossVMParams {
     fn data(&self) -> Vec<u8> {
+        if let Some(calldata) = &self.calldata {
+            return calldata.clone();
+        }
         if self.function_name.is_empty() {
             return vec![];
         }
@@ -125,6 +155,27 @@ impl<'a> TXExecutor<'a> {
     }
 
     pub fn cross_vm_call(&mut self, params: CrossVMParams) -> DbResult<CrossVMReturn> {
+        if params.depth >= self.spec.max_depth {
+            return Ok(CrossVMReturn {
+                substate: Substate::new(),
+                result: Err(vm::Error::InternalContract(
+                    "Cross-VM call depth limit reached".into(),
+                )),
+            });
+        }
+        // The coin bridge trusts the caller info of the `(string,bytes[])`
+        // calls from the cross space contract, which a typed call could forge.
+        if params.calldata.is_some()
+            && params.sender == *CROSS_SPACE_CONTRACT_ADDRESS
+            && params.receiver == *COIN_BRIDGE_CONTRACT_ADDRESS
+        {
+            return Ok(CrossVMReturn {
+                substate: Substate::new(),
+                result: Err(vm::Error::InternalContract(
+                    "Typed calls cannot call the coin bridge".into(),
+                )),
+            });
+        }
         let pre_check_result = self.cross_vm_call_preprocessing(params)?;
 
         let (top_frame, frame_stack) = match pre_check_result {
@@ -141,6 +192,22 @@ impl<'a> TXExecutor<'a> {
         return Ok(CrossVMReturn { substate, result });
     }
 
+    /// The call depth limit shared by EVM frames and cross-VM calls.
+    pub fn max_call_depth(&self) -> usize {
+        self.spec.max_depth
+    }
+
+    /// Finalizes the substate accrued by the `cross_vm_call`s of one Move
+    /// transaction (does suicides). Returns the balance burned with the
+    /// killed contracts.
+    pub fn cross_vm_postprocessing(&mut self, substate: &mut Substate) -> DbResult<U256> {
+        let issued = self.state.total_issued_tokens();
+        let mut observer = MultiObservers::with_no_tracing();
+        let subsubstate = self.kill_process(&substate.suicides, observer.as_state_tracer())?;
+        substate.accrue(subsubstate);
+        Ok(issued - self.state.total_issued_tokens())
+    }
+
     fn transact_preprocessing(
         &mut self,
         tx: &impl TransactionInfo,
@@ -169,8 +236,24 @@ impl<'a> TXExecutor<'a> {
             ));
         }
 
-        let base_gas_required =
-            gas_required_for(&*tx.action() == &Action::Create, &tx.data(), spec);
+        let base_fee = self.env.base_fee_per_gas;
+        if check_settings.charge_gas && *tx.gas_price() < base_fee {
+            return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
+                TxDropError::GasPriceBelowBaseFee {
+                    base_fee,
+                    gas_price: *tx.gas_price(),
+                },
+            )));
+        }
+        let gas_price = tx.effective_gas_price(&base_fee);
+
+        let access_list = tx.access_list();
+        let base_gas_required = gas_required_for(
+            &*tx.action() == &Action::Create,
+            &tx.data(),
+            access_list.as_deref(),
+            spec,
+        );
         if *tx.gas() < base_gas_required.into() {
             return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
                 TxDropError::NotEnoughBaseGas {
@@ -182,7 +265,7 @@ impl<'a> TXExecutor<'a> {
 
         let balance = self.state.balance(&sender)?;
         let gas_cost = if check_settings.charge_gas {
-            tx.gas().full_mul(*tx.gas_price())
+            tx.gas().full_mul(gas_price)
         } else {
             0.into()
         };
@@ -230,6 +313,7 @@ impl<'a> TXExecutor<'a> {
                     },
                     Executed::not_enough_balance_fee_charged(
                         tx,
+                        &gas_price,
                         &actual_gas_cost,
                         observer.tracer.map_or(Default::default(), |t| t.drain()),
                         &self.spec,
@@ -284,7 +368,7 @@ impl<'a> TXExecutor<'a> {
                     sender: sender.address,
                     original_sender: sender.address,
                     gas: init_gas,
-                    gas_price: *tx.gas_price(),
+                    gas_price,
                     value: ActionValue::Transfer(*tx.value()),
                     code: Some(Arc::new(tx.data().into_owned())),
                     data: None,
@@ -311,7 +395,7 @@ impl<'a> TXExecutor<'a> {
                     sender: sender.address,
                     original_sender: sender.address,
                     gas: init_gas,
-                    gas_price: *tx.gas_price(),
+                    gas_price,
                     value: ActionValue::Transfer(*tx.value()),
                     code: self.state.code(&address)?,
                     code_hash: self.state.code_hash(&address)?,
@@ -332,7 +416,14 @@ impl<'a> TXExecutor<'a> {
             },
         };
 
-        let frame_stack = FrameStack::new(self.state, tx_substate, observer, base_gas_required);
+        let accessed = self.accessed_set(&sender.address, access_list.as_deref());
+        let frame_stack = FrameStack::new(
+            self.state,
+            tx_substate,
+            observer,
+            base_gas_required,
+            accessed,
+        );
 
         Ok(PreCheckResult::Pass {
             top_frame,
@@ -368,6 +459,7 @@ impl<'a> TXExecutor<'a> {
             _ => 0.into(),
         };
 
+        let gas_price = tx.effective_gas_price(&self.env.base_fee_per_gas);
         // gas_used is only used to estimate gas needed
         let gas_used = *tx.gas() - gas_left;
         // gas_left should be smaller than 1/4 of gas_limit, otherwise
@@ -378,14 +470,14 @@ impl<'a> TXExecutor<'a> {
             let gas_charged = *tx.gas() - gas_refunded;
             (
                 gas_charged,
-                gas_charged.saturating_mul(*tx.gas_price()),
-                gas_refunded.saturating_mul(*tx.gas_price()),
+                gas_charged.saturating_mul(gas_price),
+                gas_refunded.saturating_mul(gas_price),
             )
         } else {
             (
                 gas_used,
-                gas_used.saturating_mul(*tx.gas_price()),
-                gas_left.saturating_mul(*tx.gas_price()),
+                gas_used.saturating_mul(gas_price),
+                gas_left.saturating_mul(gas_price),
             )
         };
 
@@ -435,6 +527,7 @@ impl<'a> TXExecutor<'a> {
                 ExecutionError::VmError(exception),
                 Executed::execution_error_fully_charged(
                     tx,
+                    &gas_price,
                     observer.tracer.map_or(Default::default(), |t| t.drain()),
                     &self.spec,
                 ),
@@ -472,10 +565,12 @@ impl<'a> TXExecutor<'a> {
     ) -> DbResult<PreCheckResult> {
         let data = cross_vm_params.data();
         let CrossVMParams {
+            sender,
             receiver,
             gas,
             gas_price,
             value,
+            depth,
             ..
         } = cross_vm_params;
         let address = receiver.with_evm_space();
@@ -484,7 +579,7 @@ impl<'a> TXExecutor<'a> {
             space: address.space,
             code_address: address.address,
             address: address.address,
-            sender: *CROSS_SPACE_CONTRACT_ADDRESS,
+            sender,
             original_sender: Address::zero(),
             gas,
             gas_price,
@@ -502,22 +597,26 @@ impl<'a> TXExecutor<'a> {
             self.machine,
             self.spec,
             &self.factory,
-            0,     /* depth */
+            depth,
             false, /* static_flag */
         );
         let mut substate = Substate::new();
+        // The value is issued here and burned in the Move VM by the caller.
         self.state.add_balance(
-            &CROSS_SPACE_CONTRACT_ADDRESS.with_evm_space(),
+            &sender.with_evm_space(),
             &value,
             cleanup_mode(&mut substate, &self.spec),
             self.spec.account_start_nonce,
         )?;
+        self.state.issue_cross_vm(value);
 
+        let accessed = self.accessed_set(&sender, None);
         let frame_stack = FrameStack::new(
             self.state,
             Substate::new(),
             MultiObservers::with_no_tracing(),
             0,
+            accessed,
         );
 
         Ok(PreCheckResult::Pass {
@@ -526,6 +625,24 @@ impl<'a> TXExecutor<'a> {
         })
     }
 
+    /// The addresses and storage keys warm at the start of a transaction
+    /// (EIP-2929): the sender, the builtins and the access list. The callee
+    /// is warmed by its frame.
+    fn accessed_set(&self, sender: &Address, access_list: Option<&AccessList>) -> AccessedSet {
+        let mut accessed = AccessedSet::new();
+        accessed.warm_address(*sender);
+        for address in self.machine.builtins().keys() {
+            accessed.warm_address(*address);
+        }
+        for item in access_list.into_iter().flatten() {
+            accessed.warm_address(item.address);
+            for key in &item.storage_keys {
+                accessed.warm_storage_key(item.address, *key);
+            }
+        }
+        accessed
+    }
+
     // TODO: maybe we can find a better interface for doing the suicide
     // post-processing.
     fn kill_process(
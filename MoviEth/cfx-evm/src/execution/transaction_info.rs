@@ -1,6 +1,6 @@
 use cfx_types::{AddressWithSpace, Space, U256};
-use primitives::{Action, SignedTransaction};
-use std::borrow::Cow;
+use primitives::{AccessList, Action, SignedTransaction};
+use std::{borrow::Cow, cmp};
 use Cow::{Borrowed, Owned};
 
 pub trait TransactionInfo {
@@ -15,6 +15,26 @@ pub trait TransactionInfo {
     fn space(&self) -> Space {
         Space::Ethereum
     }
+
+    /// The priority fee of a dynamic fee (EIP-1559) transaction, whose
+    /// `gas_price` is then the max fee per gas.
+    fn max_priority_fee_per_gas(&self) -> Option<Cow<U256>> {
+        None
+    }
+
+    /// The addresses and storage keys to warm before execution (EIP-2930).
+    fn access_list(&self) -> Option<Cow<AccessList>> {
+        None
+    }
+
+    /// The price actually paid per gas under the given block base fee.
+    fn effective_gas_price(&self, base_fee: &U256) -> U256 {
+        let gas_price = *self.gas_price();
+        match self.max_priority_fee_per_gas() {
+            Some(priority_fee) => cmp::min(gas_price, base_fee.saturating_add(*priority_fee)),
+            None => gas_price,
+        }
+    }
 }
 
 impl TransactionInfo for SignedTransaction {
@@ -45,4 +65,12 @@ impl TransactionInfo for SignedTransaction {
     fn value(&self) -> Cow<U256> {
         Borrowed((**self).value())
     }
+
+    fn max_priority_fee_per_gas(&self) -> Option<Cow<U256>> {
+        (**self).max_priority_fee_per_gas().map(Borrowed)
+    }
+
+    fn access_list(&self) -> Option<Cow<AccessList>> {
+        (**self).access_list().map(Borrowed)
+    }
 }
@@ -1,4 +1,9 @@
-use super::{super::contracts::all_internal_contracts, InternalContractTrait};
+use super::{
+    super::contracts::{
+        all_internal_contracts, coin_facade::is_coin_facade_address, coin_facade_contract,
+    },
+    InternalContractTrait,
+};
 use crate::{evm::Spec, spec::CommonParams};
 use cfx_types::{Address, AddressWithSpace};
 use primitives::BlockNumber;
@@ -8,6 +13,7 @@ use std::collections::BTreeMap;
 pub struct InternalContractMap {
     builtin: BTreeMap<Address, Box<dyn InternalContractTrait>>,
     activation_info: BTreeMap<BlockNumber, Vec<Address>>,
+    coin_facade: Option<Box<dyn InternalContractTrait>>,
 }
 
 impl std::ops::Deref for InternalContractMap {
@@ -46,6 +52,7 @@ impl InternalContractMap {
         Self {
             builtin,
             activation_info,
+            coin_facade: Some(coin_facade_contract()),
         }
     }
 
@@ -72,8 +79,11 @@ impl InternalContractMap {
         address: &AddressWithSpace,
         spec: &Spec,
     ) -> Option<&Box<dyn InternalContractTrait>> {
-        self.builtin
-            .get(&address.address)
-            .filter(|&func| func.is_active(spec))
+        let contract = if is_coin_facade_address(&address.address) {
+            self.coin_facade.as_ref()
+        } else {
+            self.builtin.get(&address.address)
+        };
+        contract.filter(|&func| func.is_active(spec))
     }
 }
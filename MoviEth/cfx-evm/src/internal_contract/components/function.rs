@@ -67,16 +67,24 @@ impl<T: SolidityFunctionConfigTrait + ExecutionTrait + IsActive> SolidityFunctio
             },
         };
 
-        let gas_left = params.gas - cost;
+        let mut gas_left = params.gas - cost;
 
         match ExecutionTrait::execute_inner(
             self,
             solidity_params,
             params,
-            gas_left,
+            &mut gas_left,
             context,
             tracer,
         ) {
+            TrapResult::Return(Err(vm::Error::InternalContractReverted(data))) => {
+                let length = data.len();
+                TrapResult::Return(Ok(GasLeft::NeedsReturn {
+                    gas_left,
+                    data: ReturnData::new(data, 0, length),
+                    apply_state: false,
+                }))
+            },
             TrapResult::Return(output) => {
                 let vm_result = output.and_then(|output| {
                     let output = output.abi_encode();
@@ -139,11 +147,14 @@ pub trait PreExecCheckTrait: Send + Sync {
 }
 
 pub trait ExecutionTrait: Send + Sync + InterfaceTrait {
+    /// `gas_left` is the gas left after the upfront payment. A function which
+    /// uses gas while executing, e.g. for a call into the Move VM, deducts it
+    /// from `gas_left`, also when it reverts.
     fn execute_inner(
         &self,
         input: Self::Input,
         params: &ActionParams,
-        gas_left: U256,
+        gas_left: &mut U256,
         context: &mut InternalRefContext,
         tracer: &mut dyn VmObserve,
     ) -> ExecTrapResult<<Self as InterfaceTrait>::Output>;
@@ -168,7 +179,7 @@ where
         &self,
         input: Self::Input,
         params: &ActionParams,
-        _gas_left: U256,
+        _gas_left: &mut U256,
         context: &mut InternalRefContext,
         tracer: &mut dyn VmObserve,
     ) -> ExecTrapResult<<Self as InterfaceTrait>::Output> {
@@ -0,0 +1,289 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Wraps ERC-20 tokens into Move coins. The bridge locks the tokens it wraps,
+//! and `0x1::coin_bridge` mints and burns the wrapped coins. Amounts are the
+//! same on both sides, so fee-on-transfer or rebasing tokens are not supported.
+
+use super::{
+    super::impls::coin_bridge::{call_erc20, encode_call, process_trap},
+    coin_facade::coin_facade_address,
+    cross_space::{
+        call_move_vm, charge_move_call, check_move_call_depth, check_not_delegated,
+        move_call_allowance, move_call_depth, move_call_result, move_error_revert,
+    },
+    preludes::*,
+};
+use crate::{
+    internal_bail,
+    observer::trace::MoveCall,
+    vm::{ExecTrapError, ExecTrapResult},
+};
+use cfx_parameters::internal_contract_addresses::{
+    COIN_BRIDGE_CONTRACT_ADDRESS, CROSS_SPACE_CONTRACT_ADDRESS,
+};
+use cfx_types::U256;
+use move_core_types::{language_storage::TypeTag as MoveTypeTag, parser::parse_type_tag};
+use solidity_abi::{ABIDecodable, ABIEncodable};
+
+type Bytes = Vec<u8>;
+
+/// Caller info of the EVM calls of `0x1::coin_bridge`, the only ones which can
+/// release locked tokens.
+const BRIDGE_CALLER_INFO: &str =
+    "0x0000000000000000000000000000000000000000000000000000000000000001::coin_bridge::BridgeCall";
+
+make_solidity_contract! {
+    pub struct CoinBridge(COIN_BRIDGE_CONTRACT_ADDRESS, generate_fn_table, "active_at_genesis");
+}
+
+fn generate_fn_table() -> SolFnTable {
+    make_function_table!(Wrap, Unwrap, Release, CoinFacade)
+}
+
+group_impl_is_active!("genesis", Wrap, Unwrap, Release, CoinFacade);
+
+make_solidity_function! {
+    struct Wrap((Address, H256, U256), "wrap(address,bytes32,uint256)");
+}
+
+impl_function_type!(Wrap, "non_payable_write", gas: |spec: &Spec| spec.call_gas);
+
+impl ExecutionTrait for Wrap {
+    /// Locks `amount` of `token` with `transferFrom`, which the caller has to
+    /// approve, and mints as much of the wrapped coin to the Move account `to`.
+    fn execute_inner(
+        &self,
+        (token, to, amount): (Address, H256, U256),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<()> {
+        let mut wrap = || -> vm::Result<ExecTrapError> {
+            check_not_delegated(params)?;
+            let amount = coin_amount(amount)?;
+            let marker = wrapped_marker(token, params, gas_left, context, tracer)?;
+            call_coin_bridge::<()>(
+                params.address,
+                "mint_wrapped",
+                vec![marker],
+                (token.0.to_vec(), to, amount).abi_encode(),
+                gas_left,
+                context,
+                tracer,
+            )?;
+            let data = encode_call(
+                "transferFrom(address,address,uint256)",
+                (params.sender, params.address, U256::from(amount)),
+            );
+            call_erc20(token, data, params, *gas_left, context)
+        };
+        process_trap(wrap())
+    }
+}
+
+make_solidity_function! {
+    struct Unwrap((Address, Address, U256), "unwrap(address,address,uint256)");
+}
+
+impl_function_type!(Unwrap, "non_payable_write", gas: |spec: &Spec| spec.call_gas);
+
+impl ExecutionTrait for Unwrap {
+    /// Burns `amount` of the wrapped coin of the caller, and unlocks as much of
+    /// `token` to `to`.
+    fn execute_inner(
+        &self,
+        (token, to, amount): (Address, Address, U256),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<()> {
+        let mut unwrap = || -> vm::Result<ExecTrapError> {
+            check_not_delegated(params)?;
+            let amount = coin_amount(amount)?;
+            let marker = wrapped_marker(token, params, gas_left, context, tracer)?;
+            call_coin_bridge::<()>(
+                params.address,
+                "burn_wrapped",
+                vec![marker],
+                (token.0.to_vec(), params.sender.0.to_vec(), amount).abi_encode(),
+                gas_left,
+                context,
+                tracer,
+            )?;
+            let data = encode_call("transfer(address,uint256)", (to, U256::from(amount)));
+            call_erc20(token, data, params, *gas_left, context)
+        };
+        process_trap(unwrap())
+    }
+}
+
+make_solidity_function! {
+    struct Release((String, Vec<Bytes>), "release(string,bytes[])");
+}
+
+impl_function_type!(Release, "non_payable_write", gas: |spec: &Spec| spec.call_gas);
+
+impl ExecutionTrait for Release {
+    /// Unlocks tokens for a wrapped coin burned by `0x1::coin_bridge::unwrap`.
+    /// The parameters are the BCS encoded token, receiver and amount.
+    fn execute_inner(
+        &self,
+        (caller_info, data): (String, Vec<Bytes>),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        _tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<()> {
+        let mut release = || -> vm::Result<ExecTrapError> {
+            check_not_delegated(params)?;
+            if params.sender != *CROSS_SPACE_CONTRACT_ADDRESS || caller_info != BRIDGE_CALLER_INFO {
+                internal_bail!("Only 0x1::coin_bridge can release tokens");
+            }
+            let (token, to, amount) = match data.as_slice() {
+                [token, to, amount] => (
+                    bcs_evm_address(token)?,
+                    bcs_evm_address(to)?,
+                    bcs::from_bytes::<u64>(amount)
+                        .map_err(|e| vm::Error::InternalContract(format!("{}", e)))?,
+                ),
+                _ => internal_bail!("Incorrect number of parameters"),
+            };
+            let data = encode_call("transfer(address,uint256)", (to, U256::from(amount)));
+            call_erc20(token, data, params, *gas_left, context)
+        };
+        process_trap(release())
+    }
+}
+
+make_solidity_function! {
+    struct CoinFacade(String, "coinFacade(string)", Address);
+}
+
+impl_function_type!(CoinFacade, "query", gas: |spec: &Spec| spec.sha3_gas);
+
+impl SimpleExecutionTrait for CoinFacade {
+    /// The ERC-20 facade of the coin type `type_name`, e.g.
+    /// `0x1::aptos_coin::AptosCoin`. It works once the coin has been
+    /// registered with `0x1::coin_bridge::register_coin`.
+    fn execute_inner(
+        &self,
+        type_name: String,
+        _params: &ActionParams,
+        _context: &mut InternalRefContext,
+        _tracer: &mut dyn VmObserve,
+    ) -> vm::Result<Address> {
+        Ok(coin_facade_address(&type_name))
+    }
+}
+
+/// Calls `0x1::coin_bridge::{function}` as `caller` with ABI encoded `args`,
+/// and decodes its return values. The gas used by the Move call is deducted
+/// from `gas_left`.
+pub(super) fn call_coin_bridge<T: ABIDecodable>(
+    caller: Address,
+    function: &str,
+    type_args: Vec<MoveTypeTag>,
+    args: Bytes,
+    gas_left: &mut U256,
+    context: &mut InternalRefContext,
+    tracer: &mut dyn VmObserve,
+) -> vm::Result<T> {
+    check_move_call_depth(context.depth, context.spec)?;
+    let allowance = move_call_allowance(*gas_left, context.spec);
+    let depth = move_call_depth(context.depth);
+    let framework = H256::from_low_u64_be(1);
+
+    let (res, gas_used) = call_move_vm(context, |vm, state| {
+        tracer.record_move_call(&MoveCall {
+            from: caller,
+            address: framework,
+            module: "coin_bridge".into(),
+            function: function.into(),
+            type_args: type_args.iter().map(ToString::to_string).collect(),
+            value: U256::zero(),
+            gas: allowance,
+            input: args.clone(),
+        });
+        vm.call_move_vm_typed(
+            state,
+            depth,
+            caller,
+            framework.0.to_vec(),
+            "coin_bridge".into(),
+            function.into(),
+            args,
+            type_args,
+            U256::zero(),
+            allowance,
+        )
+    })?;
+    charge_move_call(gas_left, allowance, gas_used);
+    tracer.record_move_call_result(&move_call_result(&res, gas_used));
+    let output = res.map_err(move_error_revert)?;
+    Ok(T::abi_decode(&output)?)
+}
+
+/// Parses a type name returned by `0x1::coin_bridge`.
+pub(super) fn parse_move_type(type_name: Bytes) -> vm::Result<MoveTypeTag> {
+    String::from_utf8(type_name)
+        .ok()
+        .and_then(|type_name| parse_type_tag(&type_name).ok())
+        .ok_or_else(|| vm::Error::InternalContract("Cannot parse type tag".into()))
+}
+
+/// Coins count their amounts in `u64`.
+pub(super) fn coin_amount(amount: U256) -> vm::Result<u64> {
+    if amount > U256::from(u64::MAX) {
+        internal_bail!("Amount does not fit in a u64");
+    }
+    Ok(amount.as_u64())
+}
+
+/// The marker type of the wrapped coin of `token`.
+fn wrapped_marker(
+    token: Address,
+    params: &ActionParams,
+    gas_left: &mut U256,
+    context: &mut InternalRefContext,
+    tracer: &mut dyn VmObserve,
+) -> vm::Result<MoveTypeTag> {
+    let type_name: Bytes = call_coin_bridge(
+        params.address,
+        "wrapped_marker",
+        vec![],
+        token.0.to_vec().abi_encode(),
+        gas_left,
+        context,
+        tracer,
+    )?;
+    parse_move_type(type_name)
+}
+
+fn bcs_evm_address(bytes: &[u8]) -> vm::Result<Address> {
+    match bcs::from_bytes::<Bytes>(bytes) {
+        Ok(address) if address.len() == 20 => Ok(Address::from_slice(&address)),
+        _ => internal_bail!("Invalid EVM address"),
+    }
+}
+
+#[test]
+fn test_coin_bridge_sig() {
+    check_func_signature!(Wrap, "1df7de0e");
+    check_func_signature!(Unwrap, "8cc7104f");
+    check_func_signature!(Release, "b3264653");
+    check_func_signature!(CoinFacade, "8e7364ae");
+
+    assert_eq!(coin_amount(U256::from(u64::MAX)).unwrap(), u64::MAX);
+    assert!(coin_amount(U256::from(u64::MAX) + 1).is_err());
+
+    let address = bcs::to_bytes(&vec![0x11u8; 20]).unwrap();
+    assert_eq!(
+        bcs_evm_address(&address).unwrap(),
+        Address::repeat_byte(0x11)
+    );
+    assert!(bcs_evm_address(&bcs::to_bytes(&vec![0x11u8; 32]).unwrap()).is_err());
+}
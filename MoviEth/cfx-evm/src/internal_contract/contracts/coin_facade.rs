@@ -0,0 +1,384 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! ERC-20 facades of Move coins. A facade keeps no EVM state: it asks
+//! `0x1::coin_bridge` for the coin type registered at its address, and reads
+//! and moves the `CoinStore`s of the Move accounts of EVM accounts, which
+//! `0x1::account_link` resolves. Only transfers through the facade emit
+//! `Transfer` events.
+
+use super::{
+    coin_bridge::{call_coin_bridge, coin_amount, parse_move_type},
+    cross_space::check_not_delegated,
+    preludes::*,
+};
+use crate::vm::{ExecTrapResult, TrapResult};
+use cfx_parameters::internal_contract_addresses::COIN_FACADE_BASE_ADDRESS;
+use cfx_types::U256;
+use move_core_types::language_storage::TypeTag as MoveTypeTag;
+use solidity_abi::ABIEncodable;
+
+type Bytes = Vec<u8>;
+
+/// Length of the address prefix shared by the facades.
+const FACADE_PREFIX_LEN: usize = 8;
+
+make_solidity_contract! {
+    pub struct CoinFacade(COIN_FACADE_BASE_ADDRESS, generate_fn_table, "active_at_genesis");
+}
+
+/// The facade address of the coin type `type_name`: the prefix of
+/// `COIN_FACADE_BASE_ADDRESS` followed by the first 12 bytes of the Keccak-256
+/// hash of the type name. Should be in-sync with `0x1::coin_bridge`.
+pub fn coin_facade_address(type_name: &str) -> Address {
+    let hash = keccak(type_name.as_bytes());
+    let mut address = *COIN_FACADE_BASE_ADDRESS;
+    address.0[FACADE_PREFIX_LEN..].copy_from_slice(&hash[..20 - FACADE_PREFIX_LEN]);
+    address
+}
+
+pub fn is_coin_facade_address(address: &Address) -> bool {
+    address.0[..FACADE_PREFIX_LEN] == COIN_FACADE_BASE_ADDRESS.0[..FACADE_PREFIX_LEN]
+}
+
+fn generate_fn_table() -> SolFnTable {
+    make_function_table!(
+        Name,
+        Symbol,
+        Decimals,
+        TotalSupply,
+        BalanceOf,
+        Allowance,
+        Transfer,
+        Approve,
+        TransferFrom
+    )
+}
+
+group_impl_is_active!(
+    "genesis",
+    Name,
+    Symbol,
+    Decimals,
+    TotalSupply,
+    BalanceOf,
+    Allowance,
+    Transfer,
+    Approve,
+    TransferFrom,
+);
+
+make_solidity_event! {
+    pub struct TransferEvent("Transfer(address,address,uint256)", indexed: (Address, Address), non_indexed: U256);
+}
+
+make_solidity_event! {
+    pub struct ApprovalEvent("Approval(address,address,uint256)", indexed: (Address, Address), non_indexed: U256);
+}
+
+/// The coin type of the facade called with `params`.
+fn coin_type(
+    params: &ActionParams,
+    gas_left: &mut U256,
+    context: &mut InternalRefContext,
+    tracer: &mut dyn VmObserve,
+) -> vm::Result<MoveTypeTag> {
+    check_not_delegated(params)?;
+    let type_name: Bytes = call_coin_bridge(
+        params.address,
+        "facade_coin",
+        vec![],
+        params.address.0.to_vec().abi_encode(),
+        gas_left,
+        context,
+        tracer,
+    )?;
+    parse_move_type(type_name)
+}
+
+/// Calls `0x1::coin_bridge::{function}<CoinType>` for the coin of the facade.
+fn call_coin<T: solidity_abi::ABIDecodable>(
+    function: &str,
+    args: Bytes,
+    params: &ActionParams,
+    gas_left: &mut U256,
+    context: &mut InternalRefContext,
+    tracer: &mut dyn VmObserve,
+) -> vm::Result<T> {
+    let coin = coin_type(params, gas_left, context, tracer)?;
+    call_coin_bridge(
+        params.address,
+        function,
+        vec![coin],
+        args,
+        gas_left,
+        context,
+        tracer,
+    )
+}
+
+/// An EVM account as passed to `0x1::coin_bridge`, which resolves its Move
+/// account.
+fn evm_account(address: Address) -> Bytes {
+    address.0.to_vec()
+}
+
+/// An approval above the `u64` range of coins is unlimited, like the
+/// `uint256` maximum is for most ERC-20s.
+fn allowance_amount(amount: U256) -> u64 {
+    if amount > U256::from(u64::MAX) {
+        u64::MAX
+    } else {
+        amount.as_u64()
+    }
+}
+
+make_solidity_function! {
+    struct Name((), "name()", String);
+}
+impl_function_type!(Name, "query", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for Name {
+    fn execute_inner(
+        &self,
+        _: (),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<String> {
+        let name = call_coin::<Bytes>("name", vec![], params, gas_left, context, tracer);
+        TrapResult::Return(name.map(|name| String::from_utf8_lossy(&name).into_owned()))
+    }
+}
+
+make_solidity_function! {
+    struct Symbol((), "symbol()", String);
+}
+impl_function_type!(Symbol, "query", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for Symbol {
+    fn execute_inner(
+        &self,
+        _: (),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<String> {
+        let symbol = call_coin::<Bytes>("symbol", vec![], params, gas_left, context, tracer);
+        TrapResult::Return(symbol.map(|symbol| String::from_utf8_lossy(&symbol).into_owned()))
+    }
+}
+
+make_solidity_function! {
+    // The ABI encodes an `uint8` like any `uint`.
+    struct Decimals((), "decimals()", u16);
+}
+impl_function_type!(Decimals, "query", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for Decimals {
+    fn execute_inner(
+        &self,
+        _: (),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<u16> {
+        TrapResult::Return(call_coin("decimals", vec![], params, gas_left, context, tracer))
+    }
+}
+
+make_solidity_function! {
+    struct TotalSupply((), "totalSupply()", U256);
+}
+impl_function_type!(TotalSupply, "query", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for TotalSupply {
+    fn execute_inner(
+        &self,
+        _: (),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<U256> {
+        TrapResult::Return(call_coin("total_supply", vec![], params, gas_left, context, tracer))
+    }
+}
+
+make_solidity_function! {
+    struct BalanceOf(Address, "balanceOf(address)", U256);
+}
+impl_function_type!(BalanceOf, "query", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for BalanceOf {
+    fn execute_inner(
+        &self,
+        owner: Address,
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<U256> {
+        let args = evm_account(owner).abi_encode();
+        TrapResult::Return(call_coin("balance_of", args, params, gas_left, context, tracer))
+    }
+}
+
+make_solidity_function! {
+    struct Allowance((Address, Address), "allowance(address,address)", U256);
+}
+impl_function_type!(Allowance, "query", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for Allowance {
+    fn execute_inner(
+        &self,
+        (owner, spender): (Address, Address),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<U256> {
+        let args = (evm_account(owner), evm_account(spender)).abi_encode();
+        let allowance = call_coin::<u64>("allowance", args, params, gas_left, context, tracer);
+        TrapResult::Return(allowance.map(|allowance| match allowance {
+            u64::MAX => U256::MAX,
+            allowance => allowance.into(),
+        }))
+    }
+}
+
+make_solidity_function! {
+    struct Transfer((Address, U256), "transfer(address,uint256)", bool);
+}
+impl_function_type!(Transfer, "non_payable_write", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for Transfer {
+    fn execute_inner(
+        &self,
+        (to, amount): (Address, U256),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<bool> {
+        let mut transfer = || {
+            let args = (
+                evm_account(params.sender),
+                evm_account(to),
+                coin_amount(amount)?,
+            );
+            call_coin::<()>(
+                "transfer",
+                args.abi_encode(),
+                params,
+                gas_left,
+                context,
+                tracer,
+            )?;
+            TransferEvent::log(&(params.sender, to), &amount, params, context)?;
+            Ok(true)
+        };
+        TrapResult::Return(transfer())
+    }
+}
+
+make_solidity_function! {
+    struct Approve((Address, U256), "approve(address,uint256)", bool);
+}
+impl_function_type!(Approve, "non_payable_write", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for Approve {
+    fn execute_inner(
+        &self,
+        (spender, amount): (Address, U256),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<bool> {
+        let mut approve = || {
+            let args = (
+                evm_account(params.sender),
+                evm_account(spender),
+                allowance_amount(amount),
+            );
+            call_coin::<()>(
+                "approve",
+                args.abi_encode(),
+                params,
+                gas_left,
+                context,
+                tracer,
+            )?;
+            ApprovalEvent::log(&(params.sender, spender), &amount, params, context)?;
+            Ok(true)
+        };
+        TrapResult::Return(approve())
+    }
+}
+
+make_solidity_function! {
+    struct TransferFrom((Address, Address, U256), "transferFrom(address,address,uint256)", bool);
+}
+impl_function_type!(TransferFrom, "non_payable_write", gas: |spec: &Spec| spec.call_gas);
+impl ExecutionTrait for TransferFrom {
+    fn execute_inner(
+        &self,
+        (from, to, amount): (Address, Address, U256),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<bool> {
+        let mut transfer_from = || {
+            let args = (
+                evm_account(params.sender),
+                evm_account(from),
+                evm_account(to),
+                coin_amount(amount)?,
+            );
+            call_coin::<()>(
+                "transfer_from",
+                args.abi_encode(),
+                params,
+                gas_left,
+                context,
+                tracer,
+            )?;
+            TransferEvent::log(&(from, to), &amount, params, context)?;
+            Ok(true)
+        };
+        TrapResult::Return(transfer_from())
+    }
+}
+
+#[test]
+fn test_coin_facade_sig() {
+    check_func_signature!(Name, "06fdde03");
+    check_func_signature!(Symbol, "95d89b41");
+    check_func_signature!(Decimals, "313ce567");
+    check_func_signature!(TotalSupply, "18160ddd");
+    check_func_signature!(BalanceOf, "70a08231");
+    check_func_signature!(Allowance, "dd62ed3e");
+    check_func_signature!(Transfer, "a9059cbb");
+    check_func_signature!(Approve, "095ea7b3");
+    check_func_signature!(TransferFrom, "23b872dd");
+
+    check_event_signature!(
+        TransferEvent,
+        "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+    );
+    check_event_signature!(
+        ApprovalEvent,
+        "8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925"
+    );
+}
+
+#[test]
+fn test_coin_facade_address() {
+    let address = coin_facade_address("0x1::aptos_coin::AptosCoin");
+    assert_eq!(
+        address,
+        "0889000000000000628b61d691bea8ec1a8b916b".parse().unwrap()
+    );
+    assert!(is_coin_facade_address(&address));
+    assert!(!is_coin_facade_address(
+        &"0888000000000000000000000000000000000003".parse().unwrap()
+    ));
+}
@@ -3,10 +3,20 @@
 // See http://www.gnu.org/licenses/
 
 use super::preludes::*;
-use crate::{internal_bail, state::cleanup_mode};
-use cfx_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+use crate::{
+    internal_bail,
+    observer::trace::{MoveCall, MoveCallResult, Outcome},
+    state::cleanup_mode,
+    vm::{ExecTrapResult, TrapResult},
+};
+use cfx_parameters::{
+    cross_vm::{internal_gas_to_evm_gas_ceil, CROSS_VM_CALL_DEPTH},
+    internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS,
+};
+use cfx_state::{native_token::wei_to_octas, CallMoveVMError, CallMoveVMTrait, StateTrait};
 use cfx_types::{Address, AddressSpaceUtil, U256};
 use move_core_types::language_storage::TypeTag as MoveTypeTag;
+use solidity_abi::ABIEncodable;
 
 type Bytes = Vec<u8>;
 type Bytes32 = [u8; 32];
@@ -18,6 +28,8 @@ make_solidity_contract! {
 fn generate_fn_table() -> SolFnTable {
     make_function_table!(
         CallToMove,
+        CallToMoveTyped,
+        CallToMoveAsSigner,
         Log,
         EncodeU64,
         EncodeBytes32,
@@ -29,6 +41,8 @@ fn generate_fn_table() -> SolFnTable {
 group_impl_is_active!(
     "genesis",
     CallToMove,
+    CallToMoveTyped,
+    CallToMoveAsSigner,
     Log,
     EncodeU64,
     EncodeBytes32,
@@ -40,9 +54,20 @@ make_solidity_function! {
     pub struct CallToMove((Bytes32, String, String, Vec<Bytes>,Vec<Bytes>), "callMove(bytes32,string,string,bytes[],bytes[])", Bytes);
 }
 
-impl_function_type!(CallToMove, "payable_write", gas: |_| U256::zero());
+impl_function_type!(CallToMove, "payable_write");
 
-impl SimpleExecutionTrait for CallToMove {
+impl UpfrontPaymentTrait for CallToMove {
+    fn upfront_gas_payment(
+        &self,
+        _input: &Self::Input,
+        params: &ActionParams,
+        context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(move_call_gas(params, context.spec))
+    }
+}
+
+impl ExecutionTrait for CallToMove {
     fn execute_inner(
         &self,
         (address, module, function, data, encoded_types): (
@@ -53,13 +78,11 @@ impl SimpleExecutionTrait for CallToMove {
             Vec<Bytes>,
         ),
         params: &ActionParams,
+        gas_left: &mut U256,
         context: &mut InternalRefContext,
-        _tracer: &mut dyn VmObserve,
-    ) -> vm::Result<Bytes> {
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<Bytes> {
         let call = || {
-            let value = params.value.value();
-            let caller = params.sender;
-            let function = format!("ihe_{}", function);
             // IMPORTANT (Vlad): logs
             // if !module.is_empty() {
             //     println!(
@@ -73,38 +96,29 @@ impl SimpleExecutionTrait for CallToMove {
             //     }
             // }
 
-            context.state.sub_balance(
-                &CROSS_SPACE_CONTRACT_ADDRESS.with_evm_space(),
-                &value,
-                &mut cleanup_mode(context.substate, context.spec),
-            )?;
-            let vm = if let Some(vm) = context.state.as_move_vm() {
-                vm
-            } else {
-                internal_bail!("Cannot call move vm");
-            };
-
-            let types = if let Ok(types) = encoded_types
-                .into_iter()
-                .map(|raw| -> Result<MoveTypeTag, _> { bcs::from_bytes(&raw) })
-                .collect()
-            {
-                types
-            } else {
-                internal_bail!("Cannot decode type tag");
-            };
-
-            vm.call_move_vm(
-                caller,
-                address.to_vec(),
-                module,
-                function,
-                data,
-                types,
-                value,
-                params.gas,
+            let input = bcs::to_bytes(&data).unwrap_or_default();
+            execute_move_call(
+                (address, module, function, data, encoded_types),
+                input,
+                params,
+                gas_left,
+                context,
+                tracer,
+                |vm, state, call| {
+                    vm.call_move_vm(
+                        state,
+                        call.depth,
+                        call.caller,
+                        call.address,
+                        call.module,
+                        call.function,
+                        call.data,
+                        call.types,
+                        call.value,
+                        call.allowance,
+                    )
+                },
             )
-            .map_err(|err| vm::Error::InternalContract(format!("Fail to call move vm: {}", err)))
         };
 
         let res = call();
@@ -122,13 +136,360 @@ impl SimpleExecutionTrait for CallToMove {
                 // }
             },
             Err(vm::Error::InternalContract(reason)) => {
-                println!(" Fail: Internal Error: {}", reason);
+                debug!("Call to Move failed: internal error: {}", reason);
+            },
+            Err(vm::Error::InternalContractReverted(data)) => {
+                debug!("Call to Move reverted: 0x{}", hex::encode(data));
             },
             Err(err) => {
-                println!(" Fail: {}", err);
+                debug!("Call to Move failed: {}", err);
             },
         }
-        res
+        TrapResult::Return(res)
+    }
+}
+
+/// Reverts the EVM caller with a Solidity decodable reason, so that contracts
+/// can `try/catch` Move failures. A Move VM error is reported as the custom
+/// error `MoveError(uint64 status, uint64 subStatus, string location)`, e.g.
+/// an abort has status `ABORTED` (4016) and the abort code as `subStatus`.
+/// Other failures are reported as `Error(string)`.
+pub(super) fn move_error_revert(err: CallMoveVMError) -> vm::Error {
+    let (signature, data) = match err {
+        CallMoveVMError::Vm {
+            status,
+            sub_status,
+            location,
+            ..
+        } => (
+            "MoveError(uint64,uint64,string)",
+            (status, sub_status.unwrap_or(0), location).abi_encode(),
+        ),
+        CallMoveVMError::Other(msg) => (
+            "Error(string)",
+            format!("Fail to call move vm: {}", msg).abi_encode(),
+        ),
+    };
+    let selector = keccak_hash::keccak(signature);
+    vm::Error::InternalContractReverted([&selector[..4], &data[..]].concat())
+}
+
+/// Upfront gas of a Move call, charged like an EVM `CALL` to an existing
+/// account.
+fn move_call_gas(params: &ActionParams, spec: &Spec) -> U256 {
+    let mut gas = spec.call_gas;
+    if !params.value.value().is_zero() {
+        gas += spec.call_value_transfer_gas;
+    }
+    U256::from(gas)
+}
+
+/// Gas forwarded to a Move call out of the gas left, keeping back a part of it
+/// for the caller like the EVM `CALL` does (EIP-150).
+pub(super) fn move_call_allowance(gas_left: U256, spec: &Spec) -> U256 {
+    match spec.sub_gas_cap_divisor {
+        Some(divisor) => gas_left - gas_left / U256::from(divisor),
+        None => gas_left,
+    }
+}
+
+/// Deducts the EVM gas paying for the Move internal gas used by a call from the
+/// gas left. The rest of the allowance stays with the caller.
+pub(super) fn charge_move_call(gas_left: &mut U256, allowance: U256, gas_used: u64) {
+    *gas_left -= U256::from(internal_gas_to_evm_gas_ceil(gas_used)).min(allowance);
+}
+
+/// The trace of the result of a Move call which used `gas_used`.
+pub(super) fn move_call_result(
+    res: &Result<Bytes, CallMoveVMError>,
+    gas_used: u64,
+) -> MoveCallResult {
+    match res {
+        Ok(return_data) => MoveCallResult {
+            outcome: Outcome::Success,
+            gas_used: gas_used.into(),
+            return_data: return_data.clone(),
+            error: String::new(),
+        },
+        Err(err) => MoveCallResult {
+            outcome: Outcome::Reverted,
+            gas_used: gas_used.into(),
+            return_data: vec![],
+            error: err.to_string(),
+        },
+    }
+}
+
+/// The Move functions trust the caller they are given, i.e. the sender of a
+/// call from an internal contract. A `DELEGATECALL` or `CALLCODE` keeps the
+/// sender of the delegating contract, which could then act for it.
+pub(super) fn check_not_delegated(params: &ActionParams) -> vm::Result<()> {
+    if params.address != params.code_address {
+        internal_bail!("Cannot be called with DELEGATECALL or CALLCODE");
+    }
+    Ok(())
+}
+
+/// Call depth of a Move call made by the frame at `depth`. Switching to the
+/// Move VM costs `CROSS_VM_CALL_DEPTH` levels of the `max_depth` limit the
+/// Move call shares with the EVM frames.
+pub(super) fn move_call_depth(depth: usize) -> usize {
+    depth + CROSS_VM_CALL_DEPTH
+}
+
+/// Refuses a Move call from the frame at `depth` when its call depth would
+/// reach `max_depth`.
+pub(super) fn check_move_call_depth(depth: usize, spec: &Spec) -> vm::Result<()> {
+    if move_call_depth(depth) >= spec.max_depth {
+        internal_bail!("Cross-VM call depth limit reached");
+    }
+    Ok(())
+}
+
+/// Runs `call` with the Move VM of the state and the state itself, which the
+/// Move code calls back into the EVM on. Returns the result of `call` with the
+/// Move internal gas the call used.
+pub(super) fn call_move_vm<R>(
+    context: &mut InternalRefContext,
+    call: impl FnOnce(&dyn CallMoveVMTrait, &mut dyn StateTrait) -> R,
+) -> vm::Result<(R, u64)> {
+    let mut call = Some(call);
+    let mut output = None;
+    context.state.with_move_vm(&mut |vm, state| {
+        if let Some(call) = call.take() {
+            output = Some((call(vm, state), vm.last_call_gas_used()));
+        }
+    });
+    match output {
+        Some(output) => Ok(output),
+        None => internal_bail!("Cannot call move vm"),
+    }
+}
+
+/// Burns the value sent along with the call, which the Move VM issues to the
+/// callee, and returns the decoded type arguments. The value must be a whole
+/// number of octas, and the Move call must stay within the call depth limit.
+fn prepare_move_call(
+    encoded_types: Vec<Bytes>,
+    params: &ActionParams,
+    context: &mut InternalRefContext,
+) -> vm::Result<Vec<MoveTypeTag>> {
+    check_not_delegated(params)?;
+    check_move_call_depth(context.depth, context.spec)?;
+    let value = params.value.value();
+    if wei_to_octas(value).is_none() {
+        internal_bail!("Value is not a whole number of octas");
+    }
+    context.state.sub_balance(
+        &CROSS_SPACE_CONTRACT_ADDRESS.with_evm_space(),
+        &value,
+        &mut cleanup_mode(context.substate, context.spec),
+    )?;
+    context.state.burn_cross_vm(value);
+
+    let types = if let Ok(types) = encoded_types
+        .into_iter()
+        .map(|raw| -> Result<MoveTypeTag, _> { bcs::from_bytes(&raw) })
+        .collect()
+    {
+        types
+    } else {
+        internal_bail!("Cannot decode type tag");
+    };
+    Ok(types)
+}
+
+/// A Move call made for the EVM caller, with the arguments `data`.
+struct MoveCallArgs<D> {
+    depth: usize,
+    caller: Address,
+    address: Vec<u8>,
+    module: String,
+    function: String,
+    data: D,
+    types: Vec<MoveTypeTag>,
+    value: U256,
+    allowance: U256,
+}
+
+/// Calls the Move function `function` of `module` at `address` for the EVM
+/// caller: checks the call and burns its value, forwards an allowance out of
+/// the gas left, runs `call` on the Move VM and charges the gas it used. The
+/// call is traced with `input` as its arguments. A failed Move call reverts
+/// with `move_error_revert`.
+fn execute_move_call<D>(
+    (address, module, function, data, encoded_types): (Bytes32, String, String, D, Vec<Bytes>),
+    input: Bytes,
+    params: &ActionParams,
+    gas_left: &mut U256,
+    context: &mut InternalRefContext,
+    tracer: &mut dyn VmObserve,
+    call: impl FnOnce(
+        &dyn CallMoveVMTrait,
+        &mut dyn StateTrait,
+        MoveCallArgs<D>,
+    ) -> Result<Bytes, CallMoveVMError>,
+) -> vm::Result<Bytes> {
+    let value = params.value.value();
+    let caller = params.sender;
+
+    let allowance = move_call_allowance(*gas_left, context.spec);
+    let types = prepare_move_call(encoded_types, params, context)?;
+    let depth = move_call_depth(context.depth);
+
+    let (res, gas_used) = call_move_vm(context, |vm, state| {
+        tracer.record_move_call(&MoveCall {
+            from: caller,
+            address: address.into(),
+            module: module.clone(),
+            function: function.clone(),
+            type_args: types.iter().map(ToString::to_string).collect(),
+            value,
+            gas: allowance,
+            input,
+        });
+        call(
+            vm,
+            state,
+            MoveCallArgs {
+                depth,
+                caller,
+                address: address.to_vec(),
+                module,
+                function,
+                data,
+                types,
+                value,
+                allowance,
+            },
+        )
+    })?;
+    charge_move_call(gas_left, allowance, gas_used);
+    tracer.record_move_call_result(&move_call_result(&res, gas_used));
+    res.map_err(move_error_revert)
+}
+
+make_solidity_function! {
+    pub struct CallToMoveTyped((Bytes32, String, String, Bytes, Vec<Bytes>), "callMoveTyped(bytes32,string,string,bytes,bytes[])", Bytes);
+}
+
+impl_function_type!(CallToMoveTyped, "payable_write");
+
+impl UpfrontPaymentTrait for CallToMoveTyped {
+    fn upfront_gas_payment(
+        &self,
+        _input: &Self::Input,
+        params: &ActionParams,
+        context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(move_call_gas(params, context.spec))
+    }
+}
+
+impl ExecutionTrait for CallToMoveTyped {
+    /// Calls the function the module registers under `function` with
+    /// `#[cross_vm]`. `data` is the ABI encoding of its parameters after the
+    /// caller, and its return values are ABI encoded in the output.
+    fn execute_inner(
+        &self,
+        (address, module, function, data, encoded_types): (
+            Bytes32,
+            String,
+            String,
+            Bytes,
+            Vec<Bytes>,
+        ),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<Bytes> {
+        let input = data.clone();
+        TrapResult::Return(execute_move_call(
+            (address, module, function, data, encoded_types),
+            input,
+            params,
+            gas_left,
+            context,
+            tracer,
+            |vm, state, call| {
+                vm.call_move_vm_typed(
+                    state,
+                    call.depth,
+                    call.caller,
+                    call.address,
+                    call.module,
+                    call.function,
+                    call.data,
+                    call.types,
+                    call.value,
+                    call.allowance,
+                )
+            },
+        ))
+    }
+}
+
+make_solidity_function! {
+    pub struct CallToMoveAsSigner((Bytes32, String, String, Bytes, Vec<Bytes>), "callMoveAsSigner(bytes32,string,string,bytes,bytes[])");
+}
+
+impl_function_type!(CallToMoveAsSigner, "payable_write");
+
+impl UpfrontPaymentTrait for CallToMoveAsSigner {
+    fn upfront_gas_payment(
+        &self,
+        _input: &Self::Input,
+        params: &ActionParams,
+        context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(move_call_gas(params, context.spec))
+    }
+}
+
+impl ExecutionTrait for CallToMoveAsSigner {
+    /// Calls the entry function `function` as is, with the signer of the Move
+    /// account linked to the caller, which must have delegated its signer for
+    /// it with `0x1::signer_delegation`. `data` is the ABI encoding of the
+    /// parameters after the signer, and the value is issued to the account.
+    fn execute_inner(
+        &self,
+        (address, module, function, data, encoded_types): (
+            Bytes32,
+            String,
+            String,
+            Bytes,
+            Vec<Bytes>,
+        ),
+        params: &ActionParams,
+        gas_left: &mut U256,
+        context: &mut InternalRefContext,
+        tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<()> {
+        let input = data.clone();
+        let res = execute_move_call(
+            (address, module, function, data, encoded_types),
+            input,
+            params,
+            gas_left,
+            context,
+            tracer,
+            |vm, state, call| {
+                vm.call_move_vm_as_signer(
+                    state,
+                    call.depth,
+                    call.caller,
+                    call.address,
+                    call.module,
+                    call.function,
+                    call.data,
+                    call.types,
+                    call.value,
+                    call.allowance,
+                )
+            },
+        );
+        TrapResult::Return(res.map(|_| ()))
     }
 }
 
@@ -518,6 +879,68 @@ fn test_cross_space_contract_sig() {
     );
 }
 
+#[test]
+fn test_move_call_gas() {
+    let spec = Spec::new_spec_for_test();
+    let mut gas_left = U256::from(64_000);
+    let allowance = move_call_allowance(gas_left, &spec);
+    assert_eq!(allowance, U256::from(63_000));
+
+    charge_move_call(&mut gas_left, allowance, 1_000_000);
+    assert_eq!(gas_left, U256::from(54_000));
+    charge_move_call(&mut gas_left, allowance, 1);
+    assert_eq!(gas_left, U256::from(53_999));
+    charge_move_call(&mut gas_left, U256::from(4_000), 1_000_000);
+    assert_eq!(gas_left, U256::from(49_999));
+}
+
+#[test]
+fn test_move_call_depth() {
+    let spec = Spec::new_spec_for_test();
+    assert!(check_move_call_depth(0, &spec).is_ok());
+    let last = spec.max_depth - CROSS_VM_CALL_DEPTH - 1;
+    assert!(check_move_call_depth(last, &spec).is_ok());
+    assert!(check_move_call_depth(last + 1, &spec).is_err());
+    assert!(check_move_call_depth(spec.max_depth, &spec).is_err());
+    // A call stack switches between the VMs at most 7 times with 1024 frames.
+    assert!(check_move_call_depth(6 * CROSS_VM_CALL_DEPTH, &spec).is_ok());
+    assert!(check_move_call_depth(7 * CROSS_VM_CALL_DEPTH, &spec).is_err());
+}
+
+#[test]
+fn test_move_error_revert() {
+    use solidity_abi::ABIDecodable;
+
+    let err = move_error_revert(CallMoveVMError::Other("bad input".into()));
+    let data = match err {
+        vm::Error::InternalContractReverted(data) => data,
+        _ => panic!("expect a revert"),
+    };
+    assert_eq!(
+        crate::execution::revert_reason_decode(&data),
+        "Fail to call move vm: bad input"
+    );
+
+    let err = move_error_revert(CallMoveVMError::Vm {
+        status: 4016,
+        sub_status: Some(7),
+        location: "0x1::coin".into(),
+        message: String::new(),
+    });
+    let data = match err {
+        vm::Error::InternalContractReverted(data) => data,
+        _ => panic!("expect a revert"),
+    };
+    assert_eq!(
+        data[..4],
+        keccak_hash::keccak("MoveError(uint64,uint64,string)")[..4]
+    );
+    assert_eq!(
+        <(u64, u64, String)>::abi_decode(&data[4..]).unwrap(),
+        (4016, 7, "0x1::coin".to_string())
+    );
+}
+
 mod eip_1820 {
     use super::Address;
     pub use rustc_hex::FromHex;
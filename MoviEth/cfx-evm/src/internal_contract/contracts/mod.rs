@@ -2,6 +2,8 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+pub(super) mod coin_bridge;
+pub(super) mod coin_facade;
 mod context;
 pub(super) mod cross_space;
 mod future;
@@ -44,9 +46,15 @@ pub fn all_internal_contracts() -> Vec<Box<dyn super::InternalContractTrait>> {
         Box::new(context::Context::instance()),
         Box::new(cross_space::CrossSpaceCall::instance()),
         Box::new(system_storage::SystemStorage::instance()),
-        Box::new(future::Reserved3::instance()),
+        Box::new(coin_bridge::CoinBridge::instance()),
         Box::new(future::Reserved8::instance()),
         Box::new(future::Reserved9::instance()),
         Box::new(future::Reserved11::instance()),
     ]
 }
+
+/// The ERC-20 facade of the Move coins. It is not at a single address, so it
+/// is looked up with `coin_facade::is_coin_facade_address`.
+pub fn coin_facade_contract() -> Box<dyn super::InternalContractTrait> {
+    Box::new(coin_facade::CoinFacade::instance())
+}
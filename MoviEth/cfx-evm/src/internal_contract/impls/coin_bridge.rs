@@ -0,0 +1,126 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::super::components::InternalRefContext;
+use crate::{
+    internal_bail,
+    observer::VmObserve,
+    vm::{
+        self, ActionParams, ActionValue, CallType, Context, CreateType, Exec, ExecTrapError,
+        ExecTrapResult, GasLeft, MessageCallResult, ParamsType, ResumeCall, ReturnData, TrapError,
+        TrapResult,
+    },
+};
+use cfx_types::{Address, AddressSpaceUtil, U256};
+use solidity_abi::{ABIDecodable, ABIEncodable};
+
+/// The calldata of a call to `signature` with ABI encoded `args`.
+pub fn encode_call<T: ABIEncodable>(signature: &str, args: T) -> Vec<u8> {
+    let selector = keccak_hash::keccak(signature);
+    [&selector[..4], &args.abi_encode()[..]].concat()
+}
+
+/// Calls `token` from the internal contract, forwarding all but a part of
+/// `gas_left` like the EVM `CALL` does. The internal contract returns once the
+/// call is done, and reverts unless the token reports success, i.e. returns
+/// nothing or `true`.
+pub fn call_erc20(
+    token: Address,
+    data: Vec<u8>,
+    params: &ActionParams,
+    gas_left: U256,
+    context: &mut InternalRefContext,
+) -> vm::Result<ExecTrapError> {
+    if context.depth >= context.spec.max_depth {
+        internal_bail!("Exceed Depth");
+    }
+
+    // A call to an account without code succeeds without moving any token.
+    let address = token.with_space(params.space);
+    let code = match context.state.code(&address)? {
+        Some(code) if !code.is_empty() => code,
+        _ => internal_bail!("Token is not a contract"),
+    };
+    let code_hash = context.state.code_hash(&address)?;
+
+    let call_gas = match context.spec.sub_gas_cap_divisor {
+        Some(divisor) => gas_left - gas_left / U256::from(divisor),
+        None => gas_left,
+    };
+    let next_params = ActionParams {
+        space: params.space,
+        code_address: token,
+        code_hash,
+        address: token,
+        sender: params.address,
+        original_sender: params.original_sender,
+        gas: call_gas,
+        gas_price: params.gas_price,
+        value: ActionValue::Transfer(U256::zero()),
+        code: Some(code),
+        data: Some(data),
+        call_type: CallType::Call,
+        create_type: CreateType::None,
+        params_type: ParamsType::Separate,
+    };
+    Ok(TrapError::Call(
+        next_params,
+        Box::new(Erc20Resume {
+            gas_retained: gas_left - call_gas,
+        }),
+    ))
+}
+
+pub fn process_trap<T>(result: vm::Result<ExecTrapError>) -> ExecTrapResult<T> {
+    match result {
+        Ok(trap) => TrapResult::SubCallCreate(trap),
+        Err(err) => TrapResult::Return(Err(err)),
+    }
+}
+
+struct Erc20Resume {
+    gas_retained: U256,
+}
+
+impl ResumeCall for Erc20Resume {
+    fn resume_call(self: Box<Self>, result: MessageCallResult) -> Box<dyn Exec> {
+        let result = match result {
+            MessageCallResult::Success(gas_left, data) => {
+                let success = data.is_empty() || bool::abi_decode(&data).unwrap_or(false);
+                let data = if success {
+                    ReturnData::empty()
+                } else {
+                    let reason = "ERC-20 call returned false".to_string();
+                    let data = encode_call("Error(string)", reason);
+                    let length = data.len();
+                    ReturnData::new(data, 0, length)
+                };
+                Ok(GasLeft::NeedsReturn {
+                    gas_left: gas_left + self.gas_retained,
+                    data,
+                    apply_state: success,
+                })
+            },
+            MessageCallResult::Reverted(gas_left, data) => Ok(GasLeft::NeedsReturn {
+                gas_left: gas_left + self.gas_retained,
+                data,
+                apply_state: false,
+            }),
+            MessageCallResult::Failed(err) => Err(err),
+        };
+        Box::new(Erc20Result(result))
+    }
+}
+
+struct Erc20Result(vm::Result<GasLeft>);
+
+impl Exec for Erc20Result {
+    fn exec(
+        self: Box<Self>,
+        _context: &mut dyn Context,
+        _tracer: &mut dyn VmObserve,
+    ) -> ExecTrapResult<GasLeft> {
+        TrapResult::Return(self.0)
+    }
+}
@@ -2,6 +2,7 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 pub(super) mod admin;
+pub(super) mod coin_bridge;
 pub(super) mod cross_space;
 
 pub use self::admin::suicide;
@@ -9,5 +9,6 @@ mod utils;
 
 pub use self::{
     components::{InterfaceTrait, InternalContractMap, InternalContractTrait, InternalRefContext},
+    contracts::coin_facade::{coin_facade_address, is_coin_facade_address},
     impls::admin::suicide,
 };
@@ -24,13 +24,14 @@ mod vm_factory;
 pub const EVM_CHAINID: u64 = 129;
 
 pub use call_create_frame::contract_address;
-pub use cfx_state::{CallMoveVMTrait, StateTrait};
+pub use cfx_state::{native_token, CallMoveVMError, CallMoveVMTrait, StateTrait};
 pub use cfx_statedb::{StateDb, StateDbExt, StateDbTrait};
 pub use cfx_storage::StorageTrait;
 pub use evm::FinalizationResult;
 pub use execution::{
     CrossVMParams, CrossVMReturn, ExecutionOutcome, TXExecutor, TransactOptions, TransactionInfo,
 };
+pub use internal_contract::{coin_facade_address, is_coin_facade_address};
 pub use machine::{new_machine_with_builtin, Machine};
 pub use spec::CommonParams;
 pub use state::{State, Substate};
@@ -25,7 +25,9 @@ impl ErrorUnwind {
                 Action::Create(create) => errors.accept_create(create),
                 Action::CallResult(result) => errors.accept_call_result(result),
                 Action::CreateResult(result) => errors.accept_create_result(result),
-                Action::InternalTransferAction(_) => {},
+                Action::InternalTransferAction(_)
+                | Action::MoveCall(_)
+                | Action::MoveCallResult(_) => {},
             }
         }
         errors
@@ -6,6 +6,7 @@ use crate::{
     call_create_frame::FrameReturn,
     vm::{ActionParams, Result as VmResult},
 };
+use trace::{MoveCall, MoveCallResult};
 pub use cfx_state::tracer::{AddressPocket, StateTracer};
 
 pub mod error_unwind;
@@ -34,6 +35,12 @@ pub trait VmObserve: StateTracer {
 
     /// Prepares create result trace
     fn record_create_result(&mut self, result: &VmResult<FrameReturn>);
+
+    /// Prepares the trace of a call into the Move VM.
+    fn record_move_call(&mut self, _call: &MoveCall) {}
+
+    /// Prepares the result trace of a call into the Move VM.
+    fn record_move_call_result(&mut self, _result: &MoveCallResult) {}
 }
 
 /// Nonoperative observer. Does not trace anything.
@@ -66,6 +73,14 @@ where
     fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
         (*self).record_create_result(result);
     }
+
+    fn record_move_call(&mut self, call: &MoveCall) {
+        (*self).record_move_call(call);
+    }
+
+    fn record_move_call_result(&mut self, result: &MoveCallResult) {
+        (*self).record_move_call_result(result);
+    }
 }
 
 impl<S, T> VmObserve for (S, T)
@@ -92,6 +107,16 @@ where
         self.0.record_create_result(result);
         self.1.record_create_result(result);
     }
+
+    fn record_move_call(&mut self, call: &MoveCall) {
+        self.0.record_move_call(call);
+        self.1.record_move_call(call);
+    }
+
+    fn record_move_call_result(&mut self, result: &MoveCallResult) {
+        self.0.record_move_call_result(result);
+        self.1.record_move_call_result(result);
+    }
 }
 
 // impl<S, T> VmObserve for (&mut S, &mut T)
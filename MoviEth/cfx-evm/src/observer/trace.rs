@@ -296,6 +296,53 @@ impl InternalTransferAction {
     }
 }
 
+/// Description of a call from the EVM into the Move VM, made through the
+/// cross space internal contract.
+#[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveCall {
+    /// The EVM account calling the Move function.
+    pub from: Address,
+    /// The account of the Move module.
+    pub address: H256,
+    /// The Move module. It is empty for a plain transfer.
+    pub module: String,
+    /// The Move function.
+    pub function: String,
+    /// The type arguments of the Move function.
+    pub type_args: Vec<String>,
+    /// The value transferred to `address`.
+    pub value: U256,
+    /// The gas available for the Move call.
+    pub gas: U256,
+    /// The arguments as passed to the Move VM, i.e. a BCS encoded
+    /// `vector<vector<u8>>` for `callMove`, or ABI encoded for `callMoveTyped`
+    /// and `callMoveAsSigner`.
+    pub input: Bytes,
+}
+
+impl MoveCall {
+    /// Returns the Move call bloom, which contains only the caller address.
+    pub fn bloom(&self) -> Bloom {
+        BloomInput::Raw(self.from.as_bytes()).into()
+    }
+}
+
+/// Description of the result of a call into the Move VM.
+#[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveCallResult {
+    /// The outcome of the Move call. A failed Move call reverts its caller.
+    pub outcome: Outcome,
+    /// The Move internal gas used, including the gas used before a failure.
+    pub gas_used: U256,
+    /// Output data
+    pub return_data: Bytes,
+    /// Why the Move call failed, e.g. the abort code and location of an
+    /// abort. It is empty on success.
+    pub error: String,
+}
+
 /// Description of an action that we trace; will be either a call or a create.
 #[derive(Debug, Clone, PartialEq, EnumDiscriminants)]
 #[strum_discriminants(name(ActionType))]
@@ -310,6 +357,10 @@ pub enum Action {
     CreateResult(CreateResult),
     /// It's an internal transfer action
     InternalTransferAction(InternalTransferAction),
+    /// It's a call into the Move VM
+    MoveCall(MoveCall),
+    /// It's the result of a call into the Move VM
+    MoveCallResult(MoveCallResult),
 }
 
 impl Encodable for Action {
@@ -336,6 +387,14 @@ impl Encodable for Action {
                 s.append(&4u8);
                 s.append(internal_action);
             },
+            Action::MoveCall(ref move_call) => {
+                s.append(&5u8);
+                s.append(move_call);
+            },
+            Action::MoveCallResult(ref move_call_result) => {
+                s.append(&6u8);
+                s.append(move_call_result);
+            },
         }
     }
 }
@@ -349,6 +408,8 @@ impl Decodable for Action {
             2 => rlp.val_at(1).map(Action::CallResult),
             3 => rlp.val_at(1).map(Action::CreateResult),
             4 => rlp.val_at(1).map(Action::InternalTransferAction),
+            5 => rlp.val_at(1).map(Action::MoveCall),
+            6 => rlp.val_at(1).map(Action::MoveCallResult),
             _ => Err(DecoderError::Custom("Invalid action type.")),
         }
     }
@@ -363,6 +424,8 @@ impl Action {
             Action::CallResult(_) => Bloom::default(),
             Action::CreateResult(ref create_result) => create_result.bloom(),
             Action::InternalTransferAction(ref internal_action) => internal_action.bloom(),
+            Action::MoveCall(ref move_call) => move_call.bloom(),
+            Action::MoveCallResult(_) => Bloom::default(),
         }
     }
 }
@@ -509,7 +572,9 @@ impl TransactionExecTraces {
                         sublen_stack.pop();
                     }
                 },
-                Action::InternalTransferAction(_) => {},
+                Action::InternalTransferAction(_)
+                | Action::MoveCall(_)
+                | Action::MoveCallResult(_) => {},
             }
         }
         if !stack_index.is_empty() {
@@ -564,7 +629,9 @@ impl TransactionExecTraces {
                         traces.push(trace);
                     }
                 },
-                Action::InternalTransferAction(_) => {
+                Action::InternalTransferAction(_)
+                | Action::MoveCall(_)
+                | Action::MoveCallResult(_) => {
                     traces.push(trace);
                 },
             }
@@ -628,9 +695,13 @@ impl Into<Vec<TransactionExecTraces>> for BlockExecTraces {
 #[cfg(test)]
 mod tests {
     use crate::{
-        observer::trace::{Action, BlockExecTraces, Call, ExecTrace, TransactionExecTraces},
+        observer::trace::{
+            Action, BlockExecTraces, Call, ExecTrace, MoveCall, MoveCallResult, Outcome,
+            TransactionExecTraces,
+        },
         vm::CallType,
     };
+    use cfx_types::H256;
     use rlp::*;
 
     #[test]
@@ -687,9 +758,33 @@ mod tests {
             valid: true,
         };
 
+        let move_call = ExecTrace {
+            action: Action::MoveCall(MoveCall {
+                from: "3d0768da09ce77d25e2d998e6a7b6ed4b9116c2d".parse().unwrap(),
+                address: H256::from_low_u64_be(1),
+                module: "coin".into(),
+                function: "ihe_transfer".into(),
+                type_args: vec!["0x1::aptos_coin::AptosCoin".into()],
+                value: 0.into(),
+                gas: 0x010C78.into(),
+                input: vec![0x02, 0x01, 0x01, 0x00],
+            }),
+            valid: true,
+        };
+
+        let move_call_result = ExecTrace {
+            action: Action::MoveCallResult(MoveCallResult {
+                outcome: Outcome::Reverted,
+                gas_used: 0x0100.into(),
+                return_data: vec![],
+                error: "Move VM error 4016 (sub status Some(7)) at 0x1::coin: ".into(),
+            }),
+            valid: false,
+        };
+
         let block_traces = BlockExecTraces(vec![
             TransactionExecTraces(vec![flat_trace]),
-            TransactionExecTraces(vec![flat_trace1]),
+            TransactionExecTraces(vec![flat_trace1, move_call, move_call_result]),
         ]);
 
         let encoded = ::rlp::encode(&block_traces);
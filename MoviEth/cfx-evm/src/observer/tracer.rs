@@ -3,6 +3,7 @@ use crate::{
     call_create_frame::FrameReturn,
     observer::trace::{
         Action, Call, CallResult, Create, CreateResult, ExecTrace, InternalTransferAction,
+        MoveCall, MoveCallResult,
     },
     vm::{ActionParams, Result as VmResult},
 };
@@ -92,6 +93,16 @@ impl VmObserve for ExecutiveTracer {
         }
         self.traces.push(action);
     }
+
+    fn record_move_call(&mut self, call: &MoveCall) {
+        self.valid_indices.push(self.traces.len());
+        self.traces.push(Action::MoveCall(call.clone()));
+    }
+
+    fn record_move_call_result(&mut self, result: &MoveCallResult) {
+        self.valid_indices.push(self.traces.len());
+        self.traces.push(Action::MoveCallResult(result.clone()));
+    }
 }
 
 impl ExecutiveTracer {
@@ -66,6 +66,8 @@ pub struct TransitionsBlockNumber {
     /// CIP-105: PoS staking based minimal votes.
     pub cip105: BlockNumber,
     pub cip_sigma_fix: BlockNumber,
+    /// EIP-2929: Gas cost increases for state access opcodes
+    pub eip2929: BlockNumber,
 }
 
 #[derive(Default, Debug, Clone)]
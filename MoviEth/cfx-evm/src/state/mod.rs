@@ -5,7 +5,7 @@
 use self::account_entry::{AccountEntry, AccountState};
 pub use self::{
     account_entry::OverlayAccount,
-    substate::{cleanup_mode, FrameStackInfo, Substate},
+    substate::{cleanup_mode, AccessedSet, FrameStackInfo, Substate},
 };
 use crate::hash::KECCAK_EMPTY;
 use cfx_bytes::Bytes;
@@ -45,11 +45,15 @@ pub enum RequireCache {
 struct WorldStatistics {
     // This is the total number of tokens issued.
     total_issued_tokens: U256,
+    // The tokens issued and burned by cross-VM calls since the state was
+    // created. They are not committed.
+    cross_vm_issued_tokens: U256,
+    cross_vm_burned_tokens: U256,
 }
 
 pub struct State<'a> {
     db: StateDb<'a>,
-    move_vm: Option<&'a mut dyn CallMoveVMTrait>,
+    move_vm: Option<&'a dyn CallMoveVMTrait>,
 
     // Only created once for txpool notification.
     // Each element is an Ok(Account) for updated account, or
@@ -102,10 +106,16 @@ impl<'a> StateTrait for State<'a> {
 }
 
 impl<'a> StateOpsTrait for State<'a> {
-    fn as_move_vm(&mut self) -> Option<&mut dyn CallMoveVMTrait> {
-        match (&mut self.move_vm).as_mut() {
-            Some(vm) => Some(&mut *vm),
-            None => None,
+    fn with_move_vm(
+        &mut self,
+        f: &mut dyn FnMut(&dyn CallMoveVMTrait, &mut dyn StateTrait),
+    ) -> bool {
+        match self.move_vm {
+            Some(vm) => {
+                f(vm, self);
+                true
+            },
+            None => false,
         }
     }
 
@@ -122,6 +132,17 @@ impl<'a> StateOpsTrait for State<'a> {
             self.world_statistics.total_issued_tokens.saturating_sub(v);
     }
 
+    fn issue_cross_vm(&mut self, v: U256) {
+        self.world_statistics.total_issued_tokens += v;
+        self.world_statistics.cross_vm_issued_tokens += v;
+    }
+
+    fn burn_cross_vm(&mut self, v: U256) {
+        self.world_statistics.total_issued_tokens =
+            self.world_statistics.total_issued_tokens.saturating_sub(v);
+        self.world_statistics.cross_vm_burned_tokens += v;
+    }
+
     fn new_contract(
         &mut self,
         contract: &AddressWithSpace,
@@ -273,6 +294,13 @@ impl<'a> StateOpsTrait for State<'a> {
         self.world_statistics.total_issued_tokens
     }
 
+    fn cross_vm_tokens(&self) -> (U256, U256) {
+        (
+            self.world_statistics.cross_vm_issued_tokens,
+            self.world_statistics.cross_vm_burned_tokens,
+        )
+    }
+
     fn remove_contract(&mut self, address: &AddressWithSpace) -> DbResult<()> {
         Self::update_cache(
             self.cache.get_mut(),
@@ -329,6 +357,9 @@ impl<'a> CheckpointTrait for State<'a> {
     /// creation time of the checkpoint and updated after that and before
     /// the creation of the next checkpoint.
     fn checkpoint(&mut self) -> usize {
+        if let Some(move_vm) = self.move_vm {
+            move_vm.checkpoint();
+        }
         self.world_statistics_checkpoints
             .get_mut()
             .push(self.world_statistics.clone());
@@ -343,6 +374,9 @@ impl<'a> CheckpointTrait for State<'a> {
     /// `collect_ownership_changed()` was called before calling
     /// this function.
     fn discard_checkpoint(&mut self) {
+        if let Some(move_vm) = self.move_vm {
+            move_vm.discard_checkpoint();
+        }
         // merge with previous checkpoint
         let last = self.checkpoints.get_mut().pop();
         if let Some(mut checkpoint) = last {
@@ -361,6 +395,9 @@ impl<'a> CheckpointTrait for State<'a> {
 
     /// Revert to the last checkpoint and discard it.
     fn revert_to_checkpoint(&mut self) {
+        if let Some(move_vm) = self.move_vm {
+            move_vm.revert_to_checkpoint();
+        }
         if let Some(mut checkpoint) = self.checkpoints.get_mut().pop() {
             self.world_statistics = self
                 .world_statistics_checkpoints
@@ -406,6 +443,8 @@ impl<'a> State<'a> {
 
         let world_statistics = WorldStatistics {
             total_issued_tokens,
+            cross_vm_issued_tokens: U256::zero(),
+            cross_vm_burned_tokens: U256::zero(),
         };
 
         Ok(State {
@@ -421,12 +460,14 @@ impl<'a> State<'a> {
 
     pub fn new_with_move_vm(
         db: StateDb<'a>,
-        move_vm: &'a mut dyn CallMoveVMTrait,
+        move_vm: &'a dyn CallMoveVMTrait,
     ) -> DbResult<Self> {
         let total_issued_tokens = db.get_total_issued_tokens()?;
 
         let world_statistics = WorldStatistics {
             total_issued_tokens,
+            cross_vm_issued_tokens: U256::zero(),
+            cross_vm_burned_tokens: U256::zero(),
         };
 
         Ok(State {
@@ -4,7 +4,7 @@
 
 use super::CleanupMode;
 use crate::evm::{CleanDustMode, Spec};
-use cfx_types::AddressWithSpace;
+use cfx_types::{Address, AddressWithSpace, H256};
 use primitives::LogEntry;
 use std::collections::{HashMap, HashSet};
 
@@ -12,16 +12,30 @@ use std::collections::{HashMap, HashSet};
 pub struct FrameStackInfo {
     call_stack_recipient_addresses: Vec<(AddressWithSpace, bool)>,
     address_counter: HashMap<AddressWithSpace, u32>,
+    accessed: AccessedSet,
 }
 
 impl FrameStackInfo {
     pub fn new() -> Self {
+        Self::with_accessed(AccessedSet::new())
+    }
+
+    pub fn with_accessed(accessed: AccessedSet) -> Self {
         FrameStackInfo {
             call_stack_recipient_addresses: Vec::default(),
             address_counter: HashMap::default(),
+            accessed,
         }
     }
 
+    pub fn accessed(&self) -> &AccessedSet {
+        &self.accessed
+    }
+
+    pub fn accessed_mut(&mut self) -> &mut AccessedSet {
+        &mut self.accessed
+    }
+
     pub fn push(&mut self, address: AddressWithSpace, is_create: bool) {
         self.call_stack_recipient_addresses
             .push((address.clone(), is_create));
@@ -54,6 +68,80 @@ impl FrameStackInfo {
     }
 }
 
+#[derive(Debug)]
+enum AccessedItem {
+    Address(Address),
+    StorageKey(Address, H256),
+}
+
+/// The accounts and storage slots a transaction has accessed, which EIP-2929
+/// charges less for. Like the state, it keeps a checkpoint per frame, and the
+/// accesses of a reverted frame are forgotten.
+#[derive(Debug, Default)]
+pub struct AccessedSet {
+    addresses: HashSet<Address>,
+    storage_keys: HashSet<(Address, H256)>,
+    /// The accesses made since the first checkpoint, in order.
+    journal: Vec<AccessedItem>,
+    /// The journal length at each checkpoint.
+    checkpoints: Vec<usize>,
+}
+
+impl AccessedSet {
+    pub fn new() -> Self {
+        AccessedSet::default()
+    }
+
+    pub fn is_warm_address(&self, address: &Address) -> bool {
+        self.addresses.contains(address)
+    }
+
+    pub fn is_warm_storage_key(&self, address: &Address, key: &H256) -> bool {
+        self.storage_keys.contains(&(*address, *key))
+    }
+
+    pub fn warm_address(&mut self, address: Address) {
+        if self.addresses.insert(address) && !self.checkpoints.is_empty() {
+            self.journal.push(AccessedItem::Address(address));
+        }
+    }
+
+    pub fn warm_storage_key(&mut self, address: Address, key: H256) {
+        if self.storage_keys.insert((address, key)) && !self.checkpoints.is_empty() {
+            self.journal.push(AccessedItem::StorageKey(address, key));
+        }
+    }
+
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(self.journal.len());
+    }
+
+    /// Keeps the accesses since the last checkpoint and drops the checkpoint.
+    pub fn discard_checkpoint(&mut self) {
+        self.checkpoints.pop();
+        if self.checkpoints.is_empty() {
+            self.journal.clear();
+        }
+    }
+
+    /// Forgets the accesses since the last checkpoint and drops the
+    /// checkpoint.
+    pub fn revert_to_checkpoint(&mut self) {
+        if let Some(start) = self.checkpoints.pop() {
+            for item in self.journal.drain(start..) {
+                match item {
+                    AccessedItem::Address(address) => {
+                        self.addresses.remove(&address);
+                    },
+                    AccessedItem::StorageKey(address, key) => {
+                        self.storage_keys.remove(&(address, key));
+                    },
+                }
+            }
+        }
+    }
+}
+
 /// State changes which should be applied in finalize,
 /// after transaction is fully executed.
 /// A Substate object is maintained for each contract
@@ -99,10 +187,10 @@ pub fn cleanup_mode<'a>(substate: &'a mut Substate, spec: &Spec) -> CleanupMode<
 
 #[cfg(test)]
 mod tests {
-    use super::FrameStackInfo;
+    use super::{AccessedSet, FrameStackInfo};
     use crate::state::Substate;
     use cfx_state::substate_trait::SubstateMngTrait;
-    use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, Space};
+    use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, Space, H256};
     use primitives::LogEntry;
 
     #[test]
@@ -151,6 +239,32 @@ mod tests {
         get_test_address_raw(n).with_native_space()
     }
 
+    #[test]
+    fn test_accessed_set() {
+        let key = H256::from_low_u64_be(1);
+        let mut accessed = AccessedSet::new();
+        accessed.warm_address(get_test_address_raw(1));
+
+        accessed.checkpoint();
+        accessed.warm_address(get_test_address_raw(2));
+        accessed.checkpoint();
+        accessed.warm_storage_key(get_test_address_raw(2), key);
+        accessed.warm_address(get_test_address_raw(3));
+        accessed.revert_to_checkpoint();
+        assert!(accessed.is_warm_address(&get_test_address_raw(2)));
+        assert!(!accessed.is_warm_address(&get_test_address_raw(3)));
+        assert!(!accessed.is_warm_storage_key(&get_test_address_raw(2), &key));
+
+        accessed.checkpoint();
+        accessed.warm_storage_key(get_test_address_raw(2), key);
+        accessed.discard_checkpoint();
+        assert!(accessed.is_warm_storage_key(&get_test_address_raw(2), &key));
+        accessed.revert_to_checkpoint();
+        assert!(accessed.is_warm_address(&get_test_address_raw(1)));
+        assert!(!accessed.is_warm_address(&get_test_address_raw(2)));
+        assert!(!accessed.is_warm_storage_key(&get_test_address_raw(2), &key));
+    }
+
     #[test]
     fn test_callstack_info() {
         let mut call_stack = FrameStackInfo::new();
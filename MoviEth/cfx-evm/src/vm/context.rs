@@ -123,6 +123,20 @@ pub trait Context {
         call_type: CallType,
     ) -> cfx_statedb::Result<::std::result::Result<MessageCallResult, TrapKind>>;
 
+    /// Whether the transaction has accessed the address (EIP-2929).
+    fn is_warm_address(&self, address: &Address) -> bool;
+
+    /// Whether the transaction has accessed the given storage key of the
+    /// current contract (EIP-2929).
+    fn is_warm_storage_key(&self, key: &Vec<u8>) -> bool;
+
+    /// Marks the address as accessed by the transaction.
+    fn warm_address(&mut self, address: &Address);
+
+    /// Marks the given storage key of the current contract as accessed by the
+    /// transaction.
+    fn warm_storage_key(&mut self, key: &Vec<u8>);
+
     /// Returns code at given address
     fn extcode(&self, address: &Address) -> Result<Option<Arc<Bytes>>>;
 
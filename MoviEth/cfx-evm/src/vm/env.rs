@@ -26,6 +26,7 @@
 
 use cfx_types::{Address, H256, U256};
 use primitives::BlockNumber;
+use std::sync::Arc;
 
 /// Information concerning the execution environment for a
 /// message-call/contract-creation.
@@ -43,10 +44,18 @@ pub struct Env {
     pub gas_limit: U256,
     /// The last block hash.
     pub last_hash: H256,
+    /// The hashes of the most recent blocks, the oldest first. The last one
+    /// is `last_hash`.
+    pub last_hashes: Arc<Vec<H256>>,
     /// The total gas used in the block following execution of the transaction.
     pub accumulated_gas_used: U256,
     /// The epoch height.
     pub epoch_height: u64,
+    /// The chain id of the Ethereum space, as returned by `CHAINID`.
+    pub chain_id: u64,
+    /// The base fee per gas of the block (EIP-1559). Dynamic fee
+    /// transactions pay at most this plus their priority fee.
+    pub base_fee_per_gas: U256,
 }
 
 #[cfg(test)]
@@ -107,6 +107,9 @@ pub enum Error {
     BuiltIn(&'static str),
     /// Internal contract failed
     InternalContract(String),
+    /// Internal contract reverted with the given revert data. It is turned
+    /// into a `REVERT` of the internal contract call.
+    InternalContractReverted(Vec<u8>),
     /// When execution tries to modify the state in static context
     MutableCallInStaticContext,
     /// Error from storage.
@@ -184,6 +187,9 @@ impl fmt::Display for Error {
             ExceedStorageLimit => write!(f, "Exceed storage limit"),
             BuiltIn(name) => write!(f, "Built-in failed: {}", name),
             InternalContract(ref name) => write!(f, "InternalContract failed: {}", name),
+            InternalContractReverted(ref data) => {
+                write!(f, "InternalContract reverted: 0x{}", hex::encode(data))
+            },
             StateDbError(ref msg) => write!(f, "Irrecoverable state db error: {}", msg.0),
             MutableCallInStaticContext => write!(f, "Mutable call in static context"),
             Wasm(ref msg) => write!(f, "Internal error: {}", msg),
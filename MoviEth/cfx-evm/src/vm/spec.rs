@@ -159,6 +159,19 @@ pub struct Spec {
     /// CIP-105: Minimal DAO votes requirement based on PoS votes.
     pub cip105: bool,
     pub cip_sigma_fix: bool,
+    /// EIP-2929: Gas cost increases for state access opcodes, with accounts
+    /// and storage keys in the access list of the transaction warm.
+    pub eip2929: bool,
+    /// Price of the first access to a storage slot in a transaction
+    pub cold_sload_gas: usize,
+    /// Price of the first access to an account in a transaction
+    pub cold_account_access_gas: usize,
+    /// Price of later accesses to an account or a storage slot
+    pub warm_storage_read_gas: usize,
+    /// Transaction cost of each address in the access list
+    pub tx_access_list_address_gas: usize,
+    /// Transaction cost of each storage key in the access list
+    pub tx_access_list_storage_key_gas: usize,
 }
 
 /// Wasm cost table
@@ -300,6 +313,12 @@ impl Spec {
             cip98: false,
             cip105: false,
             cip_sigma_fix: false,
+            eip2929: false,
+            cold_sload_gas: 2100,
+            cold_account_access_gas: 2600,
+            warm_storage_read_gas: 100,
+            tx_access_list_address_gas: 2400,
+            tx_access_list_storage_key_gas: 1900,
         }
     }
 
@@ -320,6 +339,7 @@ impl Spec {
         spec.cip98 = number >= params.transition_numbers.cip98;
         spec.cip105 = number >= params.transition_numbers.cip105;
         spec.cip_sigma_fix = number >= params.transition_numbers.cip_sigma_fix;
+        spec.eip2929 = number >= params.transition_numbers.eip2929;
         spec
     }
 
@@ -70,6 +70,8 @@ pub struct MockContext {
     pub env: Env,
     pub spec: Spec,
     pub balances: HashMap<Address, U256>,
+    pub warm_addresses: HashSet<Address>,
+    pub warm_storage_keys: HashSet<Vec<u8>>,
     pub tracing: bool,
     pub is_static: bool,
 
@@ -192,6 +194,22 @@ impl Context for MockContext {
         Ok(Ok(MessageCallResult::Success(*gas, ReturnData::empty())))
     }
 
+    fn is_warm_address(&self, address: &Address) -> bool {
+        self.warm_addresses.contains(address)
+    }
+
+    fn is_warm_storage_key(&self, key: &Vec<u8>) -> bool {
+        self.warm_storage_keys.contains(key)
+    }
+
+    fn warm_address(&mut self, address: &Address) {
+        self.warm_addresses.insert(*address);
+    }
+
+    fn warm_storage_key(&mut self, key: &Vec<u8>) {
+        self.warm_storage_keys.insert(key.clone());
+    }
+
     fn extcode(&self, address: &Address) -> Result<Option<Arc<Bytes>>> {
         Ok(self.codes.get(address).cloned())
     }
@@ -23,11 +23,15 @@ lazy_static! {
         Address::from_str("0888000000000000000000000000000000000007").unwrap();
     pub static ref SYSTEM_STORAGE_ADDRESS: Address =
         Address::from_str("088800000000000000000000000000000000000a").unwrap();
+    pub static ref COIN_BRIDGE_CONTRACT_ADDRESS: Address =
+        Address::from_str("0888000000000000000000000000000000000003").unwrap();
+    /// The ERC-20 facades of Move coins share the first 8 bytes of this
+    /// address, and the other 12 bytes are derived from the coin type.
+    pub static ref COIN_FACADE_BASE_ADDRESS: Address =
+        Address::from_str("0889000000000000000000000000000000000000").unwrap();
 
     // We reserve more addresses so we don't need to change the genesis hash
     // in test mode each time adding new internal contracts.
-    pub static ref RESERVED3: Address =
-        Address::from_str("0888000000000000000000000000000000000003").unwrap();
     pub static ref RESERVED8: Address =
         Address::from_str("0888000000000000000000000000000000000008").unwrap();
     pub static ref RESERVED9: Address =
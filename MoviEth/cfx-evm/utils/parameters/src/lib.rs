@@ -25,3 +25,23 @@ pub mod block {
     // left can be passed to the cross space call.
     pub const CROSS_SPACE_GAS_RATIO: u64 = 10;
 }
+
+pub mod cross_vm {
+    /// Exchange rate between EVM gas and Move internal gas units. With the
+    /// default `gas_unit_scaling_factor` of 10_000, one external Move gas unit
+    /// pays for 100 EVM gas.
+    pub const INTERNAL_GAS_PER_EVM_GAS: u64 = 100;
+
+    /// Call depth a switch between the VMs adds, counted against the same
+    /// `max_depth` limit as the EVM frames. Every switch runs a new Move
+    /// session or EVM executor on the native stack, so a call stack switches
+    /// VMs at most 7 times.
+    pub const CROSS_VM_CALL_DEPTH: usize = 128;
+
+    /// EVM gas paying for `internal_gas` Move internal gas, rounded up so
+    /// that a Move call is never cheaper than the gas it used.
+    pub fn internal_gas_to_evm_gas_ceil(internal_gas: u64) -> u64 {
+        internal_gas / INTERNAL_GAS_PER_EVM_GAS
+            + (internal_gas % INTERNAL_GAS_PER_EVM_GAS != 0) as u64
+    }
+}
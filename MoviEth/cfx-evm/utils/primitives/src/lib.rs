@@ -42,7 +42,8 @@ pub use crate::{
     storage::{MptValue, NodeMerkleTriplet, StorageLayout, StorageRoot, StorageValue},
     storage_key::*,
     transaction::{
-        Action, Eip155Transaction, NativeTransaction, SignedTransaction, Transaction,
+        AccessList, AccessListItem, Action, Eip1559Transaction, Eip155Transaction,
+        Eip2930Transaction, NativeTransaction, SignedTransaction, Transaction,
         TransactionWithSignature, TransactionWithSignatureSerializePart, TxPropagateId,
     },
     transaction_index::TransactionIndex,
@@ -384,9 +384,162 @@ impl Encodable for Eip155Transaction {
 //     }
 // }
 
+/// EIP-2718 type of EIP-2930 access list transactions.
+pub const EIP2930_TYPE: u8 = 0x01;
+
+/// EIP-2718 type of EIP-1559 dynamic fee transactions.
+pub const EIP1559_TYPE: u8 = 0x02;
+
+/// An account, and storage keys of it, that a transaction declares it will
+/// access (EIP-2930).
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<H256>,
+}
+
+pub type AccessList = Vec<AccessListItem>;
+
+impl Encodable for AccessListItem {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        s.append(&self.address);
+        s.append_list(&self.storage_keys);
+    }
+}
+
+impl Decodable for AccessListItem {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        match rlp.item_count()? {
+            2 => Ok(AccessListItem {
+                address: rlp.val_at(0)?,
+                storage_keys: rlp.list_at(1)?,
+            }),
+            _ => Err(DecoderError::RlpInvalidLength),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Eip2930Transaction {
+    /// The chain id of the transaction
+    pub chain_id: u32,
+    /// Nonce.
+    pub nonce: U256,
+    /// Gas price.
+    pub gas_price: U256,
+    /// Gas paid up front for transaction execution.
+    pub gas: U256,
+    /// Action, can be either call or contract create.
+    pub action: Action,
+    /// Transferred value.
+    pub value: U256,
+    /// Transaction data.
+    pub data: Bytes,
+    /// Accounts and storage keys warmed before execution.
+    pub access_list: AccessList,
+}
+
+impl Eip2930Transaction {
+    const FIELD_COUNT: usize = 8;
+
+    fn append_fields(&self, s: &mut RlpStream) {
+        s.append(&self.chain_id);
+        s.append(&self.nonce);
+        s.append(&self.gas_price);
+        s.append(&self.gas);
+        s.append(&self.action);
+        s.append(&self.value);
+        s.append(&self.data);
+        s.append_list(&self.access_list);
+    }
+
+    fn decode_fields(rlp: &Rlp) -> Result<Self, DecoderError> {
+        Ok(Self {
+            chain_id: rlp.val_at(0)?,
+            nonce: rlp.val_at(1)?,
+            gas_price: rlp.val_at(2)?,
+            gas: rlp.val_at(3)?,
+            action: rlp.val_at(4)?,
+            value: rlp.val_at(5)?,
+            data: rlp.val_at(6)?,
+            access_list: rlp.list_at(7)?,
+        })
+    }
+}
+
+impl Encodable for Eip2930Transaction {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(Self::FIELD_COUNT);
+        self.append_fields(s);
+    }
+}
+
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Eip1559Transaction {
+    /// The chain id of the transaction
+    pub chain_id: u32,
+    /// Nonce.
+    pub nonce: U256,
+    /// Tip paid to the block author on top of the base fee, per gas.
+    pub max_priority_fee_per_gas: U256,
+    /// Maximum total price paid per gas, base fee included.
+    pub max_fee_per_gas: U256,
+    /// Gas paid up front for transaction execution.
+    pub gas: U256,
+    /// Action, can be either call or contract create.
+    pub action: Action,
+    /// Transferred value.
+    pub value: U256,
+    /// Transaction data.
+    pub data: Bytes,
+    /// Accounts and storage keys warmed before execution.
+    pub access_list: AccessList,
+}
+
+impl Eip1559Transaction {
+    const FIELD_COUNT: usize = 9;
+
+    fn append_fields(&self, s: &mut RlpStream) {
+        s.append(&self.chain_id);
+        s.append(&self.nonce);
+        s.append(&self.max_priority_fee_per_gas);
+        s.append(&self.max_fee_per_gas);
+        s.append(&self.gas);
+        s.append(&self.action);
+        s.append(&self.value);
+        s.append(&self.data);
+        s.append_list(&self.access_list);
+    }
+
+    fn decode_fields(rlp: &Rlp) -> Result<Self, DecoderError> {
+        Ok(Self {
+            chain_id: rlp.val_at(0)?,
+            nonce: rlp.val_at(1)?,
+            max_priority_fee_per_gas: rlp.val_at(2)?,
+            max_fee_per_gas: rlp.val_at(3)?,
+            gas: rlp.val_at(4)?,
+            action: rlp.val_at(5)?,
+            value: rlp.val_at(6)?,
+            data: rlp.val_at(7)?,
+            access_list: rlp.list_at(8)?,
+        })
+    }
+}
+
+impl Encodable for Eip1559Transaction {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(Self::FIELD_COUNT);
+        self.append_fields(s);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Transaction {
     Ethereum(Eip155Transaction),
+    Eip2930(Eip2930Transaction),
+    Eip1559(Eip1559Transaction),
 }
 
 impl Default for Transaction {
@@ -401,11 +554,25 @@ impl From<Eip155Transaction> for Transaction {
     }
 }
 
+impl From<Eip2930Transaction> for Transaction {
+    fn from(tx: Eip2930Transaction) -> Self {
+        Self::Eip2930(tx)
+    }
+}
+
+impl From<Eip1559Transaction> for Transaction {
+    fn from(tx: Eip1559Transaction) -> Self {
+        Self::Eip1559(tx)
+    }
+}
+
 macro_rules! access_common_ref {
     ($field:ident, $ty:ident) => {
         pub fn $field(&self) -> &$ty {
             match self {
                 Transaction::Ethereum(tx) => &tx.$field,
+                Transaction::Eip2930(tx) => &tx.$field,
+                Transaction::Eip1559(tx) => &tx.$field,
             }
         }
     };
@@ -417,6 +584,8 @@ macro_rules! access_common {
         pub fn $field(&self) -> $ty {
             match self {
                 Transaction::Ethereum(tx) => tx.$field,
+                Transaction::Eip2930(tx) => tx.$field,
+                Transaction::Eip1559(tx) => tx.$field,
             }
         }
     };
@@ -424,8 +593,6 @@ macro_rules! access_common {
 impl Transaction {
     access_common_ref!(gas, U256);
 
-    access_common_ref!(gas_price, U256);
-
     access_common_ref!(data, Bytes);
 
     access_common_ref!(nonce, U256);
@@ -434,15 +601,53 @@ impl Transaction {
 
     access_common_ref!(value, U256);
 
+    /// The gas price of a legacy or EIP-2930 transaction, or the max fee per
+    /// gas of an EIP-1559 transaction.
+    pub fn gas_price(&self) -> &U256 {
+        match self {
+            Transaction::Ethereum(tx) => &tx.gas_price,
+            Transaction::Eip2930(tx) => &tx.gas_price,
+            Transaction::Eip1559(tx) => &tx.max_fee_per_gas,
+        }
+    }
+
+    pub fn max_priority_fee_per_gas(&self) -> Option<&U256> {
+        match self {
+            Transaction::Ethereum(_) | Transaction::Eip2930(_) => None,
+            Transaction::Eip1559(tx) => Some(&tx.max_priority_fee_per_gas),
+        }
+    }
+
+    pub fn access_list(&self) -> Option<&AccessList> {
+        match self {
+            Transaction::Ethereum(_) => None,
+            Transaction::Eip2930(tx) => Some(&tx.access_list),
+            Transaction::Eip1559(tx) => Some(&tx.access_list),
+        }
+    }
+
+    /// The EIP-2718 transaction type, 0 for legacy transactions.
+    pub fn type_id(&self) -> u8 {
+        match self {
+            Transaction::Ethereum(_) => 0,
+            Transaction::Eip2930(_) => EIP2930_TYPE,
+            Transaction::Eip1559(_) => EIP1559_TYPE,
+        }
+    }
+
     pub fn chain_id(&self) -> Option<u32> {
         match self {
             Transaction::Ethereum(tx) => tx.chain_id,
+            Transaction::Eip2930(tx) => Some(tx.chain_id),
+            Transaction::Eip1559(tx) => Some(tx.chain_id),
         }
     }
 
     pub fn nonce_mut(&mut self) -> &mut U256 {
         match self {
             Transaction::Ethereum(tx) => &mut tx.nonce,
+            Transaction::Eip2930(tx) => &mut tx.nonce,
+            Transaction::Eip1559(tx) => &mut tx.nonce,
         }
     }
 }
@@ -451,19 +656,33 @@ impl Transaction {
     // This function returns the hash value used in transaction signature. It is
     // different from transaction hash. The transaction hash also contains
     // signatures.
+    //
+    // Typed transactions sign their type byte followed by the RLP list of
+    // their fields (EIP-2718).
     pub fn signature_hash(&self) -> H256 {
         let mut s = RlpStream::new();
         match self {
             Transaction::Ethereum(tx) => {
                 s.append(tx);
             },
+            Transaction::Eip2930(tx) => {
+                s.append(tx);
+            },
+            Transaction::Eip1559(tx) => {
+                s.append(tx);
+            },
+        }
+        match self.type_id() {
+            0 => keccak(s.as_raw()),
+            type_id => keccak([&[type_id][..], s.as_raw()].concat()),
         }
-        keccak(s.as_raw())
     }
 
     pub fn space(&self) -> Space {
         match self {
-            Transaction::Ethereum(_) => Space::Ethereum,
+            Transaction::Ethereum(_) | Transaction::Eip2930(_) | Transaction::Eip1559(_) => {
+                Space::Ethereum
+            },
         }
     }
 
@@ -513,8 +732,27 @@ pub struct TransactionWithSignatureSerializePart {
     pub s: U256,
 }
 
-impl Encodable for TransactionWithSignatureSerializePart {
-    fn rlp_append(&self, s: &mut RlpStream) {
+impl TransactionWithSignatureSerializePart {
+    /// The EIP-2718 encoding of the transaction: the RLP list of a legacy
+    /// transaction, or the type byte followed by the RLP list of a typed one.
+    pub fn eip2718_bytes(&self) -> Vec<u8> {
+        let mut s = RlpStream::new();
+        self.append_signed_list(&mut s);
+        match self.unsigned.type_id() {
+            0 => s.as_raw().to_vec(),
+            type_id => [&[type_id][..], s.as_raw()].concat(),
+        }
+    }
+
+    /// Decodes the EIP-2718 encoding of a transaction.
+    pub fn from_eip2718_bytes(bytes: &[u8]) -> Result<Self, DecoderError> {
+        match bytes.first() {
+            Some(first) if *first >= 0xc0 => Self::decode_legacy(&Rlp::new(bytes)),
+            _ => Self::decode_typed(bytes),
+        }
+    }
+
+    fn append_signed_list(&self, s: &mut RlpStream) {
         match self.unsigned {
             Transaction::Ethereum(ref tx) => {
                 let Eip155Transaction {
@@ -541,12 +779,24 @@ impl Encodable for TransactionWithSignatureSerializePart {
                 s.append(&self.r);
                 s.append(&self.s);
             },
+            Transaction::Eip2930(ref tx) => {
+                s.begin_list(Eip2930Transaction::FIELD_COUNT + 3);
+                tx.append_fields(s);
+                s.append(&self.v);
+                s.append(&self.r);
+                s.append(&self.s);
+            },
+            Transaction::Eip1559(ref tx) => {
+                s.begin_list(Eip1559Transaction::FIELD_COUNT + 3);
+                tx.append_fields(s);
+                s.append(&self.v);
+                s.append(&self.r);
+                s.append(&self.s);
+            },
         }
     }
-}
 
-impl Decodable for TransactionWithSignatureSerializePart {
-    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+    fn decode_legacy(rlp: &Rlp) -> Result<Self, DecoderError> {
         match rlp.item_count()? {
             9 => {
                 let nonce: U256 = rlp.val_at(0)?;
@@ -585,6 +835,65 @@ impl Decodable for TransactionWithSignatureSerializePart {
             _ => Err(DecoderError::RlpInvalidLength),
         }
     }
+
+    fn decode_typed(bytes: &[u8]) -> Result<Self, DecoderError> {
+        let (type_id, payload) = bytes.split_first().ok_or(DecoderError::RlpIsTooShort)?;
+        let rlp = Rlp::new(payload);
+        let (unsigned, field_count) = match *type_id {
+            EIP2930_TYPE => {
+                if rlp.item_count()? != Eip2930Transaction::FIELD_COUNT + 3 {
+                    return Err(DecoderError::RlpIncorrectListLen);
+                }
+                (
+                    Transaction::Eip2930(Eip2930Transaction::decode_fields(&rlp)?),
+                    Eip2930Transaction::FIELD_COUNT,
+                )
+            },
+            EIP1559_TYPE => {
+                if rlp.item_count()? != Eip1559Transaction::FIELD_COUNT + 3 {
+                    return Err(DecoderError::RlpIncorrectListLen);
+                }
+                (
+                    Transaction::Eip1559(Eip1559Transaction::decode_fields(&rlp)?),
+                    Eip1559Transaction::FIELD_COUNT,
+                )
+            },
+            _ => return Err(DecoderError::Custom("Unsupported transaction type")),
+        };
+        let v: u8 = rlp.val_at(field_count)?;
+        if v > 1 {
+            return Err(DecoderError::Custom("Invalid signature y parity"));
+        }
+        Ok(TransactionWithSignatureSerializePart {
+            unsigned,
+            v,
+            r: rlp.val_at(field_count + 1)?,
+            s: rlp.val_at(field_count + 2)?,
+        })
+    }
+}
+
+// Legacy transactions are RLP lists. Typed transactions are RLP strings holding
+// their EIP-2718 encoding, as in the transaction lists of Ethereum blocks.
+impl Encodable for TransactionWithSignatureSerializePart {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match self.unsigned {
+            Transaction::Ethereum(_) => self.append_signed_list(s),
+            Transaction::Eip2930(_) | Transaction::Eip1559(_) => {
+                s.append(&self.eip2718_bytes());
+            },
+        }
+    }
+}
+
+impl Decodable for TransactionWithSignatureSerializePart {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.is_list() {
+            Self::decode_legacy(rlp)
+        } else {
+            Self::decode_typed(rlp.data()?)
+        }
+    }
 }
 
 impl Deref for TransactionWithSignatureSerializePart {
@@ -645,6 +954,9 @@ impl DerefMut for TransactionWithSignature {
 
 impl Decodable for TransactionWithSignature {
     fn decode(d: &Rlp) -> Result<Self, DecoderError> {
+        if !d.is_list() {
+            return Self::from_raw(d.data()?);
+        }
         let hash = keccak(d.as_raw());
         let rlp_size = Some(d.as_raw().len());
         // Check item count of TransactionWithSignatureSerializePart
@@ -680,9 +992,19 @@ impl TransactionWithSignature {
         }
     }
 
+    /// Decodes a transaction from its EIP-2718 encoding, the raw bytes of
+    /// `eth_sendRawTransaction`.
+    pub fn from_raw(raw: &[u8]) -> Result<Self, DecoderError> {
+        Ok(TransactionWithSignature {
+            transaction: TransactionWithSignatureSerializePart::from_eip2718_bytes(raw)?,
+            hash: keccak(raw),
+            rlp_size: Some(raw.len()),
+        })
+    }
+
     /// Used to compute hash of created transactions
     fn compute_hash(mut self) -> TransactionWithSignature {
-        let hash = keccak(&*self.rlp_bytes());
+        let hash = keccak(self.transaction.eip2718_bytes());
         self.hash = hash;
         self
     }
@@ -864,3 +1186,46 @@ impl MallocSizeOf for SignedTransaction {
         self.transaction.size_of(ops)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keylib::{Generator, Random};
+
+    #[test]
+    fn test_typed_transaction_rlp() {
+        let keypair = Random.generate().unwrap();
+        let tx: Transaction = Eip1559Transaction {
+            chain_id: 1,
+            nonce: 1.into(),
+            max_priority_fee_per_gas: 2.into(),
+            max_fee_per_gas: 10.into(),
+            gas: 21000.into(),
+            action: Action::Call(Address::repeat_byte(0x11)),
+            value: 1.into(),
+            data: vec![1, 2],
+            access_list: vec![AccessListItem {
+                address: Address::repeat_byte(0x22),
+                storage_keys: vec![H256::repeat_byte(0x33)],
+            }],
+        }
+        .into();
+        let signed = tx.sign(keypair.secret());
+
+        let raw = signed.transaction.eip2718_bytes();
+        assert_eq!(raw[0], EIP1559_TYPE);
+        let decoded = TransactionWithSignature::from_raw(&raw).unwrap();
+        assert_eq!(decoded.transaction, signed.transaction.transaction);
+        assert_eq!(decoded.hash(), keccak(&raw));
+        assert_eq!(decoded.hash(), signed.hash());
+        assert_eq!(
+            public_to_address(&decoded.recover_public().unwrap()),
+            keypair.address()
+        );
+
+        // In RLP lists, typed transactions are strings holding their envelope.
+        let nested: TransactionWithSignature = rlp::decode(&rlp::encode(&decoded)).unwrap();
+        assert_eq!(nested.transaction, decoded.transaction);
+        assert_eq!(nested.hash(), decoded.hash());
+    }
+}
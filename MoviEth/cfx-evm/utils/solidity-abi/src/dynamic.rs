@@ -0,0 +1,337 @@
+// Copyright 2020 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! ABI encoding for values whose types are only known at runtime, e.g. parsed
+//! from a function signature like `transfer(address,uint256)`.
+
+use super::{
+    utils::{abi_require, padded_big_endian, pull_slice, LinkedBytes},
+    ABIDecodeError, ABIVariable,
+};
+use cfx_types::{Address, U256};
+use keccak_hash::keccak;
+
+/// Maximum nesting of arrays and tuples in a parsed type, so that a type
+/// string from an untrusted caller cannot overflow the stack of the parser or
+/// of the encoder.
+pub const MAX_TYPE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ABIType {
+    /// `uint<M>`, `M` is the number of bits.
+    Uint(usize),
+    Address,
+    Bool,
+    /// `bytes<M>`, `M` is the number of bytes.
+    FixedBytes(usize),
+    Bytes,
+    String,
+    /// `T[]`
+    Array(Box<ABIType>),
+    /// `T[k]`
+    FixedArray(Box<ABIType>, usize),
+    /// `(T1,T2,...)`
+    Tuple(Vec<ABIType>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ABIValue {
+    Uint(U256),
+    Address(Address),
+    Bool(bool),
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    /// Items of both `T[]` and `T[k]`.
+    Array(Vec<ABIValue>),
+    Tuple(Vec<ABIValue>),
+}
+
+impl ABIType {
+    /// Parses a canonical Solidity type name, like `uint256`, `bytes32[]` or
+    /// `(address,string)`. Signed integers and fixed point numbers are not
+    /// supported.
+    pub fn parse(ty: &str) -> Result<Self, ABIDecodeError> {
+        Self::parse_nested(ty, 0)
+    }
+
+    /// Parses a type nested in `depth` arrays and tuples.
+    fn parse_nested(ty: &str, depth: usize) -> Result<Self, ABIDecodeError> {
+        abi_require(depth <= MAX_TYPE_DEPTH, "Type nested too deeply")?;
+        let ty = ty.trim();
+        if let Some(inner) = ty.strip_suffix(']') {
+            let open = inner.rfind('[').ok_or(ABIDecodeError("Unmatched bracket"))?;
+            let item = Box::new(Self::parse_nested(&inner[..open], depth + 1)?);
+            let length = &inner[open + 1..];
+            return if length.is_empty() {
+                Ok(ABIType::Array(item))
+            } else {
+                let length = length
+                    .parse()
+                    .map_err(|_| ABIDecodeError("Invalid fixed array length"))?;
+                Ok(ABIType::FixedArray(item, length))
+            };
+        }
+        if let Some(inner) = ty.strip_prefix('(') {
+            let inner = inner
+                .strip_suffix(')')
+                .ok_or(ABIDecodeError("Unmatched parenthesis"))?;
+            return Ok(ABIType::Tuple(parse_nested_type_list(inner, depth + 1)?));
+        }
+        match ty {
+            "address" => Ok(ABIType::Address),
+            "bool" => Ok(ABIType::Bool),
+            "bytes" => Ok(ABIType::Bytes),
+            "string" => Ok(ABIType::String),
+            "uint" => Ok(ABIType::Uint(256)),
+            _ => {
+                if let Some(bits) = ty.strip_prefix("uint") {
+                    let bits: usize = bits.parse().map_err(|_| ABIDecodeError("Unknown type"))?;
+                    abi_require(bits > 0 && bits <= 256 && bits % 8 == 0, "Invalid uint size")?;
+                    Ok(ABIType::Uint(bits))
+                } else if let Some(len) = ty.strip_prefix("bytes") {
+                    let len: usize = len.parse().map_err(|_| ABIDecodeError("Unknown type"))?;
+                    abi_require(len > 0 && len <= 32, "Invalid fixed bytes size")?;
+                    Ok(ABIType::FixedBytes(len))
+                } else {
+                    Err(ABIDecodeError("Unknown type"))
+                }
+            },
+        }
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            ABIType::Bytes | ABIType::String | ABIType::Array(_) => true,
+            ABIType::FixedArray(item, _) => item.is_dynamic(),
+            ABIType::Tuple(items) => items.iter().any(ABIType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// The length of this type in the head part of an encoded list.
+    fn head_length(&self) -> Result<usize, ABIDecodeError> {
+        if self.is_dynamic() {
+            return Ok(32);
+        }
+        match self {
+            ABIType::FixedArray(item, length) => item
+                .head_length()?
+                .checked_mul(*length)
+                .ok_or(ABIDecodeError("Fixed array too long")),
+            ABIType::Tuple(items) => heads_length(items),
+            _ => Ok(32),
+        }
+    }
+}
+
+/// The length of the head part of an encoded list of `types`.
+fn heads_length(types: &[ABIType]) -> Result<usize, ABIDecodeError> {
+    types.iter().try_fold(0usize, |sum, ty| {
+        sum.checked_add(ty.head_length()?)
+            .ok_or(ABIDecodeError("Type list too long"))
+    })
+}
+
+/// Parses a comma separated list of types, e.g. the parameters in a function
+/// signature. Commas inside nested tuples are respected.
+pub fn parse_type_list(list: &str) -> Result<Vec<ABIType>, ABIDecodeError> {
+    parse_nested_type_list(list, 0)
+}
+
+/// Parses a list of types nested in `depth` arrays and tuples.
+fn parse_nested_type_list(list: &str, depth: usize) -> Result<Vec<ABIType>, ABIDecodeError> {
+    if list.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let mut types = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (idx, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or(ABIDecodeError("Unmatched parenthesis"))?
+            },
+            ',' if depth == 0 => {
+                types.push(ABIType::parse_nested(&list[start..idx], depth)?);
+                start = idx + 1;
+            },
+            _ => {},
+        }
+    }
+    abi_require(depth == 0, "Unmatched parenthesis")?;
+    types.push(ABIType::parse_nested(&list[start..], depth)?);
+    Ok(types)
+}
+
+/// Splits a function signature like `transfer(address,uint256)` into its name
+/// and parameter types.
+pub fn parse_signature(signature: &str) -> Result<(String, Vec<ABIType>), ABIDecodeError> {
+    let open = signature
+        .find('(')
+        .ok_or(ABIDecodeError("Missing parameter list"))?;
+    let params = signature[open + 1..]
+        .strip_suffix(')')
+        .ok_or(ABIDecodeError("Unmatched parenthesis"))?;
+    Ok((signature[..open].to_string(), parse_type_list(params)?))
+}
+
+/// Returns the canonical form of a signature, which is the preimage of the
+/// function selector.
+pub fn canonical_signature(name: &str, types: &[ABIType]) -> String {
+    let params: Vec<String> = types.iter().map(type_name).collect();
+    format!("{}({})", name, params.join(","))
+}
+
+fn type_name(ty: &ABIType) -> String {
+    match ty {
+        ABIType::Uint(bits) => format!("uint{}", bits),
+        ABIType::Address => "address".into(),
+        ABIType::Bool => "bool".into(),
+        ABIType::FixedBytes(len) => format!("bytes{}", len),
+        ABIType::Bytes => "bytes".into(),
+        ABIType::String => "string".into(),
+        ABIType::Array(item) => format!("{}[]", type_name(item)),
+        ABIType::FixedArray(item, len) => format!("{}[{}]", type_name(item), len),
+        ABIType::Tuple(items) => {
+            let items: Vec<String> = items.iter().map(type_name).collect();
+            format!("({})", items.join(","))
+        },
+    }
+}
+
+pub fn function_selector(name: &str, types: &[ABIType]) -> [u8; 4] {
+    let hash = keccak(canonical_signature(name, types));
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&hash[..4]);
+    selector
+}
+
+/// Encodes `values` as the argument list of `types`.
+pub fn encode_list(types: &[ABIType], values: &[ABIValue]) -> Result<Vec<u8>, ABIDecodeError> {
+    Ok(encode_list_inner(types, values)?.to_vec())
+}
+
+/// Decodes an argument list of `types`.
+pub fn decode_list(types: &[ABIType], data: &[u8]) -> Result<Vec<ABIValue>, ABIDecodeError> {
+    let pointer = &mut data.iter();
+    types.iter().map(|ty| read_value(ty, data, pointer)).collect()
+}
+
+fn encode_list_inner(
+    types: &[ABIType],
+    values: &[ABIValue],
+) -> Result<LinkedBytes, ABIDecodeError> {
+    abi_require(types.len() == values.len(), "Incorrect number of values")?;
+    let heads_length = heads_length(types)?;
+    let mut heads = LinkedBytes::new();
+    let mut tails = LinkedBytes::new();
+    for (ty, value) in types.iter().zip(values) {
+        let mut encoded = encode_value(ty, value)?;
+        if ty.is_dynamic() {
+            heads.append(&mut LinkedBytes::from_bytes(padded_big_endian(
+                heads_length + tails.len(),
+            )));
+            tails.append(&mut encoded);
+        } else {
+            heads.append(&mut encoded);
+        }
+    }
+    heads.append(&mut tails);
+    Ok(heads)
+}
+
+fn encode_value(ty: &ABIType, value: &ABIValue) -> Result<LinkedBytes, ABIDecodeError> {
+    Ok(match (ty, value) {
+        (ABIType::Uint(bits), ABIValue::Uint(v)) => {
+            abi_require(v.bits() <= *bits, "Integer overflow")?;
+            v.to_abi()
+        },
+        (ABIType::Address, ABIValue::Address(v)) => v.to_abi(),
+        (ABIType::Bool, ABIValue::Bool(v)) => v.to_abi(),
+        (ABIType::FixedBytes(len), ABIValue::FixedBytes(v)) => {
+            abi_require(v.len() == *len, "Invalid fixed bytes length")?;
+            let mut answer = vec![0u8; 32];
+            answer[..*len].copy_from_slice(v);
+            LinkedBytes::from_bytes(answer)
+        },
+        (ABIType::Bytes, ABIValue::Bytes(v)) => v.to_abi(),
+        (ABIType::String, ABIValue::String(v)) => v.to_abi(),
+        (ABIType::Array(item), ABIValue::Array(items)) => {
+            let types = vec![(**item).clone(); items.len()];
+            let mut answer = LinkedBytes::from_bytes(padded_big_endian(items.len()));
+            answer.append(&mut encode_list_inner(&types, items)?);
+            answer
+        },
+        (ABIType::FixedArray(item, len), ABIValue::Array(items)) => {
+            abi_require(items.len() == *len, "Invalid fixed array length")?;
+            encode_list_inner(&vec![(**item).clone(); *len], items)?
+        },
+        (ABIType::Tuple(types), ABIValue::Tuple(items)) => encode_list_inner(types, items)?,
+        _ => return Err(ABIDecodeError("Value does not match type")),
+    })
+}
+
+fn read_value(
+    ty: &ABIType,
+    data: &[u8],
+    pointer: &mut std::slice::Iter<u8>,
+) -> Result<ABIValue, ABIDecodeError> {
+    if ty.is_dynamic() {
+        let location = U256::from_big_endian(pull_slice(
+            pointer,
+            32,
+            "Incomplete location for dynamic input parameter",
+        )?);
+        abi_require(location < U256::from(data.len()), "Location out of bounds")?;
+        decode_value(ty, &data[location.as_usize()..])
+    } else {
+        decode_value(
+            ty,
+            pull_slice(pointer, ty.head_length()?, "Incomplete static input parameter")?,
+        )
+    }
+}
+
+fn decode_value(ty: &ABIType, data: &[u8]) -> Result<ABIValue, ABIDecodeError> {
+    Ok(match ty {
+        ABIType::Uint(bits) => {
+            let value = U256::from_abi(data)?;
+            abi_require(value.bits() <= *bits, "Integer overflow")?;
+            ABIValue::Uint(value)
+        },
+        ABIType::Address => ABIValue::Address(Address::from_abi(data)?),
+        ABIType::Bool => ABIValue::Bool(bool::from_abi(data)?),
+        ABIType::FixedBytes(len) => {
+            abi_require(data.len() >= *len, "Invalid length in byte array")?;
+            ABIValue::FixedBytes(data[..*len].to_vec())
+        },
+        ABIType::Bytes => ABIValue::Bytes(Vec::<u8>::from_abi(data)?),
+        ABIType::String => ABIValue::String(String::from_abi(data)?),
+        ABIType::Array(item) => {
+            let pointer = &mut data.iter();
+            let length = U256::from_big_endian(pull_slice(
+                pointer,
+                32,
+                "Incomplete length for dynamic input parameter",
+            )?);
+            // Every item takes at least 32 bytes, which bounds the allocation.
+            abi_require(
+                length <= U256::from(pointer.len() / 32),
+                "Invalid length in array",
+            )?;
+            let types = vec![(**item).clone(); length.as_usize()];
+            ABIValue::Array(decode_list(&types, pointer.as_slice())?)
+        },
+        ABIType::FixedArray(item, len) => {
+            // As for `T[]`, the length is checked before allocating the items.
+            abi_require(*len <= data.len() / 32, "Invalid length in fixed array")?;
+            ABIValue::Array(decode_list(&vec![(**item).clone(); *len], data)?)
+        },
+        ABIType::Tuple(types) => ABIValue::Tuple(decode_list(types, data)?),
+    })
+}
@@ -5,6 +5,7 @@
 mod array;
 mod basic;
 mod bytes;
+mod dynamic;
 mod string;
 
 #[cfg(test)]
@@ -15,7 +16,13 @@ mod utils;
 #[macro_use]
 extern crate lazy_static;
 
-pub use self::utils::{read_abi_list, ABIListWriter, LinkedBytes};
+pub use self::{
+    dynamic::{
+        canonical_signature, decode_list, encode_list, function_selector, parse_signature,
+        parse_type_list, ABIType, ABIValue,
+    },
+    utils::{read_abi_list, ABIListWriter, LinkedBytes},
+};
 use cfx_types::H256;
 use keccak_hash::keccak;
 
@@ -492,3 +492,131 @@ fn test_two_dynamic_struct() {
         input
     );
 }
+
+#[test]
+fn test_dynamic_parse() {
+    use crate::{parse_signature, ABIType};
+
+    let (name, types) = parse_signature("swap((address,uint256)[],bytes32,string)").unwrap();
+    assert_eq!(name, "swap");
+    assert_eq!(types, vec![
+        ABIType::Array(Box::new(ABIType::Tuple(vec![
+            ABIType::Address,
+            ABIType::Uint(256)
+        ]))),
+        ABIType::FixedBytes(32),
+        ABIType::String,
+    ]);
+    assert_eq!(
+        crate::canonical_signature(&name, &types),
+        "swap((address,uint256)[],bytes32,string)"
+    );
+
+    assert_eq!(
+        ABIType::parse("uint7").unwrap_err(),
+        ABIDecodeError("Invalid uint size")
+    );
+    assert_eq!(
+        ABIType::parse("int256").unwrap_err(),
+        ABIDecodeError("Unknown type")
+    );
+}
+
+#[test]
+fn test_dynamic_matches_static() {
+    use crate::{decode_list, encode_list, function_selector, parse_type_list, ABIValue};
+
+    let types = parse_type_list("address,bytes,uint64[2]").unwrap();
+    let values = vec![
+        ABIValue::Address(*ADDR1),
+        ABIValue::Bytes(vec![1, 2, 3]),
+        ABIValue::Array(vec![ABIValue::Uint(7.into()), ABIValue::Uint(8.into())]),
+    ];
+    let encoded = encode_list(&types, &values).unwrap();
+    assert_eq!(
+        encoded,
+        (*ADDR1, vec![1u8, 2, 3], [7u64, 8u64]).abi_encode()
+    );
+    assert_eq!(decode_list(&types, &encoded).unwrap(), values);
+
+    let types = parse_type_list("string[]").unwrap();
+    let values = vec![ABIValue::Array(vec![
+        ABIValue::String("abc".into()),
+        ABIValue::String("".into()),
+    ])];
+    let encoded = encode_list(&types, &values).unwrap();
+    assert_eq!(
+        encoded,
+        vec!["abc".to_string(), "".to_string()].abi_encode()
+    );
+    assert_eq!(decode_list(&types, &encoded).unwrap(), values);
+
+    // transfer(address,uint256)
+    assert_eq!(
+        function_selector("transfer", &parse_type_list("address,uint256").unwrap())
+            .to_hex::<String>(),
+        "a9059cbb"
+    );
+}
+
+#[test]
+fn test_dynamic_overflow() {
+    use crate::{decode_list, encode_list, parse_type_list, ABIValue};
+
+    let types = parse_type_list("uint8").unwrap();
+    assert_eq!(
+        encode_list(&types, &[ABIValue::Uint(256.into())]).unwrap_err(),
+        ABIDecodeError("Integer overflow")
+    );
+    assert_eq!(
+        decode_list(&types, &U256::from(256).abi_encode()).unwrap_err(),
+        ABIDecodeError("Integer overflow")
+    );
+}
+
+#[test]
+fn test_dynamic_huge_length() {
+    use crate::{decode_list, encode_list, parse_type_list, ABIValue};
+
+    let types = parse_type_list(&format!("uint256[{}]", usize::MAX)).unwrap();
+    assert_eq!(
+        decode_list(&types, &[]).unwrap_err(),
+        ABIDecodeError("Fixed array too long")
+    );
+    assert_eq!(
+        encode_list(&types, &[ABIValue::Array(vec![])]).unwrap_err(),
+        ABIDecodeError("Fixed array too long")
+    );
+
+    // The length of a fixed array of dynamic items is bounded by the data
+    // before its items are allocated.
+    let types = parse_type_list(&format!("string[{}]", usize::MAX)).unwrap();
+    let mut encoded = U256::from(32).abi_encode();
+    encoded.extend_from_slice(&[0u8; 32]);
+    assert_eq!(
+        decode_list(&types, &encoded).unwrap_err(),
+        ABIDecodeError("Invalid length in fixed array")
+    );
+}
+
+#[test]
+fn test_dynamic_parse_depth() {
+    use crate::{dynamic::MAX_TYPE_DEPTH, parse_signature, parse_type_list, ABIType};
+
+    let arrays = |depth: usize| format!("uint256{}", "[]".repeat(depth));
+    let tuples = |depth: usize| format!("{}uint256{}", "(".repeat(depth), ")".repeat(depth));
+    assert!(ABIType::parse(&arrays(MAX_TYPE_DEPTH)).is_ok());
+    assert!(parse_type_list(&tuples(MAX_TYPE_DEPTH)).is_ok());
+    assert_eq!(
+        ABIType::parse(&arrays(MAX_TYPE_DEPTH + 1)).unwrap_err(),
+        ABIDecodeError("Type nested too deeply")
+    );
+    assert_eq!(
+        parse_type_list(&tuples(MAX_TYPE_DEPTH + 1)).unwrap_err(),
+        ABIDecodeError("Type nested too deeply")
+    );
+
+    // Far deeper nesting than the stack could take is rejected as well.
+    assert!(parse_signature(&format!("f({})", tuples(1_000_000))).is_err());
+    assert!(ABIType::parse(&arrays(1_000_000)).is_err());
+}
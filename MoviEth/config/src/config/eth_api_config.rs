@@ -18,6 +18,13 @@ pub struct EthApiConfig {
     pub cors: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub threads: Option<usize>,
+
+    /// The maximum number of logs returned by one `eth_getLogs` or `eth_getFilterChanges` call.
+    #[serde(default = "default_max_logs_per_request")]
+    pub max_logs_per_request: usize,
+    /// The maximum number of blocks one log query may span.
+    #[serde(default = "default_max_log_block_range")]
+    pub max_log_block_range: u64,
 }
 
 pub const DEFAULT_ADDRESS_ETH: &str = "0.0.0.0";
@@ -31,6 +38,14 @@ fn default_disabled() -> bool {
     false
 }
 
+fn default_max_logs_per_request() -> usize {
+    10_000
+}
+
+fn default_max_log_block_range() -> u64 {
+    1_000
+}
+
 impl Default for EthApiConfig {
     fn default() -> EthApiConfig {
         EthApiConfig {
@@ -41,6 +56,8 @@ impl Default for EthApiConfig {
             keep_alive: false,
             cors: None,
             threads: None,
+            max_logs_per_request: default_max_logs_per_request(),
+            max_log_block_range: default_max_log_block_range(),
         }
     }
 }
@@ -23,6 +23,12 @@ pub struct ExecutionConfig {
     pub paranoid_type_verification: bool,
     pub paranoid_hot_potato_verification: bool,
     pub processed_transactions_detailed_counters: bool,
+    /// Whether the outputs of EVM transactions carry the Move side traces of their cross-VM
+    /// calls, which the REST API serves by transaction hash.
+    pub cross_vm_tracing: bool,
+    /// Whether blocks are executed by the three prototype workers for Move, EVM and cross-VM
+    /// transactions instead of by Block-STM with `concurrency_level` threads.
+    pub prototype_workers: bool,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -52,6 +58,8 @@ impl Default for ExecutionConfig {
             paranoid_type_verification: true,
             paranoid_hot_potato_verification: true,
             processed_transactions_detailed_counters: false,
+            cross_vm_tracing: false,
+            prototype_workers: false,
         }
     }
 }
@@ -47,14 +47,18 @@ impl ExecutedChunk {
         self.to_commit
             .iter()
             .map(|(txn, txn_data)| {
-                Ok(TransactionToCommit::new(
+                let txn_to_commit = TransactionToCommit::new(
                     txn.clone(),
                     txn_data.txn_info.clone(),
                     txn_data.state_updates().clone(),
                     txn_data.write_set().clone(),
                     txn_data.events().to_vec(),
                     txn_data.is_reconfig(),
-                ))
+                );
+                Ok(match txn_data.cross_vm_trace() {
+                    Some(trace) => txn_to_commit.with_cross_vm_trace(trace.clone()),
+                    None => txn_to_commit,
+                })
             })
             .collect()
     }
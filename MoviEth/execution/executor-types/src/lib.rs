@@ -16,8 +16,8 @@ use aptos_types::{
     proof::{accumulator::InMemoryAccumulator, AccumulatorExtensionProof, SparseMerkleProofExt},
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{
-        Transaction, TransactionInfo, TransactionListWithProof, TransactionOutputListWithProof,
-        TransactionStatus, Version,
+        CrossVmTrace, Transaction, TransactionInfo, TransactionListWithProof,
+        TransactionOutputListWithProof, TransactionStatus, Version,
     },
     write_set::WriteSet,
 };
@@ -336,6 +336,9 @@ pub struct TransactionData {
 
     /// TransactionInfo.hash()
     txn_info_hash: HashValue,
+
+    /// The Move side trace of the cross-VM calls, if cross-VM tracing is enabled.
+    cross_vm_trace: Option<CrossVmTrace>,
 }
 
 impl TransactionData {
@@ -349,6 +352,7 @@ impl TransactionData {
         gas_used: u64,
         txn_info: TransactionInfo,
         txn_info_hash: HashValue,
+        cross_vm_trace: Option<CrossVmTrace>,
     ) -> Self {
         TransactionData {
             state_updates,
@@ -360,6 +364,7 @@ impl TransactionData {
             gas_used,
             txn_info,
             txn_info_hash,
+            cross_vm_trace,
         }
     }
 
@@ -394,4 +399,8 @@ impl TransactionData {
     pub fn is_reconfig(&self) -> bool {
         !self.reconfig_events.is_empty()
     }
+
+    pub fn cross_vm_trace(&self) -> Option<&CrossVmTrace> {
+        self.cross_vm_trace.as_ref()
+    }
 }
@@ -199,6 +199,7 @@ impl ApplyChunkOutput {
             state_updates_vec,
             hashes_vec
         ) {
+            let cross_vm_trace = txn_output.cross_vm_trace().cloned();
             let (write_set, events, reconfig_events, gas_used, status) = txn_output.unpack();
             let event_tree =
                 InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes);
@@ -228,6 +229,7 @@ impl ApplyChunkOutput {
                     gas_used,
                     txn_info,
                     txn_info_hash,
+                    cross_vm_trace,
                 ),
             ))
         }
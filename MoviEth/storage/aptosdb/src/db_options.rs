@@ -15,6 +15,7 @@ pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
     vec![
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
         BLOCK_VERSION_BY_HASH_CF_NAME,
+        CROSS_VM_TRACE_CF_NAME,
         EPOCH_BY_VERSION_CF_NAME,
         EVENT_ACCUMULATOR_CF_NAME,
         EVENT_BY_KEY_CF_NAME,
@@ -100,9 +100,9 @@ use aptos_types::{
         table::{TableHandle, TableInfo},
     },
     transaction::{
-        AccountTransactionsWithProof, Transaction, TransactionInfo, TransactionListWithProof,
-        TransactionOutput, TransactionOutputListWithProof, TransactionToCommit,
-        TransactionWithProof, Version,
+        AccountTransactionsWithProof, CrossVmTrace, Transaction, TransactionInfo,
+        TransactionListWithProof, TransactionOutput, TransactionOutputListWithProof,
+        TransactionToCommit, TransactionWithProof, Version,
     },
     write_set::WriteSet,
 };
@@ -799,6 +799,9 @@ impl AptosDB {
                             txn_to_commit.transaction(),
                             cs,
                         )?;
+                        if let Some(trace) = txn_to_commit.cross_vm_trace() {
+                            self.transaction_store.put_cross_vm_trace(ver, trace, cs)?;
+                        }
                         self.transaction_store
                             .put_write_set(ver, txn_to_commit.write_set(), cs)
                     },
@@ -1182,6 +1185,25 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_event_seq_num_at_or_after_version(
+        &self,
+        event_key: &EventKey,
+        version: Version,
+    ) -> Result<Option<u64>> {
+        gauged_api("get_event_seq_num_at_or_after_version", || {
+            Ok(self
+                .event_store
+                .lookup_event_at_or_after_version(event_key, version)?
+                .map(|(_version, _index, seq_num)| seq_num))
+        })
+    }
+
+    fn get_cross_vm_trace(&self, version: Version) -> Result<Option<CrossVmTrace>> {
+        gauged_api("get_cross_vm_trace", || {
+            self.transaction_store.get_cross_vm_trace(version)
+        })
+    }
+
     fn get_transaction_iterator(
         &self,
         start_version: Version,
@@ -127,6 +127,10 @@ fn verify_txn_store_pruner(
         );
         for j in 0..i {
             verify_txn_not_in_store(transaction_store, &txns, j as u64, ledger_version);
+            assert!(transaction_store
+                .get_cross_vm_trace(j as u64)
+                .unwrap()
+                .is_none());
             // Ensure that transaction accumulator is pruned in DB. This can be done by trying to
             // read transaction proof.
             // Note: we only prune versions which are odd numbers because the even versions will be
@@ -147,6 +151,10 @@ fn verify_txn_store_pruner(
                 j as u64,
                 ledger_version,
             );
+            assert!(transaction_store
+                .get_cross_vm_trace(j as u64)
+                .unwrap()
+                .is_some());
             aptos_db.get_accumulator_summary(j as Version).unwrap();
         }
         verify_transaction_accumulator_pruned(&ledger_store, i as u64);
@@ -229,6 +237,9 @@ fn put_txn_in_store(
         transaction_store
             .put_transaction(i as u64, txns.get(i).unwrap(), &batch)
             .unwrap();
+        transaction_store
+            .put_cross_vm_trace(i as u64, &vec![], &batch)
+            .unwrap();
     }
     ledger_store
         .put_transaction_infos(0, txn_infos, &batch)
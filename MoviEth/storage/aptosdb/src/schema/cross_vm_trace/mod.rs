@@ -0,0 +1,54 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the cross-VM trace of an EVM transaction,
+//! saved by a node with cross-VM tracing enabled. It is not part of the ledger.
+//!
+//! ```text
+//! |<--key-->|<-------value------->|
+//! | version | cross_vm_trace bytes |
+//! ```
+//!
+//! `Version` is serialized in big endian so that records in RocksDB will be in order of it's
+//! numeric value.
+
+use crate::schema::{ensure_slice_len_eq, CROSS_VM_TRACE_CF_NAME};
+use anyhow::Result;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::transaction::{CrossVmTrace, Version};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::mem::size_of;
+
+define_schema!(
+    CrossVmTraceSchema,
+    Version,
+    CrossVmTrace,
+    CROSS_VM_TRACE_CF_NAME
+);
+
+impl KeyCodec<CrossVmTraceSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<CrossVmTraceSchema> for CrossVmTrace {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
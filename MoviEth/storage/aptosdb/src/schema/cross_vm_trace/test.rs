@@ -0,0 +1,59 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{CrossVmCallTrace, EthAddress, MoveCallFrame},
+    write_set::WriteSet,
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use proptest::prelude::*;
+
+fn trace(write_set: WriteSet) -> CrossVmTrace {
+    let module = ModuleId::new(AccountAddress::ONE, Identifier::new("coin").unwrap());
+    let call = MoveCallFrame {
+        module: module.clone(),
+        function: "transfer".to_string(),
+        ty_args: vec![],
+        native: false,
+        gas_used: 30,
+        aborted: false,
+        calls: vec![MoveCallFrame {
+            module,
+            function: "deposit".to_string(),
+            ty_args: vec![],
+            native: true,
+            gas_used: 10,
+            aborted: true,
+            calls: vec![],
+        }],
+    };
+    vec![CrossVmCallTrace {
+        caller: EthAddress::repeat_byte(0x11),
+        address: AccountAddress::ONE,
+        module: "coin".to_string(),
+        function: "transfer".to_string(),
+        ty_args: vec![],
+        calls: vec![call],
+        events: vec![],
+        write_set,
+        execution_gas_used: 30,
+        storage_gas_used: 5,
+        error: Some("ABORTED".to_string()),
+        reverted: true,
+    }]
+}
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        version in any::<Version>(),
+        write_set in any::<WriteSet>(),
+    ) {
+        assert_encode_decode::<CrossVmTraceSchema>(&version, &trace(write_set));
+    }
+}
+
+test_no_panic_decoding!(CrossVmTraceSchema);
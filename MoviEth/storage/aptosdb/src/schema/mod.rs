@@ -7,6 +7,7 @@
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
 pub(crate) mod block_version_by_hash;
+pub(crate) mod cross_vm_trace;
 pub(crate) mod db_metadata;
 pub(crate) mod epoch_by_version;
 pub(crate) mod event;
@@ -32,6 +33,7 @@ use anyhow::{ensure, Result};
 use aptos_schemadb::ColumnFamilyName;
 
 pub const BLOCK_VERSION_BY_HASH_CF_NAME: ColumnFamilyName = "block_version_by_hash";
+pub const CROSS_VM_TRACE_CF_NAME: ColumnFamilyName = "cross_vm_trace";
 pub const DB_METADATA_CF_NAME: ColumnFamilyName = "db_metadata";
 pub const EPOCH_BY_VERSION_CF_NAME: ColumnFamilyName = "epoch_by_version";
 pub const EVENT_ACCUMULATOR_CF_NAME: ColumnFamilyName = "event_accumulator";
@@ -80,6 +82,7 @@ pub mod fuzzing {
     pub fn fuzz_decode(data: &[u8]) {
         #[allow(unused_must_use)]
         {
+            assert_no_panic_decoding::<super::cross_vm_trace::CrossVmTraceSchema>(data);
             assert_no_panic_decoding::<super::epoch_by_version::EpochByVersionSchema>(data);
             assert_no_panic_decoding::<super::event::EventSchema>(data);
             assert_no_panic_decoding::<super::event_accumulator::EventAccumulatorSchema>(data);
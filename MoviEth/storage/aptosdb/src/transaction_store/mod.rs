@@ -6,7 +6,8 @@
 use crate::{
     errors::AptosDbError,
     schema::{
-        transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema,
+        cross_vm_trace::CrossVmTraceSchema, transaction::TransactionSchema,
+        transaction_by_account::TransactionByAccountSchema,
         transaction_by_eth_hash::TransactionByEthHashSchema,
         transaction_by_hash::TransactionByHashSchema, write_set::WriteSetSchema,
     },
@@ -20,7 +21,7 @@ use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
 use aptos_types::{
     account_address::AccountAddress,
     proof::position::Position,
-    transaction::{Transaction, Version},
+    transaction::{CrossVmTrace, Transaction, Version},
     write_set::WriteSet,
 };
 use std::sync::Arc;
@@ -217,6 +218,34 @@ impl TransactionStore {
         batch.put::<WriteSetSchema>(&version, write_set)
     }
 
+    /// Save the cross-VM trace of the transaction at `version`.
+    pub fn put_cross_vm_trace(
+        &self,
+        version: Version,
+        cross_vm_trace: &CrossVmTrace,
+        batch: &SchemaBatch,
+    ) -> Result<()> {
+        batch.put::<CrossVmTraceSchema>(&version, cross_vm_trace)
+    }
+
+    /// Get the cross-VM trace of the transaction at `version`, if it was saved.
+    pub fn get_cross_vm_trace(&self, version: Version) -> Result<Option<CrossVmTrace>> {
+        self.db.get::<CrossVmTraceSchema>(&version)
+    }
+
+    /// Prune the cross-VM traces between a range of version in [begin, end)
+    pub fn prune_cross_vm_trace(
+        &self,
+        begin: Version,
+        end: Version,
+        db_batch: &SchemaBatch,
+    ) -> Result<()> {
+        for version in begin..end {
+            db_batch.delete::<CrossVmTraceSchema>(&version)?;
+        }
+        Ok(())
+    }
+
     /// Prune the transaction by hash store given a list of transaction
     pub fn prune_transaction_by_hash(
         &self,
@@ -64,7 +64,12 @@ pub enum EvmPath {
     Account,
     Storage(Vec<u8>),
     Code,
+    /// Next sequence number of the event stream shared by all the logs of a contract.
     EventNonce,
+    /// Next sequence number of the event stream of the logs of a contract with this first topic.
+    TopicEventNonce(Vec<u8>),
+    /// Next sequence number of the event stream recording the creations of a contract.
+    CreationEventNonce,
 }
 
 impl AccessPath {
@@ -0,0 +1,57 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::transaction::EthAddress;
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    identifier::IdentStr,
+    move_resource::{MoveResource, MoveStructType},
+};
+use serde::{Deserialize, Serialize};
+
+/// The EVM address linked to the Move account holding it, see `0x1::account_link`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvmLinkResource {
+    evm_address: Vec<u8>,
+    nonce: u64,
+}
+
+impl EvmLinkResource {
+    /// `None` once the account has been unlinked.
+    pub fn evm_address(&self) -> Option<EthAddress> {
+        (self.evm_address.len() == EthAddress::len_bytes())
+            .then(|| EthAddress::from_slice(&self.evm_address))
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+impl MoveStructType for EvmLinkResource {
+    const MODULE_NAME: &'static IdentStr = ident_str!("account_link");
+    const STRUCT_NAME: &'static IdentStr = ident_str!("EvmLink");
+}
+
+impl MoveResource for EvmLinkResource {}
+
+/// The Move account linked to an EVM address, held by the default account of
+/// the EVM address (`eth_address_to_aptos_address`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveLinkResource {
+    account: AccountAddress,
+}
+
+impl MoveLinkResource {
+    pub fn account(&self) -> AccountAddress {
+        self.account
+    }
+}
+
+impl MoveStructType for MoveLinkResource {
+    const MODULE_NAME: &'static IdentStr = ident_str!("account_link");
+    const STRUCT_NAME: &'static IdentStr = ident_str!("MoveLink");
+}
+
+impl MoveResource for MoveLinkResource {}
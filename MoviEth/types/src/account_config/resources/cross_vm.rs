@@ -0,0 +1,64 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{
+    ident_str,
+    identifier::IdentStr,
+    move_resource::{MoveResource, MoveStructType},
+};
+use serde::{Deserialize, Serialize};
+
+/// A capability of `0x1::coin`, which has no fields of its own.
+#[derive(Debug, Serialize, Deserialize)]
+struct CoinCapability {
+    dummy_field: bool,
+}
+
+/// The ledger of the native token moved between Move and the EVM, see
+/// `0x1::cross_vm::NativeBridge`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NativeBridgeResource {
+    mint_cap: CoinCapability,
+    burn_cap: CoinCapability,
+    total_from_evm: u128,
+    total_to_evm: u128,
+}
+
+impl NativeBridgeResource {
+    /// Octas minted for value sent from the EVM.
+    pub fn total_from_evm(&self) -> u128 {
+        self.total_from_evm
+    }
+
+    /// Octas burned for value sent to the EVM.
+    pub fn total_to_evm(&self) -> u128 {
+        self.total_to_evm
+    }
+}
+
+impl MoveStructType for NativeBridgeResource {
+    const MODULE_NAME: &'static IdentStr = ident_str!("cross_vm");
+    const STRUCT_NAME: &'static IdentStr = ident_str!("NativeBridge");
+}
+
+impl MoveResource for NativeBridgeResource {}
+
+/// The modules of an account guarded against re-entrant calls from the EVM, see
+/// `0x1::cross_vm::ReentrancyGuard`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReentrancyGuardResource {
+    modules: Vec<String>,
+}
+
+impl ReentrancyGuardResource {
+    pub fn is_guarded(&self, module: &str) -> bool {
+        self.modules.iter().any(|guarded| guarded == module)
+    }
+}
+
+impl MoveStructType for ReentrancyGuardResource {
+    const MODULE_NAME: &'static IdentStr = ident_str!("cross_vm");
+    const STRUCT_NAME: &'static IdentStr = ident_str!("ReentrancyGuard");
+}
+
+impl MoveResource for ReentrancyGuardResource {}
@@ -1,16 +1,20 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod account_link;
 pub mod chain_id;
 pub mod coin_info;
 pub mod coin_store;
 pub mod core_account;
+pub mod cross_vm;
 pub mod sequence_info;
 pub mod transaction_validation;
 
+pub use account_link::*;
 pub use chain_id::*;
 pub use coin_info::*;
 pub use coin_store::*;
 pub use core_account::*;
+pub use cross_vm::*;
 pub use sequence_info::*;
 pub use transaction_validation::*;
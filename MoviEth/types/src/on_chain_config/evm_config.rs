@@ -0,0 +1,19 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use serde::{Deserialize, Serialize};
+
+/// Defines the chain id and the block gas limit seen by EVM code, and whether the logs of a
+/// contract go to one event stream per first topic instead of one per contract.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct EvmConfig {
+    pub chain_id: u64,
+    pub block_gas_limit: u64,
+    pub event_key_per_topic: bool,
+}
+
+impl OnChainConfig for EvmConfig {
+    const MODULE_IDENTIFIER: &'static str = "evm_block";
+    const TYPE_IDENTIFIER: &'static str = "EvmConfig";
+}
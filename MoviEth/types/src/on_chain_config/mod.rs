@@ -22,6 +22,7 @@ mod aptos_features;
 mod aptos_version;
 mod chain_id;
 mod consensus_config;
+mod evm_config;
 mod gas_schedule;
 mod validator_set;
 
@@ -35,6 +36,7 @@ pub use self::{
         ConsensusConfigV1, LeaderReputationType, OnChainConsensusConfig, ProposerAndVoterConfig,
         ProposerElectionType,
     },
+    evm_config::EvmConfig,
     gas_schedule::{GasSchedule, GasScheduleV2, StorageGasSchedule},
     validator_set::{ConsensusScheme, ValidatorSet},
 };
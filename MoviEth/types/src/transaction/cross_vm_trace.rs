@@ -0,0 +1,147 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account_address::AccountAddress, contract_event::ContractEvent,
+    transaction::ethereum::EthAddress, write_set::WriteSet,
+};
+use move_core_types::language_storage::{ModuleId, TypeTag};
+use serde::{Deserialize, Serialize};
+
+/// Move side traces of the `callMove`s of an EVM transaction, in execution order.
+pub type CrossVmTrace = Vec<CrossVmCallTrace>;
+
+/// Trace of one `callMove` of an EVM transaction, i.e. one hop from the EVM into the Move VM.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct CrossVmCallTrace {
+    /// The EVM contract which made the call.
+    pub caller: EthAddress,
+    /// Account of the called module.
+    pub address: AccountAddress,
+    /// Called module, empty for a plain transfer.
+    pub module: String,
+    /// Called function.
+    pub function: String,
+    pub ty_args: Vec<TypeTag>,
+    /// Move functions run by the call, starting with the transfer of the value sent along.
+    pub calls: Vec<MoveCallFrame>,
+    /// Events emitted by the call.
+    pub events: Vec<ContractEvent>,
+    /// Resources written by the call.
+    pub write_set: WriteSet,
+    /// Gas used to execute the Move functions.
+    pub execution_gas_used: u64,
+    /// Gas used to store the write set.
+    pub storage_gas_used: u64,
+    /// Why the call failed, with the abort code and location of an abort.
+    pub error: Option<String>,
+    /// Whether the EVM frame of the call was reverted, dropping its changes.
+    pub reverted: bool,
+}
+
+/// A Move function call of a cross-VM call, with the calls it made.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct MoveCallFrame {
+    pub module: ModuleId,
+    pub function: String,
+    pub ty_args: Vec<TypeTag>,
+    pub native: bool,
+    /// Execution gas used by the function and its callees.
+    pub gas_used: u64,
+    /// Whether the function did not return, e.g. because it or a callee aborted.
+    pub aborted: bool,
+    pub calls: Vec<MoveCallFrame>,
+}
+
+/// Builds the call tree of Move functions from the calls and returns seen by a gas meter. The gas
+/// used by a frame is the drop of the gas balance between its call and its return.
+#[derive(Debug, Default)]
+pub struct MoveCallTreeBuilder {
+    /// Frames which have not returned yet, with the balance at their call.
+    stack: Vec<(MoveCallFrame, u64)>,
+    /// Frames which have returned from the bottom of the stack.
+    calls: Vec<MoveCallFrame>,
+}
+
+impl MoveCallTreeBuilder {
+    pub fn enter(&mut self, module: ModuleId, function: &str, ty_args: Vec<TypeTag>, balance: u64) {
+        let frame = MoveCallFrame {
+            module,
+            function: function.to_string(),
+            ty_args,
+            native: false,
+            gas_used: 0,
+            aborted: false,
+            calls: vec![],
+        };
+        self.stack.push((frame, balance));
+    }
+
+    /// Marks the function called last as a native one.
+    pub fn mark_native(&mut self) {
+        if let Some((frame, _)) = self.stack.last_mut() {
+            frame.native = true;
+        }
+    }
+
+    /// Returns from the function called last.
+    pub fn exit(&mut self, balance: u64) {
+        if let Some((mut frame, entry_balance)) = self.stack.pop() {
+            frame.gas_used = entry_balance.saturating_sub(balance);
+            self.push(frame);
+        }
+    }
+
+    /// Returns the call tree, with the functions which have not returned marked as aborted.
+    pub fn finish(mut self, balance: u64) -> Vec<MoveCallFrame> {
+        while let Some((frame, _)) = self.stack.last_mut() {
+            frame.aborted = true;
+            self.exit(balance);
+        }
+        self.calls
+    }
+
+    fn push(&mut self, frame: MoveCallFrame) {
+        match self.stack.last_mut() {
+            Some((parent, _)) => parent.calls.push(frame),
+            None => self.calls.push(frame),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::ident_str;
+
+    #[test]
+    fn test_move_call_tree() {
+        let module = ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned());
+        let mut builder = MoveCallTreeBuilder::default();
+        builder.enter(module.clone(), "transfer", vec![], 100);
+        builder.enter(module.clone(), "withdraw", vec![], 90);
+        builder.enter(module.clone(), "balance", vec![], 85);
+        builder.mark_native();
+        builder.exit(80);
+        builder.exit(70);
+        builder.enter(module.clone(), "deposit", vec![], 70);
+        let calls = builder.finish(60);
+
+        assert_eq!(calls.len(), 1);
+        let transfer = &calls[0];
+        assert_eq!(transfer.gas_used, 40);
+        assert!(transfer.aborted);
+        assert_eq!(transfer.calls.len(), 2);
+
+        let withdraw = &transfer.calls[0];
+        assert_eq!(withdraw.gas_used, 20);
+        assert!(!withdraw.aborted);
+        assert!(withdraw.calls[0].native);
+        assert_eq!(withdraw.calls[0].gas_used, 5);
+
+        let deposit = &transfer.calls[1];
+        assert_eq!(deposit.function, "deposit");
+        assert_eq!(deposit.gas_used, 10);
+        assert!(deposit.aborted);
+    }
+}
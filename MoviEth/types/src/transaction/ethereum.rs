@@ -1,5 +1,5 @@
-use cfx_primitives::Action as EthAction;
-pub use ethereum_types::{Address as EthAddress, U256};
+use cfx_primitives::{AccessListItem as EthAccessListItem, Action as EthAction};
+pub use ethereum_types::{Address as EthAddress, H256, U256};
 use serde::{Deserialize, Serialize};
 
 // TODO(lpl): Cannot use `cfx_primitives::Action` because `TransactionPayload` derives `Hash`.
@@ -51,11 +51,54 @@ impl Action {
     }
 }
 
+/// An account, and storage keys of it, that a transaction declares it will access (EIP-2930).
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessListItem {
+    pub address: EthAddress,
+    pub storage_keys: Vec<H256>,
+}
+
+impl From<EthAccessListItem> for AccessListItem {
+    fn from(value: EthAccessListItem) -> Self {
+        Self {
+            address: value.address,
+            storage_keys: value.storage_keys,
+        }
+    }
+}
+
+impl From<&AccessListItem> for EthAccessListItem {
+    fn from(value: &AccessListItem) -> Self {
+        Self {
+            address: value.address,
+            storage_keys: value.storage_keys.clone(),
+        }
+    }
+}
+
+/// The EIP-2718 type of an Ethereum transaction and the fields that come with it.
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EthTransactionType {
+    /// A legacy (EIP-155) transaction. `gas_unit_price` is its gas price.
+    #[default]
+    Legacy,
+    /// An EIP-2930 transaction. `gas_unit_price` is its gas price.
+    AccessList(Vec<AccessListItem>),
+    /// An EIP-1559 transaction. `gas_unit_price` is its max fee per gas.
+    DynamicFee {
+        access_list: Vec<AccessListItem>,
+        max_priority_fee_per_gas: u64,
+    },
+}
+
+/// An Ethereum transaction of any type. Legacy transactions are encoded as
+/// `LegacyEthTransactionPayload`, without `tx_type`, see `TransactionPayload`.
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EthTransactionPayload {
     pub value: U256,
     pub action: Action,
     pub data: Vec<u8>,
+    pub tx_type: EthTransactionType,
 }
 
 impl EthTransactionPayload {
@@ -64,6 +107,72 @@ impl EthTransactionPayload {
             value,
             action,
             data,
+            tx_type: EthTransactionType::Legacy,
+        }
+    }
+
+    pub fn with_tx_type(mut self, tx_type: EthTransactionType) -> Self {
+        self.tx_type = tx_type;
+        self
+    }
+
+    /// The EIP-2718 type of the transaction, 0 for legacy transactions.
+    pub fn type_id(&self) -> u8 {
+        match self.tx_type {
+            EthTransactionType::Legacy => 0,
+            EthTransactionType::AccessList(_) => 1,
+            EthTransactionType::DynamicFee { .. } => 2,
+        }
+    }
+
+    pub fn access_list(&self) -> Option<&[AccessListItem]> {
+        match &self.tx_type {
+            EthTransactionType::Legacy => None,
+            EthTransactionType::AccessList(access_list)
+            | EthTransactionType::DynamicFee { access_list, .. } => Some(access_list),
+        }
+    }
+
+    pub fn max_priority_fee_per_gas(&self) -> Option<u64> {
+        match self.tx_type {
+            EthTransactionType::DynamicFee {
+                max_priority_fee_per_gas,
+                ..
+            } => Some(max_priority_fee_per_gas),
+            _ => None,
+        }
+    }
+}
+
+/// The encoding of legacy Ethereum transactions, which predates typed transactions.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct LegacyEthTransactionPayload {
+    value: U256,
+    action: Action,
+    data: Vec<u8>,
+}
+
+impl From<LegacyEthTransactionPayload> for EthTransactionPayload {
+    fn from(value: LegacyEthTransactionPayload) -> Self {
+        EthTransactionPayload::new(value.value, value.action, value.data)
+    }
+}
+
+/// `LegacyEthTransactionPayload` borrowed from an `EthTransactionPayload`, to encode it.
+#[derive(Serialize)]
+#[serde(rename = "LegacyEthTransactionPayload")]
+pub(crate) struct LegacyEthTransactionPayloadRef<'a> {
+    value: &'a U256,
+    action: &'a Action,
+    data: &'a [u8],
+}
+
+impl<'a> From<&'a EthTransactionPayload> for LegacyEthTransactionPayloadRef<'a> {
+    fn from(value: &'a EthTransactionPayload) -> Self {
+        Self {
+            value: &value.value,
+            action: &value.action,
+            data: &value.data,
         }
     }
 }
@@ -24,12 +24,12 @@ use aptos_crypto::{
 };
 use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use cfx_primitives::{
-    transaction::TxType, Eip155Transaction, SignedTransaction as EthSignedTransaction, Transaction as EthRawTransaction, TransactionWithSignature as EthTransaction
+    transaction::TxType, AccessList as EthAccessList, Eip1559Transaction, Eip155Transaction, Eip2930Transaction, SignedTransaction as EthSignedTransaction, Transaction as EthRawTransaction, TransactionWithSignature as EthTransaction
 };
 use move_core_types::transaction_argument::convert_txn_args;
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use std::{
     collections::HashMap,
     convert::TryFrom,
@@ -40,6 +40,7 @@ use tiny_keccak::{Hasher, Keccak};
 
 pub mod authenticator;
 mod change_set;
+mod cross_vm_trace;
 mod ethereum;
 mod module;
 mod script;
@@ -50,8 +51,10 @@ use aptos_crypto::secp256k1::{Secp256k1PublicKey, Secp256k1Signature};
 #[cfg(any(test, feature = "fuzzing"))]
 pub use change_set::NoOpChangeSetChecker;
 pub use change_set::{ChangeSet, CheckChangeSet};
-pub use ethereum::{EthAddress, EthTransactionPayload};
-use ethereum_types::Public;
+pub use cross_vm_trace::{CrossVmCallTrace, CrossVmTrace, MoveCallFrame, MoveCallTreeBuilder};
+use ethereum::{LegacyEthTransactionPayload, LegacyEthTransactionPayloadRef};
+pub use ethereum::{AccessListItem, EthAddress, EthTransactionPayload, EthTransactionType};
+use ethereum_types::{Public, U256};
 pub use module::{Module, ModuleBundle};
 use move_core_types::vm_status::AbortLocation;
 use once_cell::sync::OnceCell;
@@ -368,19 +371,46 @@ impl RawTransaction {
     }
 
     pub fn eth_transaction(&self) -> Option<EthRawTransaction> {
-        let TransactionPayload::EthTransactionPayload(EthTransactionPayload{ value, action, data }) = &self.payload else { return None };
-        Some(
-            Eip155Transaction {
+        let TransactionPayload::EthTransactionPayload(EthTransactionPayload{ value, action, data, tx_type }) = &self.payload else { return None };
+        let chain_id = self.chain_id.id() as u32;
+        Some(match tx_type {
+            EthTransactionType::Legacy => Eip155Transaction {
                 nonce: self.sequence_number.into(),
                 gas_price: self.gas_unit_price.into(),
                 gas: self.max_gas_amount.into(),
                 action: action.into(),
                 value: *value,
-                chain_id: Some(self.chain_id.id() as u32),
+                chain_id: Some(chain_id),
                 data: data.clone(),
             }
             .into(),
-        )
+            EthTransactionType::AccessList(access_list) => Eip2930Transaction {
+                chain_id,
+                nonce: self.sequence_number.into(),
+                gas_price: self.gas_unit_price.into(),
+                gas: self.max_gas_amount.into(),
+                action: action.into(),
+                value: *value,
+                data: data.clone(),
+                access_list: access_list.iter().map(Into::into).collect(),
+            }
+            .into(),
+            EthTransactionType::DynamicFee {
+                access_list,
+                max_priority_fee_per_gas,
+            } => Eip1559Transaction {
+                chain_id,
+                nonce: self.sequence_number.into(),
+                max_priority_fee_per_gas: (*max_priority_fee_per_gas).into(),
+                max_fee_per_gas: self.gas_unit_price.into(),
+                gas: self.max_gas_amount.into(),
+                action: action.into(),
+                value: *value,
+                data: data.clone(),
+                access_list: access_list.iter().map(Into::into).collect(),
+            }
+            .into(),
+        })
     }
 }
 
@@ -407,7 +437,8 @@ impl RawTransactionWithData {
 }
 
 /// Different kinds of transactions.
-#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "TransactionPayloadRepr")]
 pub enum TransactionPayload {
     /// A transaction that executes code.
     Script(Script),
@@ -419,6 +450,71 @@ pub enum TransactionPayload {
     EthTransactionPayload(EthTransactionPayload),
 }
 
+/// The encoding of `TransactionPayload`. Legacy Ethereum transactions keep the encoding they
+/// had before typed transactions, so that their bytes and hashes do not change, and typed ones
+/// are encoded as a new variant.
+#[derive(Deserialize)]
+#[serde(rename = "TransactionPayload")]
+enum TransactionPayloadRepr {
+    Script(Script),
+    ModuleBundle(ModuleBundle),
+    EntryFunction(EntryFunction),
+    EthTransactionPayload(LegacyEthTransactionPayload),
+    TypedEthTransactionPayload(EthTransactionPayload),
+}
+
+/// `TransactionPayloadRepr` borrowed from a `TransactionPayload`, so that encoding a payload
+/// does not clone it.
+#[derive(Serialize)]
+#[serde(rename = "TransactionPayload")]
+enum TransactionPayloadRef<'a> {
+    Script(&'a Script),
+    ModuleBundle(&'a ModuleBundle),
+    EntryFunction(&'a EntryFunction),
+    EthTransactionPayload(LegacyEthTransactionPayloadRef<'a>),
+    TypedEthTransactionPayload(&'a EthTransactionPayload),
+}
+
+impl Serialize for TransactionPayload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let repr = match self {
+            Self::Script(script) => TransactionPayloadRef::Script(script),
+            Self::ModuleBundle(modules) => TransactionPayloadRef::ModuleBundle(modules),
+            Self::EntryFunction(function) => TransactionPayloadRef::EntryFunction(function),
+            Self::EthTransactionPayload(payload) => match payload.tx_type {
+                EthTransactionType::Legacy => {
+                    TransactionPayloadRef::EthTransactionPayload(payload.into())
+                },
+                _ => TransactionPayloadRef::TypedEthTransactionPayload(payload),
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl TryFrom<TransactionPayloadRepr> for TransactionPayload {
+    type Error = &'static str;
+
+    fn try_from(repr: TransactionPayloadRepr) -> std::result::Result<Self, Self::Error> {
+        Ok(match repr {
+            TransactionPayloadRepr::Script(script) => Self::Script(script),
+            TransactionPayloadRepr::ModuleBundle(modules) => Self::ModuleBundle(modules),
+            TransactionPayloadRepr::EntryFunction(function) => Self::EntryFunction(function),
+            TransactionPayloadRepr::EthTransactionPayload(payload) => {
+                Self::EthTransactionPayload(payload.into())
+            },
+            // A legacy transaction has a single encoding.
+            TransactionPayloadRepr::TypedEthTransactionPayload(EthTransactionPayload {
+                tx_type: EthTransactionType::Legacy,
+                ..
+            }) => return Err("legacy Ethereum transaction encoded as a typed one"),
+            TransactionPayloadRepr::TypedEthTransactionPayload(payload) => {
+                Self::EthTransactionPayload(payload)
+            },
+        })
+    }
+}
+
 impl TransactionPayload {
     pub fn into_entry_function(self) -> EntryFunction {
         match self {
@@ -950,6 +1046,17 @@ pub struct TransactionOutput {
 
     /// The execution status.
     status: TransactionStatus,
+
+    /// The Move side trace of the cross-VM calls of an EVM transaction, if cross-VM tracing is
+    /// enabled. It is a debugging aid, stored by the node outside of the ledger and never sent to
+    /// other nodes.
+    #[serde(skip)]
+    cross_vm_trace: Option<CrossVmTrace>,
+
+    /// Whether the transaction called into the other VM, which the prototype executor checks
+    /// to re-execute the transactions it did not schedule as cross-VM ones.
+    #[serde(skip)]
+    made_cross_vm_call: bool,
 }
 
 impl TransactionOutput {
@@ -964,9 +1071,21 @@ impl TransactionOutput {
             events,
             gas_used,
             status,
+            cross_vm_trace: None,
+            made_cross_vm_call: false,
         }
     }
 
+    pub fn with_cross_vm_trace(mut self, cross_vm_trace: CrossVmTrace) -> Self {
+        self.cross_vm_trace = Some(cross_vm_trace);
+        self
+    }
+
+    pub fn with_cross_vm_call(mut self) -> Self {
+        self.made_cross_vm_call = true;
+        self
+    }
+
     pub fn into(self) -> (WriteSet, Vec<ContractEvent>) {
         (self.write_set, self.events)
     }
@@ -987,12 +1106,21 @@ impl TransactionOutput {
         &self.status
     }
 
+    pub fn cross_vm_trace(&self) -> Option<&CrossVmTrace> {
+        self.cross_vm_trace.as_ref()
+    }
+
+    pub fn made_cross_vm_call(&self) -> bool {
+        self.made_cross_vm_call
+    }
+
     pub fn unpack(self) -> (WriteSet, Vec<ContractEvent>, u64, TransactionStatus) {
         let Self {
             write_set,
             events,
             gas_used,
             status,
+            ..
         } = self;
         (write_set, events, gas_used, status)
     }
@@ -1154,6 +1282,9 @@ pub struct TransactionToCommit {
     write_set: WriteSet,
     events: Vec<ContractEvent>,
     is_reconfig: bool,
+    /// The cross-VM trace of the transaction output, see `TransactionOutput::cross_vm_trace`.
+    #[serde(skip)]
+    cross_vm_trace: Option<CrossVmTrace>,
 }
 
 impl TransactionToCommit {
@@ -1172,9 +1303,15 @@ impl TransactionToCommit {
             write_set,
             events,
             is_reconfig,
+            cross_vm_trace: None,
         }
     }
 
+    pub fn with_cross_vm_trace(mut self, cross_vm_trace: CrossVmTrace) -> Self {
+        self.cross_vm_trace = Some(cross_vm_trace);
+        self
+    }
+
     pub fn transaction(&self) -> &Transaction {
         &self.transaction
     }
@@ -1215,6 +1352,10 @@ impl TransactionToCommit {
     pub fn is_reconfig(&self) -> bool {
         self.is_reconfig
     }
+
+    pub fn cross_vm_trace(&self) -> Option<&CrossVmTrace> {
+        self.cross_vm_trace.as_ref()
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
@@ -1605,21 +1746,62 @@ impl TryFrom<EthTransaction> for SignedTransaction {
             .map_err(|e| anyhow!("recover_public error: e={:?}", e))?;
         let eth_sender = eth_public_to_address(&public);
         let aptos_sender = eth_address_to_aptos_address(&eth_sender);
+        let to_u64 = |field: &str, value: &U256| -> Result<u64> {
+            ensure!(
+                *value <= U256::from(u64::MAX),
+                "eth tx {} {} exceeds u64::MAX",
+                field,
+                value
+            );
+            Ok(value.as_u64())
+        };
+        let to_access_list = |access_list: &EthAccessList| -> Vec<AccessListItem> {
+            access_list.iter().cloned().map(Into::into).collect()
+        };
+        let tx_type = match &eth_tx.unsigned {
+            EthRawTransaction::Ethereum(_) => EthTransactionType::Legacy,
+            EthRawTransaction::Eip2930(tx) => {
+                EthTransactionType::AccessList(to_access_list(&tx.access_list))
+            },
+            EthRawTransaction::Eip1559(tx) => {
+                ensure!(
+                    tx.max_priority_fee_per_gas <= tx.max_fee_per_gas,
+                    "eth tx max priority fee per gas {} exceeds max fee per gas {}",
+                    tx.max_priority_fee_per_gas,
+                    tx.max_fee_per_gas
+                );
+                EthTransactionType::DynamicFee {
+                    access_list: to_access_list(&tx.access_list),
+                    max_priority_fee_per_gas: to_u64(
+                        "max priority fee per gas",
+                        &tx.max_priority_fee_per_gas,
+                    )?,
+                }
+            },
+        };
         let payload = EthTransactionPayload::new(
             *eth_tx.value(),
             eth_tx.action().clone().into(),
             eth_tx.data().clone(),
-        );
-        // FIXME(lpl): Check if conversion overflow (u256 to u64 and u32 to u8) is an issue here.
+        )
+        .with_tx_type(tx_type);
+        // `RawTransaction::eth_transaction` rebuilds the signed message from the chain id, so it
+        // must fit in a `ChainId`. Transactions without EIP-155 replay protection are rejected.
+        let chain_id = eth_tx
+            .chain_id()
+            .ok_or_else(|| anyhow!("eth tx without an EIP-155 chain id"))?;
+        let chain_id = match u8::try_from(chain_id) {
+            Ok(chain_id) if chain_id > 0 => ChainId::new(chain_id),
+            _ => return Err(anyhow!("eth tx chain id {} is not supported", chain_id)),
+        };
         let raw_tx = RawTransaction::new_eth(
             aptos_sender,
-            eth_tx.nonce().as_u64(),
+            to_u64("nonce", eth_tx.nonce())?,
             payload,
-            eth_tx.gas().as_u64(),
-            eth_tx.gas_price().as_u64(),
+            to_u64("gas", eth_tx.gas())?,
+            to_u64("gas price", eth_tx.gas_price())?,
             u64::MAX,
-            // FIXME(lpl): Check chain_id somewhere because it panics with 0.
-            ChainId::new(eth_tx.chain_id().unwrap_or(ChainId::test().id() as u32) as u8),
+            chain_id,
         );
         let authenticator = TransactionAuthenticator::secp256k1(
             Secp256k1PublicKey::from_public(public),
@@ -5,8 +5,9 @@ use crate::{
     account_address::AccountAddress,
     chain_id::ChainId,
     transaction::{
-        AccountTransactionsWithProof, RawTransaction, Script, SignedTransaction, Transaction,
-        TransactionInfo, TransactionListWithProof, TransactionPayload, TransactionWithProof,
+        AccountTransactionsWithProof, EthAddress, EthTransactionPayload, EthTransactionType,
+        RawTransaction, Script, SignedTransaction, Transaction, TransactionInfo,
+        TransactionListWithProof, TransactionPayload, TransactionWithProof,
     },
 };
 use aptos_crypto::{
@@ -35,6 +36,35 @@ fn test_invalid_signature() {
     assert!(!txn.signature_is_valid(), "Signature checking should fail")
 }
 
+#[test]
+fn test_legacy_eth_payload_encoding() {
+    let eth_payload = EthTransactionPayload::new(
+        7.into(),
+        cfx_primitives::Action::Call(EthAddress::repeat_byte(0x11)).into(),
+        vec![1, 2, 3],
+    );
+    // The encoding from before typed transactions: the variant, then the value, action and data.
+    let fields =
+        bcs::to_bytes(&(eth_payload.value, &eth_payload.action, &eth_payload.data)).unwrap();
+    let payload = TransactionPayload::EthTransactionPayload(eth_payload.clone());
+    assert_eq!(
+        bcs::to_bytes(&payload).unwrap(),
+        [&[3], &fields[..]].concat()
+    );
+    assert_canonical_encode_decode(payload);
+
+    let typed = TransactionPayload::EthTransactionPayload(
+        eth_payload.with_tx_type(EthTransactionType::AccessList(vec![])),
+    );
+    assert_eq!(bcs::to_bytes(&typed).unwrap()[0], 4);
+    assert_canonical_encode_decode(typed);
+
+    // A legacy transaction cannot be encoded as a typed one.
+    let legacy_tx_type = bcs::to_bytes(&EthTransactionType::Legacy).unwrap();
+    let typed_legacy = [&[4], &fields[..], &legacy_tx_type[..]].concat();
+    assert!(bcs::from_bytes::<TransactionPayload>(&typed_legacy).is_err());
+}
+
 proptest! {
     #[test]
     fn test_sign_raw_transaction(raw_txn in any::<RawTransaction>(), keypair in ed25519::keypair_strategy()) {
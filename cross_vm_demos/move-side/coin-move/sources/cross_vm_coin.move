@@ -176,6 +176,7 @@ module coin_wrapper::cross_vm_coin {
         }
     }
 
+    #[cross_vm(deposit)]
     fun ihe_deposit<CoinType>(caller: vector<u8>, message: vector<vector<u8>>): vector<u8> acquires CoinInfo, CapStore {
         assert!(caller==x"cc166f312524cc88e2c16c3bdd5735a23376b1fb", ONLY_COIN_CALLER);
 
@@ -188,6 +189,7 @@ module coin_wrapper::cross_vm_coin {
         b""
     }
 
+    #[cross_vm(withdraw)]
     fun ihe_withdraw<CoinType>(caller: vector<u8>, _message: vector<vector<u8>>): vector<u8> acquires CoinInfo, CapStore {
         assert!(caller==x"cc166f312524cc88e2c16c3bdd5735a23376b1fb", ONLY_COIN_CALLER);
 
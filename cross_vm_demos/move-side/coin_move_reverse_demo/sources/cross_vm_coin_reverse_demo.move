@@ -367,6 +367,7 @@ module coin_wrapper::cross_vm_coin_reverse_demo {
         }
     }
 
+    #[cross_vm(deposit)]
     fun ihe_deposit<CoinType>(caller: vector<u8>, message: vector<vector<u8>>): vector<u8> acquires CoinInfo, CapStore {
         assert!(caller==x"cc166f312524cc88e2c16c3bdd5735a23376b1fb", ONLY_COIN_CALLER);
 
@@ -379,6 +380,7 @@ module coin_wrapper::cross_vm_coin_reverse_demo {
         b""
     }
 
+    #[cross_vm(withdraw)]
     fun ihe_withdraw<CoinType>(caller: vector<u8>, _message: vector<vector<u8>>): vector<u8> acquires CoinInfo, CapStore {
         assert!(caller==x"cc166f312524cc88e2c16c3bdd5735a23376b1fb", ONLY_COIN_CALLER);
 
@@ -3,14 +3,17 @@ module evm_handler::hello {
     use std::string;
     use std::bcs;
 
+    #[cross_vm(hello)]
     fun ihe_hello(_caller: vector<u8>, _message: vector<vector<u8>>): vector<u8> {
         b"Hello, Ethereum Virtual Machine!"
     }
 
+    #[cross_vm(hello_with_type)]
     fun ihe_hello_with_type<Type>(_caller: vector<u8>, _message: vector<vector<u8>>): vector<u8> {
         *string::bytes(&type_info::type_name<Type>())
     }
 
+    #[cross_vm(hello_with_type_info)]
     fun ihe_hello_with_type_info<Type>(_caller: vector<u8>, _message: vector<vector<u8>>): vector<u8> {
         bcs::to_bytes(&type_info::type_of<Type>())
     }
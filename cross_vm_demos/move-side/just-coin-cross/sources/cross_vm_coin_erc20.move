@@ -182,6 +182,7 @@ module coin_wrapper::cross_vm_coin_erc20 {
         }
     }
 
+    #[cross_vm(deposit)]
     fun ihe_deposit<CoinType>(caller: vector<u8>, message: vector<vector<u8>>): vector<u8> acquires CoinInfo, CapStore {
         assert!(caller==x"cc166f312524cc88e2c16c3bdd5735a23376b1fb", ONLY_COIN_CALLER);
 
@@ -194,6 +195,7 @@ module coin_wrapper::cross_vm_coin_erc20 {
         b""
     }
 
+    #[cross_vm(withdraw)]
     fun ihe_withdraw<CoinType>(caller: vector<u8>, _message: vector<vector<u8>>): vector<u8> acquires CoinInfo, CapStore {
         assert!(caller==x"cc166f312524cc88e2c16c3bdd5735a23376b1fb", ONLY_COIN_CALLER);
 
@@ -269,5 +271,6 @@ module coin_wrapper::cross_vm_coin_erc20 {
         debug::print(&coin::balance<AptosCoin>(signer::address_of(account)));
     }
 
+    #[cross_vm(fake)]
     public fun ihe_fake(caller: vector<u8>, message: vector<vector<u8>>): vector<u8> { b"" }
 }
\ No newline at end of file
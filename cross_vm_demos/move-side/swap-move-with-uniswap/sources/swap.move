@@ -74,6 +74,7 @@ module swap_wrapper::swap {
     }
 
 
+   #[cross_vm(swap_exact_input)]
    fun ihe_swap_exact_input<X,Y>(caller: vector<u8>, message: vector<vector<u8>>): vector<u8> acquires CapStore {
         assert!(caller==x"13157441585494b5E09E066e69C779aEa08d164B", ONLY_SWAP_CALLER);
 
@@ -85,6 +86,7 @@ module swap_wrapper::swap {
         b""
    }
 
+   #[cross_vm(swap_exact_output)]
    fun ihe_swap_exact_output<X,Y>(caller: vector<u8>, message: vector<vector<u8>>): vector<u8> acquires CapStore {
         assert!(caller==x"13157441585494b5E09E066e69C779aEa08d164B", ONLY_SWAP_CALLER);
 
@@ -97,6 +99,7 @@ module swap_wrapper::swap {
         b""
    }
 
+   #[cross_vm(add_liquidity)]
    fun ihe_add_liquidity<X,Y>(caller: vector<u8>, message: vector<vector<u8>>): vector<u8> acquires CapStore {
         assert!(caller==x"13157441585494b5E09E066e69C779aEa08d164B", ONLY_SWAP_CALLER);
 
@@ -116,6 +119,7 @@ module swap_wrapper::swap {
         b""
    }
 
+   #[cross_vm(remove_liquidity)]
    fun ihe_remove_liquidity<X,Y>(caller: vector<u8>, _message: vector<vector<u8>>): vector<u8> acquires CapStore {
         assert!(caller==x"13157441585494b5E09E066e69C779aEa08d164B", ONLY_SWAP_CALLER);
 